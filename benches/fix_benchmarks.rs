@@ -1,37 +1,258 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use fix_engine::{FixField, FixMessage};
-
-fn encode_benchmark(c: &mut Criterion) {
-    let mut msg = FixMessage::new();
-    msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-    msg.add_field(FixField::new(9, b"100".to_vec()));
-    msg.add_field(FixField::new(35, b"D".to_vec()));
-    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
-    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
-    msg.add_field(FixField::new(34, b"1".to_vec()));
-    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
-
-    c.bench_function("encode_message", |b| {
-        b.iter(|| black_box(msg.clone()).encode())
-    });
-}
-
-fn decode_benchmark(c: &mut Criterion) {
-    let mut msg = FixMessage::new();
-    msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-    msg.add_field(FixField::new(9, b"100".to_vec()));
-    msg.add_field(FixField::new(35, b"D".to_vec()));
-    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
-    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
-    msg.add_field(FixField::new(34, b"1".to_vec()));
-    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
-
-    let encoded = msg.encode().unwrap();
-
-    c.bench_function("decode_message", |b| {
-        b.iter(|| FixMessage::decode(black_box(&encoded)))
-    });
-}
-
-criterion_group!(benches, encode_benchmark, decode_benchmark);
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fix_engine::bench::MessageShape;
+use fix_engine::{DecodeOptions, FieldScanner, FixField, FixMessage, RollingChecksum, SessionConfig, Template, UtcTimestamp};
+
+fn encode_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+    msg.add_field(FixField::new(35, b"D".to_vec()));
+    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+    msg.add_field(FixField::new(34, b"1".to_vec()));
+    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+
+    c.bench_function("encode_message", |b| {
+        b.iter(|| black_box(msg.clone()).encode())
+    });
+}
+
+fn decode_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+    msg.add_field(FixField::new(35, b"D".to_vec()));
+    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+    msg.add_field(FixField::new(34, b"1".to_vec()));
+    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+
+    let encoded = msg.encode().unwrap();
+
+    c.bench_function("decode_message", |b| {
+        b.iter(|| FixMessage::decode(black_box(&encoded)))
+    });
+    c.bench_function("decode_borrowed", |b| {
+        b.iter(|| FixMessage::decode_borrowed(black_box(&encoded)).map(|msg| msg.len()))
+    });
+}
+
+fn market_data_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+    msg.add_field(FixField::new(35, b"W".to_vec()));
+    msg.add_field(FixField::new(49, b"FEED".to_vec()));
+    msg.add_field(FixField::new(56, b"CLIENT".to_vec()));
+    msg.add_field(FixField::new(34, b"1024".to_vec()));
+    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+    msg.add_field(FixField::new(55, b"EUR/USD".to_vec()));
+    msg.add_field(FixField::new(268, b"2".to_vec()));
+    msg.add_field(FixField::new(269, b"0".to_vec()));
+    msg.add_field(FixField::new(270, b"1.1000".to_vec()));
+    msg.add_field(FixField::new(271, b"1000000".to_vec()));
+    msg.add_field(FixField::new(269, b"1".to_vec()));
+    msg.add_field(FixField::new(270, b"1.1002".to_vec()));
+    msg.add_field(FixField::new(271, b"2000000".to_vec()));
+
+    let encoded = msg.encode().unwrap();
+    let template = Template::from_message(&FixMessage::decode(&encoded).unwrap());
+
+    c.bench_function("decode_market_data", |b| {
+        b.iter(|| FixMessage::decode(black_box(&encoded)))
+    });
+    c.bench_function("decode_market_data_templated", |b| {
+        b.iter(|| FixMessage::decode_templated(black_box(&encoded), &template))
+    });
+    c.bench_function("decode_market_data_borrowed", |b| {
+        b.iter(|| FixMessage::decode_borrowed(black_box(&encoded)).map(|msg| msg.len()))
+    });
+}
+
+fn checksum_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+    msg.add_field(FixField::new(35, b"D".to_vec()));
+    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+    msg.add_field(FixField::new(34, b"1".to_vec()));
+    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+
+    let encoded = msg.encode().unwrap();
+    let unverified = DecodeOptions::default().with_verify_checksum(false);
+
+    // Decoding and then summing the message again to verify the CheckSum,
+    // as decode did before, against decode summing each field as it is split
+    c.bench_function("decode_checksum_two_pass", |b| {
+        b.iter(|| {
+            let data = black_box(&encoded[..]);
+            let msg = FixMessage::decode_with_options(data, &unverified)?;
+            let mut checksum = RollingChecksum::new();
+            checksum.update(&data[..data.len() - 7]);
+            checksum.verify(&data[data.len() - 4..data.len() - 1]).map(|()| msg)
+        })
+    });
+    c.bench_function("decode_checksum_single_pass", |b| {
+        b.iter(|| FixMessage::decode(black_box(&encoded)))
+    });
+}
+
+// Owned decode against the borrowed view of a message too large for the
+// view to hold inline
+fn view_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+    msg.add_field(FixField::new(35, b"W".to_vec()));
+    msg.add_field(FixField::new(55, b"EUR/USD".to_vec()));
+    msg.add_field(FixField::new(268, b"20".to_vec()));
+    for level in 0..20 {
+        msg.add_field(FixField::new(269, if level % 2 == 0 { b"0".to_vec() } else { b"1".to_vec() }));
+        msg.add_field(FixField::new(270, format!("1.{:04}", 1000 + level).into_bytes()));
+        msg.add_field(FixField::new(271, b"1000000".to_vec()));
+    }
+
+    let encoded = msg.encode().unwrap();
+
+    c.bench_function("decode_book", |b| {
+        b.iter(|| FixMessage::decode(black_box(&encoded)).map(|msg| msg.len()))
+    });
+    c.bench_function("decode_view_book", |b| {
+        b.iter(|| FixMessage::decode_view(black_box(&encoded)).map(|msg| msg.len()))
+    });
+}
+
+// Reading Symbol, Price and OrderQty off an order and stopping, as a
+// tick-to-trade path does, against decoding it whole first
+fn scan_benchmark(c: &mut Criterion) {
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+    msg.add_field(FixField::new(35, b"D".to_vec()));
+    msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+    msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+    msg.add_field(FixField::new(34, b"1".to_vec()));
+    msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+    msg.add_field(FixField::new(11, b"ORD-1".to_vec()));
+    msg.add_field(FixField::new(55, b"VOD.L".to_vec()));
+    msg.add_field(FixField::new(54, b"1".to_vec()));
+    msg.add_field(FixField::new(44, b"101.50".to_vec()));
+    msg.add_field(FixField::new(38, b"100".to_vec()));
+    msg.add_field(FixField::new(40, b"2".to_vec()));
+    msg.add_field(FixField::new(59, b"0".to_vec()));
+    msg.add_field(FixField::new(60, b"20240101-12:00:00.000".to_vec()));
+
+    let encoded = msg.encode().unwrap();
+
+    c.bench_function("read_3_fields_decode", |b| {
+        b.iter(|| {
+            let msg = FixMessage::decode(black_box(&encoded)).unwrap();
+            (msg.get_field(55).map(|f| f.value().len()), msg.get_field(44).is_some(), msg.get_field(38).is_some())
+        })
+    });
+    c.bench_function("read_3_fields_scan", |b| {
+        b.iter(|| {
+            let (mut symbol, mut price, mut qty) = (None, None, None);
+            for (tag, value) in FieldScanner::new(black_box(&encoded)) {
+                match tag {
+                    55 => symbol = Some(value),
+                    44 => price = Some(value),
+                    38 => qty = Some(value),
+                    _ => {}
+                }
+                if symbol.is_some() && price.is_some() && qty.is_some() {
+                    break;
+                }
+            }
+            (symbol.map(<[u8]>::len), price.is_some(), qty.is_some())
+        })
+    });
+}
+
+// A SecurityList-sized message: 1,000 group entries of five fields, over
+// 5,000 fields and 100 KB
+fn jumbo_benchmark(c: &mut Criterion) {
+    let shape = MessageShape { fields: 0, value_len: 14, group_entries: 1000, group_fields: 5 };
+    let encoded = shape.message(&SessionConfig::new("FIX.4.4", "SENDER", "TARGET")).encode().unwrap();
+    let members = [6001, 6002, 6003, 6004, 6005];
+
+    let msg = FixMessage::decode(&encoded).unwrap();
+    c.bench_function("encode_jumbo", |b| b.iter(|| black_box(&msg).encode()));
+    c.bench_function("decode_jumbo", |b| {
+        b.iter(|| FixMessage::decode(black_box(&encoded)).map(|msg| msg.len()))
+    });
+    c.bench_function("decode_view_jumbo_group", |b| {
+        b.iter(|| {
+            let view = FixMessage::decode_view(black_box(&encoded)).unwrap();
+            view.group(6000, &members).filter_map(Result::ok).map(|entry| entry.len()).sum::<usize>()
+        })
+    });
+}
+
+// Typed constructors formatting into the inline buffer, against
+// formatting with format! into a Vec first
+fn field_constructor_benchmark(c: &mut Criterion) {
+    let time = UtcTimestamp::new(2024, 1, 1, 12, 0, 5).unwrap().with_nanos(250_125_000, 6).unwrap();
+
+    c.bench_function("field_from_typed", |b| {
+        b.iter(|| {
+            [
+                FixField::from_u64(34, black_box(123_456)),
+                FixField::from_f64_with_precision(44, black_box(101.25), 2).unwrap(),
+                FixField::from_bool(43, black_box(true)),
+                FixField::from_timestamp_with_precision(52, black_box(time), 6).unwrap(),
+            ]
+        })
+    });
+    c.bench_function("field_from_format", |b| {
+        b.iter(|| {
+            [
+                FixField::new(34, format!("{}", black_box(123_456u64)).into_bytes()),
+                FixField::new(44, format!("{:.2}", black_box(101.25f64)).into_bytes()),
+                FixField::new(43, if black_box(true) { b"Y".to_vec() } else { b"N".to_vec() }),
+                FixField::new(52, black_box(time).to_string().into_bytes()),
+            ]
+        })
+    });
+}
+
+// 10,000 orders decoded into fresh messages, against into one reused one
+fn decode_into_benchmark(c: &mut Criterion) {
+    let config = SessionConfig::new("FIX.4.4", "SENDER", "TARGET");
+    let orders: Vec<_> = (0..10_000u64)
+        .map(|i| {
+            let mut msg = config.new_message(b"D");
+            msg.add_field(FixField::from_u64(34, i + 1));
+            msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+            msg.add_field(FixField::new(11, format!("ORDER-{:08}", i).into_bytes()));
+            msg.add_field(FixField::new(55, b"VOD.L".to_vec()));
+            msg.add_field(FixField::new(58, format!("{:-<40}", i).into_bytes()));
+            msg.encode().unwrap()
+        })
+        .collect();
+
+    c.bench_function("decode_10k_fresh", |b| {
+        b.iter(|| orders.iter().map(|order| FixMessage::decode(black_box(order)).unwrap().len()).sum::<usize>())
+    });
+    c.bench_function("decode_10k_into", |b| {
+        let mut msg = FixMessage::new();
+        b.iter(|| {
+            orders
+                .iter()
+                .map(|order| {
+                    FixMessage::decode_into(black_box(order), &mut msg).unwrap();
+                    msg.len()
+                })
+                .sum::<usize>()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    encode_benchmark,
+    decode_benchmark,
+    market_data_benchmark,
+    checksum_benchmark,
+    view_benchmark,
+    scan_benchmark,
+    jumbo_benchmark,
+    field_constructor_benchmark,
+    decode_into_benchmark
+);
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,42 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fix_engine::compression::BlockCompression;
+use fix_engine::session::{FileMessageStore, MessageStore};
+use fix_engine::{EndSeqNo, SeqNum};
+
+const MESSAGES: u64 = 1_000_000;
+
+fn execution_report(seq: u64) -> Vec<u8> {
+    format!(
+        "8=FIX.4.4\x019=0\x0135=8\x0134={seq}\x0149=VENUE\x0152=20240101-12:00:00.000000\x0156=CLIENT\x01\
+         37=X{seq}\x0111=ORD{seq}\x0117=E{seq}\x01150=F\x0139=2\x0155=VOD\x0154=1\x0138=100\x0132=100\x01\
+         31=101.25\x01151=0\x0114=100\x016=101.25\x0110=000\x01"
+    )
+    .into_bytes()
+}
+
+fn resend_range_benchmark(c: &mut Criterion) {
+    let path = std::env::temp_dir().join(format!("fix_engine_bench_journal_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let mut store = FileMessageStore::open_compressed(&path, BlockCompression::default()).unwrap();
+        for seq in 1..=MESSAGES {
+            store.save(SeqNum::new(seq).unwrap(), &execution_report(seq)).unwrap();
+        }
+    }
+    let store = FileMessageStore::open(&path).unwrap();
+    let begin = SeqNum::new(MESSAGES / 2).unwrap();
+
+    c.bench_function("journal_resend_100_of_1m_compressed", |b| {
+        let end = EndSeqNo::Bounded(SeqNum::new(MESSAGES / 2 + 99).unwrap());
+        b.iter(|| store.range(black_box(begin), end).unwrap())
+    });
+    c.bench_function("journal_resend_1_of_1m_compressed", |b| {
+        b.iter(|| store.range(black_box(begin), EndSeqNo::Bounded(begin)).unwrap())
+    });
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(benches, resend_range_benchmark);
+criterion_main!(benches);
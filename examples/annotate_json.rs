@@ -0,0 +1,33 @@
+use fix_engine::{fix_version, msg_type, DataDictionary, FieldSpec, FieldType, FixField, FixMessage, Tag};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // An ExecutionReport with a Parties group
+    let mut msg = FixMessage::new();
+    msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+    msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+    msg.add_field(FixField::new(Tag::OrderID.value(), b"ORD-1".to_vec()));
+    msg.add_field(FixField::new(Tag::ExecType.value(), b"F".to_vec()));
+    msg.add_field(FixField::new(Tag::OrdStatus.value(), b"2".to_vec()));
+    msg.add_group(
+        453,
+        &[
+            vec![FixField::new(448, b"BROKER".to_vec()), FixField::new(447, b"D".to_vec()), FixField::new(452, b"1".to_vec())],
+            vec![FixField::new(448, b"DESK".to_vec()), FixField::new(447, b"D".to_vec()), FixField::new(452, b"24".to_vec())],
+        ],
+    )?;
+    msg.add_field(FixField::new(Tag::Side.value(), b"1".to_vec()));
+    msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD".to_vec()));
+
+    // The built-in dictionary does not define Parties; extend it
+    let dict = DataDictionary::fix44()
+        .with_field(FieldSpec::new(453, "NoPartyIDs", FieldType::NumInGroup))
+        .with_field(FieldSpec::new(448, "PartyID", FieldType::String))
+        .with_field(FieldSpec::new(447, "PartyIDSource", FieldType::Char))
+        .with_field(FieldSpec::new(452, "PartyRole", FieldType::Int).with_labels([("1", "ExecutingFirm"), ("24", "CustomerAccount")]))
+        .with_group(453, &[448, 447, 452]);
+
+    println!("{}", msg.explain(&dict));
+    println!("{}", serde_json::to_string_pretty(&msg.annotate(&dict))?);
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use fix_engine::bench::{self, BenchSpec, CountingAllocator, MessageShape, Scenario};
+use std::time::Duration;
+
+// Counting allocations lets the report include them
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let shape = MessageShape { fields: 30, value_len: 12, group_entries: 4, group_fields: 3 };
+    for scenario in [Scenario::Decode, Scenario::Encode, Scenario::RoundTrip, Scenario::Framer, Scenario::SessionLoopback] {
+        let spec = BenchSpec::new(scenario).with_shape(shape).with_duration(Duration::from_millis(200));
+        let report = bench::run(&spec)?;
+        let allocations = report.allocations.map_or(0.0, |counts| counts.per_message);
+        println!(
+            "{:?}: {:.0} msg/s, {:.1} allocations/msg, p50 {} ns, p99 {} ns",
+            report.scenario, report.messages_per_sec, allocations, report.latency.p50, report.latency.p99
+        );
+    }
+    Ok(())
+}
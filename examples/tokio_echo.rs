@@ -0,0 +1,43 @@
+// Runs a session-less echo over TCP with FixCodec: the server sends every
+// Heartbeat it receives straight back, and the client checks each one
+// returns. Run with `cargo run --example tokio_echo --features tokio`.
+
+use fix_engine::transport::FixCodec;
+use fix_engine::{fix_version, FixField, MsgType, SessionConfig, Tag};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::Framed;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        let mut framed = Framed::new(stream, FixCodec::new());
+        while let Some(msg) = framed.next().await {
+            let msg = msg?;
+            if msg.msg_type() == Some(MsgType::Heartbeat) {
+                framed.send(msg).await?;
+            }
+        }
+        Ok::<_, fix_engine::transport::CodecError>(())
+    });
+
+    let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "ECHO");
+    let mut framed = Framed::new(TcpStream::connect(addr).await?, FixCodec::new());
+    for seq in 1..=3u64 {
+        let mut heartbeat = config.heartbeat(None);
+        heartbeat.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        framed.send(&heartbeat).await?;
+
+        let echoed = framed.next().await.ok_or("server closed the connection")??;
+        assert_eq!(echoed.msg_seq_num()?.map(|seq| seq.get()), Some(seq));
+        println!("echoed: {}", echoed.to_print_string());
+    }
+
+    drop(framed);
+    server.await??;
+    Ok(())
+}
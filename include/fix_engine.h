@@ -0,0 +1,136 @@
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#ifndef FIX_ENGINE_H
+#define FIX_ENGINE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every FFI call. The numbering is part of the C interface and
+ * must not change; new codes are only ever appended.
+ */
+typedef enum FixStatus {
+  FIX_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null
+   */
+  FIX_STATUS_NULL_POINTER = 1,
+  FIX_STATUS_INVALID_FORMAT = 2,
+  FIX_STATUS_INVALID_CHECKSUM = 3,
+  FIX_STATUS_INVALID_BODY_LENGTH = 4,
+  FIX_STATUS_MISSING_FIELD = 5,
+  /**
+   * The input ends before the message does
+   */
+  FIX_STATUS_INCOMPLETE = 6,
+  /**
+   * The requested tag is not in the message
+   */
+  FIX_STATUS_NOT_FOUND = 7,
+  /**
+   * The index is not below the field count
+   */
+  FIX_STATUS_OUT_OF_RANGE = 8,
+  /**
+   * The caller's buffer is too small; the required size is reported
+   */
+  FIX_STATUS_BUFFER_TOO_SMALL = 9,
+  /**
+   * Any other error reported by the codec
+   */
+  FIX_STATUS_OTHER = 10,
+  /**
+   * The call panicked; no output was written
+   */
+  FIX_STATUS_PANIC = 11,
+} FixStatus;
+
+/**
+ * A decoded message owned by the library. Opaque to C; created by
+ * `fix_msg_decode` and released with `fix_msg_free`.
+ */
+typedef struct FixMsgHandle FixMsgHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Decodes one message from `data[..len]` and stores a new handle in `*out`.
+ * `*out` is set to null on failure.
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes and `out` must be valid for
+ * writes.
+ */
+FixStatus fix_msg_decode(const uint8_t *data, size_t len, FixMsgHandle **out);
+
+/**
+ * Points `*val` at the first value of `tag` and stores its length in
+ * `*len`. The value is not NUL-terminated and stays valid until the handle
+ * is freed.
+ *
+ * # Safety
+ *
+ * `handle` must come from `fix_msg_decode` and not have been freed; `val`
+ * and `len` must be valid for writes.
+ */
+FixStatus fix_msg_get(const FixMsgHandle *handle, uint32_t tag, const uint8_t **val, size_t *len);
+
+/**
+ * Stores the number of fields, BodyLength and CheckSum included, in `*count`
+ *
+ * # Safety
+ *
+ * `handle` must come from `fix_msg_decode` and not have been freed; `count`
+ * must be valid for writes.
+ */
+FixStatus fix_msg_field_count(const FixMsgHandle *handle, size_t *count);
+
+/**
+ * The field at `index` in wire order, as for `fix_msg_get`
+ *
+ * # Safety
+ *
+ * `handle` must come from `fix_msg_decode` and not have been freed; `tag`,
+ * `val` and `len` must be valid for writes.
+ */
+FixStatus fix_msg_field_at(const FixMsgHandle *handle,
+                           size_t index,
+                           uint32_t *tag,
+                           const uint8_t **val,
+                           size_t *len);
+
+/**
+ * Encodes the message into `buf[..cap]` and stores the encoded length in
+ * `*written`. When `cap` is too small nothing is copied, `*written` holds
+ * the size needed and `BufferTooSmall` is returned.
+ *
+ * # Safety
+ *
+ * `handle` must come from `fix_msg_decode` and not have been freed; `buf`
+ * must point to `cap` writable bytes, and may only be null when `cap` is 0;
+ * `written` must be valid for writes.
+ */
+FixStatus fix_msg_encode(const FixMsgHandle *handle, uint8_t *buf, size_t cap, size_t *written);
+
+/**
+ * Releases a handle. Null is ignored.
+ *
+ * # Safety
+ *
+ * `handle` must be null or come from `fix_msg_decode`, and must not be used
+ * afterwards.
+ */
+void fix_msg_free(FixMsgHandle *handle);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FIX_ENGINE_H */
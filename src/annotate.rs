@@ -0,0 +1,226 @@
+use crate::dictionary::{DataDictionary, FieldType};
+use crate::error::FixError;
+use crate::field::{parse_uint, FixField};
+use crate::message::FixMessage;
+use crate::tags::Tag;
+use std::fmt::Write;
+
+/// What a dictionary says about one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldAnnotation {
+    pub tag: u32,
+    /// Field name; `None` for tags the dictionary does not define
+    pub name: Option<String>,
+    /// The value as text, with invalid UTF-8 replaced
+    pub value: String,
+    /// Name of the enumerated value
+    pub label: Option<String>,
+    pub field_type: Option<FieldType>,
+    /// The violation `validate` reports for this field, or a group count
+    /// that does not match its entries
+    pub issue: Option<String>,
+    /// Entries of the repeating group this field counts, each in wire order
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub entries: Vec<Vec<FieldAnnotation>>,
+}
+
+/// Every field of a message annotated against a dictionary, with repeating
+/// groups the dictionary defines nested under their count field
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MessageAnnotation {
+    pub msg_type: Option<String>,
+    pub fields: Vec<FieldAnnotation>,
+}
+
+impl MessageAnnotation {
+    /// Every field with an issue, group members included, in wire order
+    pub fn issues(&self) -> Vec<&FieldAnnotation> {
+        fn collect<'a>(fields: &'a [FieldAnnotation], out: &mut Vec<&'a FieldAnnotation>) {
+            for field in fields {
+                if field.issue.is_some() {
+                    out.push(field);
+                }
+                for entry in &field.entries {
+                    collect(entry, out);
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        collect(&self.fields, &mut out);
+        out
+    }
+}
+
+fn annotate_field(dict: &DataDictionary, field: &FixField) -> FieldAnnotation {
+    let spec = dict.field(field.tag());
+    FieldAnnotation {
+        tag: field.tag(),
+        name: spec.map(|spec| spec.name.clone()),
+        value: String::from_utf8_lossy(field.value()).into_owned(),
+        label: spec.and_then(|spec| spec.label(field.value())).map(str::to_owned),
+        field_type: spec.map(|spec| spec.field_type),
+        issue: dict.validate_field(field.tag(), field.value()).err().map(|err| err.to_string()),
+        entries: Vec::new(),
+    }
+}
+
+// Annotates the field at `*pos` and, when it counts a group, the entries
+// that follow it
+fn annotate_one(dict: &DataDictionary, fields: &[FixField], pos: &mut usize) -> FieldAnnotation {
+    let field = &fields[*pos];
+    *pos += 1;
+    let mut annotation = annotate_field(dict, field);
+    let Some(members) = dict.group(field.tag()) else {
+        return annotation;
+    };
+
+    while fields.get(*pos).is_some_and(|f| f.tag() == members[0]) {
+        let mut entry = vec![annotate_one(dict, fields, pos)];
+        while fields
+            .get(*pos)
+            .is_some_and(|f| f.tag() != members[0] && members.contains(&f.tag()))
+        {
+            entry.push(annotate_one(dict, fields, pos));
+        }
+        annotation.entries.push(entry);
+    }
+
+    if annotation.issue.is_none() {
+        if let Ok(declared) = parse_uint(field.value()) {
+            if declared != annotation.entries.len() as u64 {
+                let mismatch = FixError::GroupCountMismatch {
+                    count_tag: field.tag(),
+                    declared: declared as usize,
+                    actual: annotation.entries.len(),
+                };
+                annotation.issue = Some(mismatch.to_string());
+            }
+        }
+    }
+    annotation
+}
+
+fn write_fields(out: &mut String, fields: &[FieldAnnotation], indent: usize) {
+    for field in fields {
+        let _ = write!(out, "{:indent$}", "");
+        match &field.name {
+            Some(name) => {
+                let _ = write!(out, "{name}({})={}", field.tag, field.value);
+            }
+            None => {
+                let _ = write!(out, "{}={}", field.tag, field.value);
+            }
+        }
+        if let Some(label) = &field.label {
+            let _ = write!(out, " [{label}]");
+        }
+        if let Some(issue) = &field.issue {
+            let _ = write!(out, "  ! {issue}");
+        }
+        out.push('\n');
+
+        for entry in &field.entries {
+            let _ = write!(out, "{:indent$}  - ", "");
+            let mut lines = String::new();
+            write_fields(&mut lines, entry, indent + 4);
+            // The first field of an entry follows the "- " marker
+            out.push_str(lines.trim_start());
+        }
+    }
+}
+
+impl FixMessage {
+    /// Annotates every field with its name, type, enum label and any
+    /// violation, using the same checks as `validate_all`
+    pub fn annotate(&self, dict: &DataDictionary) -> MessageAnnotation {
+        let fields = self.field_slice();
+        let mut annotated = Vec::with_capacity(fields.len());
+        let mut pos = 0;
+        while pos < fields.len() {
+            annotated.push(annotate_one(dict, fields, &mut pos));
+        }
+
+        MessageAnnotation {
+            msg_type: self.get_str_owned(Tag::MsgType.value()),
+            fields: annotated,
+        }
+    }
+
+    /// `annotate` rendered as text, one field per line with group entries
+    /// indented under their count field, e.g. `Side(54)=1 [Buy]`. Issues
+    /// follow a '!'.
+    pub fn explain(&self, dict: &DataDictionary) -> String {
+        let mut out = String::new();
+        write_fields(&mut out, &self.annotate(dict).fields, 0);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::FieldSpec;
+
+    fn message(fields: &[(u32, &[u8])]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        for &(tag, value) in fields {
+            msg.add_field(FixField::new(tag, value.to_vec()));
+        }
+        msg
+    }
+
+    fn parties_dict() -> DataDictionary {
+        DataDictionary::fix44()
+            .with_field(FieldSpec::new(453, "NoPartyIDs", FieldType::NumInGroup))
+            .with_field(FieldSpec::new(448, "PartyID", FieldType::String))
+            .with_field(FieldSpec::new(452, "PartyRole", FieldType::Int).with_labels([("1", "ExecutingFirm")]))
+            .with_group(453, &[448, 452])
+    }
+
+    #[test]
+    fn test_annotate_nests_groups() {
+        let msg = message(&[
+            (35, b"8"),
+            (453, b"2"),
+            (448, b"BROKER"),
+            (452, b"1"),
+            (448, b"DESK"),
+            (54, b"7"),
+            (9999, b"custom"),
+        ]);
+        let annotation = msg.annotate(&parties_dict());
+
+        assert_eq!(annotation.msg_type.as_deref(), Some("8"));
+        assert_eq!(annotation.fields.len(), 4);
+        assert_eq!(annotation.fields[0].label.as_deref(), Some("ExecutionReport"));
+
+        let parties = &annotation.fields[1];
+        assert_eq!(parties.entries.len(), 2);
+        assert_eq!(parties.entries[0][1].label.as_deref(), Some("ExecutingFirm"));
+        assert_eq!(parties.entries[1].len(), 1);
+        assert_eq!(parties.issue, None);
+
+        assert_eq!(annotation.fields[2].field_type, Some(FieldType::Char));
+        assert_eq!(annotation.fields[3].name, None);
+
+        let issues: Vec<u32> = annotation.issues().iter().map(|f| f.tag).collect();
+        let violations: Vec<u32> = msg.validate_all(&parties_dict()).iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(issues, violations);
+    }
+
+    #[test]
+    fn test_explain() {
+        let msg = message(&[(35, b"8"), (453, b"3"), (448, b"BROKER"), (452, b"1"), (54, b"1")]);
+        assert_eq!(
+            msg.explain(&parties_dict()),
+            "MsgType(35)=8 [ExecutionReport]\n\
+             NoPartyIDs(453)=3  ! Repeating group 453 declares 3 entries but contains 1\n  \
+             - PartyID(448)=BROKER\n    \
+             PartyRole(452)=1 [ExecutingFirm]\n\
+             Side(54)=1 [Buy]\n"
+        );
+    }
+}
@@ -0,0 +1,497 @@
+// A self-benchmark for capacity tests, embeddable in a soak-test harness:
+// it times the codec, the framer or a pair of sessions on a generated or
+// given message, with the options and dictionary a deployment actually
+// uses, and reports plain numbers rather than criterion's output.
+
+use crate::dictionary::DataDictionary;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::framing::Framer;
+use crate::message::FixMessage;
+use crate::options::{DecodeOptions, EncodeOptions};
+use crate::profile::VenueProfile;
+use crate::seqnum::{EndSeqNo, SeqNum};
+use crate::session::{MessageStore, Session, SessionConfig};
+use crate::tags::{fix_version, msg_type, Tag};
+use bytes::Bytes;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// Tags of the generated body fields, group count and group members, from
+// the user-defined range
+const BODY_TAG: u32 = 5000;
+const GROUP_COUNT_TAG: u32 = 6000;
+const GROUP_TAG: u32 = 6001;
+
+/// What one iteration of a run does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Scenario {
+    /// Decodes the encoded message and validates it
+    Decode,
+    /// Validates the message and encodes it
+    Encode,
+    /// Encodes the message, then decodes and validates it
+    RoundTrip,
+    /// Splits the encoded message out of a `Framer`, fed in two reads
+    Framer,
+    /// One session stamps and encodes the message, and the other frames,
+    /// decodes, validates and sequences it
+    SessionLoopback,
+}
+
+/// The generated message: a NewOrderSingle header, `fields` body fields of
+/// `value_len` bytes, then a repeating group of `group_entries` entries of
+/// `group_fields` fields each
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MessageShape {
+    pub fields: usize,
+    pub value_len: usize,
+    pub group_entries: usize,
+    pub group_fields: usize,
+}
+
+impl Default for MessageShape {
+    fn default() -> Self {
+        Self { fields: 20, value_len: 8, group_entries: 0, group_fields: 3 }
+    }
+}
+
+impl MessageShape {
+    /// The message of this shape from `config`'s session
+    pub fn message(&self, config: &SessionConfig) -> FixMessage {
+        let value = |i: usize| -> Vec<u8> { (0..self.value_len).map(|j| b'A' + ((i + j) % 26) as u8).collect() };
+        let mut msg = config.new_message(msg_type::NEW_ORDER_SINGLE);
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00:00.000".to_vec()));
+        for i in 0..self.fields {
+            msg.add_field(FixField::new(BODY_TAG + i as u32, value(i)));
+        }
+        if self.group_entries > 0 {
+            msg.add_field(FixField::from_value(GROUP_COUNT_TAG, &(self.group_entries as u64)));
+            for entry in 0..self.group_entries {
+                for i in 0..self.group_fields {
+                    msg.add_field(FixField::new(GROUP_TAG + i as u32, value(entry + i)));
+                }
+            }
+        }
+        msg
+    }
+}
+
+/// When a run stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchLimit {
+    Iterations(u64),
+    Duration(Duration),
+}
+
+/// What `run` measures and with which options
+#[derive(Debug, Clone)]
+pub struct BenchSpec {
+    pub scenario: Scenario,
+    pub shape: MessageShape,
+    /// A message to use instead of the generated one
+    pub message: Option<FixMessage>,
+    pub limit: BenchLimit,
+    /// Untimed iterations run first
+    pub warmup: u64,
+    /// The sending session; the loopback's receiving session has the
+    /// CompIDs swapped
+    pub config: SessionConfig,
+    pub encode: EncodeOptions,
+    pub decode: DecodeOptions,
+    /// Messages are validated against it where set
+    pub dictionary: Option<DataDictionary>,
+}
+
+impl BenchSpec {
+    /// 100,000 iterations after 1,000 warm-up ones, of a FIX 4.4 message of
+    /// the default shape with default options and no validation
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            shape: MessageShape::default(),
+            message: None,
+            limit: BenchLimit::Iterations(100_000),
+            warmup: 1_000,
+            config: SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"),
+            encode: EncodeOptions::default(),
+            decode: DecodeOptions::default(),
+            dictionary: None,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: MessageShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_message(mut self, message: FixMessage) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: u64) -> Self {
+        self.limit = BenchLimit::Iterations(iterations);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.limit = BenchLimit::Duration(duration);
+        self
+    }
+
+    pub fn with_warmup(mut self, iterations: u64) -> Self {
+        self.warmup = iterations;
+        self
+    }
+
+    pub fn with_session_config(mut self, config: SessionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_encode_options(mut self, options: EncodeOptions) -> Self {
+        self.encode = options;
+        self
+    }
+
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.decode = options;
+        self
+    }
+
+    pub fn with_dictionary(mut self, dictionary: DataDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Takes the options, dictionary and session settings of a venue
+    pub fn with_profile(mut self, profile: &VenueProfile) -> Self {
+        self.encode = profile.encode.clone();
+        self.decode = profile.decode.clone();
+        self.dictionary = Some(profile.dictionary.clone());
+        self.config = profile.apply(self.config);
+        self
+    }
+}
+
+/// Allocations made while iterations were timed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AllocationCounts {
+    pub allocations: u64,
+    pub bytes: u64,
+    pub per_message: f64,
+}
+
+/// Time taken by single iterations, in nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LatencyPercentiles {
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub p999: u64,
+    pub max: u64,
+    pub mean: u64,
+}
+
+/// The outcome of `run`. Throughput is over the whole timed run, so it
+/// includes the cost of timing each iteration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BenchReport {
+    pub scenario: Scenario,
+    pub iterations: u64,
+    /// Encoded length of the message
+    pub message_len: usize,
+    pub elapsed_nanos: u64,
+    pub messages_per_sec: f64,
+    pub bytes_per_sec: f64,
+    /// `None` unless `CountingAllocator` is the global allocator
+    pub allocations: Option<AllocationCounts>,
+    pub latency: LatencyPercentiles,
+}
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting allocations for `BenchReport`. A harness
+/// binary installs it with
+/// `#[global_allocator] static ALLOC: CountingAllocator = CountingAllocator;`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    #[inline]
+    fn count(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    // Allocations and bytes allocated so far
+    fn totals() -> (u64, u64) {
+        (ALLOCATIONS.load(Ordering::Relaxed), ALLOCATED_BYTES.load(Ordering::Relaxed))
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+// The loopback sender's store, which keeps nothing so a long run does not
+// grow
+struct NullStore;
+
+impl MessageStore for NullStore {
+    fn save(&mut self, _seq: SeqNum, _raw: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn range(&self, _begin: SeqNum, _end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>> {
+        Ok(Vec::new())
+    }
+
+    fn last_seq(&self) -> io::Result<Option<SeqNum>> {
+        Ok(None)
+    }
+}
+
+struct Workload<'a> {
+    spec: &'a BenchSpec,
+    msg: FixMessage,
+    wire: Vec<u8>,
+    framer: Framer,
+    sender: Session,
+    receiver: Session,
+}
+
+impl Workload<'_> {
+    fn validate(&self, msg: &FixMessage) -> Result<(), FixError> {
+        match &self.spec.dictionary {
+            Some(dictionary) => msg.validate(dictionary),
+            None => Ok(()),
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<FixMessage, FixError> {
+        let msg = FixMessage::decode_with_options(data, &self.spec.decode)?;
+        self.validate(&msg)?;
+        Ok(msg)
+    }
+
+    fn next_frame(&mut self) -> Result<Bytes, FixError> {
+        self.framer.next_frame()?.ok_or(FixError::IncompleteHeader)
+    }
+
+    fn step(&mut self) -> Result<(), FixError> {
+        match self.spec.scenario {
+            Scenario::Decode => {
+                black_box(self.decode(&self.wire)?);
+            }
+            Scenario::Encode => {
+                self.validate(&self.msg)?;
+                black_box(self.msg.encode_with_options(&self.spec.encode)?);
+            }
+            Scenario::RoundTrip => {
+                let encoded = self.msg.encode_with_options(&self.spec.encode)?;
+                black_box(self.decode(&encoded)?);
+            }
+            Scenario::Framer => {
+                let (first, second) = self.wire.split_at(self.wire.len() / 2);
+                self.framer.extend_from_slice(first);
+                self.framer.extend_from_slice(second);
+                black_box(self.next_frame()?);
+            }
+            Scenario::SessionLoopback => {
+                let mut msg = self.msg.clone();
+                let encoded = self.sender.prepare_outbound(&mut msg, &mut NullStore)?;
+                self.framer.extend_from_slice(&encoded);
+                let frame = self.next_frame()?;
+                let inbound = self.decode(&frame)?;
+                black_box(self.receiver.process_inbound(&inbound)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `spec`, failing with the first error an iteration hits, e.g. a
+/// message the dictionary rejects
+pub fn run(spec: &BenchSpec) -> Result<BenchReport, FixError> {
+    let msg = spec.message.clone().unwrap_or_else(|| spec.shape.message(&spec.config));
+    let wire = msg.encode_with_options(&spec.encode)?.to_vec();
+    let mut reverse = spec.config.clone();
+    std::mem::swap(&mut reverse.sender_comp_id, &mut reverse.target_comp_id);
+    let mut workload = Workload {
+        spec,
+        msg,
+        wire,
+        framer: Framer::new(),
+        sender: Session::new(spec.config.clone()),
+        receiver: Session::new(reverse),
+    };
+
+    for _ in 0..spec.warmup {
+        workload.step()?;
+    }
+
+    let capacity = match spec.limit {
+        BenchLimit::Iterations(iterations) => iterations.min(1 << 24) as usize,
+        BenchLimit::Duration(_) => 1 << 16,
+    };
+    let mut samples: Vec<u64> = Vec::with_capacity(capacity);
+    let (mut allocations, mut bytes) = (0, 0);
+    let start = Instant::now();
+    loop {
+        let done = match spec.limit {
+            BenchLimit::Iterations(iterations) => samples.len() as u64 >= iterations,
+            BenchLimit::Duration(duration) => start.elapsed() >= duration,
+        };
+        if done {
+            break;
+        }
+        // Only the iteration itself is counted, not recording the sample
+        let (allocations_before, bytes_before) = CountingAllocator::totals();
+        let began = Instant::now();
+        workload.step()?;
+        let took = began.elapsed();
+        let (allocations_after, bytes_after) = CountingAllocator::totals();
+        allocations += allocations_after - allocations_before;
+        bytes += bytes_after - bytes_before;
+        samples.push(u64::try_from(took.as_nanos()).unwrap_or(u64::MAX));
+    }
+    let elapsed = start.elapsed();
+
+    let iterations = samples.len() as u64;
+    let messages_per_sec = iterations as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+    // Nothing has been counted at all when the allocator is not installed
+    let installed = CountingAllocator::totals().0 > 0;
+    Ok(BenchReport {
+        scenario: spec.scenario,
+        iterations,
+        message_len: workload.wire.len(),
+        elapsed_nanos: u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX),
+        messages_per_sec,
+        bytes_per_sec: messages_per_sec * workload.wire.len() as f64,
+        allocations: installed.then(|| AllocationCounts {
+            allocations,
+            bytes,
+            per_message: allocations as f64 / iterations.max(1) as f64,
+        }),
+        latency: percentiles(&mut samples),
+    })
+}
+
+fn percentiles(samples: &mut [u64]) -> LatencyPercentiles {
+    if samples.is_empty() {
+        return LatencyPercentiles::default();
+    }
+    samples.sort_unstable();
+    let at = |q: f64| samples[((q * samples.len() as f64).ceil() as usize).clamp(1, samples.len()) - 1];
+    LatencyPercentiles {
+        min: samples[0],
+        p50: at(0.5),
+        p90: at(0.9),
+        p99: at(0.99),
+        p999: at(0.999),
+        max: samples[samples.len() - 1],
+        mean: (samples.iter().map(|&s| s as u128).sum::<u128>() / samples.len() as u128) as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenarios() {
+        let shape = MessageShape { fields: 5, value_len: 4, group_entries: 2, group_fields: 2 };
+        for scenario in [
+            Scenario::Decode,
+            Scenario::Encode,
+            Scenario::RoundTrip,
+            Scenario::Framer,
+            Scenario::SessionLoopback,
+        ] {
+            let spec = BenchSpec::new(scenario).with_shape(shape).with_iterations(200).with_warmup(10);
+            let report = run(&spec).unwrap();
+            assert_eq!(report.scenario, scenario);
+            assert_eq!(report.iterations, 200);
+            assert_eq!(report.message_len, shape.message(&spec.config).encode().unwrap().len());
+            assert!(report.messages_per_sec > 0.0);
+            let latency = report.latency;
+            assert!(latency.min <= latency.p50 && latency.p50 <= latency.p99 && latency.p999 <= latency.max);
+            // The test binary does not install the counting allocator
+            assert!(report.allocations.is_none());
+        }
+
+        let report = run(&BenchSpec::new(Scenario::Decode).with_duration(Duration::from_millis(20))).unwrap();
+        assert!(report.iterations > 0 && report.elapsed_nanos >= 20_000_000);
+    }
+
+    #[test]
+    fn test_configured_strictness() {
+        // The generated body uses tags the strict profile does not define
+        let strict = VenueProfile::strict_fix44();
+        let spec = BenchSpec::new(Scenario::Decode).with_profile(&strict).with_iterations(10);
+        assert!(matches!(run(&spec), Err(FixError::UnknownTag(BODY_TAG))));
+
+        let mut order = MessageShape { fields: 0, ..MessageShape::default() }.message(&spec.config);
+        for (tag, value) in [
+            (Tag::ClOrdID, &b"ORD1"[..]),
+            (Tag::Symbol, b"VOD.L"),
+            (Tag::Side, b"1"),
+            (Tag::TransactTime, b"20240101-12:00:00.000"),
+            (Tag::OrdType, b"1"),
+        ] {
+            order.add_field(FixField::new(tag.value(), value.to_vec()));
+        }
+        let spec = spec.with_message(order).with_warmup(0);
+        assert_eq!(run(&spec).unwrap().iterations, 10);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut samples: Vec<u64> = (1..=1000).rev().collect();
+        let latency = percentiles(&mut samples);
+        assert_eq!((latency.min, latency.p50, latency.p90, latency.p99), (1, 500, 900, 990));
+        assert_eq!((latency.p999, latency.max, latency.mean), (999, 1000, 500));
+        assert_eq!(percentiles(&mut []), LatencyPercentiles::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_serializes() {
+        let report = run(&BenchSpec::new(Scenario::Encode).with_iterations(10)).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["scenario"], "Encode");
+        assert_eq!(json["iterations"], 10);
+        assert!(json["latency"]["p99"].is_u64());
+        assert!(json["allocations"].is_null());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::error::FixError;
+use crate::field::parse_uint;
+
+/// CheckSum (10) accumulated as bytes arrive, so a streaming reader can
+/// verify a message without a second pass over it. Feed every byte up to and
+/// including the SOH before "10=".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RollingChecksum {
+    sum: u8,
+}
+
+impl RollingChecksum {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.sum = bytes.iter().fold(self.sum, |sum, &b| sum.wrapping_add(b));
+    }
+
+    /// The checksum of everything fed so far
+    #[inline]
+    pub fn finalize(&self) -> u8 {
+        self.sum
+    }
+
+    /// Compares against the received CheckSum value, which must be exactly
+    /// three digits: "007", never "7" or "0007"
+    #[inline]
+    pub fn verify(&self, received: &[u8]) -> Result<(), FixError> {
+        if received.len() != 3 {
+            return Err(FixError::InvalidFormat);
+        }
+        let received = parse_uint(received).map_err(|_| FixError::InvalidFormat)?;
+        if received != self.sum as u64 {
+            return Err(FixError::InvalidChecksum);
+        }
+        Ok(())
+    }
+}
+
+// Test fixture: appends "10=nnn<SOH>" matching the bytes already in `wire`,
+// summed in one pass independently of `RollingChecksum`
+#[cfg(test)]
+pub(crate) fn append_checksum(wire: &mut Vec<u8>) {
+    let sum = wire.iter().map(|&b| b as u32).sum::<u32>() % 256;
+    wire.extend_from_slice(format!("10={:03}\x01", sum).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_single_pass_sum() {
+        let data = b"8=FIX.4.4\x019=5\x0135=0\x01";
+        let expected = (data.iter().map(|&b| b as u32).sum::<u32>() % 256) as u8;
+
+        let mut checksum = RollingChecksum::new();
+        for chunk in data.chunks(3) {
+            checksum.update(chunk);
+        }
+        assert_eq!(checksum.finalize(), expected);
+        assert!(checksum.verify(format!("{:03}", expected).as_bytes()).is_ok());
+        assert!(matches!(
+            checksum.verify(format!("{:03}", expected.wrapping_add(1)).as_bytes()),
+            Err(FixError::InvalidChecksum)
+        ));
+        assert!(matches!(checksum.verify(b"x"), Err(FixError::InvalidFormat)));
+        for unpadded in [&b"7"[..], b"07", b"0007", b" 163"] {
+            assert!(matches!(checksum.verify(unpadded), Err(FixError::InvalidFormat)), "{unpadded:?}");
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::error::FixError;
+use crate::field::{impl_text_traits, FieldValue};
+use smallvec::SmallVec;
+
+macro_rules! code_type {
+    ($(#[$doc:meta])* $name:ident, $len:literal, $valid:path) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            #[inline]
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                // Only ASCII is ever accepted
+                std::str::from_utf8(&self.0).unwrap_or_default()
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = FixError;
+
+            fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+                let code: [u8; $len] = value.try_into().map_err(|_| FixError::InvalidFieldValue)?;
+                if !code.iter().all($valid) {
+                    return Err(FixError::InvalidFieldValue);
+                }
+                Ok(Self(code))
+            }
+        }
+
+        impl FieldValue for $name {
+            #[inline]
+            fn parse(value: &[u8]) -> Result<Self, FixError> {
+                Self::try_from(value)
+            }
+
+            #[inline]
+            fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+                buf.extend_from_slice(&self.0);
+            }
+        }
+
+        impl_text_traits!($name);
+    };
+}
+
+code_type!(
+    /// ISO 4217 currency code, e.g. Currency (15) and StrikeCurrency (947)
+    Currency,
+    3,
+    u8::is_ascii_uppercase
+);
+
+code_type!(
+    /// ISO 10383 market identifier code, e.g. SecurityExchange (207)
+    Mic,
+    4,
+    is_mic_byte
+);
+
+code_type!(
+    /// ISO 3166 two-letter country code, e.g. CountryOfIssue (470)
+    Country,
+    2,
+    u8::is_ascii_uppercase
+);
+
+#[inline]
+fn is_mic_byte(b: &u8) -> bool {
+    b.is_ascii_uppercase() || b.is_ascii_digit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        let usd: Currency = "USD".parse().unwrap();
+        assert_eq!(usd.as_bytes(), b"USD");
+        assert_eq!(usd.to_string(), "USD");
+        assert_eq!(Mic::try_from(&b"XLON"[..]).unwrap().as_str(), "XLON");
+        assert_eq!("GB".parse::<Country>().unwrap().as_str(), "GB");
+        assert!("XS2A".parse::<Mic>().is_ok());
+
+        for value in ["US", "USDX", "usd", "US1", ""] {
+            assert!(value.parse::<Currency>().is_err(), "{:?} should be rejected", value);
+        }
+        assert!("XLO".parse::<Mic>().is_err());
+        assert!("xlon".parse::<Mic>().is_err());
+        assert!("GBR".parse::<Country>().is_err());
+        assert_eq!(std::mem::size_of::<Option<Mic>>(), 5);
+    }
+}
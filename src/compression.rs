@@ -0,0 +1,167 @@
+use crate::error::FixError;
+use crate::message::FixMessage;
+use bytes::Bytes;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Largest message a compressed envelope may expand to, so a corrupt or
+/// hostile envelope cannot exhaust memory
+pub const MAX_DECOMPRESSED_LEN: usize = 16 * 1024 * 1024;
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Start of a zstd frame
+pub(crate) const ZSTD_MAGIC: [u8; 4] = 0xFD2F_B528u32.to_le_bytes();
+
+/// Start of a zstd skippable frame, which decoders pass over. A compressed
+/// journal puts one before each block to say which messages it holds.
+pub(crate) const SKIPPABLE_MAGIC: [u8; 4] = 0x184D_2A50u32.to_le_bytes();
+
+/// Algorithm of a compressed envelope around an encoded message, for
+/// internal distribution links only. The envelope is not FIX: never send it
+/// to a FIX counterparty, which expects the output of `FixMessage::encode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Compresses `data` into a new envelope
+    pub fn compress(self, data: &[u8]) -> Result<Bytes, FixError> {
+        let compressed = match self {
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).map_err(compression_error)?;
+                encoder.finish().map_err(compression_error)?
+            }
+            Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL).map_err(compression_error)?,
+        };
+        Ok(Bytes::from(compressed))
+    }
+
+    /// Expands an envelope made by `compress`, failing if it would exceed
+    /// `MAX_DECOMPRESSED_LEN`
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, FixError> {
+        let mut out = Vec::new();
+        let limit = MAX_DECOMPRESSED_LEN as u64 + 1;
+        match self {
+            Compression::Gzip => flate2::read::GzDecoder::new(data).take(limit).read_to_end(&mut out),
+            Compression::Zstd => zstd::Decoder::new(data)
+                .and_then(|decoder| decoder.take(limit).read_to_end(&mut out)),
+        }
+        .map_err(compression_error)?;
+
+        if out.len() > MAX_DECOMPRESSED_LEN {
+            return Err(FixError::MessageTooLarge { len: out.len(), max: MAX_DECOMPRESSED_LEN });
+        }
+        Ok(out)
+    }
+}
+
+/// Compression of the message journal and of capture logs. Records are
+/// written in blocks, each one zstd frame, so a file can be read from any
+/// block without decompressing what precedes it, and the whole file is
+/// still a valid zstd stream that `zstd -d` restores to the plain layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockCompression {
+    pub level: i32,
+    /// Records per block. Larger blocks compress better but make a reader
+    /// decompress more to reach one record, and a crash loses the block
+    /// being filled.
+    pub block_records: usize,
+}
+
+impl BlockCompression {
+    pub fn new(level: i32, block_records: usize) -> Self {
+        Self { level, block_records: block_records.max(1) }
+    }
+
+    pub(crate) fn compress(&self, records: &[u8]) -> io::Result<Vec<u8>> {
+        zstd::bulk::compress(records, self.level)
+    }
+}
+
+impl Default for BlockCompression {
+    fn default() -> Self {
+        Self::new(ZSTD_LEVEL, 256)
+    }
+}
+
+/// Whether a file starting with `prefix` was written with `BlockCompression`
+pub(crate) fn is_compressed(prefix: &[u8]) -> bool {
+    prefix.starts_with(&ZSTD_MAGIC) || prefix.starts_with(&SKIPPABLE_MAGIC)
+}
+
+/// Wraps `reader` in a zstd decoder if its content is compressed, telling
+/// the two apart by the magic bytes at the start
+pub fn decompressing_reader<'a>(mut reader: impl BufRead + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    if is_compressed(reader.fill_buf()?) {
+        Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+fn compression_error(err: std::io::Error) -> FixError {
+    FixError::Compression(err.to_string())
+}
+
+impl FixMessage {
+    /// Encodes the message and wraps it in a compressed envelope. This is
+    /// not a FIX wire format; use `encode` for anything sent to a FIX
+    /// counterparty.
+    pub fn encode_compressed(&self, algo: Compression) -> Result<Bytes, FixError> {
+        algo.compress(&self.encode()?)
+    }
+
+    /// Unwraps an envelope made by `encode_compressed` and decodes the
+    /// message inside, with the usual BodyLength and CheckSum checks
+    pub fn decode_compressed(data: &[u8], algo: Compression) -> Result<Self, FixError> {
+        Self::decode(&algo.decompress(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::tags::{fix_version, msg_type, Tag};
+
+    #[test]
+    fn test_round_trip() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::new(Tag::Text.value(), b"repeated ".repeat(50)));
+        msg.set_encoded_text_utf8("\u{1}binary\u{0}data");
+        let encoded = msg.encode().unwrap();
+
+        for algo in [Compression::Gzip, Compression::Zstd] {
+            let envelope = msg.encode_compressed(algo).unwrap();
+            assert!(envelope.len() < encoded.len());
+            assert!(!envelope.starts_with(b"8=FIX"));
+
+            let decoded = FixMessage::decode_compressed(&envelope, algo).unwrap();
+            assert_eq!(decoded.encode().unwrap(), encoded);
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_envelopes() {
+        let envelope = Compression::Gzip.compress(b"8=FIX.4.4\x01").unwrap();
+        assert!(matches!(
+            FixMessage::decode_compressed(&envelope, Compression::Zstd),
+            Err(FixError::Compression(_))
+        ));
+        assert!(matches!(
+            FixMessage::decode_compressed(b"8=FIX.4.4\x019=5\x01", Compression::Gzip),
+            Err(FixError::Compression(_))
+        ));
+
+        let bomb = Compression::Zstd.compress(&vec![b'A'; MAX_DECOMPRESSED_LEN + 1]).unwrap();
+        assert!(matches!(
+            Compression::Zstd.decompress(&bomb),
+            Err(FixError::MessageTooLarge { .. })
+        ));
+    }
+}
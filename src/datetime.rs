@@ -0,0 +1,993 @@
+use crate::error::FixError;
+use crate::field::{impl_text_traits, parse_uint, FieldValue};
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+#[inline]
+pub(crate) fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+#[inline]
+pub(crate) fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+pub(crate) fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    let y = year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`
+pub(crate) fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parses a short run of ASCII digits
+#[inline]
+pub(crate) fn parse_digits(value: &[u8]) -> Result<u32, FixError> {
+    parse_uint(value).and_then(|v| u32::try_from(v).map_err(|_| FixError::InvalidFieldValue))
+}
+
+#[inline]
+pub(crate) fn push_digits(buf: &mut SmallVec<[u8; 32]>, value: u32, width: usize) {
+    let mut digits = [b'0'; 10];
+    let mut v = value;
+    for slot in digits[10 - width..].iter_mut().rev() {
+        *slot = b'0' + (v % 10) as u8;
+        v /= 10;
+    }
+    buf.extend_from_slice(&digits[10 - width..]);
+}
+
+/// Parses "YYYYMMDD", checking the day exists in the given month
+pub(crate) fn parse_date(value: &[u8]) -> Result<(u16, u8, u8), FixError> {
+    if value.len() != 8 {
+        return Err(FixError::InvalidFieldValue);
+    }
+    let year = parse_digits(&value[0..4])? as u16;
+    let month = parse_digits(&value[4..6])? as u8;
+    let day = parse_digits(&value[6..8])? as u8;
+    check_date(year, month, day)?;
+    Ok((year, month, day))
+}
+
+#[inline]
+pub(crate) fn check_date(year: u16, month: u8, day: u8) -> Result<(), FixError> {
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(FixError::InvalidFieldValue);
+    }
+    Ok(())
+}
+
+/// Parses "HH:MM:SS" with an optional fraction of 3, 6 or 9 digits, returning
+/// (hour, minute, second, nanos, fraction digits)
+pub(crate) fn parse_time(value: &[u8]) -> Result<(u8, u8, u8, u32, u8), FixError> {
+    if value.len() < 8 || value[2] != b':' || value[5] != b':' {
+        return Err(FixError::InvalidFieldValue);
+    }
+    let hour = parse_digits(&value[0..2])? as u8;
+    let minute = parse_digits(&value[3..5])? as u8;
+    let second = parse_digits(&value[6..8])? as u8;
+    // FIX allows 60 to represent a leap second
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(FixError::InvalidFieldValue);
+    }
+
+    let (nanos, precision) = match &value[8..] {
+        [] => (0, 0),
+        [b'.', fraction @ ..] if matches!(fraction.len(), 3 | 6 | 9) => {
+            let scale = 10u32.pow(9 - fraction.len() as u32);
+            (parse_digits(fraction)? * scale, fraction.len() as u8)
+        }
+        _ => return Err(FixError::InvalidFieldValue),
+    };
+
+    Ok((hour, minute, second, nanos, precision))
+}
+
+pub(crate) fn write_time(
+    buf: &mut SmallVec<[u8; 32]>,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanos: u32,
+    precision: u8,
+) {
+    push_digits(buf, hour as u32, 2);
+    buf.push(b':');
+    push_digits(buf, minute as u32, 2);
+    buf.push(b':');
+    push_digits(buf, second as u32, 2);
+    if precision > 0 {
+        buf.push(b'.');
+        push_digits(buf, nanos / 10u32.pow(9 - precision as u32), precision as usize);
+    }
+}
+
+macro_rules! date_type {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name {
+            year: u16,
+            month: u8,
+            day: u8,
+        }
+
+        impl $name {
+            pub fn new(year: u16, month: u8, day: u8) -> Result<Self, FixError> {
+                if year > 9999 {
+                    return Err(FixError::InvalidFieldValue);
+                }
+                check_date(year, month, day)?;
+                Ok(Self { year, month, day })
+            }
+
+            #[inline]
+            pub fn year(&self) -> u16 {
+                self.year
+            }
+
+            #[inline]
+            pub fn month(&self) -> u8 {
+                self.month
+            }
+
+            #[inline]
+            pub fn day(&self) -> u8 {
+                self.day
+            }
+        }
+
+        impl FieldValue for $name {
+            fn parse(value: &[u8]) -> Result<Self, FixError> {
+                let (year, month, day) = parse_date(value)?;
+                Ok(Self { year, month, day })
+            }
+
+            fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+                push_digits(buf, self.year as u32, 4);
+                push_digits(buf, self.month as u32, 2);
+                push_digits(buf, self.day as u32, 2);
+            }
+        }
+
+        impl_text_traits!($name);
+    };
+}
+
+date_type!(
+    /// LocalMktDate ("YYYYMMDD") in the local time of the market center,
+    /// e.g. SettlDate (64) and TradeDate (75)
+    LocalMktDate
+);
+
+date_type!(
+    /// UTCDateOnly ("YYYYMMDD")
+    UtcDateOnly
+);
+
+// Equality, ordering and hashing by `sort_key`, the time value alone, so the
+// precision kept for formatting does not take part: "12:00:00" and
+// "12:00:00.000" are equal
+macro_rules! ord_by_sort_key {
+    ($name:ident) => {
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.sort_key() == other.sort_key()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.sort_key().cmp(&other.sort_key())
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.sort_key().hash(state);
+            }
+        }
+    };
+}
+
+/// UTCTimeOnly ("HH:MM:SS[.sss]"), e.g. MDEntryTime (273). The number of
+/// fractional digits received is kept so the value formats back unchanged,
+/// but is ignored by comparison: "12:00:00" equals "12:00:00.000".
+#[derive(Debug, Clone, Copy)]
+pub struct UtcTimeOnly {
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanos: u32,
+    precision: u8,
+}
+
+impl UtcTimeOnly {
+    pub fn new(hour: u8, minute: u8, second: u8) -> Result<Self, FixError> {
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(Self { hour, minute, second, nanos: 0, precision: 0 })
+    }
+
+    /// Adds milliseconds, which are then always written
+    pub fn with_millis(mut self, millis: u16) -> Result<Self, FixError> {
+        if millis > 999 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        self.nanos = millis as u32 * 1_000_000;
+        self.precision = 3;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    #[inline]
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    #[inline]
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    #[inline]
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    #[inline]
+    fn sort_key(&self) -> (u8, u8, u8, u32) {
+        (self.hour, self.minute, self.second, self.nanos)
+    }
+}
+
+ord_by_sort_key!(UtcTimeOnly);
+
+impl FieldValue for UtcTimeOnly {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        let (hour, minute, second, nanos, precision) = parse_time(value)?;
+        Ok(Self { hour, minute, second, nanos, precision })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        write_time(buf, self.hour, self.minute, self.second, self.nanos, self.precision);
+    }
+}
+
+impl_text_traits!(UtcTimeOnly);
+
+/// MonthYear as used by MaturityMonthYear (200): "YYYYMM", "YYYYMMDD" or
+/// "YYYYMMwN" where N is the week of the month (1-5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonthYear {
+    Month { year: u16, month: u8 },
+    Day { year: u16, month: u8, day: u8 },
+    Week { year: u16, month: u8, week: u8 },
+}
+
+impl MonthYear {
+    #[inline]
+    pub fn year(&self) -> u16 {
+        match *self {
+            MonthYear::Month { year, .. } | MonthYear::Day { year, .. } | MonthYear::Week { year, .. } => year,
+        }
+    }
+
+    #[inline]
+    pub fn month(&self) -> u8 {
+        match *self {
+            MonthYear::Month { month, .. } | MonthYear::Day { month, .. } | MonthYear::Week { month, .. } => month,
+        }
+    }
+
+    // A month-only value sorts before any day or week in the same month; a
+    // week sorts from its first possible day.
+    #[inline]
+    fn sort_key(&self) -> (u16, u8, u8, u8) {
+        match *self {
+            MonthYear::Month { year, month } => (year, month, 0, 0),
+            MonthYear::Day { year, month, day } => (year, month, day, 1),
+            MonthYear::Week { year, month, week } => (year, month, (week - 1) * 7 + 1, 2),
+        }
+    }
+}
+
+impl Ord for MonthYear {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for MonthYear {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FieldValue for MonthYear {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        if value.len() < 6 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let year = parse_digits(&value[0..4])? as u16;
+        let month = parse_digits(&value[4..6])? as u8;
+        if !(1..=12).contains(&month) {
+            return Err(FixError::InvalidFieldValue);
+        }
+
+        match &value[6..] {
+            [] => Ok(MonthYear::Month { year, month }),
+            [b'w', week] => {
+                let week = parse_digits(&[*week])? as u8;
+                if !(1..=5).contains(&week) {
+                    return Err(FixError::InvalidFieldValue);
+                }
+                Ok(MonthYear::Week { year, month, week })
+            }
+            day @ [_, _] => {
+                let day = parse_digits(day)? as u8;
+                check_date(year, month, day)?;
+                Ok(MonthYear::Day { year, month, day })
+            }
+            _ => Err(FixError::InvalidFieldValue),
+        }
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        push_digits(buf, self.year() as u32, 4);
+        push_digits(buf, self.month() as u32, 2);
+        match *self {
+            MonthYear::Month { .. } => {}
+            MonthYear::Day { day, .. } => push_digits(buf, day as u32, 2),
+            MonthYear::Week { week, .. } => {
+                buf.push(b'w');
+                push_digits(buf, week as u32, 1);
+            }
+        }
+    }
+}
+
+impl_text_traits!(MonthYear);
+
+/// UTCTimestamp ("YYYYMMDD-HH:MM:SS[.sss]"), e.g. SendingTime (52) and
+/// TransactTime (60). Fractions of 3, 6 or 9 digits are accepted and the
+/// precision received is kept for formatting; comparison ignores it, so
+/// "20240101-12:00:00" equals "20240101-12:00:00.000".
+#[derive(Debug, Clone, Copy)]
+pub struct UtcTimestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanos: u32,
+    precision: u8,
+}
+
+impl UtcTimestamp {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Result<Self, FixError> {
+        UtcDateOnly::new(year, month, day)?;
+        UtcTimeOnly::new(hour, minute, second)?;
+        Ok(Self { year, month, day, hour, minute, second, nanos: 0, precision: 0 })
+    }
+
+    /// Sets the sub-second part, written with `precision` digits (0, 3, 6 or 9)
+    pub fn with_nanos(mut self, nanos: u32, precision: u8) -> Result<Self, FixError> {
+        if nanos >= NANOS_PER_SECOND as u32 || !matches!(precision, 0 | 3 | 6 | 9) {
+            return Err(FixError::InvalidFieldValue);
+        }
+        self.nanos = nanos;
+        self.precision = precision;
+        Ok(self)
+    }
+
+    /// The same instant written with `precision` digits (0, 3, 6 or 9),
+    /// dropping any finer ones
+    pub fn with_precision(self, precision: u8) -> Result<Self, FixError> {
+        let truncate = 10u32.pow(9 - precision.min(9) as u32);
+        self.with_nanos(self.nanos / truncate * truncate, precision)
+    }
+
+    /// Parses the RFC 3339 UTC form used by many log formats,
+    /// "YYYY-MM-DDTHH:MM:SS[.sss]Z", also accepting a "+00:00" offset
+    pub fn parse_rfc3339(value: &[u8]) -> Result<Self, FixError> {
+        let time_end = value
+            .strip_suffix(b"Z")
+            .or_else(|| value.strip_suffix(b"+00:00"))
+            .ok_or(FixError::InvalidFieldValue)?;
+        if time_end.len() < 19 || time_end[4] != b'-' || time_end[7] != b'-' || !matches!(time_end[10], b'T' | b't') {
+            return Err(FixError::InvalidFieldValue);
+        }
+
+        let date = [&time_end[0..4], &time_end[5..7], &time_end[8..10]].concat();
+        let (year, month, day) = parse_date(&date)?;
+        let (hour, minute, second, nanos, precision) = parse_time(&time_end[11..])?;
+        Self::new(year, month, day, hour, minute, second)?.with_nanos(nanos, precision)
+    }
+
+    /// Formats as RFC 3339 UTC, "YYYY-MM-DDTHH:MM:SS[.sss]Z", keeping the
+    /// precision
+    pub fn to_rfc3339(&self) -> String {
+        format!("{:04}-{:02}-{:02}T{}Z", self.year, self.month, self.day, self.time())
+    }
+
+    /// Builds a timestamp from nanoseconds since the Unix epoch
+    pub fn from_unix_nanos(nanos: i64, precision: u8) -> Result<Self, FixError> {
+        let seconds = nanos.div_euclid(NANOS_PER_SECOND);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+        if !(0..=9999).contains(&year) {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let secs_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+        let truncate = 10u32.pow(9 - precision.min(9) as u32);
+        Self::new(
+            year as u16,
+            month,
+            day,
+            (secs_of_day / 3600) as u8,
+            (secs_of_day / 60 % 60) as u8,
+            (secs_of_day % 60) as u8,
+        )?
+        .with_nanos(nanos.rem_euclid(NANOS_PER_SECOND) as u32 / truncate * truncate, precision)
+    }
+
+    /// Nanoseconds since the Unix epoch. A leap second (60) counts as the
+    /// first second of the next minute.
+    pub fn unix_nanos(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = days * SECONDS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64;
+        seconds * NANOS_PER_SECOND + self.nanos as i64
+    }
+
+    #[inline]
+    pub fn date(&self) -> UtcDateOnly {
+        UtcDateOnly { year: self.year, month: self.month, day: self.day }
+    }
+
+    #[inline]
+    pub fn time(&self) -> UtcTimeOnly {
+        UtcTimeOnly {
+            hour: self.hour,
+            minute: self.minute,
+            second: self.second,
+            nanos: self.nanos,
+            precision: self.precision,
+        }
+    }
+
+    #[inline]
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    #[inline]
+    fn sort_key(&self) -> (u16, u8, u8, u8, u8, u8, u32) {
+        (self.year, self.month, self.day, self.hour, self.minute, self.second, self.nanos)
+    }
+}
+
+ord_by_sort_key!(UtcTimestamp);
+
+/// Keeps the shortest precision that holds the nanoseconds exactly. A
+/// chrono leap second becomes second 60.
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for UtcTimestamp {
+    type Error = FixError;
+
+    fn try_from(value: chrono::NaiveDateTime) -> Result<Self, FixError> {
+        use chrono::{Datelike, Timelike};
+
+        let year = u16::try_from(value.year()).map_err(|_| FixError::InvalidFieldValue)?;
+        if year > 9999 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let (second, nanos) = match value.nanosecond() {
+            leap @ 1_000_000_000.. => (60, leap - 1_000_000_000),
+            nanos => (value.second() as u8, nanos),
+        };
+        let precision = [0, 3, 6, 9].into_iter().find(|&p| nanos % 10u32.pow(9 - p as u32) == 0).unwrap_or(9);
+        Self::new(year, value.month() as u8, value.day() as u8, value.hour() as u8, value.minute() as u8, second)?
+            .with_nanos(nanos, precision)
+    }
+}
+
+/// Second 60 becomes a chrono leap second
+#[cfg(feature = "chrono")]
+impl From<UtcTimestamp> for chrono::NaiveDateTime {
+    fn from(value: UtcTimestamp) -> Self {
+        let (second, nanos) = match value.second {
+            60 => (59, value.nanos + 1_000_000_000),
+            second => (second, value.nanos),
+        };
+        chrono::NaiveDate::from_ymd_opt(value.year as i32, value.month as u32, value.day as u32)
+            .and_then(|date| date.and_hms_nano_opt(value.hour as u32, value.minute as u32, second as u32, nanos))
+            .expect("a UtcTimestamp is a valid date and time")
+    }
+}
+
+impl FieldValue for UtcTimestamp {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        if value.len() < 17 || value[8] != b'-' {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let (year, month, day) = parse_date(&value[..8])?;
+        let (hour, minute, second, nanos, precision) = parse_time(&value[9..])?;
+        Ok(Self { year, month, day, hour, minute, second, nanos, precision })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        self.date().write(buf);
+        buf.push(b'-');
+        self.time().write(buf);
+    }
+}
+
+impl_text_traits!(UtcTimestamp);
+
+/// The zone suffix of a TZTimestamp or TZTimeOnly. The spec reads a value
+/// without a suffix as local time, so that case is kept distinct from UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TzOffset {
+    /// No suffix: local time at an unstated offset
+    Local,
+    /// "Z"
+    Utc,
+    /// "+hh" or "+hh:mm" (or '-'); `minutes` is the signed offset from UTC
+    Offset { minutes: i16, with_minutes: bool },
+}
+
+impl TzOffset {
+    /// The offset from UTC in minutes, unknown for local time
+    #[inline]
+    pub fn minutes(&self) -> Option<i16> {
+        match *self {
+            TzOffset::Local => None,
+            TzOffset::Utc => Some(0),
+            TzOffset::Offset { minutes, .. } => Some(minutes),
+        }
+    }
+
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        let (sign, rest) = match value {
+            [] => return Ok(TzOffset::Local),
+            b"Z" => return Ok(TzOffset::Utc),
+            [b'+', rest @ ..] => (1, rest),
+            [b'-', rest @ ..] => (-1, rest),
+            _ => return Err(FixError::InvalidFieldValue),
+        };
+        let (hours, mins, with_minutes) = match rest {
+            [h1, h2] => (parse_digits(&[*h1, *h2])?, 0, false),
+            [h1, h2, b':', m1, m2] => (parse_digits(&[*h1, *h2])?, parse_digits(&[*m1, *m2])?, true),
+            _ => return Err(FixError::InvalidFieldValue),
+        };
+        if hours > 14 || mins > 59 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(TzOffset::Offset { minutes: sign * (hours * 60 + mins) as i16, with_minutes })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        match *self {
+            TzOffset::Local => {}
+            TzOffset::Utc => buf.push(b'Z'),
+            TzOffset::Offset { minutes, with_minutes } => {
+                buf.push(if minutes < 0 { b'-' } else { b'+' });
+                let minutes = minutes.unsigned_abs() as u32;
+                push_digits(buf, minutes / 60, 2);
+                if with_minutes {
+                    buf.push(b':');
+                    push_digits(buf, minutes % 60, 2);
+                }
+            }
+        }
+    }
+}
+
+/// The time-of-day part shared by TZTimestamp and TZTimeOnly:
+/// "HH:MM[:SS[.sss]]" followed by an optional zone suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TzTime {
+    hour: u8,
+    minute: u8,
+    second: Option<u8>,
+    nanos: u32,
+    precision: u8,
+    offset: TzOffset,
+}
+
+impl TzTime {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        if value.len() < 5 || value[2] != b':' {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let hour = parse_digits(&value[0..2])? as u8;
+        let minute = parse_digits(&value[3..5])? as u8;
+        if hour > 23 || minute > 59 {
+            return Err(FixError::InvalidFieldValue);
+        }
+
+        let suffix_at = value
+            .iter()
+            .position(|&b| matches!(b, b'Z' | b'+' | b'-'))
+            .unwrap_or(value.len());
+        let offset = TzOffset::parse(&value[suffix_at..])?;
+
+        let (second, nanos, precision) = match &value[5..suffix_at] {
+            [] => (None, 0, 0),
+            [b':', ..] => {
+                let (_, _, second, nanos, precision) = parse_time(&value[..suffix_at])?;
+                (Some(second), nanos, precision)
+            }
+            _ => return Err(FixError::InvalidFieldValue),
+        };
+
+        Ok(Self { hour, minute, second, nanos, precision, offset })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        push_digits(buf, self.hour as u32, 2);
+        buf.push(b':');
+        push_digits(buf, self.minute as u32, 2);
+        if let Some(second) = self.second {
+            buf.push(b':');
+            push_digits(buf, second as u32, 2);
+            if self.precision > 0 {
+                buf.push(b'.');
+                push_digits(buf, self.nanos / 10u32.pow(9 - self.precision as u32), self.precision as usize);
+            }
+        }
+        self.offset.write(buf);
+    }
+
+    // Seconds since midnight in local time, nanoseconds aside
+    #[inline]
+    fn seconds_of_day(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second.unwrap_or(0) as i64
+    }
+}
+
+/// TZTimestamp ("YYYYMMDD-HH:MM[:SS[.sss]][Z | +hh[:mm] | -hh[:mm]]"), e.g.
+/// TZTransactTime (1132). The original offset and form are kept so the value
+/// formats back exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TzTimestamp {
+    date: UtcDateOnly,
+    time: TzTime,
+}
+
+impl TzTimestamp {
+    #[inline]
+    pub fn offset(&self) -> TzOffset {
+        self.time.offset
+    }
+
+    /// Converts to UTC, or `None` when the value carries no offset and is
+    /// therefore in an unstated local time
+    pub fn to_utc(&self) -> Option<UtcTimestamp> {
+        self.time.offset.minutes().and_then(|minutes| self.utc_at_offset(minutes).ok())
+    }
+
+    /// Converts to UTC treating the value as being at `local_offset_minutes`
+    /// from UTC when it has no offset of its own
+    pub fn to_utc_assuming(&self, local_offset_minutes: i16) -> Result<UtcTimestamp, FixError> {
+        self.utc_at_offset(self.time.offset.minutes().unwrap_or(local_offset_minutes))
+    }
+
+    fn utc_at_offset(&self, minutes: i16) -> Result<UtcTimestamp, FixError> {
+        let days = days_from_civil(self.date.year, self.date.month, self.date.day);
+        let seconds = days * SECONDS_PER_DAY + self.time.seconds_of_day() - minutes as i64 * 60;
+        let precision = if self.time.second.is_some() { self.time.precision } else { 0 };
+        UtcTimestamp::from_unix_nanos(seconds * NANOS_PER_SECOND + self.time.nanos as i64, precision)
+    }
+}
+
+impl FieldValue for TzTimestamp {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        if value.len() < 14 || value[8] != b'-' {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(Self {
+            date: UtcDateOnly::parse(&value[..8])?,
+            time: TzTime::parse(&value[9..])?,
+        })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        self.date.write(buf);
+        buf.push(b'-');
+        self.time.write(buf);
+    }
+}
+
+impl_text_traits!(TzTimestamp);
+
+/// TZTimeOnly ("HH:MM[:SS[.sss]][Z | +hh[:mm] | -hh[:mm]]")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TzTimeOnly {
+    time: TzTime,
+}
+
+impl TzTimeOnly {
+    #[inline]
+    pub fn offset(&self) -> TzOffset {
+        self.time.offset
+    }
+
+    /// The UTC time of day, wrapping past midnight, or `None` for local time
+    pub fn to_utc(&self) -> Option<UtcTimeOnly> {
+        let minutes = self.time.offset.minutes()?;
+        let seconds = (self.time.seconds_of_day() - minutes as i64 * 60).rem_euclid(SECONDS_PER_DAY);
+        let time = UtcTimeOnly::new((seconds / 3600) as u8, (seconds / 60 % 60) as u8, (seconds % 60) as u8).ok()?;
+        Some(UtcTimeOnly { nanos: self.time.nanos, precision: self.time.precision, ..time })
+    }
+}
+
+impl FieldValue for TzTimeOnly {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        Ok(Self { time: TzTime::parse(value)? })
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        self.time.write(buf);
+    }
+}
+
+impl_text_traits!(TzTimeOnly);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_round_trip() {
+        let date: LocalMktDate = "20240229".parse().unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+        assert_eq!(date.to_string(), "20240229");
+
+        let date = UtcDateOnly::new(2025, 1, 5).unwrap();
+        assert_eq!(date.to_string(), "20250105");
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        for value in ["20230229", "20241301", "20240100", "2024011", "2024-1-01", "20240132"] {
+            assert!(
+                matches!(value.parse::<LocalMktDate>(), Err(FixError::InvalidFieldValue)),
+                "{} should be rejected",
+                value
+            );
+        }
+        assert!(UtcDateOnly::new(2024, 4, 31).is_err());
+    }
+
+    #[test]
+    fn test_time_only_round_trip() {
+        for value in ["12:30:45", "12:30:45.123", "23:59:60.000", "00:00:00.123456"] {
+            let time: UtcTimeOnly = value.parse().unwrap();
+            assert_eq!(time.to_string(), value);
+        }
+
+        let time: UtcTimeOnly = "08:01:02.500".parse().unwrap();
+        assert_eq!((time.hour(), time.minute(), time.second()), (8, 1, 2));
+        assert_eq!(time.nanos(), 500_000_000);
+
+        let time = UtcTimeOnly::new(9, 5, 0).unwrap().with_millis(7).unwrap();
+        assert_eq!(time.to_string(), "09:05:00.007");
+    }
+
+    #[test]
+    fn test_invalid_times() {
+        for value in ["24:00:00", "12:60:00", "12:00:61", "12:00", "12:00:00.", "12:00:00.12", "20240101-12:00:00"] {
+            assert!(value.parse::<UtcTimeOnly>().is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let a: LocalMktDate = "20241231".parse().unwrap();
+        let b: LocalMktDate = "20250101".parse().unwrap();
+        assert!(a < b);
+
+        let a: UtcTimeOnly = "09:59:59.999".parse().unwrap();
+        let b: UtcTimeOnly = "10:00:00".parse().unwrap();
+        assert!(a < b);
+
+        // The precision kept for formatting does not take part
+        fn hash(value: &impl Hash) -> u64 {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+        let c: UtcTimeOnly = "10:00:00.000".parse().unwrap();
+        assert_eq!(b, c);
+        assert_eq!(b.cmp(&c), Ordering::Equal);
+        assert_eq!(hash(&b), hash(&c));
+        assert_ne!(b.to_string(), c.to_string());
+
+        let a: UtcTimestamp = "20240101-12:00:00".parse().unwrap();
+        let b: UtcTimestamp = "20240101-12:00:00.000000".parse().unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(hash(&a), hash(&b));
+        assert!(a < "20240101-12:00:00.001".parse().unwrap());
+    }
+
+    #[test]
+    fn test_month_year_forms() {
+        let month: MonthYear = "202512".parse().unwrap();
+        assert_eq!(month, MonthYear::Month { year: 2025, month: 12 });
+
+        let day: MonthYear = "20251219".parse().unwrap();
+        assert_eq!(day, MonthYear::Day { year: 2025, month: 12, day: 19 });
+
+        let week: MonthYear = "202512w3".parse().unwrap();
+        assert_eq!(week, MonthYear::Week { year: 2025, month: 12, week: 3 });
+
+        for value in ["202512", "20251219", "202512w3"] {
+            assert_eq!(value.parse::<MonthYear>().unwrap().to_string(), value);
+        }
+    }
+
+    #[test]
+    fn test_month_year_rejects_malformed() {
+        for value in ["2025w3", "202513", "20251232", "202512w6", "202512w0", "2025123", "202512W3"] {
+            assert!(value.parse::<MonthYear>().is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_month_year_ordering() {
+        let mut values: Vec<MonthYear> = ["202603", "202512w3", "20251219", "202512", "20251201"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        values.sort();
+
+        let sorted: Vec<_> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(sorted, ["202512", "20251201", "202512w3", "20251219", "202603"]);
+    }
+
+    #[test]
+    fn test_utc_timestamp_round_trip() {
+        for value in ["20240101-12:00:00", "20240101-12:00:00.123", "20240229-23:59:59.123456"] {
+            assert_eq!(value.parse::<UtcTimestamp>().unwrap().to_string(), value);
+        }
+        for value in ["20240101-12:00", "20240101 12:00:00", "20240230-12:00:00", "20240101-25:00:00"] {
+            assert!(value.parse::<UtcTimestamp>().is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_utc_timestamp_unix_nanos() {
+        let ts: UtcTimestamp = "20240301-00:00:01.500".parse().unwrap();
+        assert_eq!(ts.unix_nanos(), 1_709_251_201_500_000_000);
+        assert_eq!(UtcTimestamp::from_unix_nanos(ts.unix_nanos(), 3).unwrap(), ts);
+
+        let epoch = UtcTimestamp::from_unix_nanos(0, 0).unwrap();
+        assert_eq!(epoch.to_string(), "19700101-00:00:00");
+        let before = UtcTimestamp::from_unix_nanos(-1, 9).unwrap();
+        assert_eq!(before.to_string(), "19691231-23:59:59.999999999");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_utc_timestamp_chrono() {
+        use chrono::{NaiveDate, NaiveDateTime, Timelike};
+
+        let naive = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_micro_opt(12, 30, 5, 250_000).unwrap();
+        let ts = UtcTimestamp::try_from(naive).unwrap();
+        assert_eq!(ts.to_string(), "20240301-12:30:05.250");
+        assert_eq!(NaiveDateTime::from(ts), naive);
+        let nanos = naive.with_nanosecond(250_000_001).unwrap();
+        assert_eq!(UtcTimestamp::try_from(nanos).unwrap().to_string(), "20240301-12:30:05.250000001");
+
+        let leap: UtcTimestamp = "20161231-23:59:60.500".parse().unwrap();
+        assert_eq!(UtcTimestamp::try_from(NaiveDateTime::from(leap)).unwrap(), leap);
+        let far = NaiveDate::from_ymd_opt(10000, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert!(UtcTimestamp::try_from(far).is_err());
+        assert!(UtcTimestamp::try_from(NaiveDate::from_ymd_opt(-1, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_utc_timestamp_rfc3339() {
+        let ts = UtcTimestamp::parse_rfc3339(b"2024-01-01T12:00:00.123456Z").unwrap();
+        assert_eq!(ts.to_string(), "20240101-12:00:00.123456");
+        assert_eq!(ts.to_rfc3339(), "2024-01-01T12:00:00.123456Z");
+        assert_eq!(UtcTimestamp::parse_rfc3339(b"2024-01-01T12:00:00+00:00").unwrap().to_string(), "20240101-12:00:00");
+        for value in ["2024-01-01T12:00:00", "2024-01-01T12:00:00+01:00", "2024-01-01 12:00:00Z", "20240101-12:00:00Z"] {
+            assert!(UtcTimestamp::parse_rfc3339(value.as_bytes()).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_tz_timestamp_round_trip() {
+        for value in [
+            "20240101-12:00:00+08:00",
+            "20240101-12:00:00+08",
+            "20240101-12:00:00.123-05:30",
+            "20240101-12:00Z",
+            "20240101-12:00:00",
+        ] {
+            assert_eq!(value.parse::<TzTimestamp>().unwrap().to_string(), value);
+        }
+        for value in ["20240101-12:00:00+8", "20240101-12:00:00+15:00", "20240101-12:00:00Z+01", "20240101-12"] {
+            assert!(value.parse::<TzTimestamp>().is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_tz_timestamp_to_utc() {
+        let ts: TzTimestamp = "20240101-12:00:00+08:00".parse().unwrap();
+        assert_eq!(ts.offset().minutes(), Some(480));
+        assert_eq!(ts.to_utc().unwrap().to_string(), "20240101-04:00:00");
+
+        let ts: TzTimestamp = "20240101-02:00:00.250+05:30".parse().unwrap();
+        assert_eq!(ts.to_utc().unwrap().to_string(), "20231231-20:30:00.250");
+
+        let ts: TzTimestamp = "20240229-23:30-01".parse().unwrap();
+        assert_eq!(ts.to_utc().unwrap().to_string(), "20240301-00:30:00");
+
+        // Without an offset the value is local time and is not assumed to be UTC
+        let ts: TzTimestamp = "20240101-12:00:00".parse().unwrap();
+        assert_eq!(ts.offset(), TzOffset::Local);
+        assert!(ts.to_utc().is_none());
+        assert_eq!(ts.to_utc_assuming(60).unwrap().to_string(), "20240101-11:00:00");
+    }
+
+    #[test]
+    fn test_tz_time_only() {
+        let time: TzTimeOnly = "07:30:00-02:00".parse().unwrap();
+        assert_eq!(time.to_string(), "07:30:00-02:00");
+        assert_eq!(time.to_utc().unwrap().to_string(), "09:30:00");
+
+        let time: TzTimeOnly = "01:00+08".parse().unwrap();
+        assert_eq!(time.to_utc().unwrap().to_string(), "17:00:00");
+
+        assert!("12:00".parse::<TzTimeOnly>().unwrap().to_utc().is_none());
+    }
+}
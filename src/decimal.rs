@@ -0,0 +1,623 @@
+use crate::error::FixError;
+use crate::field::{impl_text_traits, FieldValue};
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// Largest number of decimal places a `FixDecimal` carries
+pub const MAX_SCALE: u8 = 18;
+
+const POW10: [i64; 19] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+    1_000_000_000_000,
+    10_000_000_000_000,
+    100_000_000_000_000,
+    1_000_000_000_000_000,
+    10_000_000_000_000_000,
+    100_000_000_000_000_000,
+    1_000_000_000_000_000_000,
+];
+
+/// How `FixDecimal::round` treats discarded digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Towards zero
+    Down,
+    /// Away from zero
+    Up,
+    /// Towards negative infinity
+    Floor,
+    /// Towards positive infinity
+    Ceiling,
+    /// To nearest, ties away from zero
+    HalfUp,
+    /// To nearest, ties to the even neighbour
+    HalfEven,
+}
+
+/// How `DecimalFormat` treats trailing fractional zeros
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecimalPadding {
+    /// Keep the value's own scale, only dropping zeros beyond the maximum
+    #[default]
+    AsIs,
+    /// Drop every trailing fractional zero ("1.50" becomes "1.5")
+    TrimTrailingZeros,
+    /// Pad to exactly `max_decimal_places` ("1.5" becomes "1.5000" at 4)
+    Fixed,
+}
+
+/// Venue formatting rules for a decimal field. Values are always written in
+/// positional notation; these rules only decide how many places appear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecimalFormat {
+    /// Most decimal places the venue accepts; `None` means no limit
+    pub max_decimal_places: Option<u8>,
+    pub padding: DecimalPadding,
+}
+
+impl DecimalFormat {
+    #[inline]
+    pub fn with_max_decimal_places(mut self, places: u8) -> Self {
+        self.max_decimal_places = Some(places);
+        self
+    }
+
+    #[inline]
+    pub fn with_padding(mut self, padding: DecimalPadding) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+/// A fixed-point decimal for FIX price, qty and amount fields, stored as an
+/// integer mantissa and a number of decimal places. The scale is kept as
+/// received so "100.50" formats back unchanged, while comparison and hashing
+/// are numeric ("1.0" == "1.00").
+#[derive(Debug, Clone, Copy)]
+pub struct FixDecimal {
+    mantissa: i64,
+    scale: u8,
+}
+
+impl FixDecimal {
+    pub const ZERO: FixDecimal = FixDecimal { mantissa: 0, scale: 0 };
+
+    /// `mantissa * 10^-scale`
+    #[inline]
+    pub fn new(mantissa: i64, scale: u8) -> Result<Self, FixError> {
+        if scale > MAX_SCALE {
+            return Err(FixError::DecimalOverflow);
+        }
+        Ok(Self { mantissa, scale })
+    }
+
+    #[inline]
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    #[inline]
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// Re-expresses the value with `scale` decimal places without losing
+    /// precision
+    pub fn rescale(&self, scale: u8) -> Result<Self, FixError> {
+        if scale > MAX_SCALE {
+            return Err(FixError::DecimalOverflow);
+        }
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Ok(*self),
+            Ordering::Greater => self
+                .mantissa
+                .checked_mul(POW10[(scale - self.scale) as usize])
+                .map(|mantissa| Self { mantissa, scale })
+                .ok_or(FixError::DecimalOverflow),
+            Ordering::Less => {
+                let factor = POW10[(self.scale - scale) as usize];
+                if self.mantissa % factor != 0 {
+                    return Err(FixError::DecimalOverflow);
+                }
+                Ok(Self { mantissa: self.mantissa / factor, scale })
+            }
+        }
+    }
+
+    pub fn checked_add(&self, rhs: FixDecimal) -> Result<Self, FixError> {
+        let scale = self.scale.max(rhs.scale);
+        Self::from_wide(self.widened(scale) + rhs.widened(scale), scale)
+    }
+
+    pub fn checked_sub(&self, rhs: FixDecimal) -> Result<Self, FixError> {
+        let scale = self.scale.max(rhs.scale);
+        Self::from_wide(self.widened(scale) - rhs.widened(scale), scale)
+    }
+
+    /// Exact product, e.g. notional = px * qty. Trailing zeros are dropped when
+    /// the combined scale would exceed `MAX_SCALE`.
+    pub fn checked_mul(&self, rhs: FixDecimal) -> Result<Self, FixError> {
+        let mut mantissa = (self.mantissa as i128) * (rhs.mantissa as i128);
+        let mut scale = self.scale as u32 + rhs.scale as u32;
+        while scale > MAX_SCALE as u32 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if scale > MAX_SCALE as u32 {
+            return Err(FixError::DecimalOverflow);
+        }
+        Self::from_wide(mantissa, scale as u8)
+    }
+
+    /// Rounds to at most `places` decimal places
+    pub fn round(&self, places: u8, mode: RoundingMode) -> Self {
+        if places >= self.scale {
+            return *self;
+        }
+        let factor = POW10[(self.scale - places) as usize];
+        let quotient = self.mantissa / factor;
+        let remainder = self.mantissa % factor;
+        if remainder == 0 {
+            return Self { mantissa: quotient, scale: places };
+        }
+
+        // The quotient is at most i64::MAX / 10 so stepping away cannot overflow
+        let away = rounds_away(mode, quotient as i128, remainder as i128, factor as i128);
+        let mantissa = if away { quotient + remainder.signum() } else { quotient };
+        Self { mantissa, scale: places }
+    }
+
+    /// Quotient rounded to `places` decimal places, e.g. an average price
+    /// from a notional and a quantity. Fails on division by zero.
+    pub fn checked_div(&self, rhs: FixDecimal, places: u8, mode: RoundingMode) -> Result<Self, FixError> {
+        if rhs.is_zero() || places > MAX_SCALE {
+            return Err(FixError::DecimalOverflow);
+        }
+        // self / rhs * 10^places as a ratio of integers
+        let exponent = places as i32 + rhs.scale as i32 - self.scale as i32;
+        let scaled = |value: i64, exponent: i32| {
+            10i128
+                .checked_pow(exponent as u32)
+                .and_then(|factor| (value as i128).checked_mul(factor))
+                .ok_or(FixError::DecimalOverflow)
+        };
+        let (mut numerator, mut denominator) = if exponent >= 0 {
+            (scaled(self.mantissa, exponent)?, rhs.mantissa as i128)
+        } else {
+            (self.mantissa as i128, scaled(rhs.mantissa, -exponent)?)
+        };
+        if denominator < 0 {
+            (numerator, denominator) = (-numerator, -denominator);
+        }
+
+        let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+        let away = remainder != 0 && rounds_away(mode, quotient, remainder, denominator);
+        Self::from_wide(if away { quotient + remainder.signum() } else { quotient }, places)
+    }
+
+    /// The same value with trailing fractional zeros removed
+    pub fn normalize(&self) -> Self {
+        let mut value = *self;
+        while value.scale > 0 && value.mantissa % 10 == 0 {
+            value.mantissa /= 10;
+            value.scale -= 1;
+        }
+        value
+    }
+
+    /// Rescales the value to satisfy `format`. Fails with `PrecisionExceeded`
+    /// rather than rounding when the value needs more places than allowed.
+    pub fn apply_format(&self, format: &DecimalFormat) -> Result<Self, FixError> {
+        let normalized = self.normalize();
+        if let Some(max) = format.max_decimal_places {
+            if normalized.scale > max {
+                return Err(FixError::PrecisionExceeded { scale: normalized.scale, max });
+            }
+        }
+
+        let scale = match (format.padding, format.max_decimal_places) {
+            (DecimalPadding::TrimTrailingZeros, _) => normalized.scale,
+            (DecimalPadding::Fixed, Some(max)) => max,
+            (DecimalPadding::AsIs, Some(max)) => self.scale.min(max),
+            (_, None) => self.scale,
+        };
+        normalized.rescale(scale)
+    }
+
+    #[inline]
+    fn from_wide(mantissa: i128, scale: u8) -> Result<Self, FixError> {
+        i64::try_from(mantissa)
+            .map(|mantissa| Self { mantissa, scale })
+            .map_err(|_| FixError::DecimalOverflow)
+    }
+
+    // The mantissa at a scale at least as large as our own. Fits in an i128
+    // since 10^18 * i64::MAX < i128::MAX.
+    #[inline]
+    fn widened(&self, scale: u8) -> i128 {
+        self.mantissa as i128 * POW10[(scale - self.scale) as usize] as i128
+    }
+}
+
+// Whether a quotient with a nonzero `remainder` left over from dividing by
+// the positive `divisor` moves one step away from zero
+fn rounds_away(mode: RoundingMode, quotient: i128, remainder: i128, divisor: i128) -> bool {
+    match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::Floor => remainder < 0,
+        RoundingMode::Ceiling => remainder > 0,
+        RoundingMode::HalfUp => remainder.unsigned_abs() * 2 >= divisor as u128,
+        RoundingMode::HalfEven => match (remainder.unsigned_abs() * 2).cmp(&(divisor as u128)) {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => quotient % 2 != 0,
+        },
+    }
+}
+
+impl From<i64> for FixDecimal {
+    #[inline]
+    fn from(value: i64) -> Self {
+        Self { mantissa: value, scale: 0 }
+    }
+}
+
+impl PartialEq for FixDecimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FixDecimal {}
+
+impl Ord for FixDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.widened(scale).cmp(&other.widened(scale))
+    }
+}
+
+impl PartialOrd for FixDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for FixDecimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalize();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl FieldValue for FixDecimal {
+    /// Accepts an optional leading '-', digits and an optional '.', e.g. "-1",
+    /// ".5" and "100.50". Exponents and a leading '+' are not valid FIX.
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        let (negative, digits) = match value {
+            [b'-', rest @ ..] => (true, rest),
+            _ => (false, value),
+        };
+
+        let mut mantissa: i64 = 0;
+        let mut scale: u8 = 0;
+        let mut seen_point = false;
+        let mut seen_digit = false;
+        for &b in digits {
+            match b {
+                b'0'..=b'9' => {
+                    seen_digit = true;
+                    let digit = (b - b'0') as i64;
+                    mantissa = mantissa
+                        .checked_mul(10)
+                        .and_then(|m| if negative { m.checked_sub(digit) } else { m.checked_add(digit) })
+                        .ok_or(FixError::InvalidFieldValue)?;
+                    if seen_point {
+                        scale += 1;
+                        if scale > MAX_SCALE {
+                            return Err(FixError::InvalidFieldValue);
+                        }
+                    }
+                }
+                b'.' if !seen_point => seen_point = true,
+                _ => return Err(FixError::InvalidFieldValue),
+            }
+        }
+
+        if !seen_digit {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(Self { mantissa, scale })
+    }
+
+    /// Plain positional notation, never an exponent
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        let mut itoa = itoa::Buffer::new();
+        let digits = itoa.format(self.mantissa.unsigned_abs()).as_bytes();
+        if self.mantissa < 0 {
+            buf.push(b'-');
+        }
+
+        let scale = self.scale as usize;
+        if scale == 0 {
+            buf.extend_from_slice(digits);
+        } else if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            buf.extend_from_slice(int);
+            buf.push(b'.');
+            buf.extend_from_slice(frac);
+        } else {
+            buf.extend_from_slice(b"0.");
+            buf.extend(std::iter::repeat_n(b'0', scale - digits.len()));
+            buf.extend_from_slice(digits);
+        }
+    }
+}
+
+impl_text_traits!(FixDecimal);
+
+macro_rules! decimal_kind {
+    ($(#[$doc:meta])* $name:ident, |$value:ident| $valid:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(FixDecimal);
+
+        impl $name {
+            pub fn new($value: FixDecimal) -> Result<Self, FixError> {
+                if $valid {
+                    Ok(Self($value))
+                } else {
+                    Err(FixError::InvalidFieldValue)
+                }
+            }
+
+            #[inline]
+            pub fn value(&self) -> FixDecimal {
+                self.0
+            }
+        }
+
+        impl From<$name> for FixDecimal {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<FixDecimal> for $name {
+            type Error = FixError;
+
+            #[inline]
+            fn try_from(value: FixDecimal) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl FieldValue for $name {
+            #[inline]
+            fn parse(value: &[u8]) -> Result<Self, FixError> {
+                Self::new(FixDecimal::parse(value)?)
+            }
+
+            #[inline]
+            fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+                self.0.write(buf)
+            }
+        }
+
+        impl_text_traits!($name);
+    };
+}
+
+decimal_kind!(
+    /// Qty fields such as OrderQty (38) and LastQty (32), which may not be
+    /// negative
+    Qty,
+    |value| !value.is_negative()
+);
+
+decimal_kind!(
+    /// Amt fields, a price multiplied by a quantity, in a currency
+    Amt,
+    |_value| true
+);
+
+decimal_kind!(
+    /// Percentage fields as a fraction, so 0.05 is 5%. Must lie in [0, 1]; use
+    /// `Percentage::new_in_range` where a field allows otherwise.
+    Percentage,
+    |value| !value.is_negative() && value <= FixDecimal::from(1)
+);
+
+impl Percentage {
+    pub fn new_in_range(value: FixDecimal, min: FixDecimal, max: FixDecimal) -> Result<Self, FixError> {
+        if value < min || value > max {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> FixDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        for value in ["0", "1", "-1", "100.50", "0.001", "-0.5", "123456789.123456789"] {
+            assert_eq!(dec(value).to_string(), value);
+        }
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("-.25").to_string(), "-0.25");
+        assert_eq!(dec("-9223372036854775808").mantissa(), i64::MIN);
+
+        for value in ["", "-", ".", "1e-3", "+1", "1.2.3", " 1", "1,000", "9223372036854775808"] {
+            assert!(value.parse::<FixDecimal>().is_err(), "{:?} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_execution_arithmetic() {
+        let order_qty = dec("1000");
+        let cum_qty = dec("250.5");
+        let last_qty = dec("49.50");
+
+        let cum_qty = cum_qty.checked_add(last_qty).unwrap();
+        assert_eq!(cum_qty, dec("300"));
+        assert_eq!(order_qty.checked_sub(cum_qty).unwrap(), dec("700"));
+        assert_eq!(dec("101.25").checked_mul(dec("300")).unwrap().to_string(), "30375.00");
+
+        assert!(dec("9223372036854775807").checked_add(dec("1")).is_err());
+        assert!(dec("92233720368547758.07").checked_add(dec("0.001")).is_err());
+        assert!(dec("4611686018427387904").checked_mul(dec("2")).is_err());
+
+        // Average price over fills of 100 @ 101.25 and 200 @ 101.30
+        let notional = dec("10125.00").checked_add(dec("20260.00")).unwrap();
+        let avg_px = notional.checked_div(dec("300"), 4, RoundingMode::HalfEven).unwrap();
+        assert_eq!(avg_px.to_string(), "101.2833");
+        assert_eq!(dec("1").checked_div(dec("-8"), 2, RoundingMode::HalfEven).unwrap().to_string(), "-0.12");
+        assert_eq!(dec("1").checked_div(dec("8"), 2, RoundingMode::HalfUp).unwrap().to_string(), "0.13");
+        assert_eq!(dec("-2.5").checked_div(dec("0.5"), 0, RoundingMode::Down).unwrap(), dec("-5"));
+        assert!(dec("1").checked_div(FixDecimal::ZERO, 2, RoundingMode::HalfUp).is_err());
+    }
+
+    #[test]
+    fn test_comparison_across_scales() {
+        assert_eq!(dec("1.0"), dec("1.00"));
+        assert!(dec("1.01") > dec("1.0"));
+        assert!(dec("-1.5") < dec("-1.49"));
+        assert!(dec("0").is_zero() && dec("0.000").is_zero());
+        assert!(dec("-0.01").is_negative() && !dec("0").is_negative());
+
+        use std::collections::HashSet;
+        let set: HashSet<_> = [dec("2.50"), dec("2.5"), dec("2.500")].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let cases = [
+            ("2.345", RoundingMode::HalfUp, "2.35"),
+            ("2.345", RoundingMode::HalfEven, "2.34"),
+            ("2.355", RoundingMode::HalfEven, "2.36"),
+            ("2.341", RoundingMode::Up, "2.35"),
+            ("2.349", RoundingMode::Down, "2.34"),
+            ("-2.345", RoundingMode::HalfUp, "-2.35"),
+            ("-2.341", RoundingMode::Floor, "-2.35"),
+            ("-2.349", RoundingMode::Ceiling, "-2.34"),
+            ("2.341", RoundingMode::Ceiling, "2.35"),
+            ("2.3", RoundingMode::Up, "2.3"),
+        ];
+        for (value, mode, expected) in cases {
+            assert_eq!(dec(value).round(2, mode).to_string(), expected, "{} {:?}", value, mode);
+        }
+    }
+
+    // Checks the checked operations against i128 reference arithmetic over a
+    // deterministic pseudo-random sample
+    #[test]
+    fn test_arithmetic_matches_i128_reference() {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..10_000 {
+            let magnitude = POW10[(next() % 19) as usize];
+            let a = FixDecimal::new((next() as i64) % magnitude, (next() % 10) as u8).unwrap();
+            let b = FixDecimal::new((next() as i64) % magnitude, (next() % 10) as u8).unwrap();
+
+            let scale = a.scale.max(b.scale);
+            let (wa, wb) = (a.widened(scale), b.widened(scale));
+
+            for (result, expected) in [(a.checked_add(b), wa + wb), (a.checked_sub(b), wa - wb)] {
+                match i64::try_from(expected) {
+                    Ok(expected) => assert_eq!(result.unwrap(), FixDecimal::new(expected, scale).unwrap()),
+                    Err(_) => assert!(result.is_err()),
+                }
+            }
+
+            let product = a.mantissa as i128 * b.mantissa as i128;
+            match (i64::try_from(product), a.scale + b.scale <= MAX_SCALE) {
+                (Ok(expected), true) => {
+                    let result = a.checked_mul(b).unwrap();
+                    assert_eq!(result, FixDecimal::new(expected, a.scale + b.scale).unwrap());
+                }
+                (Err(_), _) => assert!(a.checked_mul(b).is_err()),
+                _ => {}
+            }
+
+            assert_eq!(a.cmp(&b), wa.cmp(&wb));
+        }
+    }
+
+    #[test]
+    fn test_semantic_wrappers() {
+        assert_eq!("100".parse::<Qty>().unwrap().value(), dec("100"));
+        assert!("-1".parse::<Qty>().is_err());
+        assert!(Qty::new(dec("0")).is_ok());
+
+        assert_eq!("-12.50".parse::<Amt>().unwrap().to_string(), "-12.50");
+
+        assert_eq!("0.05".parse::<Percentage>().unwrap().value(), dec("0.05"));
+        assert!("1".parse::<Percentage>().is_ok());
+        assert!("5".parse::<Percentage>().is_err());
+        assert!("-0.01".parse::<Percentage>().is_err());
+        assert!(Percentage::new_in_range(dec("5"), dec("0"), dec("100")).is_ok());
+    }
+
+    #[test]
+    fn test_apply_format() {
+        let limited = DecimalFormat::default().with_max_decimal_places(2);
+        assert_eq!(dec("1.5").apply_format(&limited).unwrap().to_string(), "1.5");
+        assert_eq!(dec("1.5000").apply_format(&limited).unwrap().to_string(), "1.50");
+        assert_eq!(dec("-0.010").apply_format(&limited).unwrap().to_string(), "-0.01");
+        assert!(matches!(
+            dec("0.001").apply_format(&limited),
+            Err(FixError::PrecisionExceeded { scale: 3, max: 2 })
+        ));
+
+        let fixed = limited.with_padding(DecimalPadding::Fixed);
+        assert_eq!(dec("7").apply_format(&fixed).unwrap().to_string(), "7.00");
+        assert!(matches!(
+            FixDecimal::from(i64::MAX).apply_format(&fixed),
+            Err(FixError::DecimalOverflow)
+        ));
+
+        let trimmed = DecimalFormat::default().with_padding(DecimalPadding::TrimTrailingZeros);
+        assert_eq!(dec("100.500").apply_format(&trimmed).unwrap().to_string(), "100.5");
+        assert_eq!(dec("100.000").apply_format(&trimmed).unwrap().to_string(), "100");
+        assert_eq!(dec("100.000").apply_format(&DecimalFormat::default()).unwrap().to_string(), "100.000");
+    }
+}
@@ -0,0 +1,1020 @@
+use crate::codes::{Country, Currency, Mic};
+use crate::datetime::{
+    LocalMktDate, MonthYear, TzTimeOnly, TzTimestamp, UtcDateOnly, UtcTimeOnly, UtcTimestamp,
+};
+use crate::decimal::{Amt, FixDecimal, Percentage, Qty};
+use crate::error::FixError;
+use crate::field::{parse_int, parse_uint, FieldValue, FixField, Flag};
+use crate::message::FixMessage;
+use crate::messages::check_range;
+use crate::messages::logon::{ENCRYPT_METHOD_RANGE, HEART_BT_INT_RANGE};
+use crate::messages::order_cancel_reject::CXL_REJ_REASON_RANGE;
+use crate::messages::allocation::ALLOC_GROUP;
+use crate::messages::trade_capture_report::SIDE_GROUP;
+use crate::seqnum::SeqNum;
+use crate::settl_type::SettlType;
+use crate::tags::{is_header_tag, is_trailer_tag, msg_type, Tag};
+use rustc_hash::FxHashMap;
+use std::ops::{ControlFlow, RangeInclusive};
+use std::sync::OnceLock;
+
+// Standard codes stop well short of 99, which is used for "Other"
+const SESSION_REJECT_REASON_RANGE: (i64, i64) = (0, 99);
+
+/// FIX data types a dictionary can assign to a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FieldType {
+    Int,
+    Length,
+    NumInGroup,
+    SeqNum,
+    Float,
+    Price,
+    PriceOffset,
+    Qty,
+    Amt,
+    Percentage,
+    Char,
+    Boolean,
+    String,
+    MultipleValueString,
+    Currency,
+    Exchange,
+    Country,
+    UtcTimestamp,
+    UtcTimeOnly,
+    UtcDateOnly,
+    LocalMktDate,
+    MonthYear,
+    TzTimestamp,
+    TzTimeOnly,
+    Data,
+    /// SettlType (63): a code or an FX tenor such as "M3"
+    SettlType,
+}
+
+impl FieldType {
+    /// Checks that `value` is well formed for this type
+    pub fn check(self, value: &[u8]) -> Result<(), FixError> {
+        fn parses<T: FieldValue>(value: &[u8]) -> Result<(), FixError> {
+            T::parse(value).map(drop)
+        }
+
+        match self {
+            FieldType::Int => parse_int(value).map(drop),
+            FieldType::Length | FieldType::NumInGroup => parse_uint(value).map(drop),
+            FieldType::SeqNum => parses::<SeqNum>(value),
+            FieldType::Float | FieldType::Price | FieldType::PriceOffset => parses::<FixDecimal>(value),
+            FieldType::Qty => parses::<Qty>(value),
+            FieldType::Amt => parses::<Amt>(value),
+            FieldType::Percentage => parses::<Percentage>(value),
+            FieldType::Char => match value {
+                [c] if c.is_ascii_graphic() => Ok(()),
+                _ => Err(FixError::InvalidFieldValue),
+            },
+            FieldType::Boolean => match value {
+                b"Y" | b"N" => Ok(()),
+                _ => Err(FixError::InvalidFieldValue),
+            },
+            FieldType::Currency => parses::<Currency>(value),
+            FieldType::Exchange => parses::<Mic>(value),
+            FieldType::Country => parses::<Country>(value),
+            FieldType::String | FieldType::MultipleValueString => {
+                if value.is_empty() {
+                    return Err(FixError::InvalidFieldValue);
+                }
+                Ok(())
+            }
+            FieldType::UtcTimestamp => parses::<UtcTimestamp>(value),
+            FieldType::UtcTimeOnly => parses::<UtcTimeOnly>(value),
+            FieldType::UtcDateOnly => parses::<UtcDateOnly>(value),
+            FieldType::LocalMktDate => parses::<LocalMktDate>(value),
+            FieldType::MonthYear => parses::<MonthYear>(value),
+            FieldType::TzTimestamp => parses::<TzTimestamp>(value),
+            FieldType::TzTimeOnly => parses::<TzTimeOnly>(value),
+            FieldType::Data => Ok(()),
+            FieldType::SettlType => parses::<SettlType>(value),
+        }
+    }
+}
+
+/// Dictionary definition of a single tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub tag: u32,
+    pub name: String,
+    pub field_type: FieldType,
+    /// Longest value the counterparty accepts, in bytes
+    pub max_len: Option<usize>,
+    /// Enumerated values; empty when any well-formed value is allowed
+    pub values: Vec<Vec<u8>>,
+    /// Names of enumerated values, e.g. "Buy" for Side (54) = 1
+    pub labels: Vec<(Vec<u8>, String)>,
+    /// Value the spec or venue implies when the field is absent
+    pub default: Option<Vec<u8>>,
+    /// Inclusive bounds for an integer field
+    pub int_range: Option<(i64, i64)>,
+    /// Attribute name in FIXML, e.g. "Sym" for Symbol (55)
+    pub fixml_name: Option<String>,
+}
+
+impl FieldSpec {
+    pub fn new(tag: u32, name: impl Into<String>, field_type: FieldType) -> Self {
+        Self {
+            tag,
+            name: name.into(),
+            field_type,
+            max_len: None,
+            values: Vec::new(),
+            labels: Vec::new(),
+            default: None,
+            int_range: None,
+            fixml_name: None,
+        }
+    }
+
+    pub fn with_fixml_name(mut self, name: impl Into<String>) -> Self {
+        self.fixml_name = Some(name.into());
+        self
+    }
+
+    pub fn with_int_range(mut self, min: i64, max: i64) -> Self {
+        self.int_range = Some((min, max));
+        self
+    }
+
+    pub fn with_default(mut self, value: impl AsRef<[u8]>) -> Self {
+        self.default = Some(value.as_ref().to_vec());
+        self
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn with_values<V: AsRef<[u8]>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.values = values.into_iter().map(|v| v.as_ref().to_vec()).collect();
+        self
+    }
+
+    /// Names enumerated values. Unlike `with_values` this does not restrict
+    /// what is accepted.
+    pub fn with_labels<V: AsRef<[u8]>>(mut self, labels: impl IntoIterator<Item = (V, &'static str)>) -> Self {
+        self.labels = labels
+            .into_iter()
+            .map(|(value, name)| (value.as_ref().to_vec(), name.to_owned()))
+            .collect();
+        self
+    }
+
+    /// The name of `value`, when it has one
+    pub fn label(&self, value: &[u8]) -> Option<&str> {
+        self.labels.iter().find(|(v, _)| v == value).map(|(_, name)| name.as_str())
+    }
+
+    /// Checks length, type, integer range and enumerated values, in that
+    /// order
+    pub fn validate(&self, value: &[u8]) -> Result<(), FixError> {
+        if let Some(max_len) = self.max_len {
+            if value.len() > max_len {
+                return Err(FixError::ValueTooLong { tag: self.tag, len: value.len(), width: max_len });
+            }
+        }
+        self.field_type.check(value)?;
+        if let Some(range) = self.int_range {
+            check_range(self.tag, parse_int(value)?, range)?;
+        }
+        if !self.values.is_empty() && !self.values.iter().any(|v| v == value) {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(())
+    }
+}
+
+/// The body of one message type: the fields and repeating groups it may
+/// carry besides the standard header and trailer, and which are required
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageSpec {
+    pub msg_type: Vec<u8>,
+    pub name: String,
+    fields: Vec<u32>,
+    // Count tags of the groups, whose members come from the dictionary
+    groups: Vec<u32>,
+    required: Vec<u32>,
+}
+
+impl MessageSpec {
+    pub fn new(msg_type: impl Into<Vec<u8>>, name: impl Into<String>) -> Self {
+        Self {
+            msg_type: msg_type.into(),
+            name: name.into(),
+            fields: Vec::new(),
+            groups: Vec::new(),
+            required: Vec::new(),
+        }
+    }
+
+    pub fn with_required(mut self, tags: &[u32]) -> Self {
+        self.fields.extend_from_slice(tags);
+        self.required.extend_from_slice(tags);
+        self
+    }
+
+    pub fn with_optional(mut self, tags: &[u32]) -> Self {
+        self.fields.extend_from_slice(tags);
+        self
+    }
+
+    /// Adds the repeating group introduced by `count_tag`, as defined by
+    /// `DataDictionary::add_group`
+    pub fn with_group(mut self, count_tag: u32, required: bool) -> Self {
+        self.groups.push(count_tag);
+        if required {
+            self.required.push(count_tag);
+        }
+        self
+    }
+
+    /// Whether `tag` is a body field of the message, outside any group
+    #[inline]
+    pub fn has_field(&self, tag: u32) -> bool {
+        self.fields.contains(&tag)
+    }
+
+    /// Whether the message carries the group introduced by `count_tag`
+    #[inline]
+    pub fn has_group(&self, count_tag: u32) -> bool {
+        self.groups.contains(&count_tag)
+    }
+
+    /// Required fields and count tags of required groups
+    #[inline]
+    pub fn required(&self) -> &[u32] {
+        &self.required
+    }
+}
+
+// `DataDictionary::fix44`, built once for callers that only read it
+pub(crate) fn shared_fix44() -> &'static DataDictionary {
+    static DICTIONARY: OnceLock<DataDictionary> = OnceLock::new();
+    DICTIONARY.get_or_init(DataDictionary::fix44)
+}
+
+/// Field definitions used to validate messages for a counterparty
+#[derive(Debug, Clone, Default)]
+pub struct DataDictionary {
+    fields: FxHashMap<u32, FieldSpec>,
+    // Members of each repeating group by its count tag, delimiter first
+    groups: FxHashMap<u32, Vec<u32>>,
+    messages: FxHashMap<Vec<u8>, MessageSpec>,
+    report_unknown_tags: bool,
+}
+
+impl DataDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The FIX 4.4 definitions of the tags in `Tag`
+    pub fn fix44() -> Self {
+        use FieldType::*;
+
+        let specs = [
+            (Tag::Account, "Account", String),
+            (Tag::BeginSeqNo, "BeginSeqNo", SeqNum),
+            (Tag::BeginString, "BeginString", String),
+            (Tag::BodyLength, "BodyLength", Length),
+            (Tag::ClOrdID, "ClOrdID", String),
+            (Tag::Currency, "Currency", Currency),
+            (Tag::EndSeqNo, "EndSeqNo", Int),
+            (Tag::LastPx, "LastPx", Price),
+            (Tag::LastQty, "LastQty", Qty),
+            (Tag::MsgType, "MsgType", String),
+            (Tag::NewSeqNo, "NewSeqNo", SeqNum),
+            (Tag::OrderID, "OrderID", String),
+            (Tag::OrdStatus, "OrdStatus", Char),
+            (Tag::OrdType, "OrdType", Char),
+            (Tag::Price, "Price", Price),
+            (Tag::OrderQty, "OrderQty", Qty),
+            (Tag::CumQty, "CumQty", Qty),
+            (Tag::LeavesQty, "LeavesQty", Qty),
+            (Tag::AvgPx, "AvgPx", Price),
+            (Tag::ExecID, "ExecID", String),
+            (Tag::OrigClOrdID, "OrigClOrdID", String),
+            (Tag::MsgSeqNum, "MsgSeqNum", SeqNum),
+            (Tag::LocateReqd, "LocateReqd", Boolean),
+            (Tag::SenderCompID, "SenderCompID", String),
+            (Tag::TargetCompID, "TargetCompID", String),
+            (Tag::SendingTime, "SendingTime", UtcTimestamp),
+            (Tag::RefSeqNum, "RefSeqNum", SeqNum),
+            (Tag::Quantity, "Quantity", Qty),
+            (Tag::Side, "Side", Char),
+            (Tag::Symbol, "Symbol", String),
+            (Tag::Text, "Text", String),
+            (Tag::TransactTime, "TransactTime", UtcTimestamp),
+            (Tag::SettlType, "SettlType", SettlType),
+            (Tag::SettlDate, "SettlDate", LocalMktDate),
+            (Tag::AllocID, "AllocID", String),
+            (Tag::AllocTransType, "AllocTransType", Char),
+            (Tag::NoOrders, "NoOrders", NumInGroup),
+            (Tag::TradeDate, "TradeDate", LocalMktDate),
+            (Tag::NoAllocs, "NoAllocs", NumInGroup),
+            (Tag::AllocAccount, "AllocAccount", String),
+            (Tag::AllocQty, "AllocQty", Qty),
+            (Tag::TestReqID, "TestReqID", String),
+            (Tag::GapFillFlag, "GapFillFlag", Boolean),
+            (Tag::ExecType, "ExecType", Char),
+            (Tag::MaturityMonthYear, "MaturityMonthYear", MonthYear),
+            (Tag::SecurityExchange, "SecurityExchange", Exchange),
+            (Tag::MDEntryTime, "MDEntryTime", UtcTimeOnly),
+            (Tag::CxlRejResponseTo, "CxlRejResponseTo", Char),
+            (Tag::MessageEncoding, "MessageEncoding", String),
+            (Tag::EncodedTextLen, "EncodedTextLen", Length),
+            (Tag::EncodedText, "EncodedText", Data),
+            (Tag::CountryOfIssue, "CountryOfIssue", Country),
+            (Tag::NoSides, "NoSides", NumInGroup),
+            (Tag::AllocType, "AllocType", Int),
+            (Tag::TradeReportID, "TradeReportID", String),
+            (Tag::StrikeCurrency, "StrikeCurrency", Currency),
+            (Tag::TZTransactTime, "TZTransactTime", TzTimestamp),
+        ];
+
+        let mut dict = Self::new();
+        for (tag, name, field_type) in specs {
+            dict.add_field(FieldSpec::new(tag.value(), name, field_type));
+        }
+        dict.add_field(FieldSpec::new(Tag::CheckSum.value(), "CheckSum", String).with_max_len(3));
+        dict.add_field(FieldSpec::new(Tag::PossDupFlag.value(), "PossDupFlag", Boolean).with_default("N"));
+        dict.add_field(FieldSpec::new(Tag::PossResend.value(), "PossResend", Boolean).with_default("N"));
+
+        let int_ranges = [
+            (Tag::EncryptMethod, "EncryptMethod", ENCRYPT_METHOD_RANGE),
+            (Tag::HeartBtInt, "HeartBtInt", HEART_BT_INT_RANGE),
+            (Tag::CxlRejReason, "CxlRejReason", CXL_REJ_REASON_RANGE),
+            (Tag::SessionRejectReason, "SessionRejectReason", SESSION_REJECT_REASON_RANGE),
+        ];
+        for (tag, name, (min, max)) in int_ranges {
+            dict.add_field(FieldSpec::new(tag.value(), name, Int).with_int_range(min, max));
+        }
+
+        // FIXML names are scoped to their element, so e.g. Qty is OrderQty
+        // in OrdQty and AllocQty in Alloc
+        let fixml_names = [
+            (Tag::Account, "Acct"),
+            (Tag::AvgPx, "AvgPx"),
+            (Tag::ClOrdID, "ClOrdID"),
+            (Tag::CumQty, "CumQty"),
+            (Tag::Currency, "Ccy"),
+            (Tag::ExecID, "ExecID"),
+            (Tag::LastPx, "LastPx"),
+            (Tag::LastQty, "LastQty"),
+            (Tag::OrderID, "OrdID"),
+            (Tag::OrderQty, "Qty"),
+            (Tag::OrdStatus, "OrdStat"),
+            (Tag::OrigClOrdID, "OrigClOrdID"),
+            (Tag::MsgSeqNum, "SeqNum"),
+            (Tag::PossDupFlag, "PosDup"),
+            (Tag::SenderCompID, "SID"),
+            (Tag::TargetCompID, "TID"),
+            (Tag::SendingTime, "Snt"),
+            (Tag::Quantity, "Qty"),
+            (Tag::Side, "Side"),
+            (Tag::Symbol, "Sym"),
+            (Tag::Text, "Txt"),
+            (Tag::TransactTime, "TxnTm"),
+            (Tag::SettlDate, "SettlDt"),
+            (Tag::AllocID, "ID"),
+            (Tag::AllocTransType, "TransTyp"),
+            (Tag::TradeDate, "TrdDt"),
+            (Tag::AllocAccount, "Acct"),
+            (Tag::AllocQty, "Qty"),
+            (Tag::PossResend, "PosRsnd"),
+            (Tag::CxlRejReason, "CxlRejRsn"),
+            (Tag::ExecType, "ExecTyp"),
+            (Tag::LeavesQty, "LeavesQty"),
+            (Tag::MaturityMonthYear, "MMY"),
+            (Tag::SecurityExchange, "Exch"),
+            (Tag::CxlRejResponseTo, "CxlRejRspTo"),
+            (Tag::CountryOfIssue, "Ctry"),
+            (Tag::TradeReportID, "RptID"),
+            (Tag::StrikeCurrency, "StrkCcy"),
+            (Tag::AllocType, "Typ"),
+        ];
+        for (tag, name) in fixml_names {
+            if let Some(spec) = dict.fields.get_mut(&tag.value()) {
+                spec.fixml_name = Some(name.to_string());
+            }
+        }
+
+        let labels: [(Tag, &[(&str, &'static str)]); 5] = [
+            (
+                Tag::MsgType,
+                &[
+                    ("0", "Heartbeat"),
+                    ("1", "TestRequest"),
+                    ("2", "ResendRequest"),
+                    ("3", "Reject"),
+                    ("4", "SequenceReset"),
+                    ("5", "Logout"),
+                    ("8", "ExecutionReport"),
+                    ("9", "OrderCancelReject"),
+                    ("A", "Logon"),
+                    ("D", "NewOrderSingle"),
+                    ("AE", "TradeCaptureReport"),
+                ],
+            ),
+            (Tag::Side, &[("1", "Buy"), ("2", "Sell"), ("5", "SellShort")]),
+            (
+                Tag::OrdStatus,
+                &[
+                    ("0", "New"),
+                    ("1", "PartiallyFilled"),
+                    ("2", "Filled"),
+                    ("4", "Canceled"),
+                    ("8", "Rejected"),
+                ],
+            ),
+            (
+                Tag::ExecType,
+                &[("0", "New"), ("4", "Canceled"), ("8", "Rejected"), ("F", "Trade")],
+            ),
+            (Tag::CxlRejResponseTo, &[("1", "OrderCancelRequest"), ("2", "OrderCancelReplaceRequest")]),
+        ];
+        for (tag, names) in labels {
+            if let Some(spec) = dict.fields.remove(&tag.value()) {
+                dict.add_field(spec.with_labels(names.iter().copied()));
+            }
+        }
+
+        dict.add_group(Tag::NoSides.value(), &SIDE_GROUP);
+        dict.add_group(Tag::NoOrders.value(), &[Tag::ClOrdID.value(), Tag::OrderID.value()]);
+        dict.add_group(Tag::NoAllocs.value(), &ALLOC_GROUP);
+
+        let instrument = [
+            Tag::Symbol.value(),
+            Tag::MaturityMonthYear.value(),
+            Tag::SecurityExchange.value(),
+            Tag::CountryOfIssue.value(),
+            Tag::StrikeCurrency.value(),
+        ];
+        dict.add_message(
+            MessageSpec::new(msg_type::NEW_ORDER_SINGLE, "NewOrderSingle")
+                .with_required(&[
+                    Tag::ClOrdID.value(),
+                    Tag::Symbol.value(),
+                    Tag::Side.value(),
+                    Tag::TransactTime.value(),
+                    Tag::OrdType.value(),
+                ])
+                .with_optional(&instrument[1..])
+                .with_optional(&[
+                    Tag::Account.value(),
+                    Tag::SettlDate.value(),
+                    Tag::LocateReqd.value(),
+                    Tag::OrderQty.value(),
+                    Tag::Price.value(),
+                    Tag::Currency.value(),
+                    Tag::Text.value(),
+                ]),
+        );
+        dict.add_message(
+            MessageSpec::new(msg_type::TRADE_CAPTURE_REPORT, "TradeCaptureReport")
+                .with_required(&[
+                    Tag::TradeReportID.value(),
+                    Tag::ExecType.value(),
+                    Tag::LastQty.value(),
+                    Tag::LastPx.value(),
+                ])
+                .with_optional(&instrument)
+                .with_optional(&[
+                    Tag::Currency.value(),
+                    Tag::TradeDate.value(),
+                    Tag::TransactTime.value(),
+                    Tag::Text.value(),
+                ])
+                .with_group(Tag::NoSides.value(), true),
+        );
+        dict
+    }
+
+    /// Adds or replaces the definition of `spec.tag`
+    pub fn add_field(&mut self, spec: FieldSpec) {
+        self.fields.insert(spec.tag, spec);
+    }
+
+    pub fn with_field(mut self, spec: FieldSpec) -> Self {
+        self.add_field(spec);
+        self
+    }
+
+    /// Defines the repeating group introduced by `count_tag`. `members[0]` is
+    /// the delimiter that starts every entry, as for `group_entries`.
+    pub fn add_group(&mut self, count_tag: u32, members: &[u32]) {
+        self.groups.insert(count_tag, members.to_vec());
+    }
+
+    pub fn with_group(mut self, count_tag: u32, members: &[u32]) -> Self {
+        self.add_group(count_tag, members);
+        self
+    }
+
+    /// Members of the group introduced by `count_tag`, delimiter first
+    #[inline]
+    pub fn group(&self, count_tag: u32) -> Option<&[u32]> {
+        self.groups.get(&count_tag).map(Vec::as_slice)
+    }
+
+    /// Adds or replaces the definition of `spec.msg_type`
+    pub fn add_message(&mut self, spec: MessageSpec) {
+        self.messages.insert(spec.msg_type.clone(), spec);
+    }
+
+    pub fn with_message(mut self, spec: MessageSpec) -> Self {
+        self.add_message(spec);
+        self
+    }
+
+    #[inline]
+    pub fn message(&self, msg_type: &[u8]) -> Option<&MessageSpec> {
+        self.messages.get(msg_type)
+    }
+
+    /// A builder for messages of type `msg_type`, which must be defined
+    pub fn builder(&self, msg_type: &[u8]) -> Result<MessageBuilder<'_>, FixError> {
+        let spec = self
+            .message(msg_type)
+            .ok_or_else(|| FixError::UnknownMsgType(String::from_utf8_lossy(msg_type).into_owned()))?;
+        Ok(MessageBuilder::new(self, spec))
+    }
+
+    /// Also report tags the dictionary does not define, as `UnknownTag`
+    pub fn with_unknown_tag_reporting(mut self, report: bool) -> Self {
+        self.report_unknown_tags = report;
+        self
+    }
+
+    #[inline]
+    pub fn field(&self, tag: u32) -> Option<&FieldSpec> {
+        self.fields.get(&tag)
+    }
+
+    /// The tag whose FIXML attribute name is `name`. Names are only unique
+    /// within an element, so where several tags share one this is the
+    /// lowest of them; see `tags_for_fixml_name`.
+    pub fn tag_for_fixml_name(&self, name: &str) -> Option<u32> {
+        self.tags_for_fixml_name(name).min()
+    }
+
+    /// Every tag whose FIXML attribute name is `name`, in no particular order
+    pub fn tags_for_fixml_name<'d>(&'d self, name: &'d str) -> impl Iterator<Item = u32> + 'd {
+        self.fields
+            .values()
+            .filter(move |spec| spec.fixml_name.as_deref() == Some(name))
+            .map(|spec| spec.tag)
+    }
+
+    /// Reads a Boolean field, falling back to the dictionary default when it
+    /// is absent. `Ok(None)` when it is absent and has no default.
+    pub fn get_flag(&self, msg: &FixMessage, tag: u32) -> Result<Option<Flag>, FixError> {
+        if let Some(value) = msg.get_bool(tag)? {
+            return Ok(Some(Flag::Explicit(value)));
+        }
+        match self.fields.get(&tag).and_then(|spec| spec.default.as_deref()) {
+            Some(default) => bool::parse(default).map(|value| Some(Flag::Defaulted(value))),
+            None => Ok(None),
+        }
+    }
+
+    /// Validates a single value, treating undefined tags according to
+    /// `with_unknown_tag_reporting`
+    pub fn validate_field(&self, tag: u32, value: &[u8]) -> Result<(), FixError> {
+        match self.fields.get(&tag) {
+            Some(spec) => spec.validate(value),
+            None if self.report_unknown_tags => Err(FixError::UnknownTag(tag)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FixMessage {
+    /// Validates the message against `dict`, stopping at the first
+    /// violation. See `validate_all` for what is checked.
+    pub fn validate(&self, dict: &DataDictionary) -> Result<(), FixError> {
+        match self.check(dict, |_, err| ControlFlow::Break(err)) {
+            ControlFlow::Break(err) => Err(err),
+            ControlFlow::Continue(()) => Ok(()),
+        }
+    }
+
+    /// Validates the message against `dict` and returns all violations, for
+    /// a complete conformance report on a message. Every field's value is
+    /// checked. When the dictionary defines the message type, so is its
+    /// structure as `MessageBuilder` enforces it: defined tags the type does
+    /// not carry, group members outside an entry of their group and group
+    /// counts that disagree with the entries are reported in wire order,
+    /// followed by missing required fields and groups.
+    pub fn validate_all(&self, dict: &DataDictionary) -> Vec<(u32, FixError)> {
+        let mut violations = Vec::new();
+        let _ = self.check(dict, |tag, err| {
+            violations.push((tag, err));
+            ControlFlow::<()>::Continue(())
+        });
+        violations
+    }
+
+    fn check<B>(&self, dict: &DataDictionary, mut report: impl FnMut(u32, FixError) -> ControlFlow<B>) -> ControlFlow<B> {
+        let spec = self.get_field(Tag::MsgType.value()).and_then(|f| dict.message(f.value()));
+        let mut group: Option<OpenGroup> = None;
+
+        for field in self.field_slice() {
+            let tag = field.tag();
+            if let Err(err) = dict.validate_field(tag, field.value()) {
+                report(tag, err)?;
+            }
+            let Some(spec) = spec else {
+                continue;
+            };
+            if let Some(open) = &mut group {
+                if open.members.first() == Some(&tag) {
+                    open.entries += 1;
+                    continue;
+                }
+                if open.members.contains(&tag) {
+                    if open.entries == 0 {
+                        let count_tag = open.count_tag;
+                        report(tag, FixError::GroupFieldOutsideGroup { tag, count_tag })?;
+                    }
+                    continue;
+                }
+            }
+            if let Some(closed) = group.take() {
+                closed.check_count(&mut report)?;
+            }
+            if is_header_tag(tag) || is_trailer_tag(tag) || spec.has_field(tag) {
+                continue;
+            }
+            if spec.has_group(tag) {
+                if let Some(members) = dict.group(tag) {
+                    let declared = parse_uint(field.value()).map_or(0, |count| count as usize);
+                    group = Some(OpenGroup { count_tag: tag, members, declared, entries: 0 });
+                }
+                continue;
+            }
+            let owner = spec
+                .groups
+                .iter()
+                .copied()
+                .find(|&count_tag| dict.group(count_tag).is_some_and(|members| members.contains(&tag)));
+            if let Some(count_tag) = owner {
+                report(tag, FixError::GroupFieldOutsideGroup { tag, count_tag })?;
+            } else if dict.field(tag).is_some() {
+                report(tag, FixError::TagNotInMessage { tag, msg_type: spec.name.clone() })?;
+            }
+        }
+
+        if let Some(closed) = group {
+            closed.check_count(&mut report)?;
+        }
+        for &tag in spec.map_or(&[][..], MessageSpec::required) {
+            if self.get_field(tag).is_none() {
+                report(tag, FixError::MissingField(tag))?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// A repeating group whose entries `check` is reading
+struct OpenGroup<'d> {
+    count_tag: u32,
+    members: &'d [u32],
+    declared: usize,
+    entries: usize,
+}
+
+impl OpenGroup<'_> {
+    fn check_count<B>(&self, report: &mut impl FnMut(u32, FixError) -> ControlFlow<B>) -> ControlFlow<B> {
+        if self.declared == self.entries {
+            return ControlFlow::Continue(());
+        }
+        let (count_tag, declared, actual) = (self.count_tag, self.declared, self.entries);
+        report(count_tag, FixError::GroupCountMismatch { count_tag, declared, actual })
+    }
+}
+
+/// Builds a message of one type from a `DataDictionary`, refusing at each
+/// step what `validate` would only report afterwards: tags the message type
+/// does not define, group members outside an entry of their group and
+/// values that break the field's rules. Header and trailer fields are
+/// allowed on every message. `build` then checks required fields are
+/// present.
+#[derive(Debug, Clone)]
+pub struct MessageBuilder<'d> {
+    dict: &'d DataDictionary,
+    spec: &'d MessageSpec,
+    msg: FixMessage,
+    custom: Vec<RangeInclusive<u32>>,
+}
+
+impl<'d> MessageBuilder<'d> {
+    fn new(dict: &'d DataDictionary, spec: &'d MessageSpec) -> Self {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), spec.msg_type.clone()));
+        Self { dict, spec, msg, custom: Vec::new() }
+    }
+
+    /// Also allows the user-defined tags in `tags`. Their values are
+    /// checked only if the dictionary defines them.
+    pub fn allow_custom(mut self, tags: RangeInclusive<u32>) -> Self {
+        self.custom.push(tags);
+        self
+    }
+
+    /// Sets `tag`, replacing any earlier value
+    pub fn set(&mut self, tag: u32, value: impl AsRef<[u8]>) -> Result<&mut Self, FixError> {
+        let value = value.as_ref();
+        if tag == Tag::MsgType.value() {
+            return Err(FixError::FieldConflict(tag));
+        }
+        if let Some(count_tag) = self.group_of(tag) {
+            return Err(FixError::GroupFieldOutsideGroup { tag, count_tag });
+        }
+        if self.spec.has_field(tag) || is_header_tag(tag) || is_trailer_tag(tag) {
+            self.dict.validate_field(tag, value)?;
+        } else if self.is_custom(tag) {
+            if let Some(spec) = self.dict.field(tag) {
+                spec.validate(value)?;
+            }
+        } else {
+            return Err(FixError::TagNotInMessage { tag, msg_type: self.msg_type_name() });
+        }
+        self.msg.set_field(FixField::new(tag, value.to_vec()));
+        Ok(self)
+    }
+
+    /// Adds the repeating group introduced by `count_tag`, as
+    /// `FixMessage::add_group` does, after checking every entry holds only
+    /// members of the group with valid values
+    pub fn add_group<E: AsRef<[FixField]>>(&mut self, count_tag: u32, entries: &[E]) -> Result<&mut Self, FixError> {
+        let members = match self.dict.group(count_tag) {
+            Some(members) if self.spec.has_group(count_tag) => members,
+            _ => return Err(FixError::TagNotInMessage { tag: count_tag, msg_type: self.msg_type_name() }),
+        };
+        if self.msg.get_field(count_tag).is_some() {
+            return Err(FixError::FieldConflict(count_tag));
+        }
+        for field in entries.iter().flat_map(|entry| entry.as_ref()) {
+            if !members.contains(&field.tag()) {
+                return Err(FixError::TagNotInGroup { tag: field.tag(), count_tag });
+            }
+            self.dict.validate_field(field.tag(), field.value())?;
+        }
+        self.msg.add_group(count_tag, entries)?;
+        Ok(self)
+    }
+
+    /// The message, once every required field and group is present
+    pub fn build(self) -> Result<FixMessage, FixError> {
+        match self.spec.required().iter().find(|&&tag| self.msg.get_field(tag).is_none()) {
+            Some(&missing) => Err(FixError::MissingField(missing)),
+            None => Ok(self.msg),
+        }
+    }
+
+    // The group of this message `tag` belongs to, unless it is also a
+    // field of the message itself
+    fn group_of(&self, tag: u32) -> Option<u32> {
+        if self.spec.has_field(tag) {
+            return None;
+        }
+        self.spec.groups.iter().copied().find(|&count_tag| {
+            count_tag == tag || self.dict.group(count_tag).is_some_and(|members| members.contains(&tag))
+        })
+    }
+
+    fn is_custom(&self, tag: u32) -> bool {
+        self.custom.iter().any(|range| range.contains(&tag))
+    }
+
+    fn msg_type_name(&self) -> String {
+        self.spec.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+
+    fn message(fields: &[(u32, &[u8])]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        for &(tag, value) in fields {
+            msg.add_field(FixField::new(tag, value.to_vec()));
+        }
+        msg
+    }
+
+    #[test]
+    fn test_field_types() {
+        assert!(FieldType::Boolean.check(b"Y").is_ok());
+        assert!(FieldType::Boolean.check(b"y").is_err());
+        assert!(FieldType::Char.check(b"1").is_ok());
+        assert!(FieldType::Char.check(b"12").is_err());
+        assert!(FieldType::Qty.check(b"100").is_ok());
+        assert!(FieldType::Qty.check(b"-100").is_err());
+        assert!(FieldType::Percentage.check(b"5").is_err());
+        assert!(FieldType::Currency.check(b"USD").is_ok());
+        assert!(FieldType::Currency.check(b"US").is_err());
+        assert!(FieldType::Exchange.check(b"XLON").is_ok());
+        assert!(FieldType::Country.check(b"gb").is_err());
+        assert!(FieldType::LocalMktDate.check(b"20240229").is_ok());
+        assert!(FieldType::LocalMktDate.check(b"20230229").is_err());
+        assert!(FieldType::UtcTimeOnly.check(b"12:30:00.123").is_ok());
+        assert!(FieldType::UtcTimeOnly.check(b"20240101-12:30:00").is_err());
+        assert!(FieldType::SettlType.check(b"M3").is_ok());
+        assert!(FieldType::SettlType.check(b"M03").is_err());
+    }
+
+    #[test]
+    fn test_validate_all_collects_every_violation() {
+        let dict = DataDictionary::fix44()
+            .with_field(FieldSpec::new(1, "Account", FieldType::String).with_max_len(4))
+            .with_field(FieldSpec::new(54, "Side", FieldType::Char).with_values(["1", "2"]));
+        let msg = message(&[
+            (8, b"FIX.4.4"),
+            (35, b"AE"),
+            (34, b"0"),
+            (1, b"ACCOUNT"),
+            (54, b"7"),
+            (64, b"20240231"),
+            (9999, b"custom"),
+        ]);
+
+        // Values first, then structure against the TradeCaptureReport spec,
+        // then the required fields that are missing
+        let violations = msg.validate_all(&dict);
+        let tags: Vec<u32> = violations.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, [34, 1, 1, 54, 54, 64, 64, 571, 150, 32, 31, 552]);
+        assert!(matches!(violations[1].1, FixError::ValueTooLong { tag: 1, len: 7, width: 4 }));
+        assert!(matches!(violations[2].1, FixError::GroupFieldOutsideGroup { tag: 1, count_tag: 552 }));
+        assert!(matches!(violations[6].1, FixError::TagNotInMessage { tag: 64, .. }));
+        assert!(matches!(violations[11].1, FixError::MissingField(552)));
+        assert!(matches!(msg.validate(&dict), Err(FixError::InvalidFieldValue)));
+
+        let dict = dict.with_unknown_tag_reporting(true);
+        let violations = msg.validate_all(&dict);
+        assert!(violations.iter().any(|violation| matches!(violation, (9999, FixError::UnknownTag(9999)))));
+    }
+
+    #[test]
+    fn test_validate_structure() {
+        let dict = DataDictionary::fix44();
+        let report = |sides: &[(u32, &[u8])]| {
+            let mut fields: Vec<(u32, &[u8])> =
+                vec![(35, b"AE"), (571, b"TR-1"), (150, b"F"), (32, b"100"), (31, b"101.25")];
+            fields.extend_from_slice(sides);
+            fields.push((10, b"000"));
+            message(&fields)
+        };
+
+        let valid = report(&[(552, b"2"), (54, b"1"), (37, b"O-1"), (54, b"2"), (1, b"ACC")]);
+        assert!(valid.validate_all(&dict).is_empty());
+
+        // NoSides declares one entry too many, and a member precedes the
+        // delimiter of the first entry
+        let miscounted = report(&[(552, b"3"), (54, b"1"), (54, b"2")]);
+        assert!(matches!(
+            &miscounted.validate_all(&dict)[..],
+            [(552, FixError::GroupCountMismatch { count_tag: 552, declared: 3, actual: 2 })]
+        ));
+        let undelimited = report(&[(552, b"1"), (37, b"O-1"), (54, b"1")]);
+        assert!(matches!(
+            &undelimited.validate_all(&dict)[..],
+            [(37, FixError::GroupFieldOutsideGroup { tag: 37, count_tag: 552 })]
+        ));
+        assert!(matches!(undelimited.validate(&dict), Err(FixError::GroupFieldOutsideGroup { tag: 37, .. })));
+
+        // A defined tag the message type does not carry, after the group
+        let stray = report(&[(552, b"1"), (54, b"1"), (40, b"2")]);
+        assert!(matches!(
+            &stray.validate_all(&dict)[..],
+            [(40, FixError::TagNotInMessage { tag: 40, msg_type })] if msg_type == "TradeCaptureReport"
+        ));
+
+        // Only values are checked on message types the dictionary lacks
+        assert!(message(&[(35, b"8"), (40, b"2"), (54, b"1")]).validate_all(&dict).is_empty());
+    }
+
+    #[test]
+    fn test_int_ranges() {
+        let msg = message(&[(35, b"A"), (98, b"0"), (108, b"-5"), (373, b"100"), (102, b"x")]);
+        let violations = msg.validate_all(&DataDictionary::fix44());
+
+        assert_eq!(violations.len(), 3);
+        assert!(matches!(violations[0], (108, FixError::ValueOutOfRange { tag: 108, value: -5, .. })));
+        assert!(matches!(
+            violations[1],
+            (373, FixError::ValueOutOfRange { tag: 373, value: 100, min: 0, max: 99 })
+        ));
+        assert!(matches!(violations[2], (102, FixError::InvalidFieldValue)));
+    }
+
+    #[test]
+    fn test_flag_defaults() {
+        let dict = DataDictionary::fix44();
+        let absent = message(&[(35, b"D")]);
+        let explicit = message(&[(35, b"D"), (43, b"N"), (114, b"Y")]);
+
+        assert_eq!(dict.get_flag(&absent, 43).unwrap(), Some(Flag::Defaulted(false)));
+        assert_eq!(dict.get_flag(&explicit, 43).unwrap(), Some(Flag::Explicit(false)));
+        // LocateReqd's default is venue specific, so the standard dictionary has none
+        assert_eq!(dict.get_flag(&absent, 114).unwrap(), None);
+        assert_eq!(dict.get_flag(&explicit, 114).unwrap(), Some(Flag::Explicit(true)));
+
+        let venue = dict.with_field(FieldSpec::new(114, "LocateReqd", FieldType::Boolean).with_default("Y"));
+        assert_eq!(venue.get_flag(&absent, 114).unwrap(), Some(Flag::Defaulted(true)));
+    }
+
+    #[test]
+    fn test_valid_message_has_no_violations() {
+        let msg = message(&[
+            (8, b"FIX.4.4"),
+            (35, b"AE"),
+            (34, b"12"),
+            (52, b"20240101-12:00:00.000"),
+            (571, b"TR-1"),
+            (150, b"F"),
+            (32, b"100"),
+            (31, b"101.25"),
+            (552, b"1"),
+            (54, b"1"),
+        ]);
+        assert!(msg.validate_all(&DataDictionary::fix44()).is_empty());
+        assert!(msg.validate(&DataDictionary::fix44()).is_ok());
+    }
+
+    #[test]
+    fn test_builder() {
+        let dict = DataDictionary::fix44();
+        let mut order = dict.builder(msg_type::NEW_ORDER_SINGLE).unwrap().allow_custom(5000..=5999);
+        order
+            .set(11, "ORD1")
+            .unwrap()
+            .set(55, "VOD.L")
+            .unwrap()
+            .set(54, "1")
+            .unwrap()
+            .set(49, "CLIENT")
+            .unwrap()
+            .set(5001, "desk-7")
+            .unwrap();
+
+        assert!(matches!(
+            order.set(571, "TR-1"),
+            Err(FixError::TagNotInMessage { tag: 571, ref msg_type }) if msg_type == "NewOrderSingle"
+        ));
+        assert_eq!(order.set(9999, "x").unwrap_err().to_string(), "Tag 9999 is not defined on message type NewOrderSingle");
+        assert!(matches!(order.set(38, "-5"), Err(FixError::InvalidFieldValue)));
+        assert!(matches!(order.set(35, "8"), Err(FixError::FieldConflict(35))));
+        assert!(matches!(order.add_group(552, &[[FixField::new(54, b"1".to_vec())]]), Err(FixError::TagNotInMessage { tag: 552, .. })));
+
+        // TransactTime and OrdType are still missing
+        assert!(matches!(order.clone().build(), Err(FixError::MissingField(60))));
+        order.set(60, "20240101-12:00:00.000").unwrap().set(40, "2").unwrap().set(44, "101.25").unwrap();
+        let msg = order.build().unwrap();
+        assert!(msg.validate(&dict).is_ok());
+        assert_eq!(msg.get_field(5001).unwrap().value(), b"desk-7");
+
+        assert!(matches!(dict.builder(b"ZZ"), Err(FixError::UnknownMsgType(t)) if t == "ZZ"));
+    }
+
+    #[test]
+    fn test_builder_groups() {
+        let dict = DataDictionary::fix44();
+        let mut report = dict.builder(msg_type::TRADE_CAPTURE_REPORT).unwrap();
+        report.set(571, "TR-1").unwrap().set(150, "F").unwrap().set(32, "100").unwrap().set(31, "101.25").unwrap();
+
+        // Side and OrderID only exist inside NoSides entries here
+        assert!(matches!(
+            report.set(54, "1"),
+            Err(FixError::GroupFieldOutsideGroup { tag: 54, count_tag: 552 })
+        ));
+        assert!(matches!(report.set(552, "1"), Err(FixError::GroupFieldOutsideGroup { tag: 552, .. })));
+        assert!(matches!(report.clone().build(), Err(FixError::MissingField(552))));
+
+        let buy = [FixField::new(54, b"1".to_vec()), FixField::new(37, b"O-1".to_vec())];
+        let stray = [FixField::new(54, b"2".to_vec()), FixField::new(55, b"VOD.L".to_vec())];
+        assert!(matches!(report.add_group(552, &[stray]), Err(FixError::TagNotInGroup { tag: 55, count_tag: 552 })));
+        let bad_side = [FixField::new(54, b"12".to_vec())];
+        assert!(matches!(report.add_group(552, &[bad_side]), Err(FixError::InvalidFieldValue)));
+
+        let sell = [FixField::new(54, b"2".to_vec()), FixField::new(37, b"O-2".to_vec())];
+        report.add_group(552, &[buy, sell.clone()]).unwrap();
+        assert!(matches!(report.add_group(552, &[sell]), Err(FixError::FieldConflict(552))));
+        let msg = report.build().unwrap();
+        let tags: Vec<u32> = msg.field_tags().copied().collect();
+        assert_eq!(tags, [35, 571, 150, 32, 31, 552, 54, 37, 54, 37]);
+    }
+}
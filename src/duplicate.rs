@@ -0,0 +1,210 @@
+use crate::message::FixMessage;
+use crate::session::Clock;
+use crate::tags::Tag;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// Identifiers up to this long are held without a heap allocation
+type Key = SmallVec<[u8; 32]>;
+
+/// What `DuplicateDetector::check_and_record` found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateStatus {
+    /// First message with this identifier in the window; now recorded
+    New,
+    /// The identifier was recorded `age` ago
+    Duplicate { age: Duration },
+    /// The message has no value for the key tag, so cannot be checked
+    Untracked,
+}
+
+/// Catches application-level duplicates that session-level PossDup handling
+/// misses, such as an ExecutionReport resent under a new MsgSeqNum with the
+/// same ExecID (17). Remembers the identifiers of the last `capacity`
+/// messages seen within `window`; older ones are forgotten, so a duplicate
+/// arriving later than that passes as new.
+///
+/// Slots into an inbound pipeline as a filter ahead of the handler, e.g.
+/// `if let Some(msg) = detector.filter(&msg) { ... }`.
+#[derive(Debug, Clone)]
+pub struct DuplicateDetector {
+    tag: u32,
+    capacity: usize,
+    window: Duration,
+    clock: Clock,
+    seen: FxHashMap<Key, i64>,
+    // Recorded identifiers, oldest first
+    order: VecDeque<(Key, i64)>,
+}
+
+impl DuplicateDetector {
+    /// Keys messages by the value of `tag`
+    pub fn new(tag: u32, capacity: usize, window: Duration) -> Self {
+        Self {
+            tag,
+            capacity: capacity.max(1),
+            window,
+            clock: Clock::system(),
+            seen: FxHashMap::default(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Keyed by ExecID (17), for ExecutionReports
+    pub fn exec_ids(capacity: usize, window: Duration) -> Self {
+        Self::new(Tag::ExecID.value(), capacity, window)
+    }
+
+    /// Keyed by ClOrdID (11), for orders
+    pub fn cl_ord_ids(capacity: usize, window: Duration) -> Self {
+        Self::new(Tag::ClOrdID.value(), capacity, window)
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[inline]
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    /// Identifiers currently remembered
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Checks the message's identifier against those remembered, recording
+    /// it if it is new
+    pub fn check_and_record(&mut self, msg: &FixMessage) -> DuplicateStatus {
+        let now = self.clock.now_nanos();
+        self.evict_older_than(now);
+        let Some(id) = msg.get_field(self.tag).map(|field| field.value()) else {
+            return DuplicateStatus::Untracked;
+        };
+        if let Some(&recorded) = self.seen.get(id) {
+            let age = Duration::from_nanos(now.saturating_sub(recorded).max(0) as u64);
+            return DuplicateStatus::Duplicate { age };
+        }
+
+        if self.order.len() == self.capacity {
+            self.evict_oldest();
+        }
+        let key = Key::from_slice(id);
+        self.seen.insert(key.clone(), now);
+        self.order.push_back((key, now));
+        DuplicateStatus::New
+    }
+
+    /// The message unless it is a duplicate. Messages without the key tag
+    /// pass through.
+    pub fn filter<'m>(&mut self, msg: &'m FixMessage) -> Option<&'m FixMessage> {
+        match self.check_and_record(msg) {
+            DuplicateStatus::Duplicate { .. } => None,
+            DuplicateStatus::New | DuplicateStatus::Untracked => Some(msg),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    fn evict_older_than(&mut self, now: i64) {
+        let window = self.window.as_nanos().min(i64::MAX as u128) as i64;
+        while self.order.front().is_some_and(|&(_, at)| now.saturating_sub(at) >= window) {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((key, _)) = self.order.pop_front() {
+            self.seen.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+
+    const SECOND: i64 = 1_000_000_000;
+
+    fn execution_report(seq: u64, exec_id: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), b"8".to_vec()));
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg.add_field(FixField::new(Tag::ExecID.value(), exec_id.to_vec()));
+        msg
+    }
+
+    fn detector(capacity: usize) -> (DuplicateDetector, Arc<AtomicI64>) {
+        let now = Arc::new(AtomicI64::new(0));
+        let clock = {
+            let now = now.clone();
+            Clock::from_fn(move || now.load(Ordering::Relaxed))
+        };
+        (DuplicateDetector::exec_ids(capacity, Duration::from_secs(60)).with_clock(clock), now)
+    }
+
+    #[test]
+    fn test_detects_resent_exec_id() {
+        let (mut detector, now) = detector(100);
+        assert_eq!(detector.check_and_record(&execution_report(1, b"E1")), DuplicateStatus::New);
+        assert_eq!(detector.check_and_record(&execution_report(2, b"E2")), DuplicateStatus::New);
+
+        now.store(5 * SECOND, Ordering::Relaxed);
+        assert_eq!(
+            detector.check_and_record(&execution_report(3, b"E1")),
+            DuplicateStatus::Duplicate { age: Duration::from_secs(5) }
+        );
+
+        let mut no_exec_id = FixMessage::new();
+        no_exec_id.add_field(FixField::new(Tag::MsgType.value(), b"0".to_vec()));
+        assert_eq!(detector.check_and_record(&no_exec_id), DuplicateStatus::Untracked);
+
+        let inbound = [execution_report(4, b"E3"), execution_report(5, b"E2"), no_exec_id];
+        let passed: Vec<u64> = inbound
+            .iter()
+            .filter_map(|msg| detector.filter(msg))
+            .filter_map(|msg| msg.msg_seq_num().unwrap())
+            .map(|seq| seq.get())
+            .collect();
+        assert_eq!(passed, [4]);
+    }
+
+    #[test]
+    fn test_evicts_by_capacity_and_age() {
+        let (mut detector, now) = detector(2);
+        for (at, id) in [(0, b"E1"), (10, b"E2"), (20, b"E3")] {
+            now.store(at * SECOND, Ordering::Relaxed);
+            assert_eq!(detector.check_and_record(&execution_report(1, id)), DuplicateStatus::New);
+        }
+        // The oldest was dropped to stay within capacity
+        assert_eq!(detector.len(), 2);
+        assert_eq!(detector.check_and_record(&execution_report(1, b"E1")), DuplicateStatus::New);
+        assert!(matches!(detector.check_and_record(&execution_report(1, b"E3")), DuplicateStatus::Duplicate { .. }));
+
+        // E3 was recorded at 20s and E1 again at 20s; both age out at 80s
+        now.store(79 * SECOND, Ordering::Relaxed);
+        assert!(matches!(detector.check_and_record(&execution_report(1, b"E1")), DuplicateStatus::Duplicate { .. }));
+        now.store(80 * SECOND, Ordering::Relaxed);
+        assert_eq!(detector.check_and_record(&execution_report(1, b"E3")), DuplicateStatus::New);
+        assert_eq!(detector.len(), 1);
+
+        detector.clear();
+        assert!(detector.is_empty());
+    }
+}
@@ -1,15 +1,68 @@
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum FixError {
-    #[error("Invalid message format")]
-    InvalidFormat,
-    #[error("Invalid checksum")]
-    InvalidChecksum,
-    #[error("Missing required field: {0}")]
-    MissingField(u32),
-    #[error("Invalid field value")]
-    InvalidFieldValue,
-    #[error("Invalid body length")]
-    InvalidBodyLength,
+use crate::decimal::FixDecimal;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FixError {
+    #[error("Invalid message format")]
+    InvalidFormat,
+    #[error("Invalid checksum")]
+    InvalidChecksum,
+    #[error("Missing required field: {0}")]
+    MissingField(u32),
+    #[error("Invalid field value")]
+    InvalidFieldValue,
+    #[error("Invalid value for tag {tag}")]
+    InvalidFieldValueFor { tag: u32 },
+    #[error("BodyLength is {declared} but the body is {actual} bytes")]
+    InvalidBodyLength { declared: u64, actual: u64 },
+    #[error("Header is incomplete; BeginString or BodyLength has not been fully received")]
+    IncompleteHeader,
+    #[error("Header field out of order: {0}")]
+    HeaderOutOfOrder(u32),
+    #[error("Conflicting field: {0}")]
+    FieldConflict(u32),
+    #[error("Unexpected message type: {0}")]
+    UnexpectedMsgType(String),
+    #[error("Encoded message is {len} bytes, exceeding the limit of {max}")]
+    MessageTooLarge { len: usize, max: usize },
+    #[error("Decimal overflow")]
+    DecimalOverflow,
+    #[error("AllocQty values sum to {allocated} but Quantity is {quantity}")]
+    AllocationMismatch { allocated: FixDecimal, quantity: FixDecimal },
+    #[error("Decimal needs {scale} decimal places but at most {max} are allowed")]
+    PrecisionExceeded { scale: u8, max: u8 },
+    #[error("Value of tag {tag} is {len} bytes, wider than its fixed width of {width}")]
+    ValueTooLong { tag: u32, len: usize, width: usize },
+    #[error("Tag {0} is not defined in the dictionary")]
+    UnknownTag(u32),
+    #[error("Message type {0} is not defined in the dictionary")]
+    UnknownMsgType(String),
+    #[error("Tag {tag} is not defined on message type {msg_type}")]
+    TagNotInMessage { tag: u32, msg_type: String },
+    #[error("Tag {tag} is not a member of repeating group {count_tag}")]
+    TagNotInGroup { tag: u32, count_tag: u32 },
+    #[error("Tag {tag} belongs to repeating group {count_tag} and can only be set in one of its entries")]
+    GroupFieldOutsideGroup { tag: u32, count_tag: u32 },
+    #[error("Value {value} of tag {tag} is outside the allowed range {min}..={max}")]
+    ValueOutOfRange { tag: u32, value: i64, min: i64, max: i64 },
+    #[error("Unsupported message encoding: {0}")]
+    UnsupportedEncoding(String),
+    #[error("Compression failed: {0}")]
+    Compression(String),
+    #[error("Transport error: {0}")]
+    Transport(String),
+    #[error("Message store error: {0}")]
+    Store(String),
+    #[error("No session configured for {0}")]
+    UnknownSession(String),
+    #[error("Invalid session state snapshot: {0}")]
+    InvalidSnapshot(String),
+    #[error("Invalid setting at line {line}: {reason}")]
+    InvalidSetting { line: usize, reason: String },
+    #[error("Repeating group {count_tag} declares {declared} entries but contains {actual}")]
+    GroupCountMismatch {
+        count_tag: u32,
+        declared: usize,
+        actual: usize,
+    },
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use crate::datetime::UtcTimestamp;
+use crate::field::FixField;
+use crate::log::LogReader;
+use crate::message::FixMessage;
+use crate::messages::group_entries;
+use crate::tags::Tag;
+use std::io::{self, BufRead, Write};
+
+/// Columns written before the selected tags
+const METADATA_COLUMNS: [&str; 3] = ["timestamp", "msg_type", "seq_num"];
+
+/// Writes a header row and one row per message, with a column per tag in
+/// `tags` after the timestamp, MsgType and MsgSeqNum columns. The timestamp
+/// column is left empty.
+pub fn write<'a>(
+    messages: impl IntoIterator<Item = &'a FixMessage>,
+    tags: &[u32],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let exporter = CsvExporter::new(tags);
+    exporter.write_header(out)?;
+    for msg in messages {
+        exporter.write_message(None, msg, out)?;
+    }
+    Ok(())
+}
+
+/// Flattens messages into RFC 4180 CSV: CRLF line endings, and cells
+/// containing a comma, quote or line break quoted with inner quotes
+/// doubled. A missing field is an empty cell and values are written as
+/// their raw bytes. Column headers are the tag numbers.
+#[derive(Debug, Clone)]
+pub struct CsvExporter {
+    tags: Vec<u32>,
+    group: Option<(u32, Vec<u32>)>,
+}
+
+impl CsvExporter {
+    pub fn new(tags: &[u32]) -> Self {
+        Self { tags: tags.to_vec(), group: None }
+    }
+
+    /// Writes one row per entry of the repeating group introduced by
+    /// `count_tag`. Columns for `members`, whose first tag delimits each
+    /// entry, take their value from the entry; the rest are repeated from
+    /// the message. A message without the group still gets one row.
+    pub fn with_group(mut self, count_tag: u32, members: &[u32]) -> Self {
+        self.group = Some((count_tag, members.to_vec()));
+        self
+    }
+
+    pub fn write_header(&self, out: &mut impl Write) -> io::Result<()> {
+        let tags: Vec<String> = self.tags.iter().map(u32::to_string).collect();
+        let cells = METADATA_COLUMNS.iter().map(|c| c.as_bytes()).chain(tags.iter().map(String::as_bytes));
+        write_row(cells, out)
+    }
+
+    /// Writes the rows for one message, returning how many were written.
+    /// A repeating group whose entries do not match its count is
+    /// `InvalidData`.
+    pub fn write_message(&self, timestamp: Option<&UtcTimestamp>, msg: &FixMessage, out: &mut impl Write) -> io::Result<usize> {
+        let timestamp = timestamp.map(UtcTimestamp::to_string).unwrap_or_default();
+        let header_value = |tag: Tag| msg.get_field(tag.value()).map_or(&[][..], FixField::value);
+        let metadata = [timestamp.as_bytes(), header_value(Tag::MsgType), header_value(Tag::MsgSeqNum)];
+
+        let entries = match &self.group {
+            Some((count_tag, members)) => group_entries(msg, *count_tag, members)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            None => Vec::new(),
+        };
+        let members: &[u32] = self.group.as_ref().map_or(&[], |(_, members)| members);
+
+        let row = |entry: Option<&[FixField]>, out: &mut dyn Write| {
+            let cells = self.tags.iter().map(|&tag| {
+                let field = match entry {
+                    Some(entry) if members.contains(&tag) => entry.iter().find(|f| f.tag() == tag),
+                    _ if members.contains(&tag) => None,
+                    _ => msg.get_field(tag),
+                };
+                field.map_or(&[][..], FixField::value)
+            });
+            write_row(metadata.into_iter().chain(cells), out)
+        };
+
+        if entries.is_empty() {
+            row(None, out)?;
+            return Ok(1);
+        }
+        for &entry in &entries {
+            row(Some(entry), out)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Streams a log into CSV with constant memory, taking the timestamp
+    /// column from each line. Lines whose message does not decode are
+    /// skipped. Returns the number of rows written, not counting the header.
+    pub fn write_log<R: BufRead>(&self, log: LogReader<R>, out: &mut impl Write) -> io::Result<usize> {
+        self.write_header(out)?;
+        let mut rows = 0;
+        for record in log {
+            let record = record?;
+            if let Ok(msg) = &record.message {
+                rows += self.write_message(record.timestamp.as_ref(), msg, out)?;
+            }
+        }
+        Ok(rows)
+    }
+}
+
+fn write_row<'a>(cells: impl IntoIterator<Item = &'a [u8]>, out: &mut (impl Write + ?Sized)) -> io::Result<()> {
+    for (i, cell) in cells.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        write_cell(cell, out)?;
+    }
+    out.write_all(b"\r\n")
+}
+
+fn write_cell(cell: &[u8], out: &mut (impl Write + ?Sized)) -> io::Result<()> {
+    if !cell.iter().any(|b| matches!(b, b',' | b'"' | b'\r' | b'\n')) {
+        return out.write_all(cell);
+    }
+    out.write_all(b"\"")?;
+    for part in cell.split_inclusive(|&b| b == b'"') {
+        out.write_all(part)?;
+        if part.ends_with(b"\"") {
+            out.write_all(b"\"")?;
+        }
+    }
+    out.write_all(b"\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::{fix_version, msg_type};
+
+    fn order(seq: u64, symbol: &[u8], text: Option<&[u8]>) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg.add_field(FixField::new(Tag::Symbol.value(), symbol.to_vec()));
+        if let Some(text) = text {
+            msg.add_field(FixField::new(Tag::Text.value(), text.to_vec()));
+        }
+        msg
+    }
+
+    #[test]
+    fn test_write_with_quoting() {
+        let messages = [
+            order(1, b"VOD", None),
+            order(2, b"BARC", Some(b"price \"firm\", size 10")),
+            order(3, b"HSBA", Some(b"two\nlines")),
+        ];
+        let mut out = Vec::new();
+        write(&messages, &[Tag::Symbol.value(), Tag::Text.value()], &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timestamp,msg_type,seq_num,55,58\r\n\
+             ,D,1,VOD,\r\n\
+             ,D,2,BARC,\"price \"\"firm\"\", size 10\"\r\n\
+             ,D,3,HSBA,\"two\nlines\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_group_rows_from_log() {
+        let log = "2024-01-01T12:00:00.500Z IN 8=FIX.4.4|9=53|35=AE|34=7|571=T1|552=2|54=1|37=O1|1=ACC1|54=2|37=O2|10=221|\n\
+                   2024-01-01T12:00:01Z IN 8=FIX.4.4|9=18|35=AE|34=8|571=T2|10=107|\n\
+                   2024-01-01T12:00:02Z IN 8=FIX.4.4|9=18|35=AE|34=9|571=T3|10=000|\n";
+        let exporter = CsvExporter::new(&[571, 54, 37, 1])
+            .with_group(Tag::NoSides.value(), &[54, 37, 11, 1]);
+        let mut out = Vec::new();
+        let rows = exporter.write_log(LogReader::from_read(log.as_bytes()), &mut out).unwrap();
+
+        assert_eq!(rows, 3);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timestamp,msg_type,seq_num,571,54,37,1\r\n\
+             20240101-12:00:00.500,AE,7,T1,1,O1,ACC1\r\n\
+             20240101-12:00:00.500,AE,7,T1,2,O2,\r\n\
+             20240101-12:00:01,AE,8,T2,,,\r\n"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::error::FixError;
+use crate::message::FixMessage;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// Result of every FFI call. The numbering is part of the C interface and
+/// must not change; new codes are only ever appended.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    InvalidFormat = 2,
+    InvalidChecksum = 3,
+    InvalidBodyLength = 4,
+    MissingField = 5,
+    /// The input ends before the message does
+    Incomplete = 6,
+    /// The requested tag is not in the message
+    NotFound = 7,
+    /// The index is not below the field count
+    OutOfRange = 8,
+    /// The caller's buffer is too small; the required size is reported
+    BufferTooSmall = 9,
+    /// Any other error reported by the codec
+    Other = 10,
+    /// The call panicked; no output was written
+    Panic = 11,
+}
+
+impl From<FixError> for FixStatus {
+    fn from(err: FixError) -> Self {
+        match err {
+            FixError::InvalidFormat => FixStatus::InvalidFormat,
+            FixError::InvalidChecksum => FixStatus::InvalidChecksum,
+            FixError::InvalidBodyLength { .. } => FixStatus::InvalidBodyLength,
+            FixError::MissingField(_) => FixStatus::MissingField,
+            FixError::IncompleteHeader => FixStatus::Incomplete,
+            _ => FixStatus::Other,
+        }
+    }
+}
+
+/// A decoded message owned by the library. Opaque to C; created by
+/// `fix_msg_decode` and released with `fix_msg_free`.
+pub struct FixMsgHandle {
+    msg: FixMessage,
+}
+
+// Runs `f`, turning a panic into `FixStatus::Panic` so none unwinds into C
+fn guard(f: impl FnOnce() -> FixStatus) -> FixStatus {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(FixStatus::Panic)
+}
+
+/// Decodes one message from `data[..len]` and stores a new handle in `*out`.
+/// `*out` is set to null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes and `out` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_decode(data: *const u8, len: usize, out: *mut *mut FixMsgHandle) -> FixStatus {
+    if data.is_null() || out.is_null() {
+        return FixStatus::NullPointer;
+    }
+    *out = ptr::null_mut();
+    guard(|| match FixMessage::decode(std::slice::from_raw_parts(data, len)) {
+        Ok(msg) => {
+            *out = Box::into_raw(Box::new(FixMsgHandle { msg }));
+            FixStatus::Ok
+        }
+        Err(err) => err.into(),
+    })
+}
+
+/// Points `*val` at the first value of `tag` and stores its length in
+/// `*len`. The value is not NUL-terminated and stays valid until the handle
+/// is freed.
+///
+/// # Safety
+///
+/// `handle` must come from `fix_msg_decode` and not have been freed; `val`
+/// and `len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_get(
+    handle: *const FixMsgHandle,
+    tag: u32,
+    val: *mut *const u8,
+    len: *mut usize,
+) -> FixStatus {
+    if handle.is_null() || val.is_null() || len.is_null() {
+        return FixStatus::NullPointer;
+    }
+    guard(|| match (*handle).msg.get_field(tag) {
+        Some(field) => {
+            *val = field.value().as_ptr();
+            *len = field.value().len();
+            FixStatus::Ok
+        }
+        None => FixStatus::NotFound,
+    })
+}
+
+/// Stores the number of fields, BodyLength and CheckSum included, in `*count`
+///
+/// # Safety
+///
+/// `handle` must come from `fix_msg_decode` and not have been freed; `count`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_field_count(handle: *const FixMsgHandle, count: *mut usize) -> FixStatus {
+    if handle.is_null() || count.is_null() {
+        return FixStatus::NullPointer;
+    }
+    guard(|| {
+        *count = (*handle).msg.len();
+        FixStatus::Ok
+    })
+}
+
+/// The field at `index` in wire order, as for `fix_msg_get`
+///
+/// # Safety
+///
+/// `handle` must come from `fix_msg_decode` and not have been freed; `tag`,
+/// `val` and `len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_field_at(
+    handle: *const FixMsgHandle,
+    index: usize,
+    tag: *mut u32,
+    val: *mut *const u8,
+    len: *mut usize,
+) -> FixStatus {
+    if handle.is_null() || tag.is_null() || val.is_null() || len.is_null() {
+        return FixStatus::NullPointer;
+    }
+    guard(|| match (*handle).msg.field_slice().get(index) {
+        Some(field) => {
+            *tag = field.tag();
+            *val = field.value().as_ptr();
+            *len = field.value().len();
+            FixStatus::Ok
+        }
+        None => FixStatus::OutOfRange,
+    })
+}
+
+/// Encodes the message into `buf[..cap]` and stores the encoded length in
+/// `*written`. When `cap` is too small nothing is copied, `*written` holds
+/// the size needed and `BufferTooSmall` is returned.
+///
+/// # Safety
+///
+/// `handle` must come from `fix_msg_decode` and not have been freed; `buf`
+/// must point to `cap` writable bytes, and may only be null when `cap` is 0;
+/// `written` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_encode(
+    handle: *const FixMsgHandle,
+    buf: *mut u8,
+    cap: usize,
+    written: *mut usize,
+) -> FixStatus {
+    if handle.is_null() || written.is_null() || (buf.is_null() && cap > 0) {
+        return FixStatus::NullPointer;
+    }
+    guard(|| match (*handle).msg.encode() {
+        Ok(encoded) => {
+            *written = encoded.len();
+            if encoded.len() > cap {
+                return FixStatus::BufferTooSmall;
+            }
+            ptr::copy_nonoverlapping(encoded.as_ptr(), buf, encoded.len());
+            FixStatus::Ok
+        }
+        Err(err) => err.into(),
+    })
+}
+
+/// Releases a handle. Null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or come from `fix_msg_decode`, and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fix_msg_free(handle: *mut FixMsgHandle) {
+    if !handle.is_null() {
+        // Dropping a FixMessage does not panic, but nothing may unwind into C
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(handle))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEARTBEAT: &[u8] = b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01";
+
+    #[test]
+    fn test_decode_get_encode() {
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(fix_msg_decode(HEARTBEAT.as_ptr(), HEARTBEAT.len(), &mut handle), FixStatus::Ok);
+
+            let (mut val, mut len) = (ptr::null(), 0);
+            assert_eq!(fix_msg_get(handle, 35, &mut val, &mut len), FixStatus::Ok);
+            assert_eq!(std::slice::from_raw_parts(val, len), b"0");
+            assert_eq!(fix_msg_get(handle, 58, &mut val, &mut len), FixStatus::NotFound);
+
+            let mut count = 0;
+            assert_eq!(fix_msg_field_count(handle, &mut count), FixStatus::Ok);
+            let mut tag = 0;
+            assert_eq!(fix_msg_field_at(handle, count - 1, &mut tag, &mut val, &mut len), FixStatus::Ok);
+            assert_eq!(tag, 10);
+            assert_eq!(fix_msg_field_at(handle, count, &mut tag, &mut val, &mut len), FixStatus::OutOfRange);
+
+            let mut written = 0;
+            assert_eq!(fix_msg_encode(handle, ptr::null_mut(), 0, &mut written), FixStatus::BufferTooSmall);
+            let mut buf = vec![0; written];
+            assert_eq!(fix_msg_encode(handle, buf.as_mut_ptr(), buf.len(), &mut written), FixStatus::Ok);
+            assert_eq!(buf, HEARTBEAT);
+
+            fix_msg_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        unsafe {
+            let mut handle = ptr::null_mut();
+            let bad = b"8=FIX.4.4\x019=5\x0135=0\x0110=000\x01";
+            assert_eq!(fix_msg_decode(bad.as_ptr(), bad.len(), &mut handle), FixStatus::InvalidChecksum);
+            assert!(handle.is_null());
+            assert_eq!(fix_msg_decode(ptr::null(), 0, &mut handle), FixStatus::NullPointer);
+            fix_msg_free(ptr::null_mut());
+        }
+    }
+}
@@ -1,68 +1,460 @@
-use bytes::{BufMut, BytesMut};
-use std::fmt;
-use smallvec::SmallVec;
-use itoa::Buffer as ItoaBuffer;
-
-pub const SOH: u8 = 0x01;
-pub const EQUALS: u8 = b'=';
-
-thread_local! {
-    static TAG_BUFFER: std::cell::RefCell<ItoaBuffer> = std::cell::RefCell::new(ItoaBuffer::new());
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct FixField {
-    tag: u32,
-    value: SmallVec<[u8; 32]>, // Most FIX fields are small, optimize for stack allocation
-}
-
-impl FixField {
-    #[inline]
-    pub fn new(tag: u32, value: impl Into<SmallVec<[u8; 32]>>) -> Self {
-        Self {
-            tag,
-            value: value.into()
-        }
-    }
-
-    #[inline]
-    pub fn tag(&self) -> u32 {
-        self.tag
-    }
-
-    #[inline]
-    pub fn value(&self) -> &[u8] {
-        &self.value
-    }
-
-    #[inline]
-    pub fn encode(&self, buf: &mut BytesMut) {
-        TAG_BUFFER.with(|buffer| {
-            let mut buffer = buffer.borrow_mut();
-            buf.put_slice(buffer.format(self.tag).as_bytes());
-        });
-        buf.put_u8(EQUALS);
-        buf.put_slice(&self.value);
-        buf.put_u8(SOH);
-    }
-
-    #[inline]
-    pub fn encoded_len(&self) -> usize {
-        // Pre-calculate tag length using itoa
-        TAG_BUFFER.with(|buffer| {
-            let mut buffer = buffer.borrow_mut();
-            buffer.format(self.tag).len()
-        }) + 1 + self.value.len() + 1
-    }
-}
-
-impl fmt::Display for FixField {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}={}",
-            self.tag,
-            String::from_utf8_lossy(&self.value)
-        )
-    }
+use crate::datetime::UtcTimestamp;
+use crate::decimal::FixDecimal;
+use crate::error::FixError;
+use bytes::{BufMut, BytesMut};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use smallvec::SmallVec;
+use itoa::Buffer as ItoaBuffer;
+
+pub const SOH: u8 = 0x01;
+pub const EQUALS: u8 = b'=';
+
+thread_local! {
+    static TAG_BUFFER: std::cell::RefCell<ItoaBuffer> = std::cell::RefCell::new(ItoaBuffer::new());
+}
+
+/// A typed FIX value that can be read from, and written back to, the raw bytes
+/// of a field
+pub trait FieldValue: Sized {
+    fn parse(value: &[u8]) -> Result<Self, FixError>;
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>);
+}
+
+/// Parses an unsigned integer directly from ASCII digits, rejecting signs,
+/// whitespace and values that overflow
+#[inline]
+pub(crate) fn parse_uint(value: &[u8]) -> Result<u64, FixError> {
+    if value.is_empty() {
+        return Err(FixError::InvalidFieldValue);
+    }
+    value.iter().try_fold(0u64, |acc, &b| {
+        if !b.is_ascii_digit() {
+            return Err(FixError::InvalidFieldValue);
+        }
+        acc.checked_mul(10)
+            .and_then(|acc| acc.checked_add((b - b'0') as u64))
+            .ok_or(FixError::InvalidFieldValue)
+    })
+}
+
+/// Parses a signed integer with an optional leading '-'
+#[inline]
+pub(crate) fn parse_int(value: &[u8]) -> Result<i64, FixError> {
+    match value {
+        [b'-', digits @ ..] => {
+            let magnitude = parse_uint(digits)?;
+            0i64.checked_sub_unsigned(magnitude).ok_or(FixError::InvalidFieldValue)
+        }
+        _ => i64::try_from(parse_uint(value)?).map_err(|_| FixError::InvalidFieldValue),
+    }
+}
+
+impl FieldValue for u64 {
+    #[inline]
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        parse_uint(value)
+    }
+
+    #[inline]
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        buf.extend_from_slice(ItoaBuffer::new().format(*self).as_bytes());
+    }
+}
+
+impl FieldValue for i64 {
+    #[inline]
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        parse_int(value)
+    }
+
+    #[inline]
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        buf.extend_from_slice(ItoaBuffer::new().format(*self).as_bytes());
+    }
+}
+
+/// Boolean fields: "Y" or "N"
+impl FieldValue for bool {
+    #[inline]
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        match value {
+            b"Y" => Ok(true),
+            b"N" => Ok(false),
+            _ => Err(FixError::InvalidFieldValue),
+        }
+    }
+
+    #[inline]
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        buf.push(if *self { b'Y' } else { b'N' });
+    }
+}
+
+/// A Boolean field with its default applied, keeping track of whether the
+/// value was actually sent. Lets typed messages tell "absent, meaning N" from
+/// an explicit "N".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flag {
+    Explicit(bool),
+    Defaulted(bool),
+}
+
+impl Flag {
+    #[inline]
+    pub fn get(self) -> bool {
+        match self {
+            Flag::Explicit(value) | Flag::Defaulted(value) => value,
+        }
+    }
+
+    #[inline]
+    pub fn is_explicit(self) -> bool {
+        matches!(self, Flag::Explicit(_))
+    }
+}
+
+/// Implements `Display` and `FromStr` for a `FieldValue` in terms of its wire
+/// form
+macro_rules! impl_text_traits {
+    ($ty:ty) => {
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let mut buf = smallvec::SmallVec::new();
+                $crate::field::FieldValue::write(self, &mut buf);
+                f.write_str(&String::from_utf8_lossy(&buf))
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = $crate::error::FixError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <$ty as $crate::field::FieldValue>::parse(s.as_bytes())
+            }
+        }
+    };
+}
+pub(crate) use impl_text_traits;
+
+/// Which side of a fixed-width value the content sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Content first, padded on the right ("ACC1        ")
+    Left,
+    /// Padded on the left, content last ("000042")
+    Right,
+}
+
+/// Fixed-width layout some legacy counterparties require for a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddingRule {
+    pub width: usize,
+    pub pad_byte: u8,
+    pub alignment: Alignment,
+}
+
+impl PaddingRule {
+    #[inline]
+    pub fn new(width: usize, pad_byte: u8, alignment: Alignment) -> Self {
+        Self { width, pad_byte, alignment }
+    }
+
+    /// Pads `value` to the full width. Padding already present on the padded
+    /// side is stripped first, so applying the rule twice changes nothing.
+    pub fn apply(&self, tag: u32, value: &[u8]) -> Result<SmallVec<[u8; 32]>, FixError> {
+        let value = self.strip(value);
+        if value.len() > self.width {
+            return Err(FixError::ValueTooLong { tag, len: value.len(), width: self.width });
+        }
+
+        let padding = std::iter::repeat_n(self.pad_byte, self.width - value.len());
+        let mut buf = SmallVec::with_capacity(self.width);
+        match self.alignment {
+            Alignment::Left => {
+                buf.extend_from_slice(value);
+                buf.extend(padding);
+            }
+            Alignment::Right => {
+                buf.extend(padding);
+                buf.extend_from_slice(value);
+            }
+        }
+        Ok(buf)
+    }
+
+    fn strip<'a>(&self, value: &'a [u8]) -> &'a [u8] {
+        match self.alignment {
+            Alignment::Left => {
+                let len = value.iter().rposition(|&b| b != self.pad_byte).map_or(0, |i| i + 1);
+                &value[..len]
+            }
+            Alignment::Right => {
+                let start = value.iter().position(|&b| b != self.pad_byte).unwrap_or(value.len());
+                &value[start..]
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixField {
+    tag: u32,
+    value: SmallVec<[u8; 32]>, // Most FIX fields are small, optimize for stack allocation
+}
+
+impl FixField {
+    #[inline]
+    pub fn new(tag: u32, value: impl Into<SmallVec<[u8; 32]>>) -> Self {
+        Self {
+            tag,
+            value: value.into()
+        }
+    }
+
+    #[inline]
+    pub fn from_value<T: FieldValue>(tag: u32, value: &T) -> Self {
+        let mut buf = SmallVec::new();
+        value.write(&mut buf);
+        Self { tag, value: buf }
+    }
+
+    /// Formats `value` straight into the field's inline buffer, as do the
+    /// other typed constructors, so building it does not touch the heap
+    #[inline]
+    pub fn from_u64(tag: u32, value: u64) -> Self {
+        Self::from_value(tag, &value)
+    }
+
+    #[inline]
+    pub fn from_i64(tag: u32, value: i64) -> Self {
+        Self::from_value(tag, &value)
+    }
+
+    /// "Y" or "N"
+    #[inline]
+    pub fn from_bool(tag: u32, value: bool) -> Self {
+        Self::from_value(tag, &value)
+    }
+
+    #[inline]
+    pub fn from_char(tag: u32, value: char) -> Self {
+        let mut utf8 = [0; 4];
+        Self::new(tag, SmallVec::from_slice(value.encode_utf8(&mut utf8).as_bytes()))
+    }
+
+    /// `value` rounded half away from zero to exactly `places` decimal
+    /// places, e.g. 101.5 to 2 places as "101.50". NaN is an
+    /// `InvalidFieldValue`; infinities, and values or places beyond what a
+    /// `FixDecimal` holds, are a `DecimalOverflow`.
+    pub fn from_f64_with_precision(tag: u32, value: f64, places: u8) -> Result<Self, FixError> {
+        if value.is_nan() {
+            return Err(FixError::InvalidFieldValue);
+        }
+        let scaled = (value * 10f64.powi(places as i32)).round();
+        // i64::MAX as f64 rounds up to 2^63, itself out of range
+        if scaled.abs() >= i64::MAX as f64 {
+            return Err(FixError::DecimalOverflow);
+        }
+        Ok(Self::from_decimal(tag, FixDecimal::new(scaled as i64, places)?))
+    }
+
+    /// Builds a fixed-width field, failing with `ValueTooLong` rather than
+    /// truncating when `value` does not fit in `width`
+    pub fn from_padded(
+        tag: u32,
+        value: &[u8],
+        width: usize,
+        pad_byte: u8,
+        alignment: Alignment,
+    ) -> Result<Self, FixError> {
+        let value = PaddingRule::new(width, pad_byte, alignment).apply(tag, value)?;
+        Ok(Self { tag, value })
+    }
+
+    // Makes this field `tag`=`value`, reusing the value's buffer
+    #[inline]
+    pub(crate) fn overwrite(&mut self, tag: u32, value: &[u8]) {
+        self.tag = tag;
+        self.value.clear();
+        self.value.extend_from_slice(value);
+    }
+
+    #[inline]
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    #[inline]
+    pub(crate) fn tag_ref(&self) -> &u32 {
+        &self.tag
+    }
+
+    #[inline]
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// The value as text, failing with `InvalidFieldValueFor` when it is
+    /// not UTF-8
+    #[inline]
+    pub fn as_str(&self) -> Result<&str, FixError> {
+        std::str::from_utf8(&self.value).map_err(|_| self.invalid())
+    }
+
+    /// The value as text with surrounding space padding removed
+    #[inline]
+    pub fn as_trimmed_str(&self) -> Result<&str, FixError> {
+        std::str::from_utf8(&self.value)
+            .map(|value| value.trim_matches(' '))
+            .map_err(|_| FixError::InvalidFieldValue)
+    }
+
+    /// The value as an unsigned integer, read straight from the bytes:
+    /// ASCII digits only
+    #[inline]
+    pub fn as_u64(&self) -> Result<u64, FixError> {
+        parse_uint(&self.value)
+    }
+
+    /// The value as a signed integer with an optional leading '-' or '+'
+    pub fn as_i64(&self) -> Result<i64, FixError> {
+        match self.value.as_slice() {
+            [b'+', digits @ ..] => i64::try_from(parse_uint(digits)?).map_err(|_| FixError::InvalidFieldValue),
+            value => parse_int(value),
+        }
+    }
+
+    /// The value as a decimal such as a price, read straight from the
+    /// bytes; see `FixDecimal` for the accepted forms
+    #[inline]
+    pub fn as_decimal(&self) -> Result<FixDecimal, FixError> {
+        FixDecimal::parse(&self.value)
+    }
+
+    /// The value as a float, accepting the same forms as `as_decimal`
+    /// rather than Rust's, so no exponents, "inf" or "NaN". Prefer
+    /// `as_decimal` for prices that must not pick up binary rounding.
+    pub fn as_f64(&self) -> Result<f64, FixError> {
+        let value = self.as_decimal().map_err(|err| self.with_tag(err))?;
+        Ok(value.mantissa() as f64 / 10f64.powi(value.scale() as i32))
+    }
+
+    /// The value of a Boolean field: "Y" or "N"
+    #[inline]
+    pub fn as_bool(&self) -> Result<bool, FixError> {
+        self.parse_value().map_err(|err| self.with_tag(err))
+    }
+
+    /// The value of a char field such as Side (54): exactly one ASCII
+    /// character
+    #[inline]
+    pub fn as_char(&self) -> Result<char, FixError> {
+        match self.value.as_slice() {
+            [c] if c.is_ascii() => Ok(*c as char),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    #[inline]
+    fn invalid(&self) -> FixError {
+        FixError::InvalidFieldValueFor { tag: self.tag }
+    }
+
+    // Names this field's tag in an `InvalidFieldValue`, leaving other errors
+    pub(crate) fn with_tag(&self, err: FixError) -> FixError {
+        match err {
+            FixError::InvalidFieldValue => self.invalid(),
+            err => err,
+        }
+    }
+
+    /// A field holding `value` in positional notation, keeping its scale
+    #[inline]
+    pub fn from_decimal(tag: u32, value: FixDecimal) -> Self {
+        Self::from_value(tag, &value)
+    }
+
+    /// The value as a UTCTimestamp such as SendingTime (52), with no
+    /// fraction or one of 3, 6 or 9 digits. Unlike `parse_value`, a leap
+    /// second (60) is rejected, so the result always names a second that
+    /// calendar types without leap seconds can hold.
+    pub fn as_timestamp(&self) -> Result<UtcTimestamp, FixError> {
+        let timestamp = UtcTimestamp::parse(&self.value)?;
+        if timestamp.time().second() > 59 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(timestamp)
+    }
+
+    /// A field holding `value` to the millisecond, whatever its precision
+    pub fn from_timestamp(tag: u32, value: UtcTimestamp) -> Self {
+        Self::from_value(tag, &value.with_precision(3).expect("3 is a valid precision"))
+    }
+
+    /// `as_timestamp` as a chrono date and time
+    #[cfg(feature = "chrono")]
+    pub fn as_naive_datetime(&self) -> Result<chrono::NaiveDateTime, FixError> {
+        self.as_timestamp().map(Into::into)
+    }
+
+    /// A field holding `value` to the millisecond, as `from_timestamp`
+    #[cfg(feature = "chrono")]
+    pub fn from_naive_datetime(tag: u32, value: chrono::NaiveDateTime) -> Result<Self, FixError> {
+        Ok(Self::from_timestamp(tag, UtcTimestamp::try_from(value)?))
+    }
+
+    /// A field holding `value` with `precision` fractional digits: 0, 3 for
+    /// milliseconds, 6 for microseconds or 9, dropping any finer ones
+    pub fn from_timestamp_with_precision(tag: u32, value: UtcTimestamp, precision: u8) -> Result<Self, FixError> {
+        Ok(Self::from_value(tag, &value.with_precision(precision)?))
+    }
+
+    /// A UTCTimestamp field for `time`, e.g. `SystemTime::now()`, with
+    /// `precision` fractional digits as for `from_timestamp_with_precision`
+    pub fn from_system_time(tag: u32, time: SystemTime, precision: u8) -> Result<Self, FixError> {
+        let nanos = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_nanos()),
+            Err(before) => i64::try_from(before.duration().as_nanos()).map(|nanos| -nanos),
+        }
+        .map_err(|_| FixError::InvalidFieldValue)?;
+        Ok(Self::from_value(tag, &UtcTimestamp::from_unix_nanos(nanos, precision)?))
+    }
+
+    #[inline]
+    pub fn parse_value<T: FieldValue>(&self) -> Result<T, FixError> {
+        T::parse(&self.value)
+    }
+
+    #[inline]
+    pub fn encode(&self, buf: &mut BytesMut) {
+        TAG_BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            buf.put_slice(buffer.format(self.tag).as_bytes());
+        });
+        buf.put_u8(EQUALS);
+        buf.put_slice(&self.value);
+        buf.put_u8(SOH);
+    }
+
+    #[inline]
+    pub fn encoded_len(&self) -> usize {
+        // Pre-calculate tag length using itoa
+        TAG_BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            buffer.format(self.tag).len()
+        }) + 1 + self.value.len() + 1
+    }
+}
+
+impl fmt::Display for FixField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            self.tag,
+            String::from_utf8_lossy(&self.value)
+        )
+    }
 }
\ No newline at end of file
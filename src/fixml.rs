@@ -0,0 +1,555 @@
+use crate::dictionary::{shared_fix44 as fix44, DataDictionary};
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::messages::group_entries;
+use crate::tags::{msg_type, Tag};
+use std::fmt::Write as _;
+
+const BEGIN_STRING_PREFIX: &str = "FIX.";
+
+// Deepest element nesting accepted when parsing; FIXML itself needs four
+const MAX_DEPTH: usize = 32;
+
+/// An element nested in a FIXML message element
+enum Component {
+    /// One element holding the listed fields as attributes
+    Block { element: &'static str, tags: &'static [u32] },
+    /// One element per entry of the repeating group introduced by
+    /// `count_tag`; the count itself is implied by the repetition
+    Group { element: &'static str, count_tag: u32, members: &'static [u32] },
+}
+
+/// How one message type maps onto FIXML. Fields that belong to no
+/// component are attributes of the message element.
+struct Layout {
+    msg_type: &'static [u8],
+    element: &'static str,
+    components: &'static [Component],
+}
+
+const HEADER: Component = Component::Block {
+    element: "Hdr",
+    tags: &[
+        Tag::SenderCompID.value(),
+        Tag::TargetCompID.value(),
+        Tag::MsgSeqNum.value(),
+        Tag::SendingTime.value(),
+        Tag::PossDupFlag.value(),
+        Tag::PossResend.value(),
+    ],
+};
+
+const INSTRUMENT: Component = Component::Block {
+    element: "Instrmt",
+    tags: &[
+        Tag::Symbol.value(),
+        Tag::MaturityMonthYear.value(),
+        Tag::SecurityExchange.value(),
+        Tag::CountryOfIssue.value(),
+        Tag::StrikeCurrency.value(),
+    ],
+};
+
+const LAYOUTS: &[Layout] = &[
+    Layout {
+        msg_type: msg_type::EXECUTION_REPORT,
+        element: "ExecRpt",
+        components: &[
+            HEADER,
+            INSTRUMENT,
+            Component::Block { element: "OrdQty", tags: &[Tag::OrderQty.value()] },
+        ],
+    },
+    Layout {
+        msg_type: msg_type::ALLOCATION_INSTRUCTION,
+        element: "AllocInstrctn",
+        components: &[
+            HEADER,
+            Component::Group {
+                element: "OrdAlloc",
+                count_tag: Tag::NoOrders.value(),
+                members: &[Tag::ClOrdID.value(), Tag::OrderID.value()],
+            },
+            INSTRUMENT,
+            Component::Group {
+                element: "Alloc",
+                count_tag: Tag::NoAllocs.value(),
+                members: &[Tag::AllocAccount.value(), Tag::AllocQty.value()],
+            },
+        ],
+    },
+    Layout {
+        msg_type: msg_type::TRADE_CAPTURE_REPORT,
+        element: "TrdCaptRpt",
+        components: &[
+            HEADER,
+            INSTRUMENT,
+            Component::Group {
+                element: "RptSide",
+                count_tag: Tag::NoSides.value(),
+                members: &[Tag::Side.value(), Tag::OrderID.value(), Tag::ClOrdID.value(), Tag::Account.value()],
+            },
+        ],
+    },
+    Layout {
+        msg_type: msg_type::ORDER_CANCEL_REJECT,
+        element: "OrdCxlRej",
+        components: &[HEADER],
+    },
+];
+
+impl Layout {
+    fn for_msg_type(msg_type: &[u8]) -> Option<&'static Layout> {
+        LAYOUTS.iter().find(|layout| layout.msg_type == msg_type)
+    }
+
+    fn for_element(element: &str) -> Option<&'static Layout> {
+        LAYOUTS.iter().find(|layout| layout.element == element)
+    }
+
+    // Whether `tag` is written inside a component rather than on the
+    // message element
+    fn in_component(&self, tag: u32) -> bool {
+        self.components.iter().any(|component| component.contains(tag))
+    }
+}
+
+impl Component {
+    fn contains(&self, tag: u32) -> bool {
+        match self {
+            Component::Block { tags, .. } => tags.contains(&tag),
+            Component::Group { count_tag, members, .. } => *count_tag == tag || members.contains(&tag),
+        }
+    }
+}
+
+impl FixMessage {
+    /// Renders the message as FIXML with the FIX 4.4 attribute names. Only
+    /// message types with a FIXML layout are supported, currently
+    /// ExecutionReport, OrderCancelReject, AllocationInstruction and
+    /// TradeCaptureReport.
+    pub fn to_fixml(&self) -> Result<String, FixError> {
+        self.to_fixml_with(fix44())
+    }
+
+    /// Renders the message as FIXML, naming attributes from `dict`. A field
+    /// without a FIXML name is `FixError::UnknownTag`.
+    pub fn to_fixml_with(&self, dict: &DataDictionary) -> Result<String, FixError> {
+        let field_value = |tag: u32| self.get_field(tag).map(FixField::value);
+        let msg_type = field_value(Tag::MsgType.value()).ok_or(FixError::MissingField(Tag::MsgType.value()))?;
+        let layout = Layout::for_msg_type(msg_type)
+            .ok_or_else(|| FixError::UnexpectedMsgType(String::from_utf8_lossy(msg_type).into_owned()))?;
+
+        let mut xml = String::from("<FIXML");
+        if let Some(begin_string) = field_value(Tag::BeginString.value()) {
+            let begin_string = text(begin_string)?;
+            let version = begin_string.strip_prefix(BEGIN_STRING_PREFIX).unwrap_or(begin_string);
+            push_attribute(&mut xml, "v", version);
+        }
+        xml.push('>');
+
+        let mut message_attributes = Vec::new();
+        for field in self.field_slice() {
+            let tag = field.tag();
+            let framing = [Tag::BeginString, Tag::BodyLength, Tag::MsgType, Tag::CheckSum].map(|t| t.value());
+            if !framing.contains(&tag) && !layout.in_component(tag) {
+                message_attributes.push(field);
+            }
+        }
+        open_element(&mut xml, layout.element, message_attributes, dict)?;
+        xml.push('>');
+
+        for component in layout.components {
+            match component {
+                Component::Block { element, tags } => {
+                    let fields: Vec<&FixField> = tags.iter().filter_map(|&tag| self.get_field(tag)).collect();
+                    if !fields.is_empty() {
+                        open_element(&mut xml, element, fields, dict)?;
+                        xml.push_str("/>");
+                    }
+                }
+                Component::Group { element, count_tag, members } => {
+                    for entry in group_entries(self, *count_tag, members)? {
+                        open_element(&mut xml, element, entry, dict)?;
+                        xml.push_str("/>");
+                    }
+                }
+            }
+        }
+
+        let _ = write!(xml, "</{}></FIXML>", layout.element);
+        Ok(xml)
+    }
+
+    /// Parses FIXML written by `to_fixml`, or by another producer using the
+    /// same layouts and FIX 4.4 attribute names
+    pub fn from_fixml(xml: &str) -> Result<Self, FixError> {
+        Self::from_fixml_with(xml, fix44())
+    }
+
+    /// Parses FIXML, mapping attribute names back to tags with `dict`
+    pub fn from_fixml_with(xml: &str, dict: &DataDictionary) -> Result<Self, FixError> {
+        let root = Element::parse(xml)?;
+        if root.name != "FIXML" {
+            return Err(FixError::InvalidFormat);
+        }
+        let [message] = &root.children[..] else {
+            return Err(FixError::InvalidFormat);
+        };
+        let layout = Layout::for_element(&message.name)
+            .ok_or_else(|| FixError::UnexpectedMsgType(message.name.clone()))?;
+
+        let mut msg = FixMessage::new();
+        if let Some(version) = root.attribute("v") {
+            msg.add_field(FixField::new(Tag::BeginString.value(), format!("{}{}", BEGIN_STRING_PREFIX, version).into_bytes()));
+        }
+        msg.add_field(FixField::new(Tag::MsgType.value(), layout.msg_type.to_vec()));
+
+        // Header first, then the message's own attributes, then the rest
+        for component in layout.components.iter().filter(|c| matches!(c, Component::Block { element: "Hdr", .. })) {
+            add_component(&mut msg, component, message, dict)?;
+        }
+        for (name, value) in &message.attributes {
+            // A name shared with a component field means the message's own
+            let tag = field_tag(dict, name, |tag| !LAYOUTS.iter().any(|layout| layout.in_component(tag)))?;
+            msg.add_field(FixField::new(tag, value.as_bytes().to_vec()));
+        }
+        for component in layout.components.iter().filter(|c| !matches!(c, Component::Block { element: "Hdr", .. })) {
+            add_component(&mut msg, component, message, dict)?;
+        }
+        Ok(msg)
+    }
+}
+
+fn add_component(msg: &mut FixMessage, component: &Component, parent: &Element, dict: &DataDictionary) -> Result<(), FixError> {
+    let add_attributes = |msg: &mut FixMessage, element: &Element| -> Result<(), FixError> {
+        for (name, value) in &element.attributes {
+            let tag = field_tag(dict, name, |tag| component.contains(tag))?;
+            msg.add_field(FixField::new(tag, value.as_bytes().to_vec()));
+        }
+        Ok(())
+    };
+
+    match component {
+        Component::Block { element, .. } => {
+            if let Some(child) = parent.children.iter().find(|c| c.name == *element) {
+                add_attributes(msg, child)?;
+            }
+        }
+        Component::Group { element, count_tag, members } => {
+            let entries: Vec<&Element> = parent.children.iter().filter(|c| c.name == *element).collect();
+            if !entries.is_empty() {
+                msg.add_field(FixField::from_value(*count_tag, &(entries.len() as u64)));
+            }
+            for entry in entries {
+                // Group delimiter first, whatever the attribute order
+                let mut attributes: Vec<(u32, &str)> = entry
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| Ok((field_tag(dict, name, |tag| members.contains(&tag))?, value.as_str())))
+                    .collect::<Result<_, FixError>>()?;
+                attributes.sort_by_key(|&(tag, _)| members.iter().position(|&m| m == tag).unwrap_or(usize::MAX));
+                for (tag, value) in attributes {
+                    msg.add_field(FixField::new(tag, value.as_bytes().to_vec()));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// The tag `name` stands for, preferring one `in_scope` as the same name
+// can mean different fields in different elements
+fn field_tag(dict: &DataDictionary, name: &str, in_scope: impl Fn(u32) -> bool) -> Result<u32, FixError> {
+    dict.tags_for_fixml_name(name)
+        .filter(|&tag| in_scope(tag))
+        .min()
+        .or_else(|| dict.tag_for_fixml_name(name))
+        .ok_or(FixError::InvalidFormat)
+}
+
+fn text(value: &[u8]) -> Result<&str, FixError> {
+    std::str::from_utf8(value).map_err(|_| FixError::InvalidFieldValue)
+}
+
+fn open_element<'f>(
+    xml: &mut String,
+    name: &str,
+    fields: impl IntoIterator<Item = &'f FixField>,
+    dict: &DataDictionary,
+) -> Result<(), FixError> {
+    xml.push('<');
+    xml.push_str(name);
+    for field in fields {
+        let attribute = dict
+            .field(field.tag())
+            .and_then(|spec| spec.fixml_name.as_deref())
+            .ok_or(FixError::UnknownTag(field.tag()))?;
+        push_attribute(xml, attribute, text(field.value())?);
+    }
+    Ok(())
+}
+
+fn push_attribute(xml: &mut String, name: &str, value: &str) {
+    let _ = write!(xml, " {}=\"", name);
+    for c in value.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            '\'' => xml.push_str("&apos;"),
+            _ => xml.push(c),
+        }
+    }
+    xml.push('"');
+}
+
+/// The subset of XML that FIXML uses: elements with attributes, no text
+/// content, optionally preceded by an XML declaration
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn parse(xml: &str) -> Result<Self, FixError> {
+        let mut rest = xml.trim_start();
+        if let Some(declaration) = rest.strip_prefix("<?") {
+            let end = declaration.find("?>").ok_or(FixError::InvalidFormat)?;
+            rest = declaration[end + 2..].trim_start();
+        }
+        let (element, rest) = Self::parse_element(rest, 1)?;
+        if !rest.trim().is_empty() {
+            return Err(FixError::InvalidFormat);
+        }
+        Ok(element)
+    }
+
+    fn parse_element(input: &str, depth: usize) -> Result<(Self, &str), FixError> {
+        if depth > MAX_DEPTH {
+            return Err(FixError::InvalidFormat);
+        }
+        let mut rest = input.strip_prefix('<').ok_or(FixError::InvalidFormat)?;
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .ok_or(FixError::InvalidFormat)?;
+        let mut element = Element { name: rest[..name_len].to_string(), ..Default::default() };
+        rest = &rest[name_len..];
+
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("/>") {
+                return Ok((element, after));
+            }
+            if let Some(after) = rest.strip_prefix('>') {
+                rest = after;
+                break;
+            }
+            let eq = rest.find('=').ok_or(FixError::InvalidFormat)?;
+            let name = rest[..eq].trim().to_string();
+            let after_eq = rest[eq + 1..].trim_start();
+            let quote = after_eq.chars().next().filter(|&c| c == '"' || c == '\'').ok_or(FixError::InvalidFormat)?;
+            let value_end = after_eq[1..].find(quote).ok_or(FixError::InvalidFormat)?;
+            element.attributes.push((name, unescape(&after_eq[1..1 + value_end])?));
+            rest = &after_eq[value_end + 2..];
+        }
+
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("</") {
+                let after = after.strip_prefix(element.name.as_str()).ok_or(FixError::InvalidFormat)?;
+                let after = after.trim_start().strip_prefix('>').ok_or(FixError::InvalidFormat)?;
+                return Ok((element, after));
+            }
+            let (child, after) = Self::parse_element(rest, depth + 1)?;
+            element.children.push(child);
+            rest = after;
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+fn unescape(value: &str) -> Result<String, FixError> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let end = rest[amp..].find(';').ok_or(FixError::InvalidFormat)?;
+        out.push(match &rest[amp + 1..amp + end] {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => return Err(FixError::InvalidFormat),
+        });
+        rest = &rest[amp + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codes::Currency;
+    use crate::messages::order_cancel_reject::cxl_rej_response_to;
+    use crate::decimal::{FixDecimal, Qty};
+    use crate::messages::{ExecutionReport, Instrument, OrderCancelReject, TradeCaptureReport, TradeCaptureSide};
+    use crate::tags::fix_version;
+
+    fn with_header(mut msg: FixMessage) -> FixMessage {
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"CCP".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"MEMBER".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"12".to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_trade_capture_report_round_trip() {
+        let report = TradeCaptureReport::new("TR1", b'F', "100", "1.25")
+            .with_instrument(Instrument::new("VOD").with_security_exchange("XLON".parse().unwrap()))
+            .with_currency(Currency::try_from(&b"GBP"[..]).unwrap())
+            .with_side(TradeCaptureSide::new(b'1', "O1").with_account("A&B"))
+            .with_side(TradeCaptureSide::new(b'2', "O2").with_cl_ord_id("C2"));
+        let msg = with_header(report.to_message(fix_version::FIX_4_4).unwrap());
+
+        let xml = msg.to_fixml().unwrap();
+        assert_eq!(
+            xml,
+            "<FIXML v=\"4.4\"><TrdCaptRpt RptID=\"TR1\" ExecTyp=\"F\" LastQty=\"100\" LastPx=\"1.25\" Ccy=\"GBP\">\
+             <Hdr SID=\"CCP\" TID=\"MEMBER\" SeqNum=\"12\"/>\
+             <Instrmt Sym=\"VOD\" Exch=\"XLON\"/>\
+             <RptSide Side=\"1\" OrdID=\"O1\" Acct=\"A&amp;B\"/>\
+             <RptSide Side=\"2\" OrdID=\"O2\" ClOrdID=\"C2\"/>\
+             </TrdCaptRpt></FIXML>"
+        );
+
+        let parsed = FixMessage::from_fixml(&xml).unwrap();
+        assert_eq!(TradeCaptureReport::from_message(&parsed).unwrap(), report);
+        assert_eq!(parsed.get_field(Tag::SenderCompID.value()).unwrap().value(), b"CCP");
+        assert!(FixMessage::decode(&parsed.encode().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_order_cancel_reject_round_trip() {
+        let reject = OrderCancelReject::new("O1", "C2", b'0', cxl_rej_response_to::ORDER_CANCEL_REQUEST)
+            .with_orig_cl_ord_id("C1")
+            .with_cxl_rej_reason(0)
+            .with_text("Too late <filled>");
+        let msg = with_header(reject.to_message(fix_version::FIX_4_4).unwrap());
+
+        let xml = format!("<?xml version=\"1.0\"?>\n{}\n", msg.to_fixml().unwrap());
+        assert!(xml.contains("<OrdCxlRej OrdID=\"O1\" ClOrdID=\"C2\" OrigClOrdID=\"C1\" OrdStat=\"0\" CxlRejRspTo=\"1\" CxlRejRsn=\"0\" Txt=\"Too late &lt;filled&gt;\">"));
+        let parsed = FixMessage::from_fixml(&xml).unwrap();
+        assert_eq!(OrderCancelReject::from_message(&parsed).unwrap(), reject);
+    }
+
+    #[test]
+    fn test_execution_report_round_trip() {
+        let qty = |value: &str| value.parse::<Qty>().unwrap();
+        let px = |value: &str| value.parse::<FixDecimal>().unwrap();
+        let report = ExecutionReport::new("O1", "E1", b'F', b'1', b'1', qty("60"), qty("40"))
+            .with_cl_ord_id("C1")
+            .with_symbol("VOD")
+            .with_order_qty(qty("100"))
+            .with_fill(qty("40"), px("1.25"))
+            .with_avg_px(px("1.25"))
+            .with_text("A&B");
+        let msg = with_header(report.to_message(fix_version::FIX_4_4).unwrap());
+
+        let xml = msg.to_fixml().unwrap();
+        assert_eq!(
+            xml,
+            "<FIXML v=\"4.4\"><ExecRpt OrdID=\"O1\" ClOrdID=\"C1\" ExecID=\"E1\" ExecTyp=\"F\" OrdStat=\"1\" \
+             Side=\"1\" LastQty=\"40\" LastPx=\"1.25\" LeavesQty=\"60\" CumQty=\"40\" AvgPx=\"1.25\" Txt=\"A&amp;B\">\
+             <Hdr SID=\"CCP\" TID=\"MEMBER\" SeqNum=\"12\"/>\
+             <Instrmt Sym=\"VOD\"/>\
+             <OrdQty Qty=\"100\"/>\
+             </ExecRpt></FIXML>"
+        );
+        let parsed = FixMessage::from_fixml(&xml).unwrap();
+        assert_eq!(ExecutionReport::from_message(&parsed).unwrap(), report);
+    }
+
+    #[test]
+    fn test_allocation_instruction_round_trip() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::ALLOCATION_INSTRUCTION.to_vec()));
+        let mut msg = with_header(msg);
+        for (tag, value) in [
+            (Tag::AllocID, &b"AL1"[..]),
+            (Tag::AllocTransType, b"0"),
+            (Tag::AllocType, b"1"),
+            (Tag::NoOrders, b"1"),
+            (Tag::ClOrdID, b"C1"),
+            (Tag::OrderID, b"O1"),
+            (Tag::Side, b"1"),
+            (Tag::Symbol, b"VOD"),
+            (Tag::Quantity, b"300"),
+            (Tag::AvgPx, b"1.25"),
+            (Tag::TradeDate, b"20240105"),
+            (Tag::NoAllocs, b"2"),
+            (Tag::AllocAccount, b"A1"),
+            (Tag::AllocQty, b"100"),
+            (Tag::AllocAccount, b"A2"),
+            (Tag::AllocQty, b"200"),
+        ] {
+            msg.add_field(FixField::new(tag.value(), value.to_vec()));
+        }
+
+        let xml = msg.to_fixml().unwrap();
+        assert_eq!(
+            xml,
+            "<FIXML v=\"4.4\"><AllocInstrctn ID=\"AL1\" TransTyp=\"0\" Typ=\"1\" Side=\"1\" Qty=\"300\" AvgPx=\"1.25\" \
+             TrdDt=\"20240105\">\
+             <Hdr SID=\"CCP\" TID=\"MEMBER\" SeqNum=\"12\"/>\
+             <OrdAlloc ClOrdID=\"C1\" OrdID=\"O1\"/>\
+             <Instrmt Sym=\"VOD\"/>\
+             <Alloc Acct=\"A1\" Qty=\"100\"/>\
+             <Alloc Acct=\"A2\" Qty=\"200\"/>\
+             </AllocInstrctn></FIXML>"
+        );
+
+        // Qty and Acct resolve by element: Quantity on the message, AllocQty
+        // and AllocAccount in Alloc
+        let parsed = FixMessage::from_fixml(&xml).unwrap();
+        assert_eq!(parsed.get_field(Tag::Quantity.value()).unwrap().value(), b"300");
+        let allocs = parsed.group(Tag::NoAllocs.value(), &[Tag::AllocAccount.value(), Tag::AllocQty.value()]).unwrap();
+        assert_eq!(allocs.len(), 2);
+        assert_eq!(allocs[1].get_field(Tag::AllocQty.value()).unwrap().value(), b"200");
+        assert_eq!(parsed.to_fixml().unwrap(), xml);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("{}{}", "<FIXML>".repeat(depth), "</FIXML>".repeat(depth));
+        assert!(Element::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(Element::parse(&nested(MAX_DEPTH + 1)), Err(FixError::InvalidFormat)));
+        assert!(matches!(FixMessage::from_fixml(&nested(100_000)), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        assert!(matches!(msg.to_fixml(), Err(FixError::UnexpectedMsgType(_))));
+
+        let reject = OrderCancelReject::new("O1", "C2", b'0', b'1');
+        let mut msg = reject.to_message(fix_version::FIX_4_4).unwrap();
+        msg.add_field(FixField::new(9999, b"x".to_vec()));
+        assert!(matches!(msg.to_fixml(), Err(FixError::UnknownTag(9999))));
+
+        for xml in ["<FIXML><Unknown/></FIXML>", "<FIXML><OrdCxlRej Nope=\"1\"/></FIXML>", "<FIXML><OrdCxlRej>", "<FIX/>"] {
+            assert!(FixMessage::from_fixml(xml).is_err(), "{}", xml);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+pub mod sofh;
+
+pub use sofh::{SofhFrame, SofhFramer};
+
+use crate::error::FixError;
+use crate::message::{frame_len, FixMessage};
+use crate::metrics::CodecCounters;
+use bytes::{Bytes, BytesMut};
+#[cfg(feature = "latency")]
+use std::time::Instant;
+
+/// Default upper bound on a single frame, guarding against a corrupt length
+pub const DEFAULT_MAX_FRAME_LEN: usize = 1024 * 1024;
+
+/// How messages are delimited on a stream transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FramingMode {
+    /// Plain tag=value, delimited by BeginString, BodyLength and CheckSum
+    #[default]
+    Native,
+    /// Each message behind a Simple Open Framing Header
+    Sofh,
+}
+
+impl FramingMode {
+    /// Frames an encoded tag=value message for the wire
+    pub fn frame(self, encoded: &[u8]) -> Result<Bytes, FixError> {
+        match self {
+            FramingMode::Native => Ok(Bytes::copy_from_slice(encoded)),
+            FramingMode::Sofh => sofh::encode_frame(encoded, sofh::TAGVALUE_ENCODING),
+        }
+    }
+}
+
+// What every tag=value message starts with
+const MESSAGE_START: &[u8] = b"8=FIX";
+
+/// Offset of the first "8=FIX" in `data` that can start a message: at the
+/// beginning or after a byte that is not a digit, so that e.g. "58=FIX" in a
+/// Text field is not mistaken for one
+pub fn find_message_start(data: &[u8]) -> Option<usize> {
+    memchr::memmem::find_iter(data, MESSAGE_START).find(|&i| i == 0 || !data[i - 1].is_ascii_digit())
+}
+
+/// What `Framer::poll_frame` and `Framer::decode_next` found at the front of
+/// the buffer
+#[derive(Debug)]
+pub enum FrameOutcome<T> {
+    Ready(T),
+    /// The message is still arriving and at least `required` more bytes are
+    /// needed: exactly that many once BodyLength has been read, and 1 while
+    /// the header itself is incomplete and the buffer within the maximum
+    /// frame length
+    NeedMoreData { required: usize },
+    /// The bytes cannot start a message, either malformed or declaring more
+    /// than the maximum frame length. The framer has already resynced past
+    /// them, discarding `discarded` bytes.
+    Garbled { discarded: usize, error: FixError },
+}
+
+/// Splits a byte stream into tag=value messages using BodyLength. Bytes are
+/// buffered until a whole message has arrived, so reads of any size can be
+/// fed in.
+#[derive(Debug, Clone)]
+pub struct Framer {
+    buf: BytesMut,
+    max_frame_len: usize,
+    counters: CodecCounters,
+    // When the first buffered byte and the latest bytes arrived
+    #[cfg(feature = "latency")]
+    first_received: Option<Instant>,
+    #[cfg(feature = "latency")]
+    last_received: Option<Instant>,
+}
+
+/// `Framer` under the name used by stream readers that only want whole
+/// messages, fed through `push` and drained with `next_message`
+pub type FixFramer = Framer;
+
+impl Default for Framer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Framer {
+    pub fn new() -> Self {
+        Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self {
+            buf: BytesMut::new(),
+            max_frame_len,
+            counters: CodecCounters::default(),
+            #[cfg(feature = "latency")]
+            first_received: None,
+            #[cfg(feature = "latency")]
+            last_received: None,
+        }
+    }
+
+    /// Appends bytes read from the transport
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        #[cfg(feature = "latency")]
+        self.note_received(Instant::now());
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Same as `extend_from_slice`
+    #[inline]
+    pub fn push(&mut self, data: &[u8]) {
+        self.extend_from_slice(data);
+    }
+
+    /// Appends bytes read from the transport at `at`, as timed by a latency
+    /// hook
+    #[cfg(feature = "latency")]
+    pub fn extend_from_slice_at(&mut self, data: &[u8], at: Instant) {
+        self.note_received(at);
+        self.buf.extend_from_slice(data);
+    }
+
+    #[cfg(feature = "latency")]
+    fn note_received(&mut self, at: Instant) {
+        if self.buf.is_empty() {
+            self.first_received = Some(at);
+        }
+        self.last_received = Some(at);
+    }
+
+    // Bytes left after a frame or a resync arrived no earlier than the
+    // latest read
+    #[cfg(feature = "latency")]
+    fn consumed(&mut self) {
+        self.first_received = if self.buf.is_empty() { None } else { self.last_received };
+    }
+
+    /// When the first byte of the next frame was appended
+    #[cfg(feature = "latency")]
+    #[inline]
+    pub fn received_at(&self) -> Option<Instant> {
+        self.first_received
+    }
+
+    /// The next complete message, or `None` until more bytes arrive.
+    /// A malformed header is an error and leaves the buffer untouched.
+    pub fn next_frame(&mut self) -> Result<Option<Bytes>, FixError> {
+        let len = match frame_len(&self.buf) {
+            Ok(len) => len,
+            Err(FixError::IncompleteHeader) if self.buf.len() > self.max_frame_len => {
+                return Err(FixError::MessageTooLarge { len: self.buf.len(), max: self.max_frame_len })
+            }
+            Err(FixError::IncompleteHeader) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if len > self.max_frame_len {
+            return Err(FixError::MessageTooLarge { len, max: self.max_frame_len });
+        }
+        if self.buf.len() < len {
+            return Ok(None);
+        }
+        let frame = self.buf.split_to(len).freeze();
+        #[cfg(feature = "latency")]
+        self.consumed();
+        Ok(Some(frame))
+    }
+
+    /// `next_frame`, telling a message still arriving from one that never
+    /// will: a BodyLength within the maximum frame length but past the end
+    /// of the buffer needs more data, while one beyond it, or a malformed
+    /// header, is garbage the framer resyncs past
+    pub fn poll_frame(&mut self) -> FrameOutcome<Bytes> {
+        let len = match frame_len(&self.buf) {
+            Ok(len) if len > self.max_frame_len => Err(FixError::MessageTooLarge { len, max: self.max_frame_len }),
+            Ok(len) => Ok(len),
+            // A header that has not ended within a maximum frame never will
+            Err(FixError::IncompleteHeader) if self.buf.len() > self.max_frame_len => {
+                Err(FixError::MessageTooLarge { len: self.buf.len(), max: self.max_frame_len })
+            }
+            Err(FixError::IncompleteHeader) => return FrameOutcome::NeedMoreData { required: 1 },
+            Err(err) => Err(err),
+        };
+        match len {
+            Ok(len) if self.buf.len() < len => FrameOutcome::NeedMoreData { required: len - self.buf.len() },
+            Ok(len) => {
+                let frame = self.buf.split_to(len).freeze();
+                #[cfg(feature = "latency")]
+                self.consumed();
+                FrameOutcome::Ready(frame)
+            }
+            Err(error) => FrameOutcome::Garbled { discarded: self.resync(), error },
+        }
+    }
+
+    /// The next message decoded from the buffer, as `poll_frame` frames it.
+    /// Fails only when a whole frame arrived but does not decode, e.g. with
+    /// a wrong CheckSum; the frame is consumed either way.
+    pub fn decode_next(&mut self) -> Result<FrameOutcome<FixMessage>, FixError> {
+        Ok(match self.poll_frame() {
+            FrameOutcome::Ready(frame) => match FixMessage::decode(&frame) {
+                Ok(msg) => FrameOutcome::Ready(msg),
+                Err(err) => {
+                    self.counters.decode_errors += 1;
+                    return Err(err);
+                }
+            },
+            FrameOutcome::NeedMoreData { required } => FrameOutcome::NeedMoreData { required },
+            FrameOutcome::Garbled { discarded, error } => FrameOutcome::Garbled { discarded, error },
+        })
+    }
+
+    /// The next decoded message, or `None` until more bytes arrive. Bytes
+    /// that cannot start a message, e.g. leading garbage, are skipped up to
+    /// the next "8=FIX" and reported as the error; so is a whole message that
+    /// does not decode. Either way the next call carries on after them.
+    pub fn next_message(&mut self) -> Option<Result<FixMessage, FixError>> {
+        match self.decode_next() {
+            Ok(FrameOutcome::Ready(msg)) => Some(Ok(msg)),
+            Ok(FrameOutcome::NeedMoreData { .. }) => None,
+            Ok(FrameOutcome::Garbled { error, .. }) | Err(error) => Some(Err(error)),
+        }
+    }
+
+    /// Recovers from an error of `next_frame` by discarding bytes up to the
+    /// next message start after the first buffered byte. Returns the number
+    /// of bytes discarded; a trailing partial "8=FIX" is kept.
+    pub fn resync(&mut self) -> usize {
+        let skip = match self.buf.get(1..).and_then(find_message_start) {
+            Some(start) => start + 1,
+            None => {
+                let kept = (1..MESSAGE_START.len())
+                    .rev()
+                    .find(|&n| self.buf.len() > n && self.buf.ends_with(&MESSAGE_START[..n]))
+                    .unwrap_or(0);
+                self.buf.len() - kept
+            }
+        };
+        let _ = self.buf.split_to(skip);
+        self.counters.framer_resyncs += 1;
+        #[cfg(feature = "latency")]
+        self.consumed();
+        skip
+    }
+
+    /// Bytes received but not yet returned as a frame
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Resyncs so far, and frames `decode_next` failed to decode
+    #[inline]
+    pub fn counters(&self) -> CodecCounters {
+        self.counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEARTBEAT: &[u8] = b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01";
+
+    #[test]
+    fn test_framer_buffers_partial_frames() {
+        let mut stream = HEARTBEAT.repeat(2);
+        stream.extend_from_slice(&HEARTBEAT[..12]);
+
+        let mut framer = Framer::new();
+        let mut frames = Vec::new();
+        for chunk in stream.chunks(7) {
+            framer.extend_from_slice(chunk);
+            while let Some(frame) = framer.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames, [HEARTBEAT, HEARTBEAT]);
+        assert_eq!(framer.buffered_len(), 12);
+    }
+
+    #[test]
+    fn test_next_message() {
+        // Garbage, two messages in one read, then one split over three
+        let mut framer = FixFramer::new();
+        framer.push(&[&b"junk"[..], &HEARTBEAT.repeat(2)].concat());
+        assert!(matches!(framer.next_message(), Some(Err(FixError::InvalidFormat))));
+        assert_eq!(framer.next_message().unwrap().unwrap().encode().unwrap(), HEARTBEAT);
+        assert!(framer.next_message().unwrap().is_ok());
+        assert!(framer.next_message().is_none());
+
+        framer.push(&HEARTBEAT[..3]);
+        assert!(framer.next_message().is_none());
+        framer.push(&HEARTBEAT[3..20]);
+        assert!(framer.next_message().is_none());
+        assert_eq!(framer.buffered_len(), 20);
+        framer.push(&HEARTBEAT[20..]);
+        assert!(framer.next_message().unwrap().is_ok());
+        assert_eq!(framer.buffered_len(), 0);
+
+        // A whole message with a bad CheckSum is consumed
+        let mut corrupt = HEARTBEAT.to_vec();
+        corrupt[HEARTBEAT.len() - 2] = b'4';
+        framer.extend_from_slice(&[&corrupt[..], HEARTBEAT].concat());
+        assert!(matches!(framer.next_message(), Some(Err(FixError::InvalidChecksum))));
+        assert!(framer.next_message().unwrap().is_ok());
+        assert_eq!(framer.counters(), CodecCounters { decode_errors: 1, framer_resyncs: 1 });
+    }
+
+    #[test]
+    fn test_framer_errors() {
+        let mut framer = Framer::with_max_frame_len(HEARTBEAT.len() - 1);
+        framer.extend_from_slice(HEARTBEAT);
+        assert!(matches!(framer.next_frame(), Err(FixError::MessageTooLarge { .. })));
+
+        let mut framer = Framer::new();
+        framer.extend_from_slice(b"garbage");
+        assert!(matches!(framer.next_frame(), Err(FixError::InvalidFormat)));
+        assert_eq!(framer.buffered_len(), 7);
+    }
+
+    #[test]
+    fn test_find_message_start() {
+        assert_eq!(find_message_start(HEARTBEAT), Some(0));
+        assert_eq!(find_message_start(b"junk\n8=FIX.4.4"), Some(5));
+        assert_eq!(find_message_start(b"58=FIX.4.4\x01"), None);
+        assert_eq!(find_message_start(b"58=FIX\x018=FIX"), Some(7));
+        assert_eq!(find_message_start(b"8=FI"), None);
+    }
+
+    #[test]
+    fn test_framer_resync() {
+        let mut framer = Framer::new();
+        framer.extend_from_slice(b"garbage");
+        framer.extend_from_slice(HEARTBEAT);
+        assert!(framer.next_frame().is_err());
+        assert_eq!(framer.resync(), 7);
+        assert_eq!(framer.next_frame().unwrap().unwrap(), HEARTBEAT);
+
+        // A corrupt header followed by the start of a message still arriving
+        framer.extend_from_slice(b"8=FIX.4.4\x019=x\x018=FI");
+        assert!(framer.next_frame().is_err());
+        assert_eq!(framer.resync(), 14);
+        framer.extend_from_slice(&HEARTBEAT[4..]);
+        assert_eq!(framer.next_frame().unwrap().unwrap(), HEARTBEAT);
+        assert_eq!(framer.buffered_len(), 0);
+        assert_eq!(framer.counters().framer_resyncs, 2);
+    }
+
+    #[test]
+    fn test_every_split_needs_more_data() {
+        let test_request = b"8=FIX.4.4\x019=12\x0135=1\x01112=T1\x0110=123\x01";
+        let stream = [HEARTBEAT, &test_request[..]].concat();
+        // Where each message starts, ends and has its BodyLength read
+        let messages = [(0, HEARTBEAT.len(), 14), (HEARTBEAT.len(), stream.len(), HEARTBEAT.len() + 15)];
+
+        for split in 0..=stream.len() {
+            let mut framer = Framer::new();
+            framer.extend_from_slice(&stream[..split]);
+            let mut frames = Vec::new();
+            loop {
+                match framer.poll_frame() {
+                    FrameOutcome::Ready(frame) => frames.push(frame),
+                    FrameOutcome::NeedMoreData { required } => {
+                        if let Some(&(start, end, header_end)) = messages.get(frames.len()) {
+                            assert_eq!(framer.buffered_len(), split - start, "split at {}", split);
+                            // Once BodyLength is read the shortfall is exact
+                            let expected = if split >= header_end { end - split } else { 1 };
+                            assert_eq!(required, expected, "split at {}", split);
+                        }
+                        break;
+                    }
+                    FrameOutcome::Garbled { error, .. } => panic!("split at {} garbled: {:?}", split, error),
+                }
+            }
+            framer.extend_from_slice(&stream[split..]);
+            while let FrameOutcome::Ready(frame) = framer.poll_frame() {
+                frames.push(frame);
+            }
+            assert_eq!(frames, [HEARTBEAT, &test_request[..]], "split at {}", split);
+            assert_eq!(framer.buffered_len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_oversized_body_length_is_garbled() {
+        let mut framer = Framer::with_max_frame_len(64);
+        framer.extend_from_slice(b"8=FIX.4.4\x019=999\x0135=0\x01");
+        match framer.poll_frame() {
+            FrameOutcome::Garbled { discarded, error: FixError::MessageTooLarge { max: 64, .. } } => {
+                assert_eq!(discarded, 21)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(framer.buffered_len(), 0);
+
+        // Under the limit the same header is a message still arriving
+        let mut framer = Framer::with_max_frame_len(2048);
+        framer.extend_from_slice(b"8=FIX.4.4\x019=999\x0135=0\x01");
+        assert!(matches!(framer.poll_frame(), FrameOutcome::NeedMoreData { required: 1001 }));
+
+        let mut framer = Framer::new();
+        framer.extend_from_slice(b"8=FIX.4.4\x019=x\x01");
+        framer.extend_from_slice(HEARTBEAT);
+        assert!(matches!(framer.decode_next(), Ok(FrameOutcome::Garbled { discarded: 14, .. })));
+        match framer.decode_next() {
+            Ok(FrameOutcome::Ready(msg)) => assert_eq!(msg.msg_type(), Some(crate::tags::MsgType::Heartbeat)),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(framer.decode_next(), Ok(FrameOutcome::NeedMoreData { required: 1 })));
+    }
+
+    #[test]
+    fn test_endless_header_is_garbled() {
+        let mut framer = Framer::with_max_frame_len(64);
+        framer.extend_from_slice(b"8=FIX.4.4");
+        assert!(matches!(framer.poll_frame(), FrameOutcome::NeedMoreData { required: 1 }));
+
+        // A BeginString that never ends must not be buffered forever
+        framer.extend_from_slice(&[b'4'; 64]);
+        match framer.poll_frame() {
+            FrameOutcome::Garbled { discarded, error: FixError::MessageTooLarge { len: 73, max: 64 } } => {
+                assert_eq!(discarded, 73)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(framer.buffered_len(), 0);
+
+        framer.extend_from_slice(HEARTBEAT);
+        assert!(matches!(framer.poll_frame(), FrameOutcome::Ready(_)));
+
+        framer.extend_from_slice(b"8=FIX.4.4");
+        framer.extend_from_slice(&[b'4'; 64]);
+        assert!(matches!(framer.next_frame(), Err(FixError::MessageTooLarge { len: 73, max: 64 })));
+    }
+}
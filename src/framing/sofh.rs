@@ -0,0 +1,143 @@
+use super::DEFAULT_MAX_FRAME_LEN;
+use crate::error::FixError;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Encoding type of FIX tag=value messages
+pub const TAGVALUE_ENCODING: u16 = 0xF000;
+
+/// Message length (4 bytes) and encoding type (2 bytes), both big-endian
+pub const HEADER_LEN: usize = 6;
+
+/// Wraps `payload` in a Simple Open Framing Header. The length written
+/// counts the header as well as the payload.
+pub fn encode_frame(payload: &[u8], encoding: u16) -> Result<Bytes, FixError> {
+    let len = HEADER_LEN + payload.len();
+    let declared = u32::try_from(len).map_err(|_| FixError::MessageTooLarge { len, max: u32::MAX as usize })?;
+
+    let mut frame = BytesMut::with_capacity(len);
+    frame.put_u32(declared);
+    frame.put_u16(encoding);
+    frame.put_slice(payload);
+    Ok(frame.freeze())
+}
+
+/// One message read from a SOFH stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SofhFrame {
+    pub encoding: u16,
+    /// The message without its framing header
+    pub payload: Bytes,
+}
+
+/// Splits a byte stream into SOFH frames, buffering partial frames like
+/// `Framer`
+#[derive(Debug, Clone)]
+pub struct SofhFramer {
+    buf: BytesMut,
+    max_frame_len: usize,
+}
+
+impl Default for SofhFramer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SofhFramer {
+    pub fn new() -> Self {
+        Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Limits the declared length of a frame, header included
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self { buf: BytesMut::new(), max_frame_len }
+    }
+
+    /// Appends bytes read from the transport
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// The next complete frame, or `None` until more bytes arrive. A
+    /// declared length shorter than the header or above the limit is an
+    /// error and leaves the buffer untouched.
+    pub fn next_frame(&mut self) -> Result<Option<SofhFrame>, FixError> {
+        let Some(header) = self.buf.get(..HEADER_LEN) else {
+            return Ok(None);
+        };
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let encoding = u16::from_be_bytes([header[4], header[5]]);
+        if len < HEADER_LEN {
+            return Err(FixError::InvalidFormat);
+        }
+        if len > self.max_frame_len {
+            return Err(FixError::MessageTooLarge { len, max: self.max_frame_len });
+        }
+        if self.buf.len() < len {
+            return Ok(None);
+        }
+
+        let mut frame = self.buf.split_to(len);
+        frame.advance(HEADER_LEN);
+        Ok(Some(SofhFrame { encoding, payload: frame.freeze() }))
+    }
+
+    /// Bytes received but not yet returned as a frame
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing::FramingMode;
+    use crate::message::FixMessage;
+
+    const HEARTBEAT: &[u8] = b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01";
+
+    // 6 + 26 = 32 bytes, tag=value encoding
+    const HAND_CRAFTED: &[u8] = b"\x00\x00\x00\x20\xF0\x008=FIX.4.4\x019=5\x0135=0\x0110=163\x01";
+
+    #[test]
+    fn test_encode_frame() {
+        assert_eq!(encode_frame(HEARTBEAT, TAGVALUE_ENCODING).unwrap(), HAND_CRAFTED);
+        assert_eq!(FramingMode::Sofh.frame(HEARTBEAT).unwrap(), HAND_CRAFTED);
+        assert_eq!(FramingMode::Native.frame(HEARTBEAT).unwrap(), HEARTBEAT);
+        assert_eq!(encode_frame(b"", 0x5BE0).unwrap(), &b"\x00\x00\x00\x06\x5B\xE0"[..]);
+    }
+
+    #[test]
+    fn test_framer_buffers_partial_frames() {
+        let mut stream = HAND_CRAFTED.repeat(3);
+        stream.truncate(stream.len() - 1);
+
+        let mut framer = SofhFramer::new();
+        let mut frames = Vec::new();
+        for &byte in &stream {
+            framer.extend_from_slice(&[byte]);
+            while let Some(frame) = framer.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames.len(), 2);
+        for frame in &frames {
+            assert_eq!(frame.encoding, TAGVALUE_ENCODING);
+            assert_eq!(frame.payload, HEARTBEAT);
+            assert!(FixMessage::decode(&frame.payload).is_ok());
+        }
+        assert_eq!(framer.buffered_len(), HAND_CRAFTED.len() - 1);
+    }
+
+    #[test]
+    fn test_framer_errors() {
+        let mut framer = SofhFramer::new();
+        framer.extend_from_slice(b"\x00\x00\x00\x05\xF0\x00");
+        assert!(matches!(framer.next_frame(), Err(FixError::InvalidFormat)));
+
+        let mut framer = SofhFramer::with_max_frame_len(HAND_CRAFTED.len() - 1);
+        framer.extend_from_slice(&HAND_CRAFTED[..HEADER_LEN]);
+        assert!(matches!(framer.next_frame(), Err(FixError::MessageTooLarge { len: 32, .. })));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::field::{FixField, SOH};
+use crate::message::FixMessage;
+use crate::tags::{data_field_for, fix_version, msg_type, Tag, DATA_FIELDS};
+
+/// BeginStrings the generators pick from
+pub const BEGIN_STRINGS: &[&[u8]] = &[
+    fix_version::FIX_4_2,
+    fix_version::FIX_4_3,
+    fix_version::FIX_4_4,
+    fix_version::FIX_5_0,
+];
+
+/// MsgTypes the generators pick from
+pub const MSG_TYPES: &[&[u8]] = &[
+    msg_type::HEARTBEAT,
+    msg_type::TEST_REQUEST,
+    msg_type::LOGON,
+    msg_type::NEW_ORDER_SINGLE,
+    msg_type::EXECUTION_REPORT,
+    msg_type::ORDER_CANCEL_REJECT,
+    msg_type::TRADE_CAPTURE_REPORT,
+];
+
+/// Highest tag given to a generated field
+pub const MAX_GENERATED_TAG: u32 = 9999;
+
+// The NoSides group generated when groups are requested: count tag, then
+// the delimiter and one further member per entry
+const GROUP_COUNT_TAG: u32 = Tag::NoSides as u32;
+const GROUP_MEMBERS: [u32; 2] = [Tag::Side as u32, Tag::OrderID as u32];
+
+/// Whether a generated plain field may use `tag`. Excludes the tags `encode`
+/// manages, both halves of every data field, since their values are read by
+/// length, and the tags of the generated group so that it stays unambiguous.
+pub fn is_generated_tag(tag: u32) -> bool {
+    !matches!(tag, 0 | 8 | 9 | 10 | 35 | GROUP_COUNT_TAG | 54 | 37)
+        && tag <= MAX_GENERATED_TAG
+        && data_field_for(tag).is_none()
+        && !DATA_FIELDS.iter().any(|&(_, data)| data == tag)
+}
+
+// Folds an arbitrary byte onto printable ASCII, which never contains SOH
+#[inline]
+fn printable(byte: u8) -> u8 {
+    b' ' + byte % (b'~' - b' ' + 1)
+}
+
+fn group_fields(sides: &[(u8, Vec<u8>)]) -> Vec<FixField> {
+    let mut fields = Vec::with_capacity(1 + sides.len() * GROUP_MEMBERS.len());
+    fields.push(FixField::from_value(GROUP_COUNT_TAG, &(sides.len() as u64)));
+    for (side, order_id) in sides {
+        fields.push(FixField::new(GROUP_MEMBERS[0], vec![*side]));
+        fields.push(FixField::new(GROUP_MEMBERS[1], order_id.clone()));
+    }
+    fields
+}
+
+fn assemble(begin_string: &[u8], msg_type: &[u8], body: Vec<FixField>) -> FixMessage {
+    let mut msg = FixMessage::with_capacity(2 + body.len());
+    msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+    msg.add_field(FixField::new(Tag::MsgType.value(), msg_type.to_vec()));
+    for field in body {
+        msg.add_field(field);
+    }
+    msg
+}
+
+/// A way to damage an encoded message, for exercising the error paths of
+/// `decode` with input that is almost valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Corruption {
+    /// Leave the encoding as is
+    None,
+    /// Change the CheckSum value so it no longer matches
+    Checksum,
+    /// Change the BodyLength value so it no longer matches
+    BodyLength,
+    /// Drop everything from this byte offset (modulo the length) on
+    Truncate(usize),
+    /// Replace the byte at this offset (modulo the length) with SOH
+    StraySoh(usize),
+}
+
+impl Corruption {
+    /// Applies the corruption to `encoded` in place
+    pub fn apply(self, encoded: &mut Vec<u8>) {
+        if encoded.is_empty() {
+            return;
+        }
+        match self {
+            Corruption::None => {}
+            Corruption::Checksum => {
+                // The checksum digits sit between "10=" and the final SOH
+                let digit = encoded.len() - 2;
+                encoded[digit] = if encoded[digit] == b'9' { b'0' } else { encoded[digit] + 1 };
+            }
+            Corruption::BodyLength => {
+                if let Some(start) = encoded.windows(3).position(|w| w == [SOH, b'9', b'=']) {
+                    encoded.insert(start + 3, b'1');
+                }
+            }
+            Corruption::Truncate(at) => encoded.truncate(at % encoded.len()),
+            Corruption::StraySoh(at) => {
+                let at = at % encoded.len();
+                encoded[at] = SOH;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use super::*;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    const MAX_VALUE_LEN: usize = 32;
+    const MAX_FIELDS: usize = 32;
+    const MAX_GROUP_ENTRIES: usize = 4;
+
+    fn value(u: &mut Unstructured<'_>, max_len: usize) -> Result<Vec<u8>> {
+        let len = u.int_in_range(1..=max_len)?;
+        (0..len).map(|_| u8::arbitrary(u).map(printable)).collect()
+    }
+
+    /// A plain field: a tag accepted by `is_generated_tag` and a non-empty
+    /// printable ASCII value
+    impl<'a> Arbitrary<'a> for FixField {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut tag = u.int_in_range(1..=MAX_GENERATED_TAG)?;
+            while !is_generated_tag(tag) {
+                tag = tag % MAX_GENERATED_TAG + 1;
+            }
+            Ok(FixField::new(tag, value(u, MAX_VALUE_LEN)?))
+        }
+    }
+
+    /// A message `encode` accepts and `decode` reads back field for field:
+    /// BeginString and MsgType from the lists above, plain fields and
+    /// sometimes a NoSides group
+    impl<'a> Arbitrary<'a> for FixMessage {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let begin_string = *u.choose(BEGIN_STRINGS)?;
+            let msg_type = *u.choose(MSG_TYPES)?;
+            let len = u.int_in_range(0..=MAX_FIELDS)?;
+            let mut body = (0..len).map(|_| FixField::arbitrary(u)).collect::<Result<Vec<_>>>()?;
+
+            if bool::arbitrary(u)? {
+                let entries = u.int_in_range(1..=MAX_GROUP_ENTRIES)?;
+                let sides = (0..entries)
+                    .map(|_| Ok((*u.choose(b"12")?, value(u, MAX_VALUE_LEN)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                let at = u.int_in_range(0..=body.len())?;
+                body.splice(at..at, group_fields(&sides));
+            }
+
+            Ok(assemble(begin_string, msg_type, body))
+        }
+    }
+
+    /// The encoding of an arbitrary message with a corruption applied, as
+    /// input for fuzzing `decode`
+    #[derive(Debug, Clone)]
+    pub struct WireMessage {
+        pub message: FixMessage,
+        pub corruption: Corruption,
+        pub bytes: Vec<u8>,
+    }
+
+    impl<'a> Arbitrary<'a> for WireMessage {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let message = FixMessage::arbitrary(u)?;
+            let corruption = Corruption::arbitrary(u)?;
+            let mut bytes = message
+                .encode()
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_vec();
+            corruption.apply(&mut bytes);
+            Ok(Self { message, corruption, bytes })
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+pub use arbitrary_impls::WireMessage;
+
+#[cfg(feature = "proptest")]
+pub mod strategy {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// Knobs for the generated messages
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MessageParams {
+        pub max_fields: usize,
+        pub max_value_len: usize,
+        pub include_groups: bool,
+    }
+
+    impl Default for MessageParams {
+        fn default() -> Self {
+            Self {
+                max_fields: 16,
+                max_value_len: 16,
+                include_groups: false,
+            }
+        }
+    }
+
+    impl MessageParams {
+        pub fn with_max_fields(mut self, max_fields: usize) -> Self {
+            self.max_fields = max_fields;
+            self
+        }
+
+        pub fn with_max_value_len(mut self, max_value_len: usize) -> Self {
+            self.max_value_len = max_value_len.max(1);
+            self
+        }
+
+        pub fn with_groups(mut self, include_groups: bool) -> Self {
+            self.include_groups = include_groups;
+            self
+        }
+    }
+
+    fn value(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+        vec(any::<u8>().prop_map(printable), 1..=max_len.max(1))
+    }
+
+    /// A plain field as described on `is_generated_tag`
+    pub fn field(max_value_len: usize) -> impl Strategy<Value = FixField> {
+        (
+            (1..=MAX_GENERATED_TAG).prop_filter("reserved tag", |&tag| is_generated_tag(tag)),
+            value(max_value_len),
+        )
+            .prop_map(|(tag, value)| FixField::new(tag, value))
+    }
+
+    /// Messages that round-trip through `encode` and `decode`
+    pub fn message(params: MessageParams) -> impl Strategy<Value = FixMessage> {
+        let sides = if params.include_groups {
+            proptest::option::of(vec((prop_oneof![Just(b'1'), Just(b'2')], value(params.max_value_len)), 1..4))
+                .boxed()
+        } else {
+            Just(None).boxed()
+        };
+
+        (
+            proptest::sample::select(BEGIN_STRINGS),
+            proptest::sample::select(MSG_TYPES),
+            vec(field(params.max_value_len), 0..=params.max_fields),
+            sides,
+            any::<prop::sample::Index>(),
+        )
+            .prop_map(|(begin_string, msg_type, mut body, sides, at)| {
+                if let Some(sides) = sides {
+                    let at = at.index(body.len() + 1);
+                    body.splice(at..at, group_fields(&sides));
+                }
+                assemble(begin_string, msg_type, body)
+            })
+    }
+
+    /// Any `Corruption`, including none
+    pub fn corruption() -> impl Strategy<Value = Corruption> {
+        prop_oneof![
+            Just(Corruption::None),
+            Just(Corruption::Checksum),
+            Just(Corruption::BodyLength),
+            any::<usize>().prop_map(Corruption::Truncate),
+            any::<usize>().prop_map(Corruption::StraySoh),
+        ]
+    }
+
+    /// Encoded messages with a corruption applied
+    pub fn corrupted(params: MessageParams) -> impl Strategy<Value = (Vec<u8>, Corruption)> {
+        (message(params), corruption()).prop_map(|(msg, corruption)| {
+            let mut bytes = msg.encode().expect("generated messages encode").to_vec();
+            corruption.apply(&mut bytes);
+            (bytes, corruption)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corruption_checksum() {
+        let msg = assemble(fix_version::FIX_4_4, msg_type::HEARTBEAT, vec![FixField::new(112, b"T1".to_vec())]);
+        let mut bytes = msg.encode().unwrap().to_vec();
+        assert!(FixMessage::decode(&bytes).is_ok());
+        Corruption::Checksum.apply(&mut bytes);
+        assert!(FixMessage::decode(&bytes).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_messages_round_trip() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // A fixed xorshift stream keeps the test deterministic
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let data: Vec<u8> = (0..1 << 16)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+
+        let mut u = Unstructured::new(&data);
+        for _ in 0..64 {
+            let Ok(wire) = WireMessage::arbitrary(&mut u) else { break };
+            let _ = FixMessage::decode(&wire.bytes);
+            let decoded = FixMessage::decode(&wire.message.encode().unwrap()).unwrap();
+            assert!(wire.message.is_stable().unwrap());
+            assert_eq!(decoded.field_slice().len(), wire.message.field_slice().len() + 2);
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    mod properties {
+        use super::super::strategy::*;
+        use crate::message::FixMessage;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn test_decode_encode_round_trip(msg in message(MessageParams::default().with_groups(true))) {
+                let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+                let body: Vec<_> = decoded
+                    .field_slice()
+                    .iter()
+                    .filter(|f| f.tag() != 9 && f.tag() != 10)
+                    .cloned()
+                    .collect();
+                prop_assert_eq!(body.as_slice(), msg.field_slice());
+            }
+
+            #[test]
+            fn test_decode_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+                let _ = FixMessage::decode(&bytes);
+            }
+
+            #[test]
+            fn test_decode_never_panics_on_corrupted((bytes, _) in corrupted(MessageParams::default().with_groups(true))) {
+                let _ = FixMessage::decode(&bytes);
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::dictionary::{shared_fix44, DataDictionary};
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+
+/// Tags left out of a content hash because they differ between copies of
+/// the same message: BodyLength, CheckSum, MsgSeqNum and SendingTime
+pub const HASH_EXCLUDED_TAGS: [u32; 4] = [
+    Tag::BodyLength.value(),
+    Tag::CheckSum.value(),
+    Tag::MsgSeqNum.value(),
+    Tag::SendingTime.value(),
+];
+
+// FNV-1a 128 parameters from draft-eastlake-fnv
+const FNV_128_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_128_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Algorithms for `FixMessage::canonical_hash_with`. The digest is stable
+/// across releases for a given algorithm and canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// FNV-1a 128, not collision resistant: fine for deduplication, not
+    /// against tampering. The 16 byte digest, big-endian, fills the first
+    /// half of the result and the rest is zero.
+    #[default]
+    Fnv1a128,
+    /// SHA-256, for integrity checks
+    #[cfg(feature = "sha256")]
+    Sha256,
+}
+
+// The canonical form, fed to the hash one piece at a time: for each field,
+// the tag and value length as little-endian u32 and the value. Fields are
+// put in tag order, except that a repeating group the dictionary knows
+// moves as a unit, sorted by its count tag: the count, then each entry in
+// wire order with its delimiter first and its other fields in tag order.
+// Sorts are stable, so repeated tags within an entry keep their order.
+fn canonical_fields<'a>(fields: &'a [FixField], exclude: &[u32], dict: &DataDictionary) -> Vec<&'a FixField> {
+    let fields: Vec<&FixField> = fields.iter().filter(|f| !exclude.contains(&f.tag())).collect();
+    let mut canonical = Vec::with_capacity(fields.len());
+    let mut pos = 0;
+    while pos < fields.len() {
+        pos += canonical_unit(&fields[pos..], dict, &mut canonical);
+    }
+    sort_units(canonical, None)
+}
+
+// One field, or a field opening a group and the entries after it
+struct Unit<'a> {
+    fields: Vec<&'a FixField>,
+}
+
+// Reads the field or group starting `fields` into `out`, returning how many
+// fields it took
+fn canonical_unit<'a>(fields: &[&'a FixField], dict: &DataDictionary, out: &mut Vec<Unit<'a>>) -> usize {
+    let count = fields[0];
+    let Some(members) = dict.group(count.tag()) else {
+        out.push(Unit { fields: vec![count] });
+        return 1;
+    };
+    let mut unit = vec![count];
+    let mut entry: Vec<Unit<'a>> = Vec::new();
+    let mut delimited = false;
+    let mut pos = 1;
+    while let Some(field) = fields.get(pos).filter(|f| members.contains(&f.tag())) {
+        // A delimiter starts the next entry unless this one has none yet
+        if field.tag() == members[0] {
+            if delimited {
+                unit.extend(sort_units(std::mem::take(&mut entry), Some(members[0])));
+            }
+            delimited = true;
+        }
+        pos += canonical_unit(&fields[pos..], dict, &mut entry);
+    }
+    unit.extend(sort_units(entry, Some(members[0])));
+    out.push(Unit { fields: unit });
+    pos
+}
+
+fn sort_units<'a>(mut units: Vec<Unit<'a>>, delimiter: Option<u32>) -> Vec<&'a FixField> {
+    units.sort_by_key(|unit| {
+        let tag = unit.fields[0].tag();
+        (Some(tag) != delimiter, tag)
+    });
+    units.into_iter().flat_map(|unit| unit.fields).collect()
+}
+
+fn fnv1a_128(state: &mut u128, bytes: &[u8]) {
+    for &b in bytes {
+        *state ^= b as u128;
+        *state = state.wrapping_mul(FNV_128_PRIME);
+    }
+}
+
+impl FixMessage {
+    /// A content hash of the message with `HashAlgorithm::Fnv1a128`; see
+    /// `canonical_hash_with`
+    pub fn canonical_hash(&self, exclude: &[u32]) -> [u8; 32] {
+        self.canonical_hash_with(HashAlgorithm::Fnv1a128, exclude)
+    }
+
+    /// A content hash of the fields other than `exclude`, typically
+    /// `HASH_EXCLUDED_TAGS`, taken in tag order so the order fields arrived
+    /// in does not matter. The entries of a FIX 4.4 repeating group keep
+    /// their wire order and their own fields, so swapping two entries or
+    /// moving a field from one entry to another changes the hash.
+    pub fn canonical_hash_with(&self, algorithm: HashAlgorithm, exclude: &[u32]) -> [u8; 32] {
+        self.canonical_hash_in(shared_fix44(), algorithm, exclude)
+    }
+
+    /// `canonical_hash_with`, taking repeating groups from `dict`
+    pub fn canonical_hash_in(&self, dict: &DataDictionary, algorithm: HashAlgorithm, exclude: &[u32]) -> [u8; 32] {
+        let fields = canonical_fields(self.field_slice(), exclude, dict);
+        let mut digest = [0; 32];
+        match algorithm {
+            HashAlgorithm::Fnv1a128 => {
+                let mut state = FNV_128_OFFSET_BASIS;
+                for field in fields {
+                    fnv1a_128(&mut state, &field.tag().to_le_bytes());
+                    fnv1a_128(&mut state, &(field.value().len() as u32).to_le_bytes());
+                    fnv1a_128(&mut state, field.value());
+                }
+                digest[..16].copy_from_slice(&state.to_be_bytes());
+            }
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => {
+                use sha2::{Digest, Sha256};
+                let mut hasher = Sha256::new();
+                for field in fields {
+                    hasher.update(field.tag().to_le_bytes());
+                    hasher.update((field.value().len() as u32).to_le_bytes());
+                    hasher.update(field.value());
+                }
+                digest.copy_from_slice(&hasher.finalize());
+            }
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(fields: &[(u32, &[u8])]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        for &(tag, value) in fields {
+            msg.add_field(FixField::new(tag, value.to_vec()));
+        }
+        msg
+    }
+
+    const ORDER: [(u32, &[u8]); 9] = [
+        (8, b"FIX.4.4"),
+        (9, b"120"),
+        (35, b"D"),
+        (34, b"7"),
+        (52, b"20240101-12:00:00.000"),
+        (11, b"ORD1"),
+        (55, b"VOD.L"),
+        (54, b"1"),
+        (10, b"123"),
+    ];
+
+    #[test]
+    fn test_canonical_hash() {
+        let original = message(&ORDER);
+        let hash = original.canonical_hash(&HASH_EXCLUDED_TAGS);
+
+        // The same order from another feed: reordered, resequenced and
+        // restamped
+        let mut resent = ORDER;
+        resent.reverse();
+        resent[4].1 = b"20240101-12:00:01.500";
+        resent[5].1 = b"3012";
+        assert_eq!(message(&resent).canonical_hash(&HASH_EXCLUDED_TAGS), hash);
+        assert_ne!(message(&resent).canonical_hash(&[]), original.canonical_hash(&[]));
+
+        let mut amended = ORDER;
+        amended[6].1 = b"BARC.L";
+        assert_ne!(message(&amended).canonical_hash(&HASH_EXCLUDED_TAGS), hash);
+
+        // Value boundaries are part of the hash
+        let split = message(&[(58, b"ab"), (58, b"c")]);
+        assert_ne!(split.canonical_hash(&[]), message(&[(58, b"a"), (58, b"bc")]).canonical_hash(&[]));
+
+        // FNV-1a 128 of no input is its offset basis
+        assert_eq!(FixMessage::new().canonical_hash(&[])[..16], FNV_128_OFFSET_BASIS.to_be_bytes());
+        assert_eq!(hash[16..], [0; 16]);
+    }
+
+    #[test]
+    fn test_group_order() {
+        let entries = |first: &'static [u8], second: &'static [u8]| {
+            message(&[(35, b"AE"), (552, b"2"), (54, b"1"), (37, first), (54, b"2"), (37, second), (55, b"VOD.L")])
+        };
+        let hash = entries(b"A", b"B").canonical_hash(&HASH_EXCLUDED_TAGS);
+
+        // Fields of different tags may interleave differently
+        let regrouped = message(&[(35, b"AE"), (55, b"VOD.L"), (552, b"2"), (37, b"A"), (54, b"1"), (54, b"2"), (37, b"B")]);
+        assert_eq!(regrouped.canonical_hash(&HASH_EXCLUDED_TAGS), hash);
+
+        // but swapped entries are a different message
+        assert_ne!(entries(b"B", b"A").canonical_hash(&HASH_EXCLUDED_TAGS), hash);
+
+        // and so is one with a field moved to another entry
+        let paired = message(&[(552, b"2"), (54, b"1"), (37, b"A"), (54, b"2"), (1, b"ACC")]);
+        let moved = message(&[(552, b"2"), (54, b"1"), (54, b"2"), (37, b"A"), (1, b"ACC")]);
+        assert_ne!(paired.canonical_hash(&[]), moved.canonical_hash(&[]));
+        let flat = DataDictionary::new();
+        assert_eq!(
+            paired.canonical_hash_in(&flat, HashAlgorithm::Fnv1a128, &[]),
+            moved.canonical_hash_in(&flat, HashAlgorithm::Fnv1a128, &[])
+        );
+
+        // Within an entry, fields sort by tag after the delimiter and
+        // repeated tags keep their wire order
+        let reordered = message(&[(552, b"2"), (37, b"A"), (54, b"1"), (54, b"2"), (1, b"ACC")]);
+        assert_eq!(reordered.canonical_hash(&[]), paired.canonical_hash(&[]));
+        let repeated = |first: &'static [u8], second: &'static [u8]| {
+            message(&[(552, b"1"), (54, b"1"), (1, first), (1, second)]).canonical_hash(&[])
+        };
+        assert_ne!(repeated(b"X", b"Y"), repeated(b"Y", b"X"));
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        // NoPartyIDs (453) entries each holding a NoPartySubIDs (802) group
+        let dict = DataDictionary::new().with_group(453, &[448, 447, 452, 802]).with_group(802, &[523, 803]);
+        let hash = |fields: &[(u32, &[u8])]| message(fields).canonical_hash_in(&dict, HashAlgorithm::Fnv1a128, &[]);
+        let original = hash(&[
+            (35, b"D"),
+            (453, b"2"),
+            (448, b"P1"),
+            (452, b"1"),
+            (802, b"1"),
+            (523, b"S1"),
+            (803, b"2"),
+            (448, b"P2"),
+            (452, b"3"),
+            (11, b"ORD1"),
+        ]);
+        // Reordered within entries and around the group
+        let reordered = hash(&[
+            (11, b"ORD1"),
+            (453, b"2"),
+            (448, b"P1"),
+            (802, b"1"),
+            (803, b"2"),
+            (523, b"S1"),
+            (452, b"1"),
+            (448, b"P2"),
+            (452, b"3"),
+            (35, b"D"),
+        ]);
+        assert_eq!(reordered, original);
+        // The sub-party moved to the second party
+        let moved = hash(&[
+            (35, b"D"),
+            (453, b"2"),
+            (448, b"P1"),
+            (452, b"1"),
+            (448, b"P2"),
+            (452, b"3"),
+            (802, b"1"),
+            (523, b"S1"),
+            (803, b"2"),
+            (11, b"ORD1"),
+        ]);
+        assert_ne!(moved, original);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_sha256() {
+        use sha2::{Digest, Sha256};
+
+        let msg = message(&[(35, b"0"), (8, b"FIX.4.4")]);
+        let mut canonical = Vec::new();
+        for (tag, value) in [(8u32, &b"FIX.4.4"[..]), (35, b"0")] {
+            canonical.extend_from_slice(&tag.to_le_bytes());
+            canonical.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            canonical.extend_from_slice(value);
+        }
+        let digest = msg.canonical_hash_with(HashAlgorithm::Sha256, &HASH_EXCLUDED_TAGS);
+        assert_eq!(digest[..], Sha256::digest(&canonical)[..]);
+        assert_ne!(digest, msg.canonical_hash(&HASH_EXCLUDED_TAGS));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+use thiserror::Error;
+
+/// Why a message could not be converted. Every lossy case is an error, so a
+/// conversion that succeeds is exact apart from BodyLength and CheckSum,
+/// which both sides regenerate.
+#[derive(Debug, Error)]
+pub enum InteropError {
+    /// quickfix-rs only carries text; the value of this tag is not UTF-8,
+    /// e.g. raw bytes in a data field
+    #[error("Value of tag {0} is not UTF-8")]
+    NonUtf8Value(u32),
+    /// Without a data dictionary QuickFIX stores body fields in tag order,
+    /// so a message whose fields are not already in that order, typically
+    /// one with a repeating group, would come back rearranged
+    #[error("Field order cannot be represented; first difference at tag {0}")]
+    FieldOrderLost(u32),
+    #[error("QuickFIX error: {0}")]
+    QuickFix(String),
+    #[error(transparent)]
+    Fix(#[from] FixError),
+}
+
+fn quickfix_error(err: quickfix::QuickFixError) -> InteropError {
+    InteropError::QuickFix(err.to_string())
+}
+
+// Tag and value of every field except those regenerated on encode
+fn content(msg: &FixMessage) -> impl Iterator<Item = &FixField> {
+    msg.field_slice()
+        .iter()
+        .filter(|f| f.tag() != Tag::BodyLength.value() && f.tag() != Tag::CheckSum.value())
+}
+
+/// Converts through the wire format. The result is read back and compared
+/// field by field, so any rearrangement by QuickFIX is reported as
+/// `FieldOrderLost` instead of passing silently.
+impl TryFrom<&FixMessage> for quickfix::Message {
+    type Error = InteropError;
+
+    fn try_from(msg: &FixMessage) -> Result<Self, Self::Error> {
+        if let Some(field) = msg.fields().find(|f| std::str::from_utf8(f.value()).is_err()) {
+            return Err(InteropError::NonUtf8Value(field.tag()));
+        }
+        let encoded = msg.encode()?;
+        let text = std::str::from_utf8(&encoded).expect("every value was checked to be UTF-8");
+        let converted = quickfix::Message::try_from_text(text).map_err(quickfix_error)?;
+
+        let round_trip = FixMessage::try_from(&converted)?;
+        let (mut ours, mut theirs) = (content(msg), content(&round_trip));
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return Ok(converted),
+                (Some(a), Some(b)) if a == b => {}
+                (a, b) => {
+                    let tag = a.or(b).map_or(0, FixField::tag);
+                    return Err(InteropError::FieldOrderLost(tag));
+                }
+            }
+        }
+    }
+}
+
+/// Converts through the wire format, keeping fields in the order QuickFIX
+/// writes them, repeating groups included
+impl TryFrom<&quickfix::Message> for FixMessage {
+    type Error = InteropError;
+
+    fn try_from(msg: &quickfix::Message) -> Result<Self, Self::Error> {
+        let text = msg.to_fix_string().map_err(quickfix_error)?;
+        Ok(FixMessage::decode(text.as_bytes())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::{fix_version, msg_type};
+
+    const NO_PARTY_IDS: u32 = 453;
+    const PARTY_ID: u32 = 448;
+
+    // Without a data dictionary QuickFIX writes BeginString, BodyLength and
+    // MsgType, then the other header fields and the body fields each in tag
+    // order, keeping repeated tags in the order they arrived
+    fn header() -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"2".to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"CLIENT".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00:00".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"VENUE".to_vec()));
+        msg
+    }
+
+    // Already in QuickFIX's order
+    fn flat_order() -> FixMessage {
+        let mut msg = header();
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+        msg.add_field(FixField::new(Tag::Side.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD".to_vec()));
+        msg
+    }
+
+    fn new_order_single() -> FixMessage {
+        let mut msg = header();
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+        msg.add_group(
+            NO_PARTY_IDS,
+            &[
+                vec![FixField::new(PARTY_ID, b"BROKER".to_vec()), FixField::new(447, b"D".to_vec()), FixField::new(452, b"1".to_vec())],
+                vec![FixField::new(PARTY_ID, b"DESK".to_vec()), FixField::new(447, b"D".to_vec()), FixField::new(452, b"24".to_vec())],
+            ],
+        )
+        .unwrap();
+        msg.add_field(FixField::new(Tag::Side.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD".to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_quickfix_to_fix_engine() {
+        let text = std::str::from_utf8(&new_order_single().encode().unwrap()).unwrap().to_owned();
+        let qf = quickfix::Message::try_from_text(&text).unwrap();
+
+        // The order QuickFIX holds the fields in, the group's fields
+        // spread out by tag, is kept exactly
+        let msg = FixMessage::try_from(&qf).unwrap();
+        assert_eq!(msg.encode().unwrap().as_ref(), qf.to_fix_string().unwrap().as_bytes());
+        let tags: Vec<u32> = msg.field_tags().copied().collect();
+        assert_eq!(tags, [8, 9, 35, 34, 49, 52, 56, 11, 54, 55, 447, 447, 448, 448, 452, 452, 453, 10]);
+        let party_ids: Vec<&[u8]> = msg.get_fields(PARTY_ID).map(FixField::value).collect();
+        assert_eq!(party_ids, [&b"BROKER"[..], b"DESK"]);
+
+        // so it goes back unchanged
+        let back = quickfix::Message::try_from(&msg).unwrap();
+        assert_eq!(back.to_fix_string().unwrap(), qf.to_fix_string().unwrap());
+    }
+
+    #[test]
+    fn test_fix_engine_to_quickfix() {
+        let msg = flat_order();
+        let qf = quickfix::Message::try_from(&msg).unwrap();
+        let back = FixMessage::try_from(&qf).unwrap();
+        assert!(content(&back).eq(content(&msg)));
+
+        // QuickFIX would move the group's fields after Symbol, so the
+        // conversion is refused where the orders first differ
+        assert!(matches!(
+            quickfix::Message::try_from(&new_order_single()),
+            Err(InteropError::FieldOrderLost(tag)) if tag == NO_PARTY_IDS
+        ));
+
+        let mut binary = new_order_single();
+        binary.add_field(FixField::new(Tag::Text.value(), vec![0xff]));
+        assert!(matches!(quickfix::Message::try_from(&binary), Err(InteropError::NonUtf8Value(58))));
+    }
+}
@@ -1,350 +1,1843 @@
-pub mod error;
-pub mod field;
-pub mod message;
-pub mod tags;
-
-pub use error::FixError;
-pub use field::FixField;
-pub use message::FixMessage;
-pub use tags::{fix_version, msg_type, Tag};
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::field::SOH;
-    use crate::tags::{fix_version, msg_type, Tag};
-
-    #[test]
-    fn test_basic_message_encoding() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
-        msg.add_field(FixField::new(Tag::BodyLength.value(), b"100".to_vec()));
-        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
-        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
-        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"TARGET".to_vec()));
-
-        let encoded = msg.encode().unwrap();
-        let decoded = FixMessage::decode(&encoded).unwrap();
-
-        assert_eq!(decoded.get_field(Tag::BeginString.value()).unwrap().value(), fix_version::FIX_4_2);
-        assert_eq!(decoded.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::NEW_ORDER_SINGLE);
-        assert_eq!(decoded.get_field(Tag::SenderCompID.value()).unwrap().value(), b"SENDER");
-        assert_eq!(decoded.get_field(Tag::TargetCompID.value()).unwrap().value(), b"TARGET");
-    }
-
-    #[test]
-    fn test_performance_large_message() {
-        let mut msg = FixMessage::with_capacity(100);
-
-        // Add required header fields first
-        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
-        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec())); // Will be auto-calculated
-        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
-
-        // Add many fields to test performance
-        for i in 0..100 {
-            msg.add_field(FixField::new(1000 + i, format!("VALUE{}", i).into_bytes()));
-        }
-
-        let start = std::time::Instant::now();
-        let encoded = msg.encode().unwrap();
-        let encode_time = start.elapsed();
-
-        let start = std::time::Instant::now();
-        let decoded = FixMessage::decode(&encoded).unwrap();
-        let decode_time = start.elapsed();
-
-        // Verify correct encoding/decoding
-        for i in 0..100 {
-            assert_eq!(
-                decoded.get_field(1000 + i).unwrap().value(),
-                format!("VALUE{}", i).as_bytes()
-            );
-        }
-
-        println!("Encode time: {:?}, Decode time: {:?}", encode_time, decode_time);
-
-        // Additional verification
-        assert_eq!(decoded.get_field(Tag::BeginString.value()).unwrap().value(), fix_version::FIX_4_2);
-        assert_eq!(decoded.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::NEW_ORDER_SINGLE);
-    }
-
-    #[test]
-    fn test_checksum_calculation() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"100".to_vec()));
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        let encoded = msg.encode().unwrap();
-
-        // Calculate checksum manually
-        let calculated_checksum: u32 = encoded[..encoded.len() - 7]
-            .iter()
-            .map(|&b| b as u32)
-            .sum::<u32>() % 256;
-
-        let checksum_str = format!("10={:03}\x01", calculated_checksum);
-        assert!(String::from_utf8_lossy(&encoded[encoded.len() - 7..]).eq(&checksum_str));
-    }
-
-    #[test]
-    fn test_body_length_calculation() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        let encoded = msg.encode().unwrap();
-
-        // Convert to string for easier debugging
-        let msg_str = String::from_utf8_lossy(&encoded);
-        println!("Encoded message: {}", msg_str);
-
-        // Find positions of key fields
-        let body_length_tag_pos = encoded.windows(2).position(|w| w == b"9=".as_slice()).unwrap();
-        let body_length_end = encoded[body_length_tag_pos..].iter().position(|&b| b == SOH).unwrap() + body_length_tag_pos;
-        let checksum_pos = encoded.windows(3).position(|w| w == b"10=".as_slice()).unwrap();
-
-        // Extract body length value
-        let body_length_str = String::from_utf8_lossy(&encoded[body_length_tag_pos + 2..body_length_end]);
-        let body_length: usize = body_length_str.parse().unwrap();
-
-        // Calculate actual body length (from after body length field's SOH to before checksum)
-        let actual_length = checksum_pos - (body_length_end + 1);
-
-        assert_eq!(
-            body_length,
-            actual_length,
-            "Body length mismatch. Message: {}\nFound {} in message but actual length is {}",
-            msg_str,
-            body_length,
-            actual_length
-        );
-    }
-
-    #[test]
-    fn test_large_message_body_length() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        // Add some large fields
-        for i in 0..10 {
-            let value = vec![b'X'; 1000]; // 1000 byte value
-            msg.add_field(FixField::new(100 + i, value));
-        }
-
-        let encoded = msg.encode().unwrap();
-        let decoded = FixMessage::decode(&encoded).unwrap();
-
-        // Verify the message can be properly decoded
-        for i in 0..10 {
-            let field = decoded.get_field(100 + i).unwrap();
-            assert_eq!(field.value().len(), 1000);
-            assert!(field.value().iter().all(|&b| b == b'X'));
-        }
-    }
-
-    #[test]
-    fn test_complex_message_body_length() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
-        msg.add_field(FixField::new(56, b"TARGET".to_vec()));
-        msg.add_field(FixField::new(34, b"1".to_vec()));
-        msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
-
-        let encoded = msg.encode().unwrap();
-
-        // Extract and verify body length
-        let body_start = encoded.iter()
-            .position(|&b| b == b'9')
-            .unwrap();
-        let body_end = encoded[body_start..]
-            .iter()
-            .position(|&b| b == 0x01)
-            .unwrap() + body_start;
-
-        let body_length_str = String::from_utf8_lossy(&encoded[body_start + 2..body_end]);
-        let body_length: usize = body_length_str.parse().unwrap();
-
-        let checksum_start = encoded.windows(3)
-            .position(|w| w == b"10=".as_slice())
-            .unwrap();
-        let actual_length = checksum_start - (body_end + 1);
-
-        assert_eq!(body_length, actual_length);
-
-        // Decode and verify the message can be read back
-        let decoded = FixMessage::decode(&encoded).unwrap();
-        assert_eq!(decoded.get_field(49).unwrap().value(), b"SENDER");
-        assert_eq!(decoded.get_field(56).unwrap().value(), b"TARGET");
-    }
-
-    #[test]
-    fn test_invalid_checksum() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"100".to_vec()));
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        let mut encoded = msg.encode().unwrap();
-
-        // Corrupt the message
-        encoded[5] = b'X';
-
-        assert!(matches!(
-            FixMessage::decode(&encoded),
-            Err(FixError::InvalidChecksum)
-        ));
-    }
-
-    #[test]
-    fn test_missing_required_fields() {
-        let mut msg = FixMessage::new();
-
-        // Missing BeginString (8)
-        msg.add_field(FixField::new(9, b"100".to_vec()));
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        assert!(matches!(
-            msg.encode(),
-            Err(FixError::MissingField(8))
-        ));
-    }
-
-    #[test]
-    fn test_message_field_capacity() {
-        let msg = FixMessage::with_capacity(100);
-        // Verify the initial capacity is set correctly
-        assert!(msg.capacity() >= 100);
-    }
-
-    #[test]
-    fn test_large_field_values() {
-        let mut msg = FixMessage::new();
-
-        // Add required header fields
-        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
-        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
-        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
-
-        // Add a large field value that exceeds SmallVec inline capacity
-        let large_value = vec![b'X'; 1024];
-        msg.add_field(FixField::new(1000, large_value.clone()));
-
-        let encoded = msg.encode().unwrap();
-        let decoded = FixMessage::decode(&encoded).unwrap();
-
-        assert_eq!(decoded.get_field(1000).unwrap().value(), &large_value[..]);
-    }
-
-    #[test]
-    fn test_field_order_preservation() {
-        let mut msg = FixMessage::new();
-
-        // Add required header fields
-        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
-        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
-        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
-
-        // Add fields in specific order
-        let tags = vec![1000, 1001, 1002, 1003];
-        for &tag in &tags {
-            msg.add_field(FixField::new(tag, format!("VALUE{}", tag).into_bytes()));
-        }
-
-        let encoded = msg.encode().unwrap();
-        let decoded = FixMessage::decode(&encoded).unwrap();
-
-        // Verify field order matches original order
-        let decoded_tags: Vec<_> = decoded.field_tags().copied().collect();
-        for window in tags.windows(2) {
-            let pos1 = decoded_tags.iter().position(|&x| x == window[0]).unwrap();
-            let pos2 = decoded_tags.iter().position(|&x| x == window[1]).unwrap();
-            assert!(pos1 < pos2, "Fields not in correct order");
-        }
-    }
-
-    #[test]
-    fn test_message_operations() {
-        let mut msg = FixMessage::new();
-        assert!(msg.is_empty());
-        assert_eq!(msg.len(), 0);
-
-        // Add some fields
-        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
-        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
-        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
-
-        assert!(!msg.is_empty());
-        assert_eq!(msg.len(), 3);
-
-        // Test field_tags iterator
-        let tags: Vec<_> = msg.field_tags().copied().collect();
-        assert_eq!(tags.len(), 3);
-        assert!(tags.contains(&Tag::BeginString.value()));
-        assert!(tags.contains(&Tag::BodyLength.value()));
-        assert!(tags.contains(&Tag::MsgType.value()));
-    }
-
-    #[test]
-    fn test_sequential_field_tags() {
-        let mut msg = FixMessage::new();
-
-        // Add fields in a specific order
-        let field_sequence = vec![
-            (Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()),
-            (Tag::BodyLength.value(), b"0".to_vec()),
-            (Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()),
-        ];
-
-        for (tag, value) in field_sequence.iter() {
-            msg.add_field(FixField::new(*tag, value.clone()));
-        }
-
-        // Verify the sequence using field_tags
-        let tags: Vec<_> = msg.field_tags().copied().collect();
-        for (i, (expected_tag, _)) in field_sequence.iter().enumerate() {
-            assert_eq!(tags[i], *expected_tag);
-        }
-    }
-
-    #[test]
-    fn test_message_format() {
-        let mut msg = FixMessage::new();
-
-        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
-        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
-        msg.add_field(FixField::new(35, b"D".to_vec()));
-
-        let encoded = msg.encode().unwrap();
-        let msg_str = String::from_utf8_lossy(&encoded);
-
-        // Message should start with 8=FIX.4.2|
-        assert!(msg_str.starts_with("8=FIX.4.2\x01"));
-
-        // Should be followed by 9=n|
-        let body_length_start = msg_str.find("9=").unwrap();
-        assert!(body_length_start > 0);
-
-        // Should end with checksum
-        assert!(msg_str.ends_with("\x01"));
-        let checksum_part = &msg_str[msg_str.find("10=").unwrap()..];
-        assert_eq!(checksum_part.len(), 7); // "10=nnn|"
-
-        // Decode should succeed
-        let decoded = FixMessage::decode(&encoded).unwrap();
-        assert_eq!(decoded.get_field(35).unwrap().value(), b"D");
-    }
-}
\ No newline at end of file
+pub mod annotate;
+pub mod bench;
+pub mod checksum;
+pub mod codes;
+#[cfg(feature = "compression")]
+pub mod compression;
+pub mod datetime;
+pub mod decimal;
+pub mod dictionary;
+pub mod duplicate;
+pub mod error;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod field;
+#[cfg(feature = "fixml")]
+pub mod fixml;
+pub mod framing;
+pub mod hash;
+#[cfg(feature = "quickfix")]
+pub mod interop;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod fuzz;
+pub mod log;
+pub mod message;
+pub mod messages;
+pub mod metrics;
+pub mod options;
+pub mod orders;
+#[cfg(feature = "websocket")]
+pub mod probe;
+pub mod profile;
+pub mod replay;
+pub mod route;
+pub mod scanner;
+pub mod seqnum;
+pub mod session;
+pub mod settl_type;
+pub mod tags;
+pub mod template;
+#[cfg(feature = "test-util")]
+pub mod testing;
+pub mod testvectors;
+pub mod text;
+pub mod transport;
+
+pub use annotate::{FieldAnnotation, MessageAnnotation};
+pub use checksum::RollingChecksum;
+pub use codes::{Country, Currency, Mic};
+pub use datetime::{
+    LocalMktDate, MonthYear, TzOffset, TzTimeOnly, TzTimestamp, UtcDateOnly, UtcTimeOnly, UtcTimestamp,
+};
+pub use decimal::{Amt, DecimalFormat, DecimalPadding, FixDecimal, Percentage, Qty, RoundingMode};
+pub use dictionary::{DataDictionary, FieldSpec, FieldType, MessageBuilder, MessageSpec};
+pub use error::FixError;
+pub use field::{Alignment, FieldValue, FixField, Flag, PaddingRule};
+pub use message::{
+    ChangeOp, FieldChange, FixGroupEntry, FixMessage, FixMessageRef, FixMessageView, GroupEntries, GroupEntryRef, HeaderFields,
+    MergePolicy, Sections,
+};
+pub use messages::{ExecutionReport, Instrument, Logon, OrderCancelReject, ResendRequest, TradeCaptureReport, TradeCaptureSide};
+pub use options::{DecodeOptions, DecodeWarning, EncodeOptions};
+pub use scanner::FieldScanner;
+pub use seqnum::{EndSeqNo, SeqNum};
+pub use session::{Session, SessionConfig, SessionEvent};
+pub use settl_type::{SettlCode, SettlType, TenorUnit};
+pub use tags::{appl_ver_id, fix_version, msg_type, MsgType, Tag, HEADER_TAGS, TRAILER_TAGS};
+pub use template::{Template, TemplatedDecode, TemplatedMessage};
+pub use text::EncodedText;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::SOH;
+    use crate::tags::{fix_version, msg_type, Tag};
+
+    #[test]
+    fn test_basic_message_encoding() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"100".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"TARGET".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        assert_eq!(decoded.get_field(Tag::BeginString.value()).unwrap().value(), fix_version::FIX_4_2);
+        assert_eq!(decoded.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::NEW_ORDER_SINGLE);
+        assert_eq!(decoded.get_field(Tag::SenderCompID.value()).unwrap().value(), b"SENDER");
+        assert_eq!(decoded.get_field(Tag::TargetCompID.value()).unwrap().value(), b"TARGET");
+    }
+
+    #[test]
+    fn test_performance_large_message() {
+        let mut msg = FixMessage::with_capacity(100);
+
+        // Add required header fields first
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec())); // Will be auto-calculated
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+
+        // Add many fields to test performance
+        for i in 0..100 {
+            msg.add_field(FixField::new(1000 + i, format!("VALUE{}", i).into_bytes()));
+        }
+
+        let start = std::time::Instant::now();
+        let encoded = msg.encode().unwrap();
+        let encode_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        let decode_time = start.elapsed();
+
+        // Verify correct encoding/decoding
+        for i in 0..100 {
+            assert_eq!(
+                decoded.get_field(1000 + i).unwrap().value(),
+                format!("VALUE{}", i).as_bytes()
+            );
+        }
+
+        println!("Encode time: {:?}, Decode time: {:?}", encode_time, decode_time);
+
+        // Additional verification
+        assert_eq!(decoded.get_field(Tag::BeginString.value()).unwrap().value(), fix_version::FIX_4_2);
+        assert_eq!(decoded.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::NEW_ORDER_SINGLE);
+    }
+
+    #[test]
+    fn test_checksum_calculation() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"100".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+
+        // Calculate checksum manually
+        let calculated_checksum: u32 = encoded[..encoded.len() - 7]
+            .iter()
+            .map(|&b| b as u32)
+            .sum::<u32>() % 256;
+
+        let checksum_str = format!("10={:03}\x01", calculated_checksum);
+        assert!(String::from_utf8_lossy(&encoded[encoded.len() - 7..]).eq(&checksum_str));
+    }
+
+    #[test]
+    fn test_body_length_calculation() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+
+        // Convert to string for easier debugging
+        let msg_str = String::from_utf8_lossy(&encoded);
+        println!("Encoded message: {}", msg_str);
+
+        // Find positions of key fields
+        let body_length_tag_pos = encoded.windows(2).position(|w| w == b"9=".as_slice()).unwrap();
+        let body_length_end = encoded[body_length_tag_pos..].iter().position(|&b| b == SOH).unwrap() + body_length_tag_pos;
+        let checksum_pos = encoded.windows(3).position(|w| w == b"10=".as_slice()).unwrap();
+
+        // Extract body length value
+        let body_length_str = String::from_utf8_lossy(&encoded[body_length_tag_pos + 2..body_length_end]);
+        let body_length: usize = body_length_str.parse().unwrap();
+
+        // Calculate actual body length (from after body length field's SOH to before checksum)
+        let actual_length = checksum_pos - (body_length_end + 1);
+
+        assert_eq!(
+            body_length,
+            actual_length,
+            "Body length mismatch. Message: {}\nFound {} in message but actual length is {}",
+            msg_str,
+            body_length,
+            actual_length
+        );
+    }
+
+    #[test]
+    fn test_large_message_body_length() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        // Add some large fields
+        for i in 0..10 {
+            let value = vec![b'X'; 1000]; // 1000 byte value
+            msg.add_field(FixField::new(100 + i, value));
+        }
+
+        let encoded = msg.encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        // Verify the message can be properly decoded
+        for i in 0..10 {
+            let field = decoded.get_field(100 + i).unwrap();
+            assert_eq!(field.value().len(), 1000);
+            assert!(field.value().iter().all(|&b| b == b'X'));
+        }
+    }
+
+    #[test]
+    fn test_complex_message_body_length() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+        msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+        msg.add_field(FixField::new(34, b"1".to_vec()));
+        msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+
+        // Extract and verify body length
+        let body_start = encoded.iter()
+            .position(|&b| b == b'9')
+            .unwrap();
+        let body_end = encoded[body_start..]
+            .iter()
+            .position(|&b| b == 0x01)
+            .unwrap() + body_start;
+
+        let body_length_str = String::from_utf8_lossy(&encoded[body_start + 2..body_end]);
+        let body_length: usize = body_length_str.parse().unwrap();
+
+        let checksum_start = encoded.windows(3)
+            .position(|w| w == b"10=".as_slice())
+            .unwrap();
+        let actual_length = checksum_start - (body_end + 1);
+
+        assert_eq!(body_length, actual_length);
+
+        // Decode and verify the message can be read back
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded.get_field(49).unwrap().value(), b"SENDER");
+        assert_eq!(decoded.get_field(56).unwrap().value(), b"TARGET");
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"100".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        let mut encoded = msg.encode().unwrap();
+
+        // Corrupt the message
+        encoded[5] = b'X';
+
+        assert!(matches!(
+            FixMessage::decode(&encoded),
+            Err(FixError::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let mut msg = FixMessage::new();
+
+        // Missing BeginString (8)
+        msg.add_field(FixField::new(9, b"100".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        assert!(matches!(
+            msg.encode(),
+            Err(FixError::MissingField(8))
+        ));
+    }
+
+    #[test]
+    fn test_message_field_capacity() {
+        let msg = FixMessage::with_capacity(100);
+        // Verify the initial capacity is set correctly
+        assert!(msg.capacity() >= 100);
+    }
+
+    #[test]
+    fn test_large_field_values() {
+        let mut msg = FixMessage::new();
+
+        // Add required header fields
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+
+        // Add a large field value that exceeds SmallVec inline capacity
+        let large_value = vec![b'X'; 1024];
+        msg.add_field(FixField::new(1000, large_value.clone()));
+
+        let encoded = msg.encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        assert_eq!(decoded.get_field(1000).unwrap().value(), &large_value[..]);
+    }
+
+    #[test]
+    fn test_field_order_preservation() {
+        let mut msg = FixMessage::new();
+
+        // Add required header fields
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+
+        // Add fields in specific order
+        let tags = vec![1000, 1001, 1002, 1003];
+        for &tag in &tags {
+            msg.add_field(FixField::new(tag, format!("VALUE{}", tag).into_bytes()));
+        }
+
+        let encoded = msg.encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        // Verify field order matches original order
+        let decoded_tags: Vec<_> = decoded.field_tags().copied().collect();
+        for window in tags.windows(2) {
+            let pos1 = decoded_tags.iter().position(|&x| x == window[0]).unwrap();
+            let pos2 = decoded_tags.iter().position(|&x| x == window[1]).unwrap();
+            assert!(pos1 < pos2, "Fields not in correct order");
+        }
+    }
+
+    #[test]
+    fn test_message_operations() {
+        let mut msg = FixMessage::new();
+        assert!(msg.is_empty());
+        assert_eq!(msg.len(), 0);
+
+        // Add some fields
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+
+        assert!(!msg.is_empty());
+        assert_eq!(msg.len(), 3);
+
+        // Test field_tags iterator
+        let tags: Vec<_> = msg.field_tags().copied().collect();
+        assert_eq!(tags.len(), 3);
+        assert!(tags.contains(&Tag::BeginString.value()));
+        assert!(tags.contains(&Tag::BodyLength.value()));
+        assert!(tags.contains(&Tag::MsgType.value()));
+    }
+
+    #[test]
+    fn test_sequential_field_tags() {
+        let mut msg = FixMessage::new();
+
+        // Add fields in a specific order
+        let field_sequence = [
+            (Tag::BeginString.value(), fix_version::FIX_4_2.to_vec()),
+            (Tag::BodyLength.value(), b"0".to_vec()),
+            (Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()),
+        ];
+
+        for (tag, value) in field_sequence.iter() {
+            msg.add_field(FixField::new(*tag, value.clone()));
+        }
+
+        // Verify the sequence using field_tags
+        let tags: Vec<_> = msg.field_tags().copied().collect();
+        for (i, (expected_tag, _)) in field_sequence.iter().enumerate() {
+            assert_eq!(tags[i], *expected_tag);
+        }
+    }
+
+    #[test]
+    fn test_message_format() {
+        let mut msg = FixMessage::new();
+
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec())); // Will be auto-calculated
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        let msg_str = String::from_utf8_lossy(&encoded);
+
+        // Message should start with 8=FIX.4.2|
+        assert!(msg_str.starts_with("8=FIX.4.2\x01"));
+
+        // Should be followed by 9=n|
+        let body_length_start = msg_str.find("9=").unwrap();
+        assert!(body_length_start > 0);
+
+        // Should end with checksum
+        assert!(msg_str.ends_with("\x01"));
+        let checksum_part = &msg_str[msg_str.find("10=").unwrap()..];
+        assert_eq!(checksum_part.len(), 7); // "10=nnn|"
+
+        // Decode should succeed
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded.get_field(35).unwrap().value(), b"D");
+    }
+
+    #[test]
+    fn test_validate_header_order() {
+        let encoded = {
+            let mut msg = FixMessage::new();
+            msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+            msg.add_field(FixField::new(9, b"0".to_vec()));
+            msg.add_field(FixField::new(35, b"D".to_vec()));
+            msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+            msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+            msg.add_field(FixField::new(34, b"1".to_vec()));
+            msg.add_field(FixField::new(43, b"N".to_vec()));
+            msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+            msg.add_field(FixField::new(11, b"ORDER1".to_vec()));
+            msg.encode().unwrap()
+        };
+
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert!(decoded.validate_header_order().is_ok());
+    }
+
+    #[test]
+    fn test_validate_header_order_scrambled() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(56, b"TARGET".to_vec()));
+        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+        msg.add_field(FixField::new(34, b"1".to_vec()));
+
+        assert!(matches!(
+            msg.validate_header_order(),
+            Err(FixError::HeaderOutOfOrder(49))
+        ));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec()));
+
+        assert!(matches!(
+            msg.validate_header_order(),
+            Err(FixError::HeaderOutOfOrder(35))
+        ));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(9, b"0".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+        msg.add_field(FixField::new(11, b"ORDER1".to_vec()));
+        msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+
+        assert!(matches!(
+            msg.validate_header_order(),
+            Err(FixError::HeaderOutOfOrder(52))
+        ));
+    }
+
+    #[test]
+    fn test_date_and_time_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::SettlDate.value(), b"20240105".to_vec()));
+        msg.add_field(FixField::new(Tag::MDEntryTime.value(), b"12:00:00.000".to_vec()));
+        msg.add_field(FixField::new(Tag::TradeDate.value(), b"2024-01-05".to_vec()));
+
+        let settl_date = msg.get_local_mkt_date(Tag::SettlDate.value()).unwrap().unwrap();
+        assert_eq!(settl_date, LocalMktDate::new(2024, 1, 5).unwrap());
+        assert!(msg.get_utc_time_only(Tag::MDEntryTime.value()).unwrap().is_some());
+        assert!(msg.get_utc_date_only(1000).unwrap().is_none());
+        assert!(matches!(
+            msg.get_local_mkt_date(Tag::TradeDate.value()),
+            Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::TradeDate.value()
+        ));
+
+        // Setting an existing tag replaces it in place
+        msg.set_local_mkt_date(Tag::TradeDate.value(), LocalMktDate::new(2024, 1, 4).unwrap());
+        msg.set_utc_time_only(Tag::MDEntryTime.value(), UtcTimeOnly::new(13, 30, 0).unwrap());
+        msg.set_utc_date_only(1000, UtcDateOnly::new(2024, 1, 4).unwrap());
+        assert_eq!(msg.len(), 6);
+        assert_eq!(msg.field_tags().count(), 6);
+        assert_eq!(msg.get_field(Tag::TradeDate.value()).unwrap().value(), b"20240104");
+        assert_eq!(msg.get_field(Tag::MDEntryTime.value()).unwrap().value(), b"13:30:00");
+        assert_eq!(msg.field_tags().last(), Some(&1000));
+    }
+
+    #[test]
+    fn test_settl_type_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::SettlType.value(), b"M3".to_vec()));
+
+        let tenor = msg.get_settl_type(Tag::SettlType.value()).unwrap();
+        assert_eq!(tenor, Some(SettlType::Tenor { unit: TenorUnit::Month, count: 3 }));
+
+        msg.set_settl_type(Tag::SettlType.value(), SettlCode::TPlus2.into());
+        assert_eq!(msg.get_field(Tag::SettlType.value()).unwrap().value(), b"3");
+        msg.set_settl_type(Tag::SettlType.value(), SettlType::tenor(TenorUnit::Week, 2).unwrap());
+        assert_eq!(msg.get_field(Tag::SettlType.value()).unwrap().value(), b"W2");
+
+        msg.add_field(FixField::new(Tag::SettlDate.value(), b"20240105".to_vec()));
+        assert!(msg.validate_all(&DataDictionary::fix44()).is_empty());
+
+        let mut padded = FixMessage::new();
+        padded.add_field(FixField::new(Tag::SettlType.value(), b"M03".to_vec()));
+        assert!(matches!(padded.get_settl_type(Tag::SettlType.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::SettlType.value()));
+        assert_eq!(padded.validate_all(&DataDictionary::fix44())[0].0, Tag::SettlType.value());
+    }
+
+    fn merge_fixture() -> (FixMessage, FixMessage) {
+        let mut template = FixMessage::new();
+        template.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        template.add_field(FixField::new(35, b"D".to_vec()));
+        template.add_field(FixField::new(1, b"ACCOUNT".to_vec()));
+        template.add_field(FixField::new(21, b"1".to_vec()));
+
+        let mut order = FixMessage::new();
+        order.add_field(FixField::new(11, b"ORDER1".to_vec()));
+        order.add_field(FixField::new(21, b"2".to_vec()));
+        order.add_field(FixField::new(55, b"IBM".to_vec()));
+
+        (template, order)
+    }
+
+    #[test]
+    fn test_merge_keep_existing() {
+        let (mut msg, order) = merge_fixture();
+        msg.merge(&order, MergePolicy::KeepExisting).unwrap();
+
+        let tags: Vec<_> = msg.field_tags().copied().collect();
+        assert_eq!(tags, [8, 35, 1, 21, 11, 55]);
+        assert_eq!(msg.get_field(21).unwrap().value(), b"1");
+        assert_eq!(msg.get_field(55).unwrap().value(), b"IBM");
+    }
+
+    #[test]
+    fn test_merge_overwrite() {
+        let (mut msg, order) = merge_fixture();
+        msg.merge(&order, MergePolicy::Overwrite).unwrap();
+
+        let tags: Vec<_> = msg.field_tags().copied().collect();
+        assert_eq!(tags, [8, 35, 1, 21, 11, 55]);
+        assert_eq!(msg.get_field(21).unwrap().value(), b"2");
+        assert_eq!(msg.get_field(11).unwrap().value(), b"ORDER1");
+    }
+
+    #[test]
+    fn test_merge_error() {
+        let (mut msg, order) = merge_fixture();
+        assert!(matches!(
+            msg.merge(&order, MergePolicy::Error),
+            Err(FixError::FieldConflict(21))
+        ));
+        // A failed merge leaves the message untouched
+        assert_eq!(msg.len(), 4);
+        assert!(msg.get_field(11).is_none());
+
+        let mut disjoint = FixMessage::new();
+        disjoint.add_field(FixField::new(38, b"100".to_vec()));
+        disjoint.add_field(FixField::new(44, b"10.5".to_vec()));
+        msg.merge(&disjoint, MergePolicy::Error).unwrap();
+
+        let tags: Vec<_> = msg.field_tags().copied().collect();
+        assert_eq!(tags, [8, 35, 1, 21, 38, 44]);
+    }
+
+    #[test]
+    fn test_numeric_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(34, b"42".to_vec()));
+        msg.add_field(FixField::new(1000, b"-17".to_vec()));
+        msg.add_field(FixField::new(1001, b"12a".to_vec()));
+        msg.add_field(FixField::new(1002, b"".to_vec()));
+        msg.add_field(FixField::new(1003, b"18446744073709551615".to_vec()));
+
+        assert_eq!(msg.get_uint(34).unwrap(), Some(42));
+        assert_eq!(msg.get_int(34).unwrap(), Some(42));
+        assert_eq!(msg.get_int(1000).unwrap(), Some(-17));
+        assert_eq!(msg.get_uint(1003).unwrap(), Some(u64::MAX));
+        assert_eq!(msg.get_uint(2000).unwrap(), None);
+        assert_eq!(msg.get_int(2000).unwrap(), None);
+
+        assert!(matches!(msg.get_uint(1000), Err(FixError::InvalidFieldValueFor { tag: 1000 })));
+        assert!(matches!(msg.get_uint(1001), Err(FixError::InvalidFieldValueFor { tag: 1001 })));
+        assert!(matches!(msg.get_int(1002), Err(FixError::InvalidFieldValueFor { tag: 1002 })));
+        assert!(matches!(msg.get_int(1003), Err(FixError::InvalidFieldValueFor { tag: 1003 })));
+    }
+
+    #[test]
+    fn test_max_encoded_len() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(58, vec![b'X'; 8000]));
+
+        let len = msg.encode().unwrap().len();
+
+        let options = EncodeOptions { max_encoded_len: Some(len), ..Default::default() };
+        assert_eq!(msg.encode_with_options(&options).unwrap().len(), len);
+
+        let options = EncodeOptions { max_encoded_len: Some(len - 1), ..Default::default() };
+        assert!(matches!(
+            msg.encode_with_options(&options),
+            Err(FixError::MessageTooLarge { len: actual, max }) if actual == len && max == len - 1
+        ));
+    }
+
+    #[test]
+    fn test_semantic_decimal_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::LastQty.value(), b"-5".to_vec()));
+        msg.add_field(FixField::new(Tag::LastPx.value(), b"101.25".to_vec()));
+        msg.add_field(FixField::new(512, b"5".to_vec()));
+        msg.add_field(FixField::new(38, b"100".to_vec()));
+
+        assert!(matches!(msg.get_qty(Tag::LastQty.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::LastQty.value()));
+        assert!(matches!(msg.get_percentage(512), Err(FixError::InvalidFieldValueFor { tag: 512 })));
+        assert_eq!(msg.get_qty(38).unwrap().unwrap().value(), FixDecimal::from(100));
+        assert_eq!(msg.get_decimal(Tag::LastPx.value()).unwrap().unwrap().to_string(), "101.25");
+        assert!(msg.get_amt(1000).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sections() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+        msg.add_field(FixField::new(11, b"ORDER1".to_vec()));
+        msg.add_field(FixField::new(52, b"20240101-12:00:00.000".to_vec()));
+        msg.add_field(FixField::new(55, b"IBM".to_vec()));
+
+        let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+        let sections = decoded.sections();
+
+        let header: Vec<_> = sections.header().map(|f| f.tag()).collect();
+        let body: Vec<_> = sections.body().map(|f| f.tag()).collect();
+        let trailer: Vec<_> = sections.trailer().map(|f| f.tag()).collect();
+        assert_eq!(header, [8, 9, 35, 49, 52]);
+        assert_eq!(body, [11, 55]);
+        assert_eq!(trailer, [10]);
+        assert_eq!(sections.iter().count(), decoded.len());
+    }
+
+    #[test]
+    fn test_set_price_with_format() {
+        let format = DecimalFormat::default()
+            .with_max_decimal_places(4)
+            .with_padding(DecimalPadding::Fixed);
+        let mut msg = FixMessage::new();
+
+        msg.set_price(44, "1.5".parse().unwrap(), &format).unwrap();
+        assert_eq!(msg.get_field(44).unwrap().value(), b"1.5000");
+
+        // Trailing zeros beyond the limit are dropped, significant digits are not
+        msg.set_price(44, "1.234500".parse().unwrap(), &format).unwrap();
+        assert_eq!(msg.get_field(44).unwrap().value(), b"1.2345");
+        assert!(matches!(
+            msg.set_price(44, "1.23456".parse().unwrap(), &format),
+            Err(FixError::PrecisionExceeded { scale: 5, max: 4 })
+        ));
+        assert_eq!(msg.get_field(44).unwrap().value(), b"1.2345");
+
+        // Tiny values stay positional rather than "1e-8"
+        let format = DecimalFormat::default().with_padding(DecimalPadding::TrimTrailingZeros);
+        msg.set_price(44, FixDecimal::new(10, 9).unwrap(), &format).unwrap();
+        assert_eq!(msg.get_field(44).unwrap().value(), b"0.00000001");
+    }
+
+    #[test]
+    fn test_encode_applies_decimal_formats() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(44, b"101.250000".to_vec()));
+
+        let options = EncodeOptions::default().with_decimal_format(
+            44,
+            DecimalFormat::default().with_max_decimal_places(2),
+        );
+        let decoded = FixMessage::decode(&msg.encode_with_options(&options).unwrap()).unwrap();
+        assert_eq!(decoded.get_field(44).unwrap().value(), b"101.25");
+
+        msg.set_value(44, &"101.255".parse::<FixDecimal>().unwrap());
+        assert!(matches!(
+            msg.encode_with_options(&options),
+            Err(FixError::PrecisionExceeded { scale: 3, max: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_padded_fields() {
+        let field = FixField::from_padded(1, b"ACC1", 12, b' ', Alignment::Left).unwrap();
+        assert_eq!(field.value(), b"ACC1        ");
+        assert_eq!(field.as_trimmed_str().unwrap(), "ACC1");
+
+        let field = FixField::from_padded(1, b"42", 6, b'0', Alignment::Right).unwrap();
+        assert_eq!(field.value(), b"000042");
+
+        assert!(matches!(
+            FixField::from_padded(1, b"ACCOUNT-TOO-LONG", 12, b' ', Alignment::Left),
+            Err(FixError::ValueTooLong { tag: 1, len: 16, width: 12 })
+        ));
+    }
+
+    #[test]
+    fn test_encode_applies_padding_rules() {
+        let options = EncodeOptions::default()
+            .with_padding_rule(1, PaddingRule::new(12, b' ', Alignment::Left));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(1, b"ACC1".to_vec()));
+
+        // Re-encoding a decoded padded message must not pad it again
+        let first = msg.encode_with_options(&options).unwrap();
+        let decoded = FixMessage::decode(&first).unwrap();
+        assert_eq!(decoded.get_field(1).unwrap().value(), b"ACC1        ");
+        assert_eq!(decoded.encode_with_options(&options).unwrap(), first);
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(1, b"ACCOUNT-TOO-LONG".to_vec()));
+        assert!(matches!(
+            msg.encode_with_options(&options),
+            Err(FixError::ValueTooLong { tag: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_print_string_round_trip() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.2".to_vec()));
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(49, b"SENDER".to_vec()));
+        msg.add_field(FixField::new(11, b"ORD1".to_vec()));
+
+        let printed = msg.to_print_string();
+        assert!(printed.starts_with("8=FIX.4.2|9="));
+        assert!(printed.ends_with('|'));
+        assert!(!printed.contains('\x01'));
+
+        let parsed = FixMessage::from_print_string(&printed).unwrap();
+        assert_eq!(parsed.to_print_string(), printed);
+        assert_eq!(parsed.get_field(11).unwrap().value(), b"ORD1");
+    }
+
+    #[test]
+    fn test_print_string_without_checksum() {
+        // As pasted from a log line: stale BodyLength, no CheckSum, no final '|'
+        let parsed = FixMessage::from_print_string("8=FIX.4.4|9=0|35=0|49=A|56=B|34=7").unwrap();
+        assert_eq!(parsed.msg_seq_num().unwrap().unwrap().get(), 7);
+        assert!(parsed.to_print_string().contains("|9=20|"));
+
+        assert!(matches!(
+            FixMessage::from_print_string("8=FIX.4.4|9=5|35=0|10=000|"),
+            Err(FixError::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn test_bool_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(43, b"Y".to_vec()));
+        msg.add_field(FixField::new(97, b"yes".to_vec()));
+
+        assert_eq!(msg.get_bool(43).unwrap(), Some(true));
+        assert_eq!(msg.get_bool(114).unwrap(), None);
+        assert!(msg.get_bool_or(114, true).unwrap());
+        assert!(msg.get_bool_or(43, false).unwrap());
+        assert!(matches!(msg.get_bool_or(97, false), Err(FixError::InvalidFieldValueFor { tag: 97 })));
+
+        assert_eq!(msg.get_flag(43, false).unwrap(), Flag::Explicit(true));
+        let defaulted = msg.get_flag(114, false).unwrap();
+        assert!(!defaulted.is_explicit());
+        assert!(!defaulted.get());
+
+        msg.set_value(114, &false);
+        assert_eq!(msg.get_field(114).unwrap().value(), b"N");
+    }
+
+    #[test]
+    fn test_business_bytes() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"TARGET".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20230815-10:00:00".to_vec()));
+        msg.add_field(FixField::new(55, b"AAPL".to_vec()));
+        msg.add_field(FixField::new(Tag::Side.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(38, b"100".to_vec()));
+        let msg = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+
+        assert_eq!(&msg.business_bytes().unwrap()[..], b"55=AAPL\x0154=1\x0138=100\x01");
+        assert_eq!(
+            &msg.bytes_between(Tag::MsgType.value(), Tag::CheckSum.value()).unwrap()[..],
+            b"49=SENDER\x0156=TARGET\x0134=1\x0152=20230815-10:00:00\x0155=AAPL\x0154=1\x0138=100\x01"
+        );
+        assert_eq!(&msg.bytes_between(54, 10).unwrap()[..], b"38=100\x01");
+        assert!(matches!(msg.bytes_between(11, 10), Err(FixError::MissingField(11))));
+    }
+
+    #[test]
+    fn test_encode_skips_framing_tags() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::CheckSum.value(), b"000".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"999".to_vec()));
+        msg.add_field(FixField::new(7, b"1".to_vec()));
+        msg.add_field(FixField::new(34, b"2".to_vec()));
+        msg.add_field(FixField::new(64, b"20240101".to_vec()));
+        msg.add_field(FixField::new(98, b"0".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019=31\x0135=0\x017=1\x0134=2\x0164=20240101\x0198=0\x0110="));
+        assert_eq!(encoded.iter().filter(|&&b| b == SOH).count(), 8);
+    }
+
+    #[test]
+    fn test_bytes_needed() {
+        use crate::message::bytes_needed;
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"1".to_vec()));
+        let encoded = msg.encode().unwrap();
+
+        // Every prefix up to the end of BodyLength is an incomplete header
+        let header_len = b"8=FIX.4.4\x019=10\x01".len();
+        for len in 0..header_len {
+            assert!(matches!(bytes_needed(&encoded[..len]), Err(FixError::IncompleteHeader)), "{}", len);
+        }
+        for len in header_len..encoded.len() {
+            assert_eq!(bytes_needed(&encoded[..len]).unwrap(), Some(encoded.len() - len));
+        }
+        assert_eq!(bytes_needed(&encoded).unwrap(), None);
+
+        let mut pipelined = encoded.to_vec();
+        pipelined.extend_from_slice(&encoded[..5]);
+        assert_eq!(bytes_needed(&pipelined).unwrap(), None);
+
+        for malformed in [&b"9=5\x01"[..], b"8=FIX.4.4\x0135=0\x01", b"8=FIX.4.4\x019=x", b"8=FIX.4.4\x019=\x01"] {
+            assert!(matches!(bytes_needed(malformed), Err(FixError::InvalidFormat)), "{:?}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_is_stable() {
+        // MsgType stored last is moved up on the first encode and stays there
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::CheckSum.value(), b"000".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        assert!(msg.is_stable().unwrap());
+
+        msg.set_encoded_text_utf8("line one\u{1}line two");
+        assert!(msg.is_stable().unwrap());
+
+        // An SOH in an ordinary field splits it in two on decode
+        msg.add_field(FixField::new(Tag::Text.value(), b"line one\x01line two".to_vec()));
+        assert!(!msg.is_stable().unwrap());
+
+        assert!(matches!(FixMessage::new().is_stable(), Err(FixError::MissingField(8))));
+    }
+
+    #[test]
+    fn test_owned_accessors() {
+        let (cl_ord_id, symbol, text) = {
+            let mut msg = FixMessage::new();
+            msg.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+            msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD".to_vec()));
+            msg.add_field(FixField::new(Tag::Text.value(), vec![0xFF, 0xFE]));
+            (
+                msg.get_owned(Tag::ClOrdID.value()),
+                msg.get_str_owned(Tag::Symbol.value()),
+                msg.get_str_owned(Tag::Text.value()),
+            )
+        };
+        assert_eq!(cl_ord_id.as_deref(), Some(&b"ORD1"[..]));
+        assert_eq!(symbol.as_deref(), Some("VOD"));
+        assert_eq!(text, None);
+        assert_eq!(FixMessage::new().get_owned(Tag::ClOrdID.value()), None);
+    }
+
+    #[test]
+    fn test_add_group_keeps_position() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::TRADE_CAPTURE_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::TradeReportID.value(), b"T1".to_vec()));
+        msg.add_group(
+            Tag::NoSides.value(),
+            &[
+                vec![FixField::new(Tag::Side.value(), b"1".to_vec()), FixField::new(Tag::OrderID.value(), b"A".to_vec())],
+                vec![FixField::new(Tag::Side.value(), b"2".to_vec())],
+            ],
+        )
+        .unwrap();
+        msg.add_field(FixField::new(Tag::TransactTime.value(), b"20240101-12:00:00".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        let body_start = encoded.windows(6).position(|w| w == b"35=AE\x01").unwrap();
+        assert!(encoded[body_start..]
+            .starts_with(b"35=AE\x01571=T1\x01552=2\x0154=1\x0137=A\x0154=2\x0160=20240101-12:00:00\x0110="));
+
+        let mismatched = [
+            vec![FixField::new(Tag::Side.value(), b"1".to_vec())],
+            vec![FixField::new(Tag::OrderID.value(), b"B".to_vec())],
+        ];
+        assert!(matches!(msg.add_group(Tag::NoSides.value(), &mismatched), Err(FixError::InvalidFormat)));
+        assert!(matches!(msg.add_group(Tag::NoSides.value(), &[Vec::new()]), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_normalize_drops_stale_framing() {
+        // BodyLength is stale: the message was edited after it was decoded
+        let raw = b"8=FIX.4.4\x019=5\x0135=0\x0134=7\x0149=S\x0152=20240101-12:00:00\x0156=T\x01112=X\x0110=000\x01";
+        let mut msg = FixMessage::new();
+        for field in raw.split(|&b| b == SOH).filter(|f| !f.is_empty()) {
+            let (tag, value) = field.split_at(field.iter().position(|&b| b == b'=').unwrap());
+            msg.add_field(FixField::new(std::str::from_utf8(tag).unwrap().parse().unwrap(), value[1..].to_vec()));
+        }
+
+        msg.normalize().unwrap();
+        assert!(msg.get_field(Tag::BodyLength.value()).is_none());
+        assert!(msg.get_field(Tag::CheckSum.value()).is_none());
+        assert_eq!(msg.field_tags().copied().collect::<Vec<_>>(), [8, 35, 34, 49, 52, 56, 112]);
+
+        msg.normalize_with(true).unwrap();
+        assert_eq!(msg.field_tags().copied().collect::<Vec<_>>(), [8, 35, 49, 56, 34, 52, 112]);
+        assert_eq!(msg.get_field(Tag::TestReqID.value()).unwrap().value(), b"X");
+
+        let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+        decoded.validate_header_order().unwrap();
+
+        let mut incomplete = FixMessage::new();
+        incomplete.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        incomplete.add_field(FixField::new(Tag::BodyLength.value(), b"5".to_vec()));
+        incomplete.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        assert!(matches!(incomplete.normalize(), Err(FixError::MissingField(49))));
+        assert_eq!(incomplete.len(), 3);
+    }
+
+    fn encoded_heartbeat(seq: u64, text: Option<&[u8]>) -> Vec<u8> {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        if let Some(text) = text {
+            msg.add_field(FixField::new(Tag::Text.value(), text.to_vec()));
+        }
+        msg.encode().unwrap().to_vec()
+    }
+
+    fn stream_seqs(data: &[u8]) -> Vec<(Result<u64, FixError>, usize)> {
+        crate::message::decode_stream(data)
+            .map(|(msg, offset)| (msg.map(|m| m.get_uint(Tag::MsgSeqNum.value()).unwrap().unwrap()), offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_stream_good_input() {
+        let first = encoded_heartbeat(1, Some(b"58=FIX.4.4 is not a message start"));
+        let second = encoded_heartbeat(2, None);
+
+        let mut data = b"capture header\n".to_vec();
+        data.extend_from_slice(&first);
+        data.push(b'\n');
+        data.extend_from_slice(&second);
+
+        let results = stream_seqs(&data);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.as_ref().ok(), Some(&1));
+        assert_eq!(results[0].1, 15);
+        assert_eq!(results[1].0.as_ref().ok(), Some(&2));
+        assert_eq!(results[1].1, 15 + first.len() + 1);
+
+        assert_eq!(crate::message::decode_stream(b"").count(), 0);
+        assert_eq!(crate::message::decode_stream(b"no messages here").count(), 0);
+    }
+
+    #[test]
+    fn test_decode_stream_recovers_from_corruption() {
+        let good = [encoded_heartbeat(1, None), encoded_heartbeat(2, None), encoded_heartbeat(3, None)];
+
+        // Bad checksum in the middle of the stream
+        let mut bad_checksum = good[1].clone();
+        let last = bad_checksum.len() - 2;
+        bad_checksum[last] = if bad_checksum[last] == b'9' { b'0' } else { bad_checksum[last] + 1 };
+
+        // BodyLength too large: the frame swallows the next message
+        let mut too_long = encoded_heartbeat(4, None);
+        let digit = too_long.windows(3).position(|w| w == b"\x019=").unwrap() + 3;
+        too_long.insert(digit, b'9');
+
+        // Malformed BodyLength
+        let bad_header = b"8=FIX.4.4\x019=abc\x0135=0\x0110=000\x01".to_vec();
+
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for part in [&good[0], &bad_checksum, &too_long, &good[1], &bad_header, &good[2]] {
+            offsets.push(data.len());
+            data.extend_from_slice(part);
+        }
+        // Truncated tail
+        offsets.push(data.len());
+        data.extend_from_slice(&good[0][..good[0].len() - 4]);
+
+        let results = stream_seqs(&data);
+        let offsets_seen: Vec<usize> = results.iter().map(|(_, offset)| *offset).collect();
+        assert_eq!(offsets_seen, offsets);
+        assert_eq!(results[0].0.as_ref().ok(), Some(&1));
+        assert!(matches!(results[1].0, Err(FixError::InvalidChecksum)));
+        assert!(results[2].0.is_err());
+        assert_eq!(results[3].0.as_ref().ok(), Some(&2));
+        assert!(matches!(results[4].0, Err(FixError::InvalidFormat)));
+        assert_eq!(results[5].0.as_ref().ok(), Some(&3));
+        assert!(matches!(results[6].0, Err(FixError::InvalidBodyLength { .. })));
+    }
+
+    #[test]
+    fn test_decode_header() {
+        use crate::message::decode_header;
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"VENUE".to_vec()));
+        // XmlData (213) may hold SOH
+        msg.add_field(FixField::new(212, b"5".to_vec()));
+        msg.add_field(FixField::new(213, b"<a\x01/>".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"4".to_vec()));
+        msg.add_field(FixField::new(Tag::OrderID.value(), b"X1".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"CLIENT".to_vec()));
+        let encoded = msg.encode().unwrap();
+
+        let header = decode_header(&encoded).unwrap();
+        assert_eq!(header.msg_type(), msg_type::EXECUTION_REPORT);
+        assert_eq!(header.get(213), Some(&b"<a\x01/>"[..]));
+        assert_eq!(header.get(Tag::MsgSeqNum.value()), Some(&b"4"[..]));
+        // Reading stops at the first body field
+        assert_eq!(header.get(Tag::TargetCompID.value()), None);
+        assert_eq!(header.iter().map(|(tag, _)| tag).collect::<Vec<_>>(), [8, 9, 35, 49, 212, 213, 34]);
+
+        // Only the header needs to be present and well formed
+        assert!(decode_header(&encoded[..30]).is_ok());
+        assert!(matches!(decode_header(b"8=FIX.4.4\x019=5\x01"), Err(FixError::IncompleteHeader)));
+        assert!(matches!(decode_header(b"8=FIX.4.4\x0135=0\x01"), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_change_tracking() {
+        let mut inbound = FixMessage::new();
+        for (tag, value) in [(8, &b"FIX.4.4"[..]), (35, b"D"), (49, b"CLIENT"), (56, b"GATEWAY"), (11, b"ORD1"), (55, b"VOD.L")] {
+            inbound.add_field(FixField::new(tag, value.to_vec()));
+        }
+        let mut msg = FixMessage::decode(&inbound.encode().unwrap()).unwrap();
+        assert!(!msg.is_tracking());
+        assert!(msg.changes().is_empty());
+
+        // A routing rewrite: new target, a venue account, no symbol suffix
+        msg.enable_tracking();
+        msg.set_field(FixField::new(Tag::TargetCompID.value(), b"VENUE".to_vec()));
+        msg.add_field(FixField::new(Tag::Account.value(), b"ACC1".to_vec()));
+        assert_eq!(msg.remove_field(Tag::Symbol.value()).unwrap().value(), b"VOD.L");
+        assert!(msg.remove_field(Tag::Symbol.value()).is_none());
+        let mut overlay = FixMessage::new();
+        overlay.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1-V".to_vec()));
+        msg.merge(&overlay, MergePolicy::Overwrite).unwrap();
+
+        let change = |tag: Tag, old: Option<&[u8]>, new: Option<&[u8]>, op| FieldChange {
+            tag: tag.value(),
+            old: old.map(<[u8]>::to_vec),
+            new: new.map(<[u8]>::to_vec),
+            op,
+        };
+        assert_eq!(
+            msg.changes(),
+            [
+                change(Tag::TargetCompID, Some(b"GATEWAY"), Some(b"VENUE"), ChangeOp::Set),
+                change(Tag::Account, None, Some(b"ACC1"), ChangeOp::Add),
+                change(Tag::Symbol, Some(b"VOD.L"), None, ChangeOp::Remove),
+                change(Tag::ClOrdID, Some(b"ORD1"), Some(b"ORD1-V"), ChangeOp::Set),
+            ]
+        );
+        assert_eq!(msg.get_field(Tag::Symbol.value()), None);
+        assert_eq!(msg.get_field(Tag::Account.value()).unwrap().value(), b"ACC1");
+
+        msg.clear_changes();
+        msg.normalize().unwrap_err();
+        msg.set_utc_timestamp(Tag::SendingTime.value(), UtcTimestamp::new(2024, 1, 1, 0, 0, 0).unwrap());
+        msg.set_value(Tag::MsgSeqNum.value(), &7u64);
+        msg.normalize().unwrap();
+        let ops: Vec<(u32, ChangeOp)> = msg.take_changes().iter().map(|c| (c.tag, c.op)).collect();
+        assert_eq!(ops, [(52, ChangeOp::Add), (34, ChangeOp::Add), (9, ChangeOp::Remove), (10, ChangeOp::Remove)]);
+        assert!(msg.is_tracking() && msg.changes().is_empty());
+
+        msg.disable_tracking();
+        msg.set_value(Tag::MsgSeqNum.value(), &8u64);
+        assert!(msg.changes().is_empty());
+    }
+
+    #[test]
+    fn test_decode_checks_body_length() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"0".to_vec()));
+        msg.add_field(FixField::new(112, b"PING".to_vec()));
+        let encoded = String::from_utf8(msg.encode().unwrap().to_vec()).unwrap();
+        assert!(encoded.starts_with("8=FIX.4.4\x019=14\x01"));
+
+        // Rewrites BodyLength and fixes up the CheckSum to match
+        let with_body_length = |value: &str| {
+            let mut wire = encoded[..encoded.len() - 7].replacen("9=14", value, 1).into_bytes();
+            checksum::append_checksum(&mut wire);
+            FixMessage::decode(&wire)
+        };
+        assert!(with_body_length("9=14").is_ok());
+        assert!(matches!(with_body_length("9=13"), Err(FixError::InvalidBodyLength { declared: 13, actual: 14 })));
+        assert!(matches!(with_body_length("9=15"), Err(FixError::InvalidBodyLength { declared: 15, actual: 14 })));
+        assert!(matches!(with_body_length("9=1x"), Err(FixError::InvalidFormat)));
+        assert!(matches!(with_body_length("58=14"), Err(FixError::MissingField(9))));
+    }
+
+    #[test]
+    fn test_repeated_tags_survive_round_trip() {
+        let party = |id: &[u8], role: &[u8]| {
+            [FixField::new(448, id.to_vec()), FixField::new(447, b"D".to_vec()), FixField::new(452, role.to_vec())]
+        };
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"8".to_vec()));
+        msg.add_field(FixField::new(17, b"EXEC-1".to_vec()));
+        msg.add_group(453, &[party(b"BROKER", b"1"), party(b"DESK", b"24"), party(b"CLEARER", b"4")])
+            .unwrap();
+        msg.add_field(FixField::new(55, b"VOD.L".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded.encode().unwrap(), encoded);
+        assert_eq!(decoded.len(), msg.len() + 2);
+
+        assert_eq!(decoded.get_field(448).unwrap().value(), b"BROKER");
+        let ids: Vec<&[u8]> = decoded.get_fields(448).map(FixField::value).collect();
+        assert_eq!(ids, [&b"BROKER"[..], b"DESK", b"CLEARER"]);
+        let roles: Vec<&[u8]> = decoded.get_fields(452).map(FixField::value).collect();
+        assert_eq!(roles, [&b"1"[..], b"24", b"4"]);
+        assert_eq!(decoded.get_fields(447).count(), 3);
+        assert!(decoded.get_all_fields(452).eq(decoded.get_fields(452)));
+        assert_eq!(decoded.get_fields(1).count(), 0);
+    }
+
+    #[test]
+    fn test_decode_truncated_input() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"B".to_vec()));
+        msg.add_field(FixField::new(95, b"4".to_vec()));
+        msg.add_field(FixField::new(96, b"a\x01=b".to_vec()));
+        msg.add_field(FixField::new(58, b"NEWS".to_vec()));
+        let encoded = msg.encode().unwrap();
+
+        // Every cut fails cleanly: one after a complete field lacks the
+        // CheckSum, any other is cut inside a field
+        for len in 0..encoded.len() {
+            match FixMessage::decode(&encoded[..len]) {
+                Err(FixError::MissingField(10)) => assert_eq!(encoded[len - 1], SOH, "cut at {len}"),
+                Err(FixError::InvalidFormat) => {}
+                other => panic!("cut at {len}: {other:?}"),
+            }
+        }
+
+        let invalid_format = |data: &[u8]| matches!(FixMessage::decode(data), Err(FixError::InvalidFormat));
+        assert!(invalid_format(b""));
+        assert!(invalid_format(b"8"));
+        assert!(invalid_format(&encoded[..encoded.len() - 1]));
+        assert!(invalid_format(b"8=FIX.4.4\x019=1"));
+    }
+
+    #[test]
+    fn test_decode_rejects_bytes_after_checksum() {
+        let config = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let heartbeat = config.heartbeat(None).encode().unwrap().to_vec();
+        let test_request = config.test_request(b"PING").encode().unwrap().to_vec();
+        assert!(FixMessage::decode(&heartbeat).is_ok());
+
+        let trailing_field = [&heartbeat[..], b"58=junk\x01"].concat();
+        let back_to_back = [&heartbeat[..], &test_request[..]].concat();
+        for data in [&trailing_field, &back_to_back] {
+            assert!(matches!(FixMessage::decode(data), Err(FixError::InvalidFormat)));
+            assert!(matches!(FixMessage::decode_borrowed(data), Err(FixError::InvalidFormat)));
+            assert!(matches!(FixMessage::decode_view(data), Err(FixError::InvalidFormat)));
+            let mut msg = FixMessage::new();
+            assert!(matches!(FixMessage::decode_into(data, &mut msg), Err(FixError::InvalidFormat)));
+            // The scanner itself stops at the first CheckSum
+            assert_eq!(FieldScanner::new(data).last().map(|(tag, _)| tag), Some(10));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_utf8_tags() {
+        let cases: [&[u8]; 5] = [
+            b"\xff=FIX.4.4\x019=5\x0135=0\x0110=000\x01",
+            b"8=FIX.4.4\x01\xc3\x28=5\x0135=0\x0110=000\x01",
+            b"8=FIX.4.4\x019=5\x013\xe2\x82=0\x0110=000\x01",
+            b"8=FIX.4.4\x019=10\x0135=0\x01\xf0\x9f\x92=x\x0110=000\x01",
+            b"8=FIX.4.4\x019=5\x0135=0\x011\x80=000\x01",
+        ];
+        for data in cases {
+            assert!(matches!(FixMessage::decode(data), Err(FixError::InvalidFormat)), "{data:?}");
+        }
+
+        // Nor may the CheckSum value be anything but ASCII digits
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"0".to_vec()));
+        let mut encoded = msg.encode().unwrap().to_vec();
+        let len = encoded.len();
+        encoded[len - 3] = 0xff;
+        assert!(matches!(FixMessage::decode(&encoded), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_integer_accessors() {
+        let field = |value: &[u8]| FixField::new(34, value.to_vec());
+        assert_eq!(field(b"42").as_u64().unwrap(), 42);
+        assert_eq!(field(b"18446744073709551615").as_u64().unwrap(), u64::MAX);
+        assert_eq!(field(b"-42").as_i64().unwrap(), -42);
+        assert_eq!(field(b"+42").as_i64().unwrap(), 42);
+        assert_eq!(field(b"-9223372036854775808").as_i64().unwrap(), i64::MIN);
+        for invalid in [&b""[..], b"+", b"-", b"4 2", b" 42", b"42 ", b"+-4", b"4.2", b"9223372036854775808"] {
+            assert!(matches!(field(invalid).as_i64(), Err(FixError::InvalidFieldValue)), "{invalid:?}");
+        }
+        assert!(matches!(field(b"-1").as_u64(), Err(FixError::InvalidFieldValue)));
+        assert!(matches!(field(b"18446744073709551616").as_u64(), Err(FixError::InvalidFieldValue)));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(field(b"+7"));
+        assert_eq!(msg.get_i64(34).unwrap().unwrap(), 7);
+        assert!(msg.get_i64(35).is_none());
+    }
+
+    #[test]
+    fn test_decimal_accessors() {
+        let px = |value: &[u8]| FixField::new(Tag::Price.value(), value.to_vec()).as_decimal();
+        assert_eq!(px(b"101.25").unwrap(), FixDecimal::new(10125, 2).unwrap());
+        assert_eq!(px(b"-0.5").unwrap(), FixDecimal::new(-5, 1).unwrap());
+        assert_eq!(px(b".5").unwrap().to_string(), "0.5");
+        assert_eq!(px(b"100.500").unwrap().scale(), 3);
+        for garbage in [&b""[..], b"1e3", b"+1", b"1.2.3", b"abc", b"1 "] {
+            assert!(matches!(px(garbage), Err(FixError::InvalidFieldValue)), "{:?}", garbage);
+        }
+
+        let field = FixField::from_decimal(Tag::Price.value(), FixDecimal::new(1, 18).unwrap());
+        assert_eq!(field.value(), b"0.000000000000000001");
+        let field = FixField::from_decimal(Tag::Price.value(), FixDecimal::new(-100500, 3).unwrap());
+        assert_eq!(field.value(), b"-100.500");
+        assert_eq!(field.as_decimal().unwrap(), px(b"-100.5").unwrap());
+    }
+
+    #[test]
+    fn test_timestamp_accessors() {
+        let sending_time = |value: &[u8]| FixField::new(Tag::SendingTime.value(), value.to_vec()).as_timestamp();
+        let seconds = sending_time(b"20240101-12:00:05").unwrap();
+        assert_eq!((seconds.time().second(), seconds.time().nanos()), (5, 0));
+        assert_eq!(sending_time(b"20240101-12:00:05.250").unwrap().time().nanos(), 250_000_000);
+        assert_eq!(sending_time(b"20240101-12:00:05.250125").unwrap().time().nanos(), 250_125_000);
+
+        // A leap second is valid FIX but has no place in most calendar types
+        assert!(FixField::new(52, b"20161231-23:59:60".to_vec()).parse_value::<UtcTimestamp>().is_ok());
+        for garbage in [&b"20161231-23:59:60"[..], b"20161231-23:59:60.500", b"20240230-12:00:00", b"20240101-12:00:05.25", b""] {
+            assert!(matches!(sending_time(garbage), Err(FixError::InvalidFieldValue)), "{:?}", garbage);
+        }
+
+        let field = FixField::from_timestamp(Tag::TransactTime.value(), seconds);
+        assert_eq!(field.value(), b"20240101-12:00:05.000");
+        let micros = sending_time(b"20240101-12:00:05.250999").unwrap();
+        assert_eq!(FixField::from_timestamp(60, micros).value(), b"20240101-12:00:05.250");
+        assert_eq!(FixField::from_timestamp(60, micros).as_timestamp().unwrap().precision(), 3);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_naive_datetime_accessors() {
+        let naive = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_micro_opt(12, 0, 5, 250_999).unwrap();
+        let field = FixField::from_naive_datetime(Tag::TransactTime.value(), naive).unwrap();
+        assert_eq!(field.value(), b"20240101-12:00:05.250");
+        assert_eq!(field.as_naive_datetime().unwrap(), chrono::Timelike::with_nanosecond(&naive, 250_000_000).unwrap());
+        assert!(FixField::new(52, b"20161231-23:59:60".to_vec()).as_naive_datetime().is_err());
+    }
+
+    #[test]
+    fn test_encode_without_body_length_or_checksum() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"TARGET".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019=25\x0135=0\x01"));
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(decoded.get_field(Tag::TargetCompID.value()).unwrap().value(), b"TARGET");
+        assert!(decoded.get_field(Tag::CheckSum.value()).is_some());
+
+        // Stale values stored for them change nothing
+        msg.add_field(FixField::new(Tag::BodyLength.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::CheckSum.value(), b"999".to_vec()));
+        assert_eq!(msg.encode().unwrap(), encoded);
+
+        let mut no_msg_type = FixMessage::new();
+        no_msg_type.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        assert!(matches!(no_msg_type.encode(), Err(FixError::MissingField(35))));
+        assert!(matches!(FixMessage::new().encode(), Err(FixError::MissingField(8))));
+    }
+
+    #[test]
+    fn test_encode_header_in_canonical_order() {
+        let mut in_order = FixMessage::new();
+        in_order.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        in_order.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        in_order.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
+
+        // MsgType first and BeginString last, with a stale BodyLength between
+        let mut reversed = FixMessage::new();
+        reversed.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        reversed.add_field(FixField::new(Tag::SenderCompID.value(), b"SENDER".to_vec()));
+        reversed.add_field(FixField::new(Tag::BodyLength.value(), b"99".to_vec()));
+        reversed.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+
+        let encoded = reversed.encode().unwrap();
+        assert_eq!(encoded, in_order.encode().unwrap());
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019=15\x0135=0\x0149=SENDER\x01"));
+        FixMessage::decode(&encoded).unwrap().validate_header_order().unwrap();
+    }
+
+    #[test]
+    fn test_decode_borrowed() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), b"W".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"EUR/USD".to_vec()));
+        msg.add_field(FixField::new(268, b"2".to_vec()));
+        msg.add_field(FixField::new(270, b"1.1000".to_vec()));
+        msg.add_field(FixField::new(270, b"1.1002".to_vec()));
+        msg.add_field(FixField::new(95, b"3".to_vec()));
+        msg.add_field(FixField::new(96, b"a\x01b".to_vec()));
+        let encoded = msg.encode().unwrap();
+
+        let borrowed = FixMessage::decode_borrowed(&encoded).unwrap();
+        assert_eq!(borrowed.msg_type(), b"W");
+        assert_eq!(borrowed.get_field(Tag::Symbol.value()), Some(&b"EUR/USD"[..]));
+        assert_eq!(borrowed.get_fields(270).collect::<Vec<_>>(), [&b"1.1000"[..], b"1.1002"]);
+        assert_eq!(borrowed.get_field(96), Some(&b"a\x01b"[..]));
+        assert!(borrowed.get_field(Tag::ClOrdID.value()).is_none());
+        let value = borrowed.get_field(Tag::Symbol.value()).unwrap();
+        assert!(encoded.as_ptr_range().contains(&value.as_ptr()));
+
+        let owned = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(borrowed.field_tags().collect::<Vec<_>>(), owned.field_tags().copied().collect::<Vec<_>>());
+        assert_eq!(borrowed.to_owned().encode().unwrap(), owned.encode().unwrap());
+        assert_eq!(borrowed.len(), owned.len());
+
+        // Rejected exactly as the owning decode rejects
+        let mut bad_checksum = encoded.to_vec();
+        let n = bad_checksum.len();
+        bad_checksum[n - 2] = if bad_checksum[n - 2] == b'0' { b'1' } else { b'0' };
+        let mut bad_length = encoded.to_vec();
+        let body_length = encoded.iter().position(|&b| b == SOH).unwrap() + 3;
+        bad_length[body_length] = b'9';
+        for bad in [&bad_checksum[..], &bad_length[..], &encoded[..n - 7]] {
+            let borrowed = FixMessage::decode_borrowed(bad).unwrap_err();
+            assert_eq!(format!("{:?}", borrowed), format!("{:?}", FixMessage::decode(bad).unwrap_err()));
+        }
+        let lenient = DecodeOptions::default().with_verify_checksum(false);
+        assert!(FixMessageRef::decode_with_options(&bad_checksum, &lenient).is_ok());
+    }
+
+    #[test]
+    fn test_msg_type_enum() {
+        for bytes in [msg_type::HEARTBEAT, msg_type::LOGON, msg_type::NEW_ORDER_SINGLE, b"F", b"W", b"AE", b"BZ"] {
+            assert_eq!(MsgType::from_bytes(bytes).unwrap().as_bytes(), bytes);
+        }
+        assert_eq!(MsgType::from_bytes(b"F"), Some(MsgType::OrderCancelRequest));
+        assert_eq!(MsgType::from_bytes(b"W"), Some(MsgType::Other(b"W"[..].into())));
+        assert!(MsgType::from_bytes(b"").is_none());
+        assert!(MsgType::SequenceReset.is_admin());
+        assert!(!MsgType::ExecutionReport.is_admin());
+
+        let config = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        assert_eq!(config.logon().msg_type(), Some(MsgType::Logon));
+        let msg = config.new_message(b"AB");
+        match msg.msg_type() {
+            Some(MsgType::Other(bytes)) => assert_eq!(&bytes[..], b"AB"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(FixMessage::new().msg_type().is_none());
+    }
+
+    #[test]
+    fn test_decode_view() {
+        // More fields than are held inline
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), b"W".to_vec()));
+        for i in 0..100u32 {
+            msg.add_field(FixField::from_value(5000 + i, &(i as u64)));
+        }
+        let encoded = msg.encode().unwrap();
+
+        let view: FixMessageView = FixMessage::decode_view(&encoded).unwrap();
+        assert_eq!(view.len(), 104);
+        assert_eq!(view.get_field(5099), Some(&b"99"[..]));
+        assert_eq!(view.iter().nth(1), Some((9, &encoded[12..15])));
+        assert_eq!(view.to_owned().encode().unwrap(), encoded);
+        assert!(matches!(FixMessage::decode_view(&encoded[1..]), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_custom_delimiter() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD.L".to_vec()));
+        msg.add_field(FixField::new(95, b"5".to_vec()));
+        msg.add_field(FixField::new(96, b"a|b\x01c".to_vec()));
+        let wire = msg.encode().unwrap();
+
+        let piped = msg.encode_with_delimiter(b'|').unwrap();
+        assert!(piped.starts_with(b"8=FIX.4.4|9="));
+        assert!(piped.ends_with(b"|"));
+        assert!(piped.windows(9).any(|w| w == b"96=a|b\x01c|"));
+        // The CheckSum is that of the SOH form
+        assert_eq!(piped[piped.len() - 7..piped.len() - 1], wire[wire.len() - 7..wire.len() - 1]);
+        assert_eq!(msg.encode_with_delimiter(SOH).unwrap(), wire);
+
+        let decoded = FixMessage::decode_with_delimiter(&piped, b'|').unwrap();
+        assert_eq!(decoded.get_field(96).unwrap().value(), b"a|b\x01c");
+        assert_eq!(decoded.encode().unwrap(), wire);
+
+        let mut tampered = piped.to_vec();
+        let symbol = tampered.windows(5).position(|w| w == b"VOD.L").unwrap();
+        tampered[symbol] = b'W';
+        assert!(matches!(FixMessage::decode_with_delimiter(&tampered, b'|'), Err(FixError::InvalidChecksum)));
+        assert!(FixMessage::decode(&piped).is_err());
+    }
+
+    #[test]
+    fn test_set_and_remove_field() {
+        let mut msg = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").new_message(msg_type::NEW_ORDER_SINGLE);
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"7".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00:00.000".to_vec()));
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD.L".to_vec()));
+        let tags: Vec<u32> = msg.field_tags().copied().collect();
+
+        // Resending: refresh SendingTime where it stands, flag the duplicate
+        msg.set_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00:05.000".to_vec()));
+        msg.set_field(FixField::new(Tag::PossDupFlag.value(), b"Y".to_vec()));
+        assert_eq!(msg.len(), tags.len() + 1);
+        assert_eq!(msg.field_tags().copied().collect::<Vec<_>>(), [&tags[..], &[43]].concat());
+        assert_eq!(msg.get_field(Tag::SendingTime.value()).unwrap().value(), b"20240101-12:00:05.000");
+
+        assert_eq!(msg.remove_field(Tag::Symbol.value()).unwrap().value(), b"VOD.L");
+        assert_eq!(msg.len(), tags.len());
+        assert!(!msg.field_tags().any(|&tag| tag == Tag::Symbol.value()));
+        assert!(msg.remove_field(Tag::Symbol.value()).is_none());
+        assert_eq!(msg.len(), tags.len());
+
+        let encoded = msg.encode().unwrap();
+        assert!(!encoded.windows(4).any(|w| w == b"\x0155="));
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert!(decoded.get_field(Tag::Symbol.value()).is_none());
+        assert_eq!(decoded.get_field(Tag::PossDupFlag.value()).unwrap().value(), b"Y");
+    }
+
+    #[test]
+    fn test_jumbo_message() {
+        // 1,000 entries of five 14-byte fields: over 5,000 fields and 100 KB
+        let shape = bench::MessageShape { fields: 0, value_len: 14, group_entries: 1000, group_fields: 5 };
+        let msg = shape.message(&session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"));
+        let encoded = msg.encode().unwrap();
+        assert!(encoded.len() > 100_000);
+
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert!(decoded.len() > 5000);
+        assert_eq!(decoded.encode().unwrap(), encoded);
+
+        let view = FixMessage::decode_view(&encoded).unwrap();
+        assert_eq!(view.len(), decoded.len());
+        let members = [6001, 6002, 6003, 6004, 6005];
+        let mut entries = 0;
+        for entry in view.group(6000, &members) {
+            let entry = entry.unwrap();
+            assert_eq!(entry.len(), 5);
+            assert_eq!(entry.get_field(6003).unwrap().len(), 14);
+            entries += 1;
+        }
+        assert_eq!(entries, 1000);
+        assert_eq!(view.group(6000, &members).last().unwrap().unwrap().iter().count(), 5);
+        assert_eq!(view.group(6999, &members).count(), 0);
+
+        // An entry must open with the delimiter, and NoXxx must add up
+        let results: Vec<_> = view.group(6000, &[6002, 6001, 6003, 6004, 6005]).collect();
+        assert!(matches!(results[0], Err(FixError::InvalidFormat)));
+        assert_eq!(results.len(), 1);
+        let results: Vec<_> = view.group(6000, &[]).collect();
+        assert!(matches!(results[..], [Err(FixError::InvalidFormat)]));
+        assert_eq!(view.group(6999, &[]).count(), 0);
+        let mut short = shape.message(&session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"));
+        short.set_field(FixField::new(6000, b"1001".to_vec()));
+        let encoded = short.encode().unwrap();
+        let view = FixMessage::decode_view(&encoded).unwrap();
+        let last = view.group(6000, &members).last().unwrap();
+        assert!(matches!(last, Err(FixError::GroupCountMismatch { count_tag: 6000, declared: 1001, actual: 1000 })));
+    }
+
+    #[test]
+    fn test_text_float_and_flag_accessors() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD.L".to_vec()));
+        msg.add_field(FixField::new(Tag::Side.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::Price.value(), b"101.25".to_vec()));
+        msg.add_field(FixField::new(Tag::PossDupFlag.value(), b"Y".to_vec()));
+        msg.add_field(FixField::new(Tag::OrderQty.value(), b"-100".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"12a".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00".to_vec()));
+        msg.add_field(FixField::new(Tag::Text.value(), vec![0xff, 0xfe]));
+
+        assert_eq!(msg.get_str(Tag::Symbol.value()).unwrap(), Some("VOD.L"));
+        assert_eq!(msg.get_char(Tag::Side.value()).unwrap(), Some('1'));
+        assert_eq!(msg.get_f64(Tag::Price.value()).unwrap(), Some(101.25));
+        assert!(msg.get_field(Tag::PossDupFlag.value()).unwrap().as_bool().unwrap());
+        assert_eq!(msg.get_str(Tag::Account.value()).unwrap(), None);
+
+        // A negative quantity is a valid integer but not a valid Qty
+        assert_eq!(msg.get_int(Tag::OrderQty.value()).unwrap(), Some(-100));
+        assert!(matches!(msg.get_qty(Tag::OrderQty.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::OrderQty.value()));
+        assert!(matches!(msg.msg_seq_num(), Err(FixError::InvalidFieldValueFor { tag: 34 })));
+        assert!(matches!(msg.get_utc_timestamp(Tag::SendingTime.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::SendingTime.value()));
+        assert!(matches!(msg.get_str(Tag::Text.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::Text.value()));
+        assert!(matches!(msg.get_char(Tag::Symbol.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::Symbol.value()));
+        assert!(matches!(msg.get_f64(Tag::Symbol.value()), Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::Symbol.value()));
+        let field = |value: &[u8]| FixField::new(Tag::Price.value(), value.to_vec());
+        for garbage in [&b"1e3"[..], b"inf", b"NaN", b""] {
+            assert!(matches!(field(garbage).as_f64(), Err(FixError::InvalidFieldValueFor { tag: 44 })), "{:?}", garbage);
+        }
+        assert!(matches!(field(b"y").as_bool(), Err(FixError::InvalidFieldValueFor { tag: 44 })));
+    }
+
+    #[test]
+    fn test_typed_constructors() {
+        assert_eq!(FixField::from_u64(34, 12345).value(), b"12345");
+        assert_eq!(FixField::from_u64(34, u64::MAX).value(), b"18446744073709551615");
+        assert_eq!(FixField::from_i64(38, -100).value(), b"-100");
+        assert_eq!(FixField::from_bool(43, true).value(), b"Y");
+        assert_eq!(FixField::from_bool(43, false).value(), b"N");
+        assert_eq!(FixField::from_char(54, '1').value(), b"1");
+
+        let px = |value: f64, places: u8| FixField::from_f64_with_precision(44, value, places);
+        assert_eq!(px(101.5, 2).unwrap().value(), b"101.50");
+        assert_eq!(px(-0.125, 2).unwrap().value(), b"-0.13");
+        assert_eq!(px(0.0001, 4).unwrap().value(), b"0.0001");
+        assert_eq!(px(99.6, 0).unwrap().value(), b"100");
+        assert_eq!(px(1e-7, 3).unwrap().value(), b"0.000");
+        assert!(matches!(px(f64::NAN, 2), Err(FixError::InvalidFieldValue)));
+        assert!(matches!(px(f64::INFINITY, 2), Err(FixError::DecimalOverflow)));
+        assert!(matches!(px(1e18, 2), Err(FixError::DecimalOverflow)));
+        assert!(matches!(px(1.0, 19), Err(FixError::DecimalOverflow)));
+
+        let time = UtcTimestamp::new(2024, 1, 1, 12, 0, 5).unwrap().with_nanos(250_125_999, 9).unwrap();
+        let stamp = |precision: u8| FixField::from_timestamp_with_precision(52, time, precision);
+        assert_eq!(stamp(0).unwrap().value(), b"20240101-12:00:05");
+        assert_eq!(stamp(3).unwrap().value(), b"20240101-12:00:05.250");
+        assert_eq!(stamp(6).unwrap().value(), b"20240101-12:00:05.250125");
+        assert!(matches!(stamp(4), Err(FixError::InvalidFieldValue)));
+
+        let system = std::time::UNIX_EPOCH + std::time::Duration::from_nanos(time.unix_nanos() as u64);
+        assert_eq!(FixField::from_system_time(52, system, 6).unwrap().value(), b"20240101-12:00:05.250125");
+        let before = std::time::UNIX_EPOCH - std::time::Duration::from_millis(1500);
+        assert_eq!(FixField::from_system_time(52, before, 3).unwrap().value(), b"19691231-23:59:58.500");
+        assert_eq!(FixField::from_system_time(52, system, 3).unwrap().as_timestamp().unwrap().precision(), 3);
+    }
+
+    #[test]
+    fn test_encode_when_rules_change_body_length_digits() {
+        let message = |account: &[u8], price: &[u8]| {
+            let mut msg = FixMessage::new();
+            msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+            msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+            msg.add_field(FixField::new(Tag::Account.value(), account.to_vec()));
+            msg.add_field(FixField::new(Tag::Price.value(), price.to_vec()));
+            msg.add_field(FixField::new(Tag::Text.value(), vec![b'X'; 73]));
+            msg
+        };
+        // A body of 99 bytes as stored
+        let stored = message(b"ACC", b"1.50000");
+        assert!(stored.encode().unwrap().starts_with(b"8=FIX.4.4\x019=99\x01"));
+
+        // Padding takes it from two digits to three, and trimming a price
+        // takes another from three to two
+        let padded = EncodeOptions::default().with_padding_rule(1, PaddingRule::new(12, b' ', Alignment::Left));
+        let encoded = stored.encode_with_options(&padded).unwrap();
+        assert_eq!(encoded, message(b"ACC         ", b"1.50000").encode().unwrap());
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019=108\x0135=D\x01"));
+
+        let trimmed = EncodeOptions::default().with_decimal_format(
+            Tag::Price.value(),
+            DecimalFormat::default().with_padding(DecimalPadding::TrimTrailingZeros),
+        );
+        let encoded = message(b"ACC", b"1.500000000000").encode_with_options(&trimmed).unwrap();
+        assert_eq!(encoded, message(b"ACC", b"1.5").encode().unwrap());
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019=95\x0135=D\x01"));
+        FixMessage::decode(&encoded).unwrap();
+
+        let limited = EncodeOptions { max_encoded_len: Some(encoded.len() - 1), ..trimmed };
+        assert!(matches!(
+            message(b"ACC", b"1.500000000000").encode_with_options(&limited),
+            Err(FixError::MessageTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_checksum_must_be_three_digits() {
+        let heartbeat = |test_req_id: String| {
+            let mut msg = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").heartbeat(None);
+            msg.add_field(FixField::new(Tag::TestReqID.value(), test_req_id.into_bytes()));
+            msg.encode().unwrap().to_vec()
+        };
+        // Each 'A' adds 65, so some length gives a CheckSum of 100
+        let wire = (1..1000).map(|n| heartbeat("A".repeat(n))).find(|wire| wire.ends_with(b"10=100\x01")).unwrap();
+        assert!(FixMessage::decode(&wire).is_ok());
+
+        let body = &wire[..wire.len() - 7];
+        for checksum in [&b"5"[..], b"0256", b"0100", b"1e2"] {
+            let wire = [body, b"10=", checksum, b"\x01"].concat();
+            assert!(matches!(FixMessage::decode(&wire), Err(FixError::InvalidFormat)), "{:?}", checksum);
+        }
+        let wire = [body, b"10=256\x01"].concat();
+        assert!(matches!(FixMessage::decode(&wire), Err(FixError::InvalidChecksum)));
+    }
+
+    #[test]
+    fn test_encode_into_existing_buffer() {
+        let config = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let first = config.heartbeat(None);
+        let second = config.heartbeat(Some(b"PING"));
+
+        let mut buf = bytes::BytesMut::from(&b"leftover"[..]);
+        let n = first.encode_into(&mut buf).unwrap();
+        assert_eq!(n, first.encode().unwrap().len());
+        let m = second.encode_into(&mut buf).unwrap();
+        assert_eq!(&buf[..8], b"leftover");
+        // Each message is framed on its own bytes alone
+        assert_eq!(&buf[8..8 + n], &first.encode().unwrap()[..]);
+        assert_eq!(&buf[8 + n..], &second.encode().unwrap()[..]);
+        assert_eq!(buf.len(), 8 + n + m);
+        let mut framer = framing::Framer::new();
+        framer.extend_from_slice(&buf[8..]);
+        assert!(framer.next_message().unwrap().is_ok());
+        assert_eq!(framer.next_message().unwrap().unwrap().get_field(Tag::TestReqID.value()).unwrap().value(), b"PING");
+
+        // A failed encode leaves the buffer as it was
+        let limited = EncodeOptions { max_encoded_len: Some(m - 1), ..EncodeOptions::default() };
+        assert!(second.encode_into_with_options(&mut buf, &limited).is_err());
+        assert!(FixMessage::new().encode_into(&mut buf).is_err());
+        assert_eq!(buf.len(), 8 + n + m);
+
+        let mut journal = Vec::new();
+        assert_eq!(first.encode_to_writer(&mut journal).unwrap(), n);
+        assert_eq!(second.encode_to_writer(&mut journal).unwrap(), m);
+        assert_eq!(journal, &buf[8..]);
+        let err = FixMessage::new().encode_to_writer(&mut journal).unwrap_err();
+        assert!(matches!(err.get_ref().and_then(|e| e.downcast_ref()), Some(FixError::MissingField(8))));
+    }
+
+    #[test]
+    fn test_fields_in_wire_order() {
+        let mut msg = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").new_message(b"W");
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"EUR/USD".to_vec()));
+        let entries = [
+            [FixField::new(269, b"0".to_vec()), FixField::new(270, b"1.1000".to_vec())],
+            [FixField::new(269, b"1".to_vec()), FixField::new(270, b"1.1002".to_vec())],
+        ];
+        msg.add_group(268, &entries).unwrap();
+
+        let fields: Vec<&FixField> = msg.fields().collect();
+        assert_eq!(fields.len(), msg.len());
+        assert!(fields.iter().map(|field| field.tag()).eq(msg.field_tags().copied()));
+        let prices: Vec<&[u8]> = msg.fields().filter(|field| field.tag() == 270).map(FixField::value).collect();
+        assert_eq!(prices, [&b"1.1000"[..], b"1.1002"]);
+
+        // Field by field against the decoded copy
+        let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+        let framing = [Tag::BodyLength.value(), Tag::CheckSum.value()];
+        assert!(decoded.fields().filter(|field| !framing.contains(&field.tag())).eq(msg.fields()));
+    }
+
+    #[test]
+    fn test_clear_and_decode_into() {
+        let config = session::SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let mut order = config.new_message(msg_type::NEW_ORDER_SINGLE);
+        order.add_field(FixField::new(Tag::Text.value(), vec![b'x'; 100]));
+        for i in 0..40u64 {
+            order.add_field(FixField::from_u64(5000 + i as u32, i));
+        }
+        let order = order.encode().unwrap();
+        let heartbeat = config.heartbeat(Some(b"PING")).encode().unwrap();
+
+        let decoded = |data: &[u8]| FixMessage::decode(data).unwrap();
+        let mut msg = FixMessage::new();
+        FixMessage::decode_into(&order, &mut msg).unwrap();
+        let capacity = msg.capacity();
+        assert!(msg.fields().eq(decoded(&order).fields()));
+
+        FixMessage::decode_into(&heartbeat, &mut msg).unwrap();
+        assert!(msg.fields().eq(decoded(&heartbeat).fields()));
+        assert!(msg.get_field(Tag::Text.value()).is_none());
+        assert_eq!(msg.get_field(Tag::TestReqID.value()).unwrap().value(), b"PING");
+        assert_eq!(msg.capacity(), capacity);
+
+        FixMessage::decode_into(&order, &mut msg).unwrap();
+        assert!(msg.fields().eq(decoded(&order).fields()));
+        assert_eq!(msg.capacity(), capacity);
+
+        // A failed decode leaves it empty, not half overwritten
+        let mut corrupt = heartbeat.to_vec();
+        let n = corrupt.len();
+        corrupt[n - 2] = if corrupt[n - 2] == b'0' { b'1' } else { b'0' };
+        assert!(matches!(FixMessage::decode_into(&corrupt, &mut msg), Err(FixError::InvalidChecksum)));
+        assert!(msg.is_empty());
+
+        FixMessage::decode_into(&order, &mut msg).unwrap();
+        msg.enable_tracking();
+        msg.set_field(FixField::new(Tag::Symbol.value(), b"VOD.L".to_vec()));
+        msg.clear();
+        assert_eq!((msg.len(), msg.is_empty()), (0, true));
+        assert!(msg.get_field(Tag::BeginString.value()).is_none());
+        assert_eq!(msg.field_tags().count(), 0);
+        assert!(msg.take_changes().is_empty());
+        assert_eq!(msg.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_group_entries() {
+        let parties = "453=3|448=BROKER|447=D|452=1|448=DESK|452=24|448=CLEARER|447=D|452=4|";
+        let order = |group: &str| {
+            let mut msg = FixMessage::new();
+            msg.add_field(FixField::new(35, b"8".to_vec()));
+            msg.add_field(FixField::new(17, b"EXEC-1".to_vec()));
+            for (tag, value) in group.split_terminator('|').filter_map(|pair| pair.split_once('=')) {
+                msg.add_field(FixField::new(tag.parse().unwrap(), value.as_bytes().to_vec()));
+            }
+            msg.add_field(FixField::new(55, b"VOD.L".to_vec()));
+            msg
+        };
+        let members = [448, 447, 452];
+
+        let msg = order(parties);
+        let entries = msg.group(453, &members).unwrap();
+        let ids: Vec<&[u8]> = entries.iter().map(|entry| entry.get_field(448).unwrap().value()).collect();
+        assert_eq!(ids, [&b"BROKER"[..], b"DESK", b"CLEARER"]);
+        // An optional member missing from one entry
+        assert!(entries[1].get_field(447).is_none());
+        assert_eq!(entries[1].len(), 2);
+        assert!(entries[2].fields().map(FixField::tag).eq([448, 447, 452]));
+        // The field after the group belongs to no entry
+        assert!(entries[2].get_field(55).is_none());
+        assert!(msg.group(268, &[269]).unwrap().is_empty());
+
+        let short = order(&parties.replace("453=3", "453=4"));
+        assert!(matches!(
+            short.group(453, &members),
+            Err(FixError::GroupCountMismatch { count_tag: 453, declared: 4, actual: 3 })
+        ));
+        let no_delimiter = order(&parties.replace("448=BROKER|", ""));
+        assert!(matches!(no_delimiter.group(453, &members), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_data_fields_round_trip() {
+        let raw = b"a\x01b=c\x01\x01=".to_vec();
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"B".to_vec()));
+        msg.add_data_field(95, 96, raw.clone());
+        msg.add_data_field(5001, 5002, raw.clone());
+        msg.add_field(FixField::new(58, b"NEWS".to_vec()));
+        assert_eq!(msg.get_field(95).unwrap().value(), b"8");
+        let encoded = msg.encode().unwrap();
+
+        // Without the custom pair its value ends at the first SOH and "b=c"
+        // is read as a field
+        assert!(matches!(FixMessage::decode(&encoded), Err(FixError::InvalidFormat)));
+
+        // Custom pairs add to the standard ones, given as a slice, a Vec
+        // or a shared list
+        const CUSTOM: &[(u32, u32)] = &[(5001, 5002)];
+        let options = DecodeOptions::default().with_data_fields(CUSTOM);
+        assert_eq!(options.data_field_for(95), Some(96));
+        let shared: std::sync::Arc<[(u32, u32)]> = vec![(5001, 5002)].into();
+        let owned = DecodeOptions::default().with_data_fields(vec![(6001, 6002)]).with_data_fields(shared.clone());
+        assert_eq!((owned.data_field_for(6001), owned.data_field_for(5001)), (Some(6002), Some(5002)));
+        assert_eq!(FixMessage::decode_with_options(&encoded, &owned).unwrap().get_field(5002).unwrap().value(), &raw[..]);
+        assert_eq!(DecodeOptions::default().with_data_fields(shared).data_field_for(5001), Some(5002));
+        let decoded = FixMessage::decode_with_options(&encoded, &options).unwrap();
+        assert_eq!(decoded.get_field(96).unwrap().value(), &raw[..]);
+        assert_eq!(decoded.get_field(5002).unwrap().value(), &raw[..]);
+        assert_eq!(decoded.get_field(58).unwrap().value(), b"NEWS");
+        assert_eq!(decoded.encode().unwrap(), encoded);
+        let view = FixMessageRef::decode_with_options(&encoded, &options).unwrap();
+        assert_eq!(view.get_field(5002), Some(&raw[..]));
+    }
+
+    #[test]
+    fn test_fixt_application_version() {
+        let mut msg = SessionConfig::new(fix_version::FIXT_1_1, "CLIENT", "VENUE").new_message(msg_type::NEW_ORDER_SINGLE);
+        assert_eq!(msg.application_version(), None);
+        msg.add_field(FixField::new(Tag::ApplVerID.value(), appl_ver_id::FIX_5_0_SP2.to_vec()));
+        let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+        assert_eq!(decoded.get_field(8).unwrap().value(), b"FIXT.1.1");
+        assert_eq!(decoded.application_version(), Some(appl_ver_id::FIX_5_0_SP2));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::FixError;
+use crate::message::{FieldChange, FixMessage};
+use std::io;
+
+/// Receives what a rewrite step changed in a message, e.g. for compliance
+/// to see how a gateway transformed an order. `LogWriter` writes the
+/// changes into its log.
+pub trait AuditSink {
+    fn record(&mut self, msg: &FixMessage, changes: &[FieldChange]) -> io::Result<()>;
+}
+
+/// Collects the changes in memory
+impl AuditSink for Vec<FieldChange> {
+    fn record(&mut self, _msg: &FixMessage, changes: &[FieldChange]) -> io::Result<()> {
+        self.extend_from_slice(changes);
+        Ok(())
+    }
+}
+
+/// Runs `rewrite` on `msg` with change tracking on and hands `sink` the
+/// fields it added, replaced or removed, if any, even when it fails.
+/// Tracking is left as it was, along with anything recorded before. A sink
+/// that fails to record is reported as `FixError::Store`.
+pub fn audited_rewrite<T>(
+    msg: &mut FixMessage,
+    sink: &mut impl AuditSink,
+    rewrite: impl FnOnce(&mut FixMessage) -> Result<T, FixError>,
+) -> Result<T, FixError> {
+    let was_tracking = msg.is_tracking();
+    let before = msg.changes().len();
+    msg.enable_tracking();
+    let result = rewrite(msg);
+
+    let changes = &msg.changes()[before..];
+    let recorded = if changes.is_empty() { Ok(()) } else { sink.record(msg, changes) };
+    if !was_tracking {
+        msg.disable_tracking();
+    }
+    recorded.map_err(|err| FixError::Store(format!("audit: {}", err)))?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::message::ChangeOp;
+
+    #[test]
+    fn test_audited_rewrite() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(35, b"D".to_vec()));
+        msg.add_field(FixField::new(1, b"CLIENT-ACC".to_vec()));
+        msg.add_field(FixField::new(58, b"note".to_vec()));
+
+        let mut sink = Vec::new();
+        audited_rewrite(&mut msg, &mut sink, |msg| {
+            msg.set_field(FixField::new(1, b"HOUSE".to_vec()));
+            msg.remove_field(58);
+            msg.add_field(FixField::new(5001, b"desk-7".to_vec()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            sink,
+            [
+                FieldChange { tag: 1, old: Some(b"CLIENT-ACC".to_vec()), new: Some(b"HOUSE".to_vec()), op: ChangeOp::Set },
+                FieldChange { tag: 58, old: Some(b"note".to_vec()), new: None, op: ChangeOp::Remove },
+                FieldChange { tag: 5001, old: None, new: Some(b"desk-7".to_vec()), op: ChangeOp::Add },
+            ]
+        );
+        assert!(!msg.is_tracking());
+
+        // Changes made before a failure are still audited, and a caller's
+        // own tracking keeps its earlier changes
+        msg.enable_tracking();
+        msg.add_field(FixField::new(5002, b"x".to_vec()));
+        let mut sink = Vec::new();
+        let failed = audited_rewrite(&mut msg, &mut sink, |msg| {
+            msg.set_field(FixField::new(1, b"OTHER".to_vec()));
+            Err::<(), _>(FixError::InvalidFieldValue)
+        });
+        assert!(matches!(failed, Err(FixError::InvalidFieldValue)));
+        assert_eq!(sink.len(), 1);
+        assert_eq!(sink[0].tag, 1);
+        assert!(msg.is_tracking());
+        assert_eq!(msg.changes().len(), 2);
+    }
+}
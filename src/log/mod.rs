@@ -0,0 +1,41 @@
+pub mod audit;
+pub mod policy;
+pub mod reader;
+pub mod writer;
+
+pub use audit::{audited_rewrite, AuditSink};
+pub use policy::{LogPolicy, LogSampler, SharedLogPolicy};
+pub use reader::{LogReader, LogRecord};
+pub use writer::{LogWriter, LogWriterConfig, Rotation, SyncPolicy, TimestampFormat};
+
+/// Which way a logged message travelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    // Markers seen in common log layouts
+    pub(crate) fn from_marker(marker: &[u8]) -> Option<Self> {
+        const INBOUND: &[&[u8]] = &[b"in", b"<", b"<-", b"<<", b"incoming", b"recv", b"received"];
+        const OUTBOUND: &[&[u8]] = &[b"out", b">", b"->", b">>", b"outgoing", b"send", b"sent"];
+
+        let matches = |markers: &[&[u8]]| markers.iter().any(|m| m.eq_ignore_ascii_case(marker));
+        if matches(INBOUND) {
+            Some(Direction::Inbound)
+        } else if matches(OUTBOUND) {
+            Some(Direction::Outbound)
+        } else {
+            None
+        }
+    }
+
+    /// The marker `LogWriter` writes
+    pub(crate) fn marker(self) -> &'static str {
+        match self {
+            Direction::Inbound => "IN",
+            Direction::Outbound => "OUT",
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::{data_field_for, msg_type, Tag};
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+
+// MsgTypes are one to three characters, so are held without a heap
+// allocation
+type Key = SmallVec<[u8; 4]>;
+
+/// Reject (3), OrderCancelReject (9), MarketDataRequestReject (Y),
+/// BusinessMessageReject (j) and QuoteRequestReject (AG)
+pub const REJECT_MSG_TYPES: &[&[u8]] = &[msg_type::REJECT, msg_type::ORDER_CANCEL_REJECT, b"Y", b"j", b"AG"];
+
+// Never shortened, so a truncated line still has a sound header
+const UNTRUNCATED_TAGS: [u32; 4] = [
+    Tag::BeginString.value(),
+    Tag::BodyLength.value(),
+    Tag::MsgType.value(),
+    Tag::CheckSum.value(),
+];
+
+/// Which messages a `LogWriter` writes and how much of their values. The
+/// rules are compiled into a per-MsgType table as they are set, so checking
+/// a message costs a table lookup.
+///
+/// A sample rate of N logs one message in N of that MsgType, 1 logs every
+/// one and 0 none. Always-logged MsgTypes ignore their sample rate.
+#[derive(Debug, Clone)]
+pub struct LogPolicy {
+    default_rate: u32,
+    rates: FxHashMap<Key, u32>,
+    always: Vec<Key>,
+    always_admin: bool,
+    max_value_len: Option<usize>,
+    // Compiled rates of the one-character MsgTypes, by character, and of
+    // any longer ones given a rule
+    single: [u32; 128],
+    longer: FxHashMap<Key, u32>,
+}
+
+impl LogPolicy {
+    /// Logs every message in full
+    pub fn new() -> Self {
+        Self {
+            default_rate: 1,
+            rates: FxHashMap::default(),
+            always: Vec::new(),
+            always_admin: false,
+            max_value_len: None,
+            single: [1; 128],
+            longer: FxHashMap::default(),
+        }
+    }
+
+    /// The rate for MsgTypes without their own
+    pub fn with_default_sample_rate(mut self, rate: u32) -> Self {
+        self.default_rate = rate;
+        self.compile();
+        self
+    }
+
+    pub fn with_sample_rate(mut self, msg_type: &[u8], rate: u32) -> Self {
+        self.rates.insert(Key::from_slice(msg_type), rate);
+        self.compile();
+        self
+    }
+
+    pub fn with_always_log(mut self, msg_type: &[u8]) -> Self {
+        self.always.push(Key::from_slice(msg_type));
+        self.compile();
+        self
+    }
+
+    /// Always logs session-level messages, see `msg_type::is_admin`
+    pub fn with_always_log_admin(mut self) -> Self {
+        self.always_admin = true;
+        self.compile();
+        self
+    }
+
+    /// Always logs the `REJECT_MSG_TYPES`
+    pub fn with_always_log_rejects(mut self) -> Self {
+        self.always.extend(REJECT_MSG_TYPES.iter().map(|&t| Key::from_slice(t)));
+        self.compile();
+        self
+    }
+
+    /// Logs values longer than `max` bytes as their first `max` bytes and
+    /// their length, e.g. "ABCD...[120]". BeginString, BodyLength, MsgType
+    /// and CheckSum are kept whole, and the length field of a shortened data
+    /// field is rewritten to match.
+    pub fn with_max_value_len(mut self, max: usize) -> Self {
+        self.max_value_len = Some(max);
+        self
+    }
+
+    #[inline]
+    pub fn max_value_len(&self) -> Option<usize> {
+        self.max_value_len
+    }
+
+    /// The sample rate applying to `msg_type`
+    #[inline]
+    pub fn sample_rate(&self, msg_type: &[u8]) -> u32 {
+        match *msg_type {
+            [c] if c.is_ascii() => self.single[c as usize],
+            _ => self.longer.get(msg_type).copied().unwrap_or(self.default_rate),
+        }
+    }
+
+    fn rule(&self, msg_type: &[u8]) -> u32 {
+        if (self.always_admin && msg_type::is_admin(msg_type)) || self.always.iter().any(|t| t[..] == *msg_type) {
+            1
+        } else {
+            self.rates.get(msg_type).copied().unwrap_or(self.default_rate)
+        }
+    }
+
+    fn compile(&mut self) {
+        for c in 0..128u8 {
+            self.single[c as usize] = self.rule(&[c]);
+        }
+        let longer: Vec<Key> = self.rates.keys().chain(&self.always).filter(|t| t.len() > 1).cloned().collect();
+        self.longer = longer.into_iter().map(|t| (t.clone(), self.rule(&t))).collect();
+    }
+
+    /// `msg` with over-long values shortened, or `None` if none are
+    pub fn truncate(&self, msg: &FixMessage) -> Option<FixMessage> {
+        let max = self.max_value_len?;
+        let long = |field: &FixField| field.value().len() > max && !UNTRUNCATED_TAGS.contains(&field.tag());
+        if !msg.fields().any(long) {
+            return None;
+        }
+
+        let mut fields: Vec<FixField> = Vec::with_capacity(msg.len());
+        for field in msg.field_slice() {
+            if !long(field) {
+                fields.push(field.clone());
+                continue;
+            }
+            let mut value = field.value()[..max].to_vec();
+            value.extend_from_slice(format!("...[{}]", field.value().len()).as_bytes());
+            // The length field always immediately precedes its data field
+            if let Some(length) = fields.last_mut().filter(|f| data_field_for(f.tag()) == Some(field.tag())) {
+                *length = FixField::from_value(length.tag(), &(value.len() as u64));
+            }
+            fields.push(FixField::new(field.tag(), value));
+        }
+        let mut out = FixMessage::with_capacity(fields.len());
+        for field in fields {
+            out.add_field(field);
+        }
+        Some(out)
+    }
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `LogPolicy` that can be replaced while writers use it. Clones share
+/// the policy, and a writer picks up a new one with its next message.
+#[derive(Debug, Clone, Default)]
+pub struct SharedLogPolicy {
+    current: Arc<ArcSwap<LogPolicy>>,
+}
+
+impl SharedLogPolicy {
+    pub fn new(policy: LogPolicy) -> Self {
+        Self { current: Arc::new(ArcSwap::from_pointee(policy)) }
+    }
+
+    /// The policy in force
+    pub fn load(&self) -> Arc<LogPolicy> {
+        self.current.load_full()
+    }
+
+    /// Swaps in `policy` for every holder
+    pub fn store(&self, policy: LogPolicy) {
+        self.current.store(Arc::new(policy));
+    }
+}
+
+/// Counts messages per MsgType to apply a policy's sample rates. The
+/// counters start at the seed, and a message is logged when its MsgType's
+/// count is a multiple of the rate, so the same seed and messages always
+/// give the same selection.
+#[derive(Debug, Clone)]
+pub struct LogSampler {
+    seed: u64,
+    single: [u64; 128],
+    longer: FxHashMap<Key, u64>,
+}
+
+impl LogSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, single: [seed; 128], longer: FxHashMap::default() }
+    }
+
+    /// Whether the next message of `msg_type` is logged under `policy`
+    pub fn should_log(&mut self, policy: &LogPolicy, msg_type: &[u8]) -> bool {
+        let rate = policy.sample_rate(msg_type);
+        if rate <= 1 {
+            return rate == 1;
+        }
+        let count = match *msg_type {
+            [c] if c.is_ascii() => &mut self.single[c as usize],
+            _ => self.longer.entry(Key::from_slice(msg_type)).or_insert(self.seed),
+        };
+        let log = *count % rate as u64 == 0;
+        *count = count.wrapping_add(1);
+        log
+    }
+}
+
+impl Default for LogSampler {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compiled_rates() {
+        let policy = LogPolicy::new()
+            .with_default_sample_rate(100)
+            .with_sample_rate(b"W", 10)
+            .with_sample_rate(b"X", 0)
+            .with_sample_rate(b"AE", 5)
+            .with_sample_rate(msg_type::HEARTBEAT, 50)
+            .with_sample_rate(b"j", 50)
+            .with_always_log_admin()
+            .with_always_log_rejects();
+        assert_eq!(policy.sample_rate(b"W"), 10);
+        assert_eq!(policy.sample_rate(b"X"), 0);
+        assert_eq!(policy.sample_rate(b"AE"), 5);
+        assert_eq!(policy.sample_rate(b"D"), 100);
+        assert_eq!(policy.sample_rate(b"BE"), 100);
+        // Always-logged types win whatever order the rules were set in
+        assert_eq!(policy.sample_rate(msg_type::HEARTBEAT), 1);
+        assert_eq!(policy.sample_rate(b"j"), 1);
+        assert_eq!(policy.sample_rate(b"AG"), 1);
+        assert_eq!(policy.sample_rate(msg_type::LOGON), 1);
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic() {
+        let policy = LogPolicy::new().with_sample_rate(b"W", 4).with_sample_rate(b"AE", 3);
+        let run = |seed: u64| -> Vec<bool> {
+            let mut sampler = LogSampler::new(seed);
+            (0..8)
+                .flat_map(|_| [sampler.should_log(&policy, b"W"), sampler.should_log(&policy, b"AE")])
+                .collect()
+        };
+        assert_eq!(run(7), run(7));
+
+        let w: Vec<usize> = (0..8).filter(|&i| run(0)[2 * i]).collect();
+        assert_eq!(w, [0, 4]);
+        let w: Vec<usize> = (0..8).filter(|&i| run(1)[2 * i]).collect();
+        assert_eq!(w, [3, 7]);
+        let ae: Vec<usize> = (0..8).filter(|&i| run(1)[2 * i + 1]).collect();
+        assert_eq!(ae, [2, 5]);
+
+        let mut sampler = LogSampler::default();
+        assert!((0..5).all(|_| sampler.should_log(&policy, b"D")));
+        assert!(!sampler.should_log(&LogPolicy::new().with_sample_rate(b"D", 0), b"D"));
+    }
+
+    #[test]
+    fn test_swap_at_runtime() {
+        let shared = SharedLogPolicy::new(LogPolicy::new());
+        let writer_side = shared.clone();
+        assert_eq!(writer_side.load().sample_rate(b"W"), 1);
+        shared.store(LogPolicy::new().with_sample_rate(b"W", 1000));
+        assert_eq!(writer_side.load().sample_rate(b"W"), 1000);
+    }
+}
@@ -0,0 +1,199 @@
+use super::Direction;
+use crate::datetime::UtcTimestamp;
+use crate::error::FixError;
+use crate::field::{FieldValue, SOH};
+use crate::framing::find_message_start;
+use crate::message::FixMessage;
+use crate::options::{DecodeOptions, DecodeWarning};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// One message line of a log
+#[derive(Debug)]
+pub struct LogRecord {
+    pub timestamp: Option<UtcTimestamp>,
+    pub direction: Option<Direction>,
+    /// The message as logged from "8=" on, with '|' delimiters converted to
+    /// SOH
+    pub raw: Vec<u8>,
+    pub message: Result<FixMessage, FixError>,
+    /// What the reader's decode options let through
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Reads FIX messages out of text logs such as QuickFIX message logs
+/// ("20240101-12:00:00.123 : 8=FIX.4.2^A...") and RFC 3339 captures
+/// ("2024-01-01T12:00:00.123Z IN 8=FIX.4.4|..."). The delimiter and any
+/// timestamp or direction prefix are detected per line; lines without a
+/// message are skipped and a truncated message is returned with its decode
+/// error.
+pub struct LogReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    options: DecodeOptions,
+}
+
+impl<R: BufRead> LogReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: Vec::new(), options: DecodeOptions::default() }
+    }
+}
+
+impl<R> LogReader<R> {
+    /// Decodes messages with `options`, e.g. to replay a log of a
+    /// counterparty that got BodyLength wrong with the mismatches reported in
+    /// `LogRecord::warnings` instead of failing the messages
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<R: Read> LogReader<BufReader<R>> {
+    pub fn from_read(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl LogReader<Box<dyn BufRead>> {
+    /// Opens a log file, decompressing it if it starts with zstd magic bytes
+    /// as the files of a compressing `LogWriter` do
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        #[cfg(feature = "compression")]
+        let reader = crate::compression::decompressing_reader(reader)?;
+        #[cfg(not(feature = "compression"))]
+        let reader: Box<dyn BufRead> = Box::new(reader);
+        Ok(Self::new(reader))
+    }
+}
+
+impl<R: BufRead> Iterator for LogReader<R> {
+    type Item = io::Result<LogRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    if let Some(record) = parse_line(&self.line, &self.options) {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+fn parse_line(line: &[u8], options: &DecodeOptions) -> Option<LogRecord> {
+    let line = line.trim_ascii_end();
+    let start = find_message_start(line)?;
+
+    let mut timestamp = None;
+    let mut direction = None;
+    for token in line[..start].split(|b| b.is_ascii_whitespace()) {
+        let token = token.strip_suffix(b":").unwrap_or(token);
+        let token = token.strip_prefix(b"[").unwrap_or(token);
+        let token = token.strip_suffix(b"]").unwrap_or(token);
+        if token.is_empty() {
+            continue;
+        }
+        if timestamp.is_none() {
+            timestamp = UtcTimestamp::parse(token)
+                .or_else(|_| UtcTimestamp::parse_rfc3339(token))
+                .ok();
+            if timestamp.is_some() {
+                continue;
+            }
+        }
+        direction = direction.or_else(|| Direction::from_marker(token));
+    }
+
+    let mut raw = line[start..].to_vec();
+    if memchr::memchr(SOH, &raw).is_none() {
+        raw.iter_mut().filter(|b| **b == b'|').for_each(|b| *b = SOH);
+    }
+    let (message, warnings) = match FixMessage::decode_with_warnings(&raw, options) {
+        Ok((message, warnings)) => (Ok(message), warnings),
+        Err(err) => (Err(err), Vec::new()),
+    };
+
+    Some(LogRecord { timestamp, direction, raw, message, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::Tag;
+
+    // QuickFIX file log: FIX timestamp, " : ", SOH-delimited message
+    const QUICKFIX_LOG: &[u8] = b"20240101-12:00:00.123 : 8=FIX.4.2\x019=66\x0135=A\x0134=1\x0149=CLIENT\x0152=20240101-12:00:00.123\x0156=VENUE\x0198=0\x01108=30\x0110=037\x01
+20240101-12:00:00.200 : Session FIX.4.2:CLIENT->VENUE schedule is daily
+20240101-12:00:30.125 : 8=FIX.4.2\x019=54\x0135=0\x0134=2\x0149=VENUE\x0152=20240101-12:00:30.125\x0156=CLIENT\x0110=254\x01
+";
+
+    // Capture layout: RFC 3339 timestamp, direction, pipe-delimited message,
+    // with the last line cut off mid-write
+    const CAPTURE_LOG: &str = "2024-01-01T12:00:01.000123Z OUT 8=FIX.4.4|9=81|35=D|34=7|49=CLIENT|52=20240101-12:00:01.000|56=VENUE|11=ORD1|54=1|55=VOD|38=100|10=104|\r
+# connection stats: 2 msgs\r
+2024-01-01T12:00:01.002456Z IN 8=FIX.4.4|9=77|35=8|34=9|49=VENUE|52=20240101-12:00:01.002|56=CLIENT|37=X1|17=E1|150=0|39=0|10=055|\r
+2024-01-01T12:00:01.003000Z IN 8=FIX.4.4|9=77|35=8|34=10|49=VEN";
+
+    #[test]
+    fn test_quickfix_log() {
+        let records: Vec<LogRecord> = LogReader::new(QUICKFIX_LOG).map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2);
+
+        let logon = &records[0];
+        assert_eq!(logon.timestamp.unwrap().to_string(), "20240101-12:00:00.123");
+        assert_eq!(logon.direction, None);
+        let message = logon.message.as_ref().unwrap();
+        assert_eq!(message.get_uint(Tag::HeartBtInt.value()).unwrap(), Some(30));
+        assert_eq!(records[1].message.as_ref().unwrap().msg_seq_num().unwrap().unwrap().get(), 2);
+    }
+
+    #[test]
+    fn test_capture_log_with_truncated_line() {
+        let records: Vec<LogRecord> = LogReader::from_read(CAPTURE_LOG.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].direction, Some(Direction::Outbound));
+        assert_eq!(records[0].timestamp.unwrap().to_string(), "20240101-12:00:01.000123");
+        assert!(records[0].raw.ends_with(b"10=104\x01"));
+        let order = records[0].message.as_ref().unwrap();
+        assert_eq!(order.get_field(Tag::ClOrdID.value()).unwrap().value(), b"ORD1");
+
+        assert_eq!(records[1].direction, Some(Direction::Inbound));
+        assert!(records[1].message.is_ok());
+
+        assert_eq!(records[2].direction, Some(Direction::Inbound));
+        assert!(records[2].message.is_err());
+    }
+
+    #[test]
+    fn test_message_start() {
+        assert_eq!(find_message_start(b"8=FIX.4.2"), Some(0));
+        assert_eq!(find_message_start(b"x 58=FIX it 8=FIX.4.4"), Some(12));
+        assert_eq!(find_message_start(b"no message here"), None);
+    }
+
+    #[test]
+    fn test_known_bad_body_length() {
+        // The heartbeat of the QuickFIX log as a counterparty sent it, with
+        // BodyLength 2 short and a CheckSum to match
+        let log = b"20240101-12:00:30.125 : 8=FIX.4.2\x019=52\x0135=0\x0134=2\x0149=VENUE\x0152=20240101-12:00:30.125\x0156=CLIENT\x0110=252\x01\n";
+        let record = LogReader::new(&log[..]).next().unwrap().unwrap();
+        assert!(matches!(record.message, Err(FixError::InvalidBodyLength { declared: 52, actual: 54 })));
+
+        let options = DecodeOptions::default().with_verify_body_length(false);
+        let record = LogReader::new(&log[..]).with_decode_options(options).next().unwrap().unwrap();
+        assert_eq!(record.message.unwrap().msg_seq_num().unwrap().unwrap().get(), 2);
+        assert_eq!(record.warnings, [DecodeWarning::BodyLength { declared: 52, actual: 54 }]);
+        assert_eq!(record.warnings[0].to_string(), "BodyLength is 52 but the body is 54 bytes");
+    }
+}
@@ -0,0 +1,627 @@
+use super::{AuditSink, Direction, LogSampler, SharedLogPolicy};
+#[cfg(feature = "compression")]
+use crate::compression::{is_compressed, BlockCompression};
+use crate::datetime::UtcTimestamp;
+use crate::field::{FixField, SOH};
+use crate::message::{FieldChange, FixMessage};
+use crate::tags::{data_field_for, Tag};
+use bytes::BytesMut;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Replaces the value of every redacted field
+const REDACTED: &[u8] = b"***";
+
+/// How each line's timestamp is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// "2024-01-01T12:00:00.123456Z"
+    #[default]
+    Rfc3339,
+    /// "20240101-12:00:00.123456", as QuickFIX writes it
+    Fix,
+}
+
+/// When the current file is moved aside and a new one started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    Never,
+    /// Before a line would take the file past this many bytes
+    Size(u64),
+    /// Once a record is this much later than the file's first record
+    Interval(Duration),
+}
+
+/// When written lines are synced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// Left to the OS; lines are buffered until `flush`, rotation or drop
+    Never,
+    /// Lines are flushed as written and the file synced when it is rotated
+    #[default]
+    OnRotate,
+    /// Every line is flushed and synced before `write` returns
+    EveryMessage,
+}
+
+/// Line layout, rotation, sync and redaction settings of a `LogWriter`
+#[derive(Debug, Clone, Default)]
+pub struct LogWriterConfig {
+    pub timestamp_format: TimestampFormat,
+    pub rotation: Rotation,
+    pub sync: SyncPolicy,
+    /// Tags whose values are written as "***", e.g. Password (554)
+    pub redacted_tags: Vec<u32>,
+    /// Sampling and truncation; every message is written in full without
+    pub policy: Option<SharedLogPolicy>,
+    /// Where the policy's sampling counters start
+    pub sample_seed: u64,
+    /// Writes lines in zstd blocks, which `LogReader::open` detects.
+    /// Buffered lines are kept until their block fills or the writer is
+    /// flushed, and `Rotation::Size` counts them uncompressed until then.
+    #[cfg(feature = "compression")]
+    pub compression: Option<BlockCompression>,
+}
+
+impl LogWriterConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_sync(mut self, sync: SyncPolicy) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    pub fn with_redacted_tag(mut self, tag: u32) -> Self {
+        self.redacted_tags.push(tag);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: SharedLogPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_sample_seed(mut self, seed: u64) -> Self {
+        self.sample_seed = seed;
+        self
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: BlockCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+}
+
+/// Appends one line per message, "<timestamp> <IN|OUT> 8=FIX.4.4|9=...|",
+/// in the layout `LogReader` reads. Rotated files are renamed to
+/// "<path>.1", "<path>.2" and so on, the lowest unused number first.
+pub struct LogWriter {
+    path: PathBuf,
+    config: LogWriterConfig,
+    file: BufWriter<File>,
+    written: u64,
+    first_record: Option<i64>,
+    line: Vec<u8>,
+    sampler: LogSampler,
+    // Lines of the compressed block being filled, and how many
+    #[cfg(feature = "compression")]
+    block: (Vec<u8>, usize),
+}
+
+impl LogWriter {
+    /// Opens `path` for appending, creating it if needed. An existing file
+    /// compressed differently from `config` is rotated aside first, so no
+    /// file mixes plain lines and compressed blocks.
+    pub fn open(path: impl AsRef<Path>, config: LogWriterConfig) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).read(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        let mut prefix = Vec::with_capacity(4);
+        (&file).take(4).read_to_end(&mut prefix)?;
+
+        #[cfg(feature = "compression")]
+        let mismatched = written > 0 && is_compressed(&prefix) != config.compression.is_some();
+        #[cfg(not(feature = "compression"))]
+        let mismatched = false;
+        let mut writer = Self {
+            path,
+            sampler: LogSampler::new(config.sample_seed),
+            config,
+            file: BufWriter::new(file),
+            written,
+            first_record: None,
+            line: Vec::new(),
+            #[cfg(feature = "compression")]
+            block: (Vec::new(), 0),
+        };
+        if mismatched {
+            writer.rotate()?;
+        }
+        Ok(writer)
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn config(&self) -> &LogWriterConfig {
+        &self.config
+    }
+
+    /// Writes `msg` stamped with the current time
+    pub fn log(&mut self, direction: Direction, msg: &FixMessage) -> io::Result<()> {
+        self.write(now()?, direction, msg)
+    }
+
+    /// Writes `msg` stamped with `timestamp`, rotating first if the rotation
+    /// policy calls for it. A message the log policy samples out is skipped.
+    pub fn write(&mut self, timestamp: UtcTimestamp, direction: Direction, msg: &FixMessage) -> io::Result<()> {
+        let policy = self.config.policy.as_ref().map(SharedLogPolicy::load);
+        let truncated = match &policy {
+            Some(policy) => {
+                let msg_type = msg.get_field(Tag::MsgType.value()).map_or(&[][..], FixField::value);
+                if !self.sampler.should_log(policy, msg_type) {
+                    return Ok(());
+                }
+                policy.truncate(msg)
+            }
+            None => None,
+        };
+        self.render(timestamp, direction, truncated.as_ref().unwrap_or(msg));
+        self.append(timestamp)
+    }
+
+    /// Writes one line per change in `changes`, as recorded by a message
+    /// with tracking enabled, so the log shows what a rewrite step did to
+    /// `msg`: "<timestamp> CHG <MsgType> <Add|Set|Remove> <tag> <old> ->
+    /// <new>", with "-" for a missing value and redacted values masked.
+    /// `LogReader` skips these lines, and the log policy does not sample
+    /// them out.
+    pub fn write_changes(&mut self, timestamp: UtcTimestamp, msg: &FixMessage, changes: &[FieldChange]) -> io::Result<()> {
+        let msg_type = msg.get_field(Tag::MsgType.value()).map_or(&b"-"[..], FixField::value);
+        for change in changes {
+            self.render_prefix(timestamp, "CHG");
+            self.line.extend_from_slice(msg_type);
+            let redacted = self.config.redacted_tags.contains(&change.tag);
+            let value = |value: &Option<Vec<u8>>| match value {
+                Some(_) if redacted => REDACTED.to_vec(),
+                Some(value) => value.clone(),
+                None => b"-".to_vec(),
+            };
+            let (old, new) = (value(&change.old), value(&change.new));
+            let _ = write!(self.line, " {:?} {} ", change.op, change.tag);
+            self.line.extend_from_slice(&old);
+            self.line.extend_from_slice(b" -> ");
+            self.line.extend_from_slice(&new);
+            self.line.push(b'\n');
+            self.append(timestamp)?;
+        }
+        Ok(())
+    }
+
+    // Writes the rendered line, rotating first if the rotation policy calls
+    // for it
+    fn append(&mut self, timestamp: UtcTimestamp) -> io::Result<()> {
+        let at = timestamp.unix_nanos();
+        let rotate = match self.config.rotation {
+            Rotation::Never => false,
+            Rotation::Size(max) => self.written > 0 && self.written + self.line.len() as u64 > max,
+            Rotation::Interval(interval) => self
+                .first_record
+                .is_some_and(|first| at.saturating_sub(first) >= interval.as_nanos() as i64),
+        };
+        if rotate {
+            self.rotate()?;
+        }
+        self.first_record.get_or_insert(at);
+
+        self.written += self.line.len() as u64;
+        #[cfg(feature = "compression")]
+        if let Some(compression) = self.config.compression {
+            self.block.0.extend_from_slice(&self.line);
+            self.block.1 += 1;
+            if self.block.1 >= compression.block_records {
+                self.write_block()?;
+            }
+        } else {
+            self.file.write_all(&self.line)?;
+        }
+        #[cfg(not(feature = "compression"))]
+        self.file.write_all(&self.line)?;
+        match self.config.sync {
+            SyncPolicy::Never => Ok(()),
+            SyncPolicy::OnRotate => self.file.flush(),
+            SyncPolicy::EveryMessage => self.sync(),
+        }
+    }
+
+    /// Writes buffered lines, ending any compressed block early
+    pub fn flush(&mut self) -> io::Result<()> {
+        #[cfg(feature = "compression")]
+        self.write_block()?;
+        self.file.flush()
+    }
+
+    /// Flushes buffered lines and syncs the file to disk
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.file.get_ref().sync_data()
+    }
+
+    #[cfg(feature = "compression")]
+    fn write_block(&mut self) -> io::Result<()> {
+        let (Some(compression), (lines, count @ 1..)) = (self.config.compression, &mut self.block) else {
+            return Ok(());
+        };
+        let compressed = compression.compress(lines)?;
+        self.file.write_all(&compressed)?;
+        self.written = self.written - lines.len() as u64 + compressed.len() as u64;
+        lines.clear();
+        *count = 0;
+        Ok(())
+    }
+
+    /// Moves the current file aside and starts a new one, returning where
+    /// the old file went
+    pub fn rotate(&mut self) -> io::Result<PathBuf> {
+        if self.config.sync != SyncPolicy::Never {
+            self.sync()?;
+        } else {
+            self.flush()?;
+        }
+
+        let rotated = (1..)
+            .map(|n| {
+                let mut name = self.path.clone().into_os_string();
+                name.push(format!(".{}", n));
+                PathBuf::from(name)
+            })
+            .find(|candidate| !candidate.exists())
+            .unwrap_or_default();
+        std::fs::rename(&self.path, &rotated)?;
+
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.file = BufWriter::new(file);
+        self.written = 0;
+        self.first_record = None;
+        Ok(rotated)
+    }
+
+    fn render_prefix(&mut self, timestamp: UtcTimestamp, marker: &str) {
+        self.line.clear();
+        let stamp = match self.config.timestamp_format {
+            TimestampFormat::Rfc3339 => timestamp.to_rfc3339(),
+            TimestampFormat::Fix => timestamp.to_string(),
+        };
+        self.line.extend_from_slice(stamp.as_bytes());
+        self.line.push(b' ');
+        self.line.extend_from_slice(marker.as_bytes());
+        self.line.push(b' ');
+    }
+
+    fn render(&mut self, timestamp: UtcTimestamp, direction: Direction, msg: &FixMessage) {
+        self.render_prefix(timestamp, direction.marker());
+
+        let redacted;
+        let msg = if msg.field_tags().any(|tag| self.config.redacted_tags.contains(tag)) {
+            redacted = self.redact(msg);
+            &redacted
+        } else {
+            msg
+        };
+        // A message that cannot be encoded is logged as stored
+        let bytes = msg.encode().unwrap_or_else(|_| {
+            let mut buf = BytesMut::with_capacity(msg.fields().map(FixField::encoded_len).sum());
+            for field in msg.field_slice() {
+                field.encode(&mut buf);
+            }
+            buf
+        });
+        self.line.extend(bytes.iter().map(|&b| if b == SOH { b'|' } else { b }));
+        self.line.push(b'\n');
+    }
+
+    // Masks the redacted values, keeping the length field of a redacted data
+    // field consistent so the line still decodes
+    fn redact(&self, msg: &FixMessage) -> FixMessage {
+        let redacted = |tag: u32| self.config.redacted_tags.contains(&tag);
+        let mut out = FixMessage::with_capacity(msg.len());
+        for field in msg.field_slice() {
+            let tag = field.tag();
+            if redacted(tag) {
+                out.add_field(FixField::new(tag, REDACTED.to_vec()));
+            } else if data_field_for(tag).is_some_and(redacted) {
+                out.add_field(FixField::from_value(tag, &(REDACTED.len() as u64)));
+            } else {
+                out.add_field(field.clone());
+            }
+        }
+        out
+    }
+}
+
+impl AuditSink for LogWriter {
+    /// `write_changes` stamped with the current time
+    fn record(&mut self, msg: &FixMessage, changes: &[FieldChange]) -> io::Result<()> {
+        self.write_changes(now()?, msg, changes)
+    }
+}
+
+impl fmt::Debug for LogWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogWriter")
+            .field("path", &self.path)
+            .field("config", &self.config)
+            .field("written", &self.written)
+            .finish_non_exhaustive()
+    }
+}
+
+// `LogWriter::log` on a writer shared between transports, with a failure
+// reported as `FixError::Store`
+#[cfg(any(feature = "tokio", feature = "websocket"))]
+pub(crate) fn log_shared(
+    log: &std::sync::Mutex<LogWriter>,
+    direction: Direction,
+    msg: &FixMessage,
+) -> Result<(), crate::error::FixError> {
+    let mut log = log.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    log.log(direction, msg).map_err(|err| crate::error::FixError::Store(format!("log: {}", err)))
+}
+
+fn now() -> io::Result<UtcTimestamp> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as i64);
+    UtcTimestamp::from_unix_nanos(nanos, 6).map_err(io::Error::other)
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::LogReader;
+    use crate::message::ChangeOp;
+    use crate::tags::{fix_version, msg_type, Tag};
+    use std::io::BufReader;
+
+    // Password (554)
+    const PASSWORD: u32 = 554;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fix_engine_log_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("messages.log")
+    }
+
+    fn message(seq: u64) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::LOGON.to_vec()));
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg.add_field(FixField::new(Tag::EncryptMethod.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::HeartBtInt.value(), b"30".to_vec()));
+        msg.add_field(FixField::new(PASSWORD, b"hunter2".to_vec()));
+        msg
+    }
+
+    fn at(second: u8) -> UtcTimestamp {
+        UtcTimestamp::new(2024, 1, 1, 12, 0, second)
+            .unwrap()
+            .with_nanos(123_000_000, 3)
+            .unwrap()
+    }
+
+    fn read_back(path: &Path) -> Vec<crate::log::LogRecord> {
+        LogReader::new(BufReader::new(File::open(path).unwrap()))
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_with_redaction() {
+        for format in [TimestampFormat::Rfc3339, TimestampFormat::Fix] {
+            let path = temp_log(&format!("round_trip_{:?}", format));
+            let config = LogWriterConfig::new()
+                .with_timestamp_format(format)
+                .with_redacted_tag(PASSWORD);
+            let mut writer = LogWriter::open(&path, config).unwrap();
+            writer.write(at(0), Direction::Outbound, &message(1)).unwrap();
+            writer.write(at(1), Direction::Inbound, &message(1)).unwrap();
+            drop(writer);
+
+            let records = read_back(&path);
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].timestamp, Some(at(0)));
+            assert_eq!(records[0].direction, Some(Direction::Outbound));
+            assert_eq!(records[1].direction, Some(Direction::Inbound));
+
+            let mut expected = message(1);
+            expected.set_field(FixField::new(PASSWORD, REDACTED.to_vec()));
+            for record in &records {
+                let logged = record.message.as_ref().unwrap();
+                assert_eq!(logged.encode().unwrap(), expected.encode().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_change_lines() {
+        let path = temp_log("changes");
+        let mut writer = LogWriter::open(&path, LogWriterConfig::new().with_redacted_tag(PASSWORD)).unwrap();
+        let mut msg = message(1);
+        writer.write(at(0), Direction::Inbound, &msg).unwrap();
+        crate::log::audited_rewrite(&mut msg, &mut writer, |msg| {
+            msg.set_field(FixField::new(Tag::HeartBtInt.value(), b"60".to_vec()));
+            msg.set_field(FixField::new(PASSWORD, b"rotated".to_vec()));
+            msg.remove_field(Tag::EncryptMethod.value());
+            Ok(())
+        })
+        .unwrap();
+        writer.write(at(1), Direction::Outbound, &msg).unwrap();
+        let added = FieldChange { tag: 1, old: None, new: Some(b"A".to_vec()), op: ChangeOp::Add };
+        writer.write_changes(at(2), &FixMessage::new(), &[added]).unwrap();
+        drop(writer);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[1].ends_with(" CHG A Set 108 30 -> 60"), "{}", lines[1]);
+        assert!(lines[2].ends_with(" CHG A Set 554 *** -> ***"), "{}", lines[2]);
+        assert!(lines[3].ends_with(" CHG A Remove 98 0 -> -"), "{}", lines[3]);
+        assert_eq!(lines[5], "2024-01-01T12:00:02.123Z CHG - Add 1 - -> A");
+
+        // The reader sees only the two messages
+        let records = read_back(&path);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].message.as_ref().unwrap().get_uint(Tag::HeartBtInt.value()).unwrap(), Some(60));
+    }
+
+    #[test]
+    fn test_redacted_data_field_stays_decodable() {
+        let path = temp_log("data_field");
+        let config = LogWriterConfig::new().with_redacted_tag(Tag::EncodedText.value());
+        let mut writer = LogWriter::open(&path, config).unwrap();
+        let mut msg = message(1);
+        msg.set_encoded_text_utf8("secret text");
+        writer.write(at(0), Direction::Outbound, &msg).unwrap();
+        drop(writer);
+
+        let records = read_back(&path);
+        let logged = records[0].message.as_ref().unwrap();
+        assert_eq!(logged.get_field(Tag::EncodedTextLen.value()).unwrap().value(), b"3");
+        assert_eq!(logged.get_field(Tag::EncodedText.value()).unwrap().value(), REDACTED);
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let path = temp_log("size_rotation");
+        let line_len = {
+            let mut writer = LogWriter::open(&path, LogWriterConfig::new()).unwrap();
+            writer.write(at(0), Direction::Outbound, &message(1)).unwrap();
+            writer.written
+        };
+        std::fs::remove_file(&path).unwrap();
+
+        let config = LogWriterConfig::new()
+            .with_rotation(Rotation::Size(line_len * 2))
+            .with_sync(SyncPolicy::EveryMessage);
+        let mut writer = LogWriter::open(&path, config).unwrap();
+        for seq in 1..=5 {
+            writer.write(at(seq as u8), Direction::Outbound, &message(seq)).unwrap();
+        }
+        drop(writer);
+
+        let seqs = |path: &Path| -> Vec<u64> {
+            read_back(path)
+                .iter()
+                .map(|r| r.message.as_ref().unwrap().msg_seq_num().unwrap().unwrap().get())
+                .collect()
+        };
+        let rotated = |n: u32| PathBuf::from(format!("{}.{}", path.display(), n));
+        assert_eq!(seqs(&rotated(1)), [1, 2]);
+        assert_eq!(seqs(&rotated(2)), [3, 4]);
+        assert_eq!(seqs(&path), [5]);
+    }
+
+    #[test]
+    fn test_interval_rotation() {
+        let path = temp_log("interval_rotation");
+        let config = LogWriterConfig::new().with_rotation(Rotation::Interval(Duration::from_secs(2)));
+        let mut writer = LogWriter::open(&path, config).unwrap();
+        for second in [0, 1, 2, 3] {
+            writer.write(at(second), Direction::Inbound, &message(1)).unwrap();
+        }
+        drop(writer);
+
+        let rotated = PathBuf::from(format!("{}.1", path.display()));
+        assert_eq!(read_back(&rotated).len(), 2);
+        assert_eq!(read_back(&path).len(), 2);
+    }
+
+    #[test]
+    fn test_policy_samples_and_truncates() {
+        let path = temp_log("policy");
+        let policy = crate::log::LogPolicy::new()
+            .with_sample_rate(msg_type::LOGON, 3)
+            .with_max_value_len(4);
+        let shared = SharedLogPolicy::new(policy);
+        let config = LogWriterConfig::new().with_policy(shared.clone()).with_sample_seed(1);
+        let mut writer = LogWriter::open(&path, config).unwrap();
+        for seq in 1..=6 {
+            let mut msg = message(seq);
+            msg.add_field(FixField::new(Tag::Text.value(), b"a long free-text value".to_vec()));
+            // RawDataLength (95) and RawData (96), with SOH in the value
+            msg.add_field(FixField::new(95, b"10".to_vec()));
+            msg.add_field(FixField::new(96, b"ab\x01cdefghi".to_vec()));
+            writer.write(at(seq as u8), Direction::Outbound, &msg).unwrap();
+        }
+        shared.store(crate::log::LogPolicy::new());
+        writer.write(at(7), Direction::Outbound, &message(7)).unwrap();
+        drop(writer);
+
+        let records = read_back(&path);
+        let logged: Vec<&FixMessage> = records.iter().map(|r| r.message.as_ref().unwrap()).collect();
+        let seqs: Vec<u64> = logged.iter().map(|m| m.msg_seq_num().unwrap().unwrap().get()).collect();
+        assert_eq!(seqs, [3, 6, 7]);
+
+        let first = logged[0];
+        assert_eq!(first.field_tags().copied().collect::<Vec<_>>(), [8, 9, 35, 34, 98, 108, 554, 58, 95, 96, 10]);
+        assert_eq!(first.get_field(Tag::BeginString.value()).unwrap().value(), b"FIX.4.4");
+        assert_eq!(first.get_field(PASSWORD).unwrap().value(), b"hunt...[7]");
+        assert_eq!(first.get_field(Tag::Text.value()).unwrap().value(), b"a lo...[22]");
+        assert_eq!(first.get_field(96).unwrap().value(), b"ab\x01c...[10]");
+        assert_eq!(first.get_field(95).unwrap().value(), b"11");
+        assert_eq!(logged[2].get_field(PASSWORD).unwrap().value(), b"hunter2");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_log() {
+        let path = temp_log("compressed");
+        std::fs::write(&path, b"plain line\n").unwrap();
+        let config = LogWriterConfig::new().with_compression(BlockCompression::new(3, 2));
+        let mut writer = LogWriter::open(&path, config).unwrap();
+        for seq in 1..=5 {
+            writer.write(at(seq as u8), Direction::Outbound, &message(seq)).unwrap();
+        }
+        assert_eq!(writer.block.1, 1);
+        drop(writer);
+
+        // The plain file was moved aside rather than appended to
+        let rotated = PathBuf::from(format!("{}.1", path.display()));
+        assert_eq!(std::fs::read(&rotated).unwrap(), b"plain line\n");
+
+        let file = std::fs::read(&path).unwrap();
+        assert!(is_compressed(&file));
+        let records: Vec<_> = LogReader::open(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[4].message.as_ref().unwrap().msg_seq_num().unwrap().unwrap().get(), 5);
+        assert_eq!(LogReader::open(&rotated).unwrap().count(), 0);
+    }
+}
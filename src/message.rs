@@ -1,225 +1,1012 @@
+use crate::checksum::RollingChecksum;
+use crate::datetime::{parse_digits, LocalMktDate, MonthYear, TzTimestamp, UtcDateOnly, UtcTimeOnly, UtcTimestamp};
+use crate::decimal::{Amt, DecimalFormat, FixDecimal, Percentage, Qty};
 use crate::error::FixError;
-use crate::field::{FixField, SOH};
-use crate::tags::Tag;
+use crate::field::{parse_uint, FieldValue, FixField, Flag, SOH};
+use crate::framing::find_message_start;
+use crate::messages::group_entries;
+use crate::options::{DecodeOptions, DecodeWarning, EncodeOptions};
+use crate::scanner::FieldScanner;
+use crate::seqnum::SeqNum;
+use crate::settl_type::SettlType;
+use crate::tags::{is_header_tag, is_trailer_tag, MsgType, Tag};
 use bytes::{BufMut, BytesMut};
 use memchr::memchr;
 use rustc_hash::FxHashMap;
 use smallvec::SmallVec;
+use std::io;
 
 const BEGIN_STRING_TAG: u32 = Tag::BeginString.value();
 const BODY_LENGTH_TAG: u32 = Tag::BodyLength.value();
 const MSG_TYPE_TAG: u32 = Tag::MsgType.value();
 const CHECKSUM_TAG: u32 = Tag::CheckSum.value();
 
+// Conventional arrangement of the session header after 8, 9 and 35
+const ORDERED_HEADER_TAGS: [u32; 4] = [
+    Tag::SenderCompID.value(),
+    Tag::TargetCompID.value(),
+    Tag::MsgSeqNum.value(),
+    Tag::SendingTime.value(),
+];
+
+// Bit set of the tags `encode` writes itself: 8, 9, 10 and 35
+const FRAMING_TAG_MASK: u64 =
+    1 << BEGIN_STRING_TAG | 1 << BODY_LENGTH_TAG | 1 << CHECKSUM_TAG | 1 << MSG_TYPE_TAG;
+
+/// Whether `encode` writes `tag` itself (BeginString, BodyLength, MsgType and
+/// CheckSum) rather than copying it from the stored fields
+#[inline]
+const fn is_header_or_trailer(tag: u32) -> bool {
+    tag < 64 && FRAMING_TAG_MASK & (1 << tag) != 0
+}
+
 const TYPICAL_MESSAGE_FIELDS: usize = 16; // Typical FIX message size
 
+// A message longer than this, e.g. a SecurityList or a mass quote with
+// thousands of fields, has its field table sized from its length before
+// decoding instead of growing it by doubling
+const JUMBO_MESSAGE_LEN: usize = 16 * 1024;
+
+// Encoded bytes per field assumed for a jumbo message: a little under what
+// such messages average, so the table seldom has to grow after all
+const JUMBO_BYTES_PER_FIELD: usize = 16;
+
+// Fields to make room for before decoding `len` bytes
+#[inline]
+fn field_capacity(len: usize) -> usize {
+    if len > JUMBO_MESSAGE_LEN {
+        len / JUMBO_BYTES_PER_FIELD
+    } else {
+        TYPICAL_MESSAGE_FIELDS
+    }
+}
+
+// Stands in for SOH in the QuickFIX log form
+const PRINT_DELIMITER: char = '|';
+
+/// How `FixMessage::merge` resolves a tag present in both messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the value already in the message
+    KeepExisting,
+    /// Replace the value in place with the one from the other message
+    Overwrite,
+    /// Fail with `FixError::FieldConflict` and leave the message unchanged
+    Error,
+}
+
+/// What a tracked mutation did to a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOp {
+    /// A new occurrence of the tag was appended
+    Add,
+    /// The first occurrence's value was replaced
+    Set,
+    /// The tag was removed
+    Remove,
+}
+
+/// One mutation recorded by a message with tracking enabled. `old` is
+/// `None` for an `Add` and `new` is `None` for a `Remove`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub tag: u32,
+    pub old: Option<Vec<u8>>,
+    pub new: Option<Vec<u8>>,
+    pub op: ChangeOp,
+}
+
+// Boxed so an untracked message pays one pointer for it
+#[derive(Debug, Clone, Default)]
+struct ChangeJournal {
+    changes: Vec<FieldChange>,
+}
+
+impl ChangeJournal {
+    fn record(&mut self, tag: u32, old: Option<&[u8]>, new: Option<&[u8]>, op: ChangeOp) {
+        self.changes.push(FieldChange { tag, old: old.map(<[u8]>::to_vec), new: new.map(<[u8]>::to_vec), op });
+    }
+}
+
+/// A message's fields split into standard header, body and trailer, each in
+/// wire order. Returned by `FixMessage::sections`.
+#[derive(Debug, Clone, Copy)]
+pub struct Sections<'a> {
+    fields: &'a [FixField],
+}
+
+impl<'a> Sections<'a> {
+    #[inline]
+    pub fn header(&self) -> impl Iterator<Item = &'a FixField> {
+        self.fields.iter().filter(|f| is_header_tag(f.tag()))
+    }
+
+    #[inline]
+    pub fn body(&self) -> impl Iterator<Item = &'a FixField> {
+        self.fields.iter().filter(|f| !is_header_tag(f.tag()) && !is_trailer_tag(f.tag()))
+    }
+
+    #[inline]
+    pub fn trailer(&self) -> impl Iterator<Item = &'a FixField> {
+        self.fields.iter().filter(|f| is_trailer_tag(f.tag()))
+    }
+
+    /// Header, then body, then trailer fields
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &'a FixField> {
+        self.header().chain(self.body()).chain(self.trailer())
+    }
+}
+
+/// One entry of a repeating group of a `FixMessage`, see
+/// `FixMessage::group`
+#[derive(Debug, Clone, Copy)]
+pub struct FixGroupEntry<'a> {
+    fields: &'a [FixField],
+}
+
+impl<'a> FixGroupEntry<'a> {
+    /// First occurrence of `tag` in this entry
+    pub fn get_field(&self, tag: u32) -> Option<&'a FixField> {
+        self.fields.iter().find(|field| field.tag() == tag)
+    }
+
+    /// Fields in wire order, delimiter first
+    #[inline]
+    pub fn fields(&self) -> impl Iterator<Item = &'a FixField> {
+        self.fields.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Always false: an entry holds at least its delimiter field
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FixMessage {
-    fields: FxHashMap<u32, FixField>,
-    field_order: SmallVec<[u32; TYPICAL_MESSAGE_FIELDS]>,
+    // Every field in wire order, including repeated tags from repeating groups
+    fields: Vec<FixField>,
+    // Position of the first occurrence of each tag
+    index: FxHashMap<u32, usize>,
+    // Mutations since tracking was enabled; `None` when it is not
+    journal: Option<Box<ChangeJournal>>,
+}
+
+impl Default for FixMessage {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FixMessage {
     #[inline]
     pub fn new() -> Self {
         Self {
-            fields: FxHashMap::default(),
-            field_order: SmallVec::new(),
+            fields: Vec::new(),
+            index: FxHashMap::default(),
+            journal: None,
         }
     }
 
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            fields: FxHashMap::with_capacity_and_hasher(capacity, Default::default()),
-            field_order: SmallVec::with_capacity(capacity),
+            fields: Vec::with_capacity(capacity),
+            index: FxHashMap::with_capacity_and_hasher(capacity, Default::default()),
+            journal: None,
         }
     }
 
+    /// Appends a field. A tag that is already present is kept as a further
+    /// occurrence rather than replacing the earlier one.
     #[inline]
     pub fn add_field(&mut self, field: FixField) {
-        let tag = field.tag();
-        self.field_order.push(tag);
-        self.fields.insert(tag, field);
+        if let Some(journal) = &mut self.journal {
+            journal.record(field.tag(), None, Some(field.value()), ChangeOp::Add);
+        }
+        self.index.entry(field.tag()).or_insert(self.fields.len());
+        self.fields.push(field);
+    }
+
+    /// Appends a repeating group at the current position: the `count_tag`
+    /// field holding the number of entries, then each entry's fields in
+    /// order. Fields added afterwards follow the group on the wire, so scalar
+    /// fields and groups are encoded in the order they were added. Every
+    /// entry must start with the same delimiter tag. Nothing is added when
+    /// `entries` is empty.
+    pub fn add_group<E: AsRef<[FixField]>>(&mut self, count_tag: u32, entries: &[E]) -> Result<(), FixError> {
+        let Some(first) = entries.first() else {
+            return Ok(());
+        };
+        let delimiter = first.as_ref().first().ok_or(FixError::InvalidFormat)?.tag();
+        if entries.iter().any(|e| e.as_ref().first().map(FixField::tag) != Some(delimiter)) {
+            return Err(FixError::InvalidFormat);
+        }
+
+        self.add_field(FixField::from_value(count_tag, &(entries.len() as u64)));
+        for entry in entries {
+            for field in entry.as_ref() {
+                self.add_field(field.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends a data field, e.g. RawData (96) with `len_tag` RawDataLength
+    /// (95), preceded by its length field holding the value's byte count.
+    /// The value may contain SOH. A pair not in `DATA_FIELDS` must be passed
+    /// to `DecodeOptions::with_data_fields` to decode again.
+    pub fn add_data_field(&mut self, len_tag: u32, data_tag: u32, value: impl Into<SmallVec<[u8; 32]>>) {
+        let value = value.into();
+        self.add_field(FixField::from_value(len_tag, &(value.len() as u64)));
+        self.add_field(FixField::new(data_tag, value));
     }
 
+    /// Returns the first occurrence of `tag`; see `get_fields` for the
+    /// others
     #[inline]
     pub fn get_field(&self, tag: u32) -> Option<&FixField> {
-        self.fields.get(&tag)
+        self.index.get(&tag).map(|&i| &self.fields[i])
     }
 
-    pub fn encode(&self) -> Result<BytesMut, FixError> {
-        // Pre-calculate message size
-        let estimated_size = self.calculate_message_size()?;
-        let mut buf = BytesMut::with_capacity(estimated_size);
+    /// Every occurrence of `tag` in message order, such as each PartyID of a
+    /// Parties group
+    pub fn get_fields(&self, tag: u32) -> impl Iterator<Item = &FixField> {
+        let first = self.index.get(&tag).copied().unwrap_or(self.fields.len());
+        self.fields[first..].iter().filter(move |field| field.tag() == tag)
+    }
 
-        // Encode BeginString
-        self.encode_field(BEGIN_STRING_TAG, &mut buf)?;
+    /// The entries of the repeating group introduced by `count_tag`, in
+    /// wire order. `members[0]` is the delimiter that starts every entry and
+    /// the group ends at the first field not in `members`; a dictionary's
+    /// `group(count_tag)` lists them that way. Empty when `count_tag` is
+    /// absent. An entry not opening with the delimiter is `InvalidFormat`,
+    /// and a count that does not match the entries found is
+    /// `GroupCountMismatch`.
+    pub fn group(&self, count_tag: u32, members: &[u32]) -> Result<Vec<FixGroupEntry<'_>>, FixError> {
+        let entries = group_entries(self, count_tag, members)?;
+        Ok(entries.into_iter().map(|fields| FixGroupEntry { fields }).collect())
+    }
 
-        // Add body length placeholder - use a small fixed size first
-        let body_length_start = buf.len();
-        buf.extend_from_slice(b"9=");
+    /// Same as `get_fields`
+    #[inline]
+    pub fn get_all_fields(&self, tag: u32) -> impl Iterator<Item = &FixField> {
+        self.get_fields(tag)
+    }
 
-        // Create temporary buffer for rest of message
-        let mut body_buf = BytesMut::with_capacity(estimated_size - body_length_start);
+    /// An owned copy of the first value of `tag`, for keeping a few fields
+    /// after the message is dropped
+    #[inline]
+    pub fn get_owned(&self, tag: u32) -> Option<Vec<u8>> {
+        self.get_field(tag).map(|field| field.value().to_vec())
+    }
 
-        // Encode message type and remaining fields to body buffer
-        self.encode_field(MSG_TYPE_TAG, &mut body_buf)?;
+    /// Like `get_owned`, as a `String`. `None` also when the value is not
+    /// valid UTF-8.
+    pub fn get_str_owned(&self, tag: u32) -> Option<String> {
+        self.get_field(tag)
+            .and_then(|field| std::str::from_utf8(field.value()).ok())
+            .map(str::to_owned)
+    }
 
-        // Batch encode remaining fields
-        for &tag in &self.field_order {
-            if tag != BEGIN_STRING_TAG &&
-                tag != BODY_LENGTH_TAG &&
-                tag != MSG_TYPE_TAG &&
-                tag != CHECKSUM_TAG {
-                self.encode_field(tag, &mut body_buf)?;
+    /// Overlays the fields of `other` onto this message, e.g. per-order fields
+    /// onto a template. Existing fields keep their position and fields only
+    /// present in `other` are appended in its order.
+    pub fn merge(&mut self, other: &FixMessage, policy: MergePolicy) -> Result<(), FixError> {
+        if policy == MergePolicy::Error {
+            if let Some(field) = other.fields.iter().find(|f| self.index.contains_key(&f.tag())) {
+                return Err(FixError::FieldConflict(field.tag()));
             }
         }
 
-        // Now we know the actual body length
-        let body_length = body_buf.len();
+        // Conflicts are judged against the fields present before the merge so
+        // that every occurrence of a tag new to this message is appended
+        let existing = self.fields.len();
+        for field in &other.fields {
+            match self.index.get(&field.tag()) {
+                Some(&i) if i < existing => {
+                    if policy == MergePolicy::Overwrite {
+                        self.set_at(i, field.clone());
+                    }
+                }
+                _ => self.add_field(field.clone()),
+            }
+        }
 
-        // Write the actual body length
-        buf.extend_from_slice(body_length.to_string().as_bytes());
-        buf.put_u8(SOH);
+        Ok(())
+    }
+
+    /// Parses the value of `tag` as `T`, returning `Ok(None)` when it is
+    /// absent. A value that does not parse is `InvalidFieldValueFor` naming
+    /// `tag`, as with the other `get_` accessors.
+    #[inline]
+    pub fn get_value<T: FieldValue>(&self, tag: u32) -> Result<Option<T>, FixError> {
+        self.get_field(tag).map(|field| field.parse_value().map_err(|err| field.with_tag(err))).transpose()
+    }
+
+    /// Writes `value` to `tag`, replacing any existing value in place
+    #[inline]
+    pub fn set_value<T: FieldValue>(&mut self, tag: u32, value: &T) {
+        self.set_field(FixField::from_value(tag, value));
+    }
+
+    /// Reads a text field, failing when it is not UTF-8
+    #[inline]
+    pub fn get_str(&self, tag: u32) -> Result<Option<&str>, FixError> {
+        self.get_field(tag).map(FixField::as_str).transpose()
+    }
+
+    /// Reads a char field such as Side (54) or OrdType (40)
+    #[inline]
+    pub fn get_char(&self, tag: u32) -> Result<Option<char>, FixError> {
+        self.get_field(tag).map(FixField::as_char).transpose()
+    }
 
-        // Add the body
-        buf.extend_from_slice(&body_buf);
+    /// Reads an unsigned integer field: `Ok(None)` when absent and
+    /// `Err(InvalidFieldValueFor)` when present but not plain ASCII digits
+    #[inline]
+    pub fn get_uint(&self, tag: u32) -> Result<Option<u64>, FixError> {
+        self.get_value(tag)
+    }
 
-        // Calculate and add checksum
-        let checksum: u32 = buf.iter().map(|&b| b as u32).sum::<u32>() % 256;
-        let mut checksum_buf = [0u8; 7]; // "10=XXX|"
-        checksum_buf[0..3].copy_from_slice(b"10=");
-        let checksum_str = format!("{:03}", checksum);
-        checksum_buf[3..6].copy_from_slice(checksum_str.as_bytes());
-        checksum_buf[6] = SOH;
-        buf.extend_from_slice(&checksum_buf);
+    /// Signed counterpart of `get_uint`, accepting a leading '-'
+    #[inline]
+    pub fn get_int(&self, tag: u32) -> Result<Option<i64>, FixError> {
+        self.get_value(tag)
+    }
 
+    /// Like `get_int`, also accepting a leading '+', with the lookup and
+    /// parse results kept apart: `None` when absent
+    #[inline]
+    pub fn get_i64(&self, tag: u32) -> Option<Result<i64, FixError>> {
+        self.get_field(tag).map(|field| field.as_i64().map_err(|err| field.with_tag(err)))
+    }
+
+    /// Reads a Boolean ("Y"/"N") field
+    #[inline]
+    pub fn get_bool(&self, tag: u32) -> Result<Option<bool>, FixError> {
+        self.get_value(tag)
+    }
+
+    /// Reads a Boolean field, using `default` when it is absent
+    #[inline]
+    pub fn get_bool_or(&self, tag: u32, default: bool) -> Result<bool, FixError> {
+        self.get_flag(tag, default).map(Flag::get)
+    }
+
+    /// Like `get_bool_or`, but records whether the default was used
+    #[inline]
+    pub fn get_flag(&self, tag: u32, default: bool) -> Result<Flag, FixError> {
+        Ok(self.get_bool(tag)?.map_or(Flag::Defaulted(default), Flag::Explicit))
+    }
+
+    #[inline]
+    pub fn get_decimal(&self, tag: u32) -> Result<Option<FixDecimal>, FixError> {
+        self.get_value(tag)
+    }
+
+    /// Reads a float field; see `FixField::as_f64`
+    #[inline]
+    pub fn get_f64(&self, tag: u32) -> Result<Option<f64>, FixError> {
+        self.get_field(tag).map(FixField::as_f64).transpose()
+    }
+
+    /// Reads a Qty field, rejecting negative quantities
+    #[inline]
+    pub fn get_qty(&self, tag: u32) -> Result<Option<Qty>, FixError> {
+        self.get_value(tag)
+    }
+
+    /// Stores a price or other decimal after applying `format`, failing with
+    /// `PrecisionExceeded` instead of rounding
+    pub fn set_price(&mut self, tag: u32, value: FixDecimal, format: &DecimalFormat) -> Result<(), FixError> {
+        self.set_value(tag, &value.apply_format(format)?);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn get_amt(&self, tag: u32) -> Result<Option<Amt>, FixError> {
+        self.get_value(tag)
+    }
+
+    /// Reads a Percentage field, rejecting values outside [0, 1]
+    #[inline]
+    pub fn get_percentage(&self, tag: u32) -> Result<Option<Percentage>, FixError> {
+        self.get_value(tag)
+    }
+
+    /// MsgType (35), or `None` when the message has none
+    #[inline]
+    pub fn msg_type(&self) -> Option<MsgType> {
+        self.get_field(MSG_TYPE_TAG).and_then(|field| MsgType::from_bytes(field.value()))
+    }
+
+    /// ApplVerID (1128), the application version of a message whose
+    /// BeginString is the transport version `fix_version::FIXT_1_1`; compare
+    /// with the `appl_ver_id` values
+    #[inline]
+    pub fn application_version(&self) -> Option<&[u8]> {
+        self.get_field(Tag::ApplVerID.value()).map(FixField::value)
+    }
+
+    /// MsgSeqNum (34)
+    #[inline]
+    pub fn msg_seq_num(&self) -> Result<Option<SeqNum>, FixError> {
+        self.get_value(Tag::MsgSeqNum.value())
+    }
+
+    /// PossDupFlag (43): the session layer may already have delivered this
+    /// sequence number
+    #[inline]
+    pub fn poss_dup(&self) -> Result<bool, FixError> {
+        self.get_bool_or(Tag::PossDupFlag.value(), false)
+    }
+
+    /// PossResend (97): the application may already have received this
+    /// business content under another sequence number, so it must dedup by
+    /// business key (e.g. ClOrdID)
+    #[inline]
+    pub fn poss_resend(&self) -> Result<bool, FixError> {
+        self.get_bool_or(Tag::PossResend.value(), false)
+    }
+
+    #[inline]
+    pub fn get_utc_timestamp(&self, tag: u32) -> Result<Option<UtcTimestamp>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_utc_timestamp(&mut self, tag: u32, value: UtcTimestamp) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_tz_timestamp(&self, tag: u32) -> Result<Option<TzTimestamp>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_tz_timestamp(&mut self, tag: u32, value: TzTimestamp) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_local_mkt_date(&self, tag: u32) -> Result<Option<LocalMktDate>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_local_mkt_date(&mut self, tag: u32, value: LocalMktDate) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_utc_date_only(&self, tag: u32) -> Result<Option<UtcDateOnly>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_utc_date_only(&mut self, tag: u32, value: UtcDateOnly) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_utc_time_only(&self, tag: u32) -> Result<Option<UtcTimeOnly>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_utc_time_only(&mut self, tag: u32, value: UtcTimeOnly) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_month_year(&self, tag: u32) -> Result<Option<MonthYear>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_month_year(&mut self, tag: u32, value: MonthYear) {
+        self.set_value(tag, &value);
+    }
+
+    #[inline]
+    pub fn get_settl_type(&self, tag: u32) -> Result<Option<SettlType>, FixError> {
+        self.get_value(tag)
+    }
+
+    #[inline]
+    pub fn set_settl_type(&mut self, tag: u32, value: SettlType) {
+        self.set_value(tag, &value);
+    }
+
+    /// Encodes the message for the wire. Only BeginString and MsgType are
+    /// required, failing with `MissingField` otherwise: BodyLength and
+    /// CheckSum are always computed, and any values stored for them are
+    /// ignored. BeginString, BodyLength and MsgType are written first and in
+    /// that order whatever order they were added in.
+    #[inline]
+    pub fn encode(&self) -> Result<BytesMut, FixError> {
+        self.encode_with_options(&EncodeOptions::default())
+    }
+
+    /// Whether encoding, decoding and encoding again reproduces the first
+    /// encoding byte for byte. `encode` normalizes the framing: BeginString,
+    /// BodyLength and MsgType are written first wherever they are stored,
+    /// BodyLength and CheckSum are recomputed, and any other copies of those
+    /// four tags are dropped. Everything else is written as stored, so
+    /// instability points at a value `decode` reads back differently, such as
+    /// an embedded SOH outside a data field or a data field whose length tag
+    /// is missing or wrong. Errors from either pass are returned as is.
+    pub fn is_stable(&self) -> Result<bool, FixError> {
+        let first = self.encode()?;
+        let second = Self::decode(&first)?.encode()?;
+        Ok(first == second)
+    }
+
+    pub fn encode_with_options(&self, options: &EncodeOptions) -> Result<BytesMut, FixError> {
+        let mut buf = BytesMut::new();
+        self.encode_into_with_options(&mut buf, options)?;
         Ok(buf)
     }
 
-    pub fn decode(data: &[u8]) -> Result<Self, FixError> {
-        let mut message = FixMessage::with_capacity(TYPICAL_MESSAGE_FIELDS);
-        let mut pos = 0;
-
-        // Fast path for required header fields
-        pos = Self::extract_field(data, pos, BEGIN_STRING_TAG, &mut message)?;
-        pos = Self::extract_field(data, pos, BODY_LENGTH_TAG, &mut message)?;
-        pos = Self::extract_field(data, pos, MSG_TYPE_TAG, &mut message)?;
-
-        // Process remaining fields using memchr for faster delimiter search
-        while pos < data.len() {
-            if let Some(field_end) = memchr(SOH, &data[pos..]) {
-                let field_data = &data[pos..pos + field_end];
-                if let Some(equals_pos) = memchr(b'=', field_data) {
-                    let tag = unsafe {
-                        // SAFETY: We know this is valid UTF-8 numeric data from FIX protocol
-                        std::str::from_utf8_unchecked(&field_data[..equals_pos])
-                    }.parse::<u32>()
-                        .map_err(|_| FixError::InvalidFormat)?;
-
-                    let value = SmallVec::from_slice(&field_data[equals_pos + 1..]);
-                    message.add_field(FixField::new(tag, value));
+    /// Appends the encoded message to `buf`, e.g. a send buffer already
+    /// holding earlier messages, and returns the number of bytes appended.
+    /// BodyLength and CheckSum cover only those bytes. On error `buf` is
+    /// left as it was.
+    #[inline]
+    pub fn encode_into(&self, buf: &mut BytesMut) -> Result<usize, FixError> {
+        self.encode_into_with_options(buf, &EncodeOptions::default())
+    }
+
+    pub fn encode_into_with_options(&self, buf: &mut BytesMut, options: &EncodeOptions) -> Result<usize, FixError> {
+        let start = buf.len();
+        let written = self.append_encoded(buf, options);
+        if written.is_err() {
+            buf.truncate(start);
+        }
+        written
+    }
+
+    /// Writes the encoded message to `writer`, e.g. a journal file, and
+    /// returns the number of bytes written. An encoding error is returned as
+    /// an `io::Error` wrapping the `FixError`.
+    pub fn encode_to_writer(&self, writer: &mut impl io::Write) -> io::Result<usize> {
+        let encoded = self.encode().map_err(io::Error::other)?;
+        writer.write_all(&encoded)?;
+        Ok(encoded.len())
+    }
+
+    fn append_encoded(&self, buf: &mut BytesMut, options: &EncodeOptions) -> Result<usize, FixError> {
+        let begin_string = self.get_field(BEGIN_STRING_TAG).ok_or(FixError::MissingField(BEGIN_STRING_TAG))?;
+        let msg_type = self.get_field(MSG_TYPE_TAG).ok_or(FixError::MissingField(MSG_TYPE_TAG))?;
+
+        // The body is written in place after a BodyLength as wide as the
+        // estimate, which is exact unless a value rule changes a length
+        let estimated_body = self.estimated_body_len();
+        let mut digits = itoa::Buffer::new();
+        let reserved = digits.format(estimated_body).len();
+        buf.reserve(begin_string.encoded_len() + 3 + reserved + estimated_body + 7);
+
+        let start = buf.len();
+        begin_string.encode(buf);
+        buf.extend_from_slice(b"9=");
+        let length_start = buf.len();
+        buf.put_bytes(b'0', reserved);
+        buf.put_u8(SOH);
+        let body_start = buf.len();
+
+        msg_type.encode(buf);
+        for field in &self.fields {
+            let tag = field.tag();
+            if !is_header_or_trailer(tag) {
+                match Self::apply_value_rules(field, options)? {
+                    Some(rewritten) => rewritten.encode(buf),
+                    None => field.encode(buf),
                 }
-                pos += field_end + 1;
-            } else {
-                return Err(FixError::InvalidFormat);
             }
         }
 
-        // Verify checksum
-        if let Some(checksum_field) = message.get_field(CHECKSUM_TAG) {
-            let calculated_checksum: u32 = data[..data.len() - 7]
-                .iter()
-                .map(|&b| b as u32)
-                .sum::<u32>() % 256;
+        let body_length = buf.len() - body_start;
+        let length = digits.format(body_length).as_bytes();
+        if let Some(max) = options.max_encoded_len {
+            let len = length_start - start + length.len() + 1 + body_length + 7;
+            if len > max {
+                return Err(FixError::MessageTooLarge { len, max });
+            }
+        }
+
+        // A rewritten value took the body length to another number of
+        // digits, so the body moves to make room for it
+        let length_end = length_start + length.len();
+        if length_end + 1 != body_start {
+            let body_end = buf.len();
+            buf.resize(body_end.max(length_end + 1 + body_length), 0);
+            buf.copy_within(body_start..body_end, length_end + 1);
+            buf.truncate(length_end + 1 + body_length);
+            buf[length_end] = SOH;
+        }
+        buf[length_start..length_end].copy_from_slice(length);
+
+        let mut checksum = RollingChecksum::new();
+        checksum.update(&buf[start..]);
+        let sum = checksum.finalize();
+        buf.extend_from_slice(&[b'1', b'0', b'=', b'0' + sum / 100, b'0' + sum / 10 % 10, b'0' + sum % 10, SOH]);
 
-            let received_checksum = unsafe {
-                // SAFETY: We know this is valid UTF-8 numeric data from FIX protocol
-                std::str::from_utf8_unchecked(checksum_field.value())
-            }.parse::<u32>()
-                .map_err(|_| FixError::InvalidFormat)?;
+        Ok(buf.len() - start)
+    }
 
-            if calculated_checksum != received_checksum {
-                return Err(FixError::InvalidChecksum);
+    // The field as rewritten by the per-tag rules in `options`, or `None` when
+    // no rule covers its tag
+    fn apply_value_rules(field: &FixField, options: &EncodeOptions) -> Result<Option<FixField>, FixError> {
+        let tag = field.tag();
+        let mut rewritten = match options.decimal_format(tag) {
+            Some(format) => {
+                let value = field.parse_value::<FixDecimal>()?.apply_format(format)?;
+                Some(FixField::from_value(tag, &value))
             }
-        } else {
-            return Err(FixError::MissingField(CHECKSUM_TAG));
+            None => None,
+        };
+        if let Some(rule) = options.padding_rule(tag) {
+            let value = rule.apply(tag, rewritten.as_ref().unwrap_or(field).value())?;
+            rewritten = Some(FixField::new(tag, value));
         }
+        Ok(rewritten)
+    }
+
+    /// Parses an encoded message, checking that BodyLength matches the
+    /// bytes between it and CheckSum and that CheckSum is correct
+    pub fn decode(data: &[u8]) -> Result<Self, FixError> {
+        Self::decode_with_options(data, &DecodeOptions::default())
+    }
+
+    /// `decode` with the CheckSum and BodyLength checks chosen by `options`,
+    /// e.g. for a legacy bridge that miscomputes one of them
+    pub fn decode_with_options(data: &[u8], options: &DecodeOptions) -> Result<Self, FixError> {
+        Self::decode_checked(data, options, &mut Vec::new())
+    }
+
+    /// `decode_with_options`, also returning what a check turned off by
+    /// `options` would have rejected, e.g. to replay logs with known-bad
+    /// BodyLengths while still recording them
+    pub fn decode_with_warnings(data: &[u8], options: &DecodeOptions) -> Result<(Self, Vec<DecodeWarning>), FixError> {
+        let mut warnings = Vec::new();
+        let message = Self::decode_checked(data, options, &mut warnings)?;
+        Ok((message, warnings))
+    }
+
+    fn decode_checked(data: &[u8], options: &DecodeOptions, warnings: &mut Vec<DecodeWarning>) -> Result<Self, FixError> {
+        Self::decode_scanned(FieldScanner::new(data).with_decode_options(options.clone()), warnings)
+    }
+
+    /// `decode` into `msg`, replacing its fields while reusing its storage:
+    /// the field table and index keep their capacity, and a value longer
+    /// than a field's inline buffer is written over one already on the heap
+    /// where possible. For a hot loop decoding message after message into
+    /// one `FixMessage`. Changes recorded by tracking are dropped; on error
+    /// `msg` is left empty.
+    pub fn decode_into(data: &[u8], msg: &mut FixMessage) -> Result<(), FixError> {
+        msg.read_scanned(FieldScanner::new(data), &mut Vec::new())
+    }
 
+    fn decode_scanned(scanner: FieldScanner<'_>, warnings: &mut Vec<DecodeWarning>) -> Result<Self, FixError> {
+        let mut message = FixMessage::with_capacity(TYPICAL_MESSAGE_FIELDS);
+        message.read_scanned(scanner, warnings)?;
         Ok(message)
     }
 
-    // Possible to remove these iterations, requires bench
+    // Replaces the fields with those scanned, overwriting the existing ones
+    // in place so that their value buffers are reused
+    fn read_scanned(&mut self, mut scanner: FieldScanner<'_>, warnings: &mut Vec<DecodeWarning>) -> Result<(), FixError> {
+        self.index.clear();
+        self.clear_changes();
+        // Only the field table: the index holds one entry per distinct tag,
+        // which a jumbo message of repeating groups has few of
+        self.fields.reserve(field_capacity(scanner.remaining()).saturating_sub(self.fields.len()));
+        let mut len = 0;
+        for (tag, value) in &mut scanner {
+            match self.fields.get_mut(len) {
+                Some(field) => field.overwrite(tag, value),
+                None => self.fields.push(FixField::new(tag, SmallVec::from_slice(value))),
+            }
+            self.index.entry(tag).or_insert(len);
+            len += 1;
+        }
+        self.fields.truncate(len);
+        let checked = scanner.finish_with_warnings(warnings);
+        if checked.is_err() {
+            self.clear();
+        }
+        checked
+    }
+
+    /// Parses an encoded message without copying it: the fields of the
+    /// returned `FixMessageRef` borrow their values from `data`. The checks
+    /// are those of `decode`.
+    pub fn decode_borrowed(data: &[u8]) -> Result<FixMessageRef<'_>, FixError> {
+        FixMessageRef::decode_with_options(data, &DecodeOptions::default())
+    }
+
+    /// Same as `decode_borrowed`
     #[inline]
-    fn calculate_message_size(&self) -> Result<usize, FixError> {
-        let mut size = 0;
+    pub fn decode_view(data: &[u8]) -> Result<FixMessageView<'_>, FixError> {
+        Self::decode_borrowed(data)
+    }
 
-        // Add space for standard fields
-        if let Some(begin_string) = self.get_field(BEGIN_STRING_TAG) {
-            size += begin_string.encoded_len();
-        } else {
-            return Err(FixError::MissingField(BEGIN_STRING_TAG));
+    /// The encoded business body alone: every field that is neither a
+    /// standard header tag (`HEADER_TAGS`) nor a trailer tag
+    /// (`TRAILER_TAGS`), in wire order, each terminated by SOH. For integrity
+    /// schemes layered over FIX that cover only the business content.
+    pub fn business_bytes(&self) -> Result<BytesMut, FixError> {
+        let mut buf = BytesMut::new();
+        for field in self.sections().body() {
+            field.encode(&mut buf);
         }
+        Ok(buf)
+    }
 
-        // Body length field: "9=XXX|"
-        size += 2;  // "9="
-        size += 10; // Maximum length for a typical body length number
-        size += 1;  // SOH
+    /// The encoded fields strictly after the first `after` field and before
+    /// the next `before` field, or to the end of the message when no `before`
+    /// follows. Fails with `MissingField` when `after` is absent.
+    pub fn bytes_between(&self, after: u32, before: u32) -> Result<BytesMut, FixError> {
+        let start = self.index.get(&after).ok_or(FixError::MissingField(after))? + 1;
+        let mut buf = BytesMut::new();
+        for field in self.fields[start..].iter().take_while(|f| f.tag() != before) {
+            field.encode(&mut buf);
+        }
+        Ok(buf)
+    }
 
-        // Add remaining fields
-        for &tag in &self.field_order {
-            if tag != BEGIN_STRING_TAG && tag != BODY_LENGTH_TAG {
-                if let Some(field) = self.get_field(tag) {
-                    size += field.encoded_len();
-                } else {
-                    return Err(FixError::MissingField(tag));
+    /// The '|'-delimited form used in QuickFIX logs and documentation, with
+    /// BodyLength and CheckSum recomputed. A message that cannot be encoded
+    /// (e.g. without BeginString) is printed field by field as stored.
+    pub fn to_print_string(&self) -> String {
+        let bytes = match self.encode() {
+            Ok(encoded) => encoded.to_vec(),
+            Err(_) => {
+                let mut buf = BytesMut::with_capacity(self.fields.iter().map(FixField::encoded_len).sum());
+                for field in &self.fields {
+                    field.encode(&mut buf);
                 }
+                buf.to_vec()
+            }
+        };
+        String::from_utf8_lossy(&bytes)
+            .chars()
+            .map(|c| if c == SOH as char { PRINT_DELIMITER } else { c })
+            .collect()
+    }
+
+    /// `encode` with each field ended by `delimiter` instead of SOH, e.g.
+    /// b'|' for logs and tests. Only the SOH form is valid FIX on the wire:
+    /// the CheckSum is always that of the SOH form, so the output verifies
+    /// once its delimiters are turned back into SOH, and read back with
+    /// `decode_with_delimiter`. Data fields keep their value as is, even one
+    /// containing SOH or `delimiter`.
+    pub fn encode_with_delimiter(&self, delimiter: u8) -> Result<BytesMut, FixError> {
+        let mut encoded = self.encode()?;
+        if delimiter != SOH {
+            let base = encoded.as_ptr() as usize;
+            let ends: Vec<usize> =
+                FieldScanner::new(&encoded).map(|(_, value)| value.as_ptr() as usize - base + value.len()).collect();
+            for end in ends {
+                encoded[end] = delimiter;
             }
         }
+        Ok(encoded)
+    }
 
-        // Checksum field: "10=XXX|"
-        size += 7;
+    /// `decode` of a message whose fields end with `delimiter` rather than
+    /// SOH, as written by `encode_with_delimiter`. The CheckSum is checked
+    /// against the SOH form.
+    pub fn decode_with_delimiter(data: &[u8], delimiter: u8) -> Result<Self, FixError> {
+        Self::decode_scanned(FieldScanner::new(data).with_delimiter(delimiter), &mut Vec::new())
+    }
 
-        Ok(size)
+    /// Parses the '|'-delimited QuickFIX log form. The trailing '|' and the
+    /// CheckSum field may be omitted, as they often are in pasted log lines,
+    /// and BodyLength is then not checked either; a CheckSum that is present
+    /// must be correct, as must BodyLength. Values containing '|' cannot be
+    /// represented in this form.
+    pub fn from_print_string(s: &str) -> Result<Self, FixError> {
+        let mut data: Vec<u8> = s
+            .trim_end()
+            .bytes()
+            .map(|b| if b == PRINT_DELIMITER as u8 { SOH } else { b })
+            .collect();
+        if data.last() != Some(&SOH) {
+            data.push(SOH);
+        }
+
+        let last_field = data[..data.len() - 1]
+            .iter()
+            .rposition(|&b| b == SOH)
+            .map_or(0, |i| i + 1);
+        if data[last_field..].starts_with(b"10=") {
+            return Self::decode(&data);
+        }
+        let mut checksum = RollingChecksum::new();
+        checksum.update(&data);
+        data.extend_from_slice(b"10=");
+        data.extend_from_slice(format!("{:03}", checksum.finalize()).as_bytes());
+        data.push(SOH);
+        Self::decode_with_options(&data, &DecodeOptions::default().with_verify_body_length(false))
     }
 
-    #[inline]
-    fn encode_field(&self, tag: u32, buf: &mut BytesMut) -> Result<(), FixError> {
-        self.get_field(tag)
-            .ok_or(FixError::MissingField(tag))
-            .map(|field| field.encode(buf))
-    }
-
-    #[inline]
-    fn extract_field(
-        data: &[u8],
-        start_pos: usize,
-        expected_tag: u32,
-        message: &mut FixMessage,
-    ) -> Result<usize, FixError> {
-        if let Some(field_end) = memchr(SOH, &data[start_pos..]) {
-            let field_data = &data[start_pos..start_pos + field_end];
-            if let Some(equals_pos) = memchr(b'=', field_data) {
-                let tag = unsafe {
-                    // SAFETY: We know this is valid UTF-8 numeric data from FIX protocol
-                    std::str::from_utf8_unchecked(&field_data[..equals_pos])
-                }.parse::<u32>()
-                    .map_err(|_| FixError::InvalidFormat)?;
-
-                if tag != expected_tag {
-                    return Err(FixError::InvalidFormat);
-                }
+    /// Cleans up an inbound message before it is stored or forwarded: drops
+    /// BodyLength and CheckSum, which `encode` regenerates, after checking
+    /// that BeginString, MsgType, SenderCompID, TargetCompID, MsgSeqNum and
+    /// SendingTime are present. The message is left unchanged on error.
+    pub fn normalize(&mut self) -> Result<(), FixError> {
+        self.normalize_with(false)
+    }
+
+    /// `normalize`, also moving the header into the canonical order of
+    /// `validate_header_order` when `canonical_order` is set. Other header
+    /// fields follow in their received order, then the body and the trailer.
+    pub fn normalize_with(&mut self, canonical_order: bool) -> Result<(), FixError> {
+        for tag in [BEGIN_STRING_TAG, MSG_TYPE_TAG].into_iter().chain(ORDERED_HEADER_TAGS) {
+            if !self.index.contains_key(&tag) {
+                return Err(FixError::MissingField(tag));
+            }
+        }
 
-                let value = SmallVec::from_slice(&field_data[equals_pos + 1..]);
-                message.add_field(FixField::new(tag, value));
-                Ok(start_pos + field_end + 1)
-            } else {
-                Err(FixError::InvalidFormat)
+        if let Some(journal) = &mut self.journal {
+            for field in self.fields.iter().filter(|f| f.tag() == BODY_LENGTH_TAG || f.tag() == CHECKSUM_TAG) {
+                journal.record(field.tag(), Some(field.value()), None, ChangeOp::Remove);
             }
+        }
+        self.fields.retain(|f| f.tag() != BODY_LENGTH_TAG && f.tag() != CHECKSUM_TAG);
+        if canonical_order {
+            // A stable sort, so groups and data fields keep their layout
+            self.fields.sort_by_key(|f| match f.tag() {
+                BEGIN_STRING_TAG => 0,
+                MSG_TYPE_TAG => 1,
+                tag => match ORDERED_HEADER_TAGS.iter().position(|&t| t == tag) {
+                    Some(rank) => 2 + rank,
+                    None if is_header_tag(tag) => 2 + ORDERED_HEADER_TAGS.len(),
+                    None if is_trailer_tag(tag) => 4 + ORDERED_HEADER_TAGS.len(),
+                    None => 3 + ORDERED_HEADER_TAGS.len(),
+                },
+            });
+        }
+
+        self.reindex();
+        Ok(())
+    }
+
+    /// Replaces the first occurrence of the field's tag where it stands, or
+    /// appends the field when the tag is absent. Later occurrences, e.g. in
+    /// a repeating group, are left alone.
+    #[inline]
+    pub fn set_field(&mut self, field: FixField) {
+        if let Some(&i) = self.index.get(&field.tag()) {
+            self.set_at(i, field);
         } else {
-            Err(FixError::InvalidFormat)
+            self.add_field(field);
+        }
+    }
+
+    /// Removes every occurrence of `tag`, returning the first, or `None`
+    /// leaving the message untouched when it has no such field
+    pub fn remove_field(&mut self, tag: u32) -> Option<FixField> {
+        let first = self.index.get(&tag).map(|&i| self.fields[i].clone())?;
+        if let Some(journal) = &mut self.journal {
+            journal.record(tag, Some(first.value()), None, ChangeOp::Remove);
         }
+        self.fields.retain(|f| f.tag() != tag);
+        self.reindex();
+        Some(first)
+    }
+
+    /// Starts recording every field added, replaced or removed, e.g. to
+    /// show an audit trail of what a rewrite step changed. Until this is
+    /// called mutations cost no more than a check for the journal.
+    pub fn enable_tracking(&mut self) {
+        self.journal.get_or_insert_with(Default::default);
+    }
+
+    /// Stops recording and drops the changes recorded
+    pub fn disable_tracking(&mut self) {
+        self.journal = None;
+    }
+
+    #[inline]
+    pub fn is_tracking(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Changes recorded since tracking was enabled or last cleared, oldest
+    /// first; empty when tracking is off
+    pub fn changes(&self) -> &[FieldChange] {
+        self.journal.as_ref().map_or(&[], |journal| &journal.changes)
+    }
+
+    /// Forgets the changes recorded, leaving tracking on
+    pub fn clear_changes(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.changes.clear();
+        }
+    }
+
+    /// The changes recorded, leaving tracking on with none
+    pub fn take_changes(&mut self) -> Vec<FieldChange> {
+        self.journal.as_mut().map(|journal| std::mem::take(&mut journal.changes)).unwrap_or_default()
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (i, field) in self.fields.iter().enumerate() {
+            self.index.entry(field.tag()).or_insert(i);
+        }
+    }
+
+    /// Verifies an inbound message carries its header in the canonical order:
+    /// 8, 9 and 35 first, followed by 49, 56, 34 and 52 (when present) ahead
+    /// of any body field. Unlike `encode`, which always writes a normalized
+    /// header, this checks the order the fields were received in.
+    pub fn validate_header_order(&self) -> Result<(), FixError> {
+        for tag in [BEGIN_STRING_TAG, BODY_LENGTH_TAG, MSG_TYPE_TAG] {
+            if !self.index.contains_key(&tag) {
+                return Err(FixError::MissingField(tag));
+            }
+        }
+
+        let mut tags = self.field_tags().copied();
+        for expected in [BEGIN_STRING_TAG, BODY_LENGTH_TAG, MSG_TYPE_TAG] {
+            match tags.next() {
+                Some(tag) if tag == expected => {}
+                Some(tag) => return Err(FixError::HeaderOutOfOrder(tag)),
+                None => return Err(FixError::MissingField(expected)),
+            }
+        }
+
+        // Rank of the last ordered header tag seen; `None` once the body starts
+        let mut last_rank = Some(0);
+        for tag in tags {
+            if tag == BEGIN_STRING_TAG || tag == BODY_LENGTH_TAG || tag == MSG_TYPE_TAG {
+                return Err(FixError::HeaderOutOfOrder(tag));
+            }
+
+            if let Some(rank) = ORDERED_HEADER_TAGS.iter().position(|&t| t == tag) {
+                match last_rank {
+                    Some(last) if rank >= last => last_rank = Some(rank),
+                    _ => return Err(FixError::HeaderOutOfOrder(tag)),
+                }
+            } else if is_header_tag(tag) {
+                if last_rank.is_none() {
+                    return Err(FixError::HeaderOutOfOrder(tag));
+                }
+            } else if tag != CHECKSUM_TAG {
+                last_rank = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Length of the body `encode` writes, before any value rules: MsgType
+    // and every field it does not write itself
+    #[inline]
+    fn estimated_body_len(&self) -> usize {
+        let msg_type = self.get_field(MSG_TYPE_TAG).map_or(0, FixField::encoded_len);
+        let fields = self.fields.iter().filter(|field| !is_header_or_trailer(field.tag()));
+        msg_type + fields.map(FixField::encoded_len).sum::<usize>()
+    }
+
+    #[inline]
+    fn set_at(&mut self, i: usize, field: FixField) {
+        if let Some(journal) = &mut self.journal {
+            journal.record(field.tag(), Some(self.fields[i].value()), Some(field.value()), ChangeOp::Set);
+        }
+        self.fields[i] = field;
     }
 
     #[inline]
@@ -236,13 +1023,390 @@ impl FixMessage {
     /// Returns an iterator over the field tags in their original order
     #[inline]
     pub fn field_tags(&self) -> impl Iterator<Item = &u32> {
-        self.field_order.iter()
+        self.fields.iter().map(FixField::tag_ref)
+    }
+
+    /// Every field in wire order, each occurrence of a repeated tag included,
+    /// e.g. to write a custom serializer or compare two messages
+    #[inline]
+    pub fn fields(&self) -> impl Iterator<Item = &FixField> {
+        self.fields.iter()
+    }
+
+    /// Splits the fields into header, body and trailer using `HEADER_TAGS`
+    /// and `TRAILER_TAGS`
+    #[inline]
+    pub fn sections(&self) -> Sections<'_> {
+        Sections { fields: &self.fields }
+    }
+
+    /// All fields in wire order, for walking repeating groups
+    #[inline]
+    pub(crate) fn field_slice(&self) -> &[FixField] {
+        &self.fields
+    }
+
+    /// Removes every field, keeping the storage for the fields that follow,
+    /// e.g. from `decode_into`. Tracking, if on, stays on with no changes
+    /// recorded.
+    pub fn clear(&mut self) {
+        self.fields.clear();
+        self.index.clear();
+        self.clear_changes();
     }
 
     /// Returns the capacity of the internal storage
     #[inline]
     #[cfg(test)]
     pub(crate) fn capacity(&self) -> usize {
-        self.field_order.capacity()
+        self.fields.capacity()
+    }
+}
+
+/// How many more bytes are needed to complete the message at the start of
+/// `data`, worked out from its BodyLength: `Some(n)` while the frame is
+/// incomplete and `None` once it is whole. Fails as `frame_len` does while
+/// the header is incomplete or malformed.
+pub fn bytes_needed(data: &[u8]) -> Result<Option<usize>, FixError> {
+    Ok(frame_len(data)?.checked_sub(data.len()).filter(|&n| n > 0))
+}
+
+/// Total length of the message at the start of `data`, from BeginString
+/// through CheckSum, as declared by its BodyLength. Fails with
+/// `FixError::IncompleteHeader` while BeginString or BodyLength is still
+/// arriving, and with `FixError::InvalidFormat` if they are malformed.
+pub fn frame_len(data: &[u8]) -> Result<usize, FixError> {
+    // Longest BodyLength value accepted, far beyond any real message
+    const MAX_BODY_LENGTH_DIGITS: usize = 9;
+    // "10=nnn" and its SOH
+    const CHECKSUM_FIELD_LEN: usize = 7;
+
+    let prefix_of = |expected: &[u8], got: &[u8]| expected.starts_with(&got[..got.len().min(expected.len())]);
+
+    if !prefix_of(b"8=", data) {
+        return Err(FixError::InvalidFormat);
+    }
+    let Some(begin_string_end) = memchr(SOH, data) else {
+        return Err(FixError::IncompleteHeader);
+    };
+
+    let rest = &data[begin_string_end + 1..];
+    if !prefix_of(b"9=", rest) {
+        return Err(FixError::InvalidFormat);
+    }
+    let digits = rest.get(2..).unwrap_or_default();
+    let digits_len = digits.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits_len > MAX_BODY_LENGTH_DIGITS {
+        return Err(FixError::InvalidFormat);
+    }
+    match digits.get(digits_len) {
+        None => return Err(FixError::IncompleteHeader),
+        Some(&SOH) if digits_len > 0 => {}
+        Some(_) => return Err(FixError::InvalidFormat),
+    }
+
+    let body_length = parse_uint(&digits[..digits_len])? as usize;
+    Ok(begin_string_end + 1 + 2 + digits_len + 1 + body_length + CHECKSUM_FIELD_LEN)
+}
+
+// The error for a message `len` bytes long by its BodyLength that runs past
+// the end of `data`, counting what there is after BodyLength as its body
+fn truncated_body(data: &[u8], len: usize) -> FixError {
+    // frame_len has found both header fields complete; the body is followed
+    // by "10=nnn" and its SOH
+    let header_len = memchr(SOH, data)
+        .and_then(|end| memchr(SOH, &data[end + 1..]).map(|len| end + len + 2))
+        .unwrap_or_default();
+    FixError::InvalidBodyLength {
+        declared: len.saturating_sub(header_len + 7) as u64,
+        actual: data.len().saturating_sub(header_len) as u64,
+    }
+}
+
+/// The standard header of an encoded message as `decode_header` found it,
+/// each value borrowed from the buffer
+#[derive(Debug, Clone)]
+pub struct HeaderFields<'a> {
+    fields: SmallVec<[(u32, &'a [u8]); 12]>,
+}
+
+impl<'a> HeaderFields<'a> {
+    /// First value of `tag` in the header
+    pub fn get(&self, tag: u32) -> Option<&'a [u8]> {
+        self.fields.iter().find(|&&(t, _)| t == tag).map(|&(_, value)| value)
+    }
+
+    #[inline]
+    pub fn msg_type(&self) -> &'a [u8] {
+        self.fields[2].1
+    }
+
+    /// Tags and values in wire order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a [u8])> + '_ {
+        self.fields.iter().copied()
+    }
+}
+
+/// Reads the standard header at the start of `data`, from BeginString,
+/// BodyLength and MsgType up to the first body field, without decoding the
+/// body or verifying the CheckSum. A cheap look at a message before deciding
+/// whether to `FixMessage::decode` it.
+pub fn decode_header(data: &[u8]) -> Result<HeaderFields<'_>, FixError> {
+    let mut scanner = FieldScanner::new(data).with_decode_options(DecodeOptions::default().with_verify_checksum(false));
+    let mut fields = SmallVec::new();
+    for (tag, value) in &mut scanner {
+        if fields.len() >= 3 && !is_header_tag(tag) {
+            break;
+        }
+        fields.push((tag, value));
+    }
+    // Any malformed header is InvalidFormat here, even a missing BodyLength
+    // that `decode` reports as such
+    if scanner.error().is_some() {
+        return Err(FixError::InvalidFormat);
+    }
+
+    if fields.len() < 3 {
+        return Err(FixError::IncompleteHeader);
+    }
+    Ok(HeaderFields { fields })
+}
+
+// Fields a borrowed message holds before spilling to the heap. At twelve
+// bytes an entry, enough for all but the largest messages.
+const BORROWED_INLINE_FIELDS: usize = 4 * TYPICAL_MESSAGE_FIELDS;
+
+/// A decoded message whose values borrow from the buffer it was decoded
+/// from, see `FixMessage::decode_borrowed`. Reading a message this way copies
+/// no values, so a handler that looks at a few fields and moves on pays for
+/// no allocation at all; `to_owned` copies it into a `FixMessage` to keep.
+#[derive(Debug, Clone)]
+pub struct FixMessageRef<'a> {
+    data: &'a [u8],
+    // Tag and the start and end offsets of its value in `data`
+    fields: SmallVec<[(u32, u32, u32); BORROWED_INLINE_FIELDS]>,
+}
+
+/// Same as `FixMessageRef`, as returned by `FixMessage::decode_view`
+pub type FixMessageView<'a> = FixMessageRef<'a>;
+
+impl<'a> FixMessageRef<'a> {
+    /// Same as `FixMessage::decode_borrowed`
+    pub fn decode(data: &'a [u8]) -> Result<Self, FixError> {
+        Self::decode_with_options(data, &DecodeOptions::default())
+    }
+
+    /// `decode` with the CheckSum and BodyLength checks chosen by `options`.
+    /// Offsets are 32-bit, so a buffer over 4 GiB is `MessageTooLarge`.
+    pub fn decode_with_options(data: &'a [u8], options: &DecodeOptions) -> Result<Self, FixError> {
+        let max = u32::MAX as usize;
+        if data.len() > max {
+            return Err(FixError::MessageTooLarge { len: data.len(), max });
+        }
+        let mut scanner = FieldScanner::new(data).with_decode_options(options.clone());
+        let mut fields = SmallVec::new();
+        fields.reserve(field_capacity(data.len()));
+        fields.extend(scanner.by_ref().map(|(tag, value)| {
+            let start = value.as_ptr() as usize - data.as_ptr() as usize;
+            (tag, start as u32, (start + value.len()) as u32)
+        }));
+        scanner.finish()?;
+        Ok(Self { data, fields })
+    }
+
+    #[inline]
+    fn value(&self, &(_, start, end): &(u32, u32, u32)) -> &'a [u8] {
+        &self.data[start as usize..end as usize]
+    }
+
+    /// First value of `tag`
+    pub fn get_field(&self, tag: u32) -> Option<&'a [u8]> {
+        self.fields.iter().find(|field| field.0 == tag).map(|field| self.value(field))
+    }
+
+    /// Every value of `tag` in message order
+    pub fn get_fields(&self, tag: u32) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.fields.iter().filter(move |field| field.0 == tag).map(|field| self.value(field))
+    }
+
+    /// The field tags in their original order
+    pub fn field_tags(&self) -> impl Iterator<Item = u32> + '_ {
+        self.fields.iter().map(|field| field.0)
+    }
+
+    /// Tags and values in wire order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a [u8])> + '_ {
+        self.fields.iter().map(|field| (field.0, self.value(field)))
     }
+
+    #[inline]
+    pub fn msg_type(&self) -> &'a [u8] {
+        self.value(&self.fields[2])
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Always false for a decoded message, which has at least its header
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// An owning copy, the same as `FixMessage::decode` gives for the bytes
+    pub fn to_owned(&self) -> FixMessage {
+        let mut message = FixMessage::with_capacity(self.fields.len());
+        for (tag, value) in self.iter() {
+            message.add_field(FixField::new(tag, SmallVec::from_slice(value)));
+        }
+        message
+    }
+
+    /// The entries of the repeating group introduced by `count_tag`, split
+    /// as for a decoded `FixMessage` but one at a time and borrowing from the
+    /// buffer, so the thousands of entries of a mass quote can be handled
+    /// without copying any. `members[0]` starts every entry and the group
+    /// ends at the first field not in `members`. No entries are yielded when
+    /// `count_tag` is absent, and an empty `members` yields `InvalidFormat`
+    /// for a non-empty group.
+    pub fn group<'m>(&'m self, count_tag: u32, members: &'m [u32]) -> GroupEntries<'m, 'a> {
+        let mut entries = GroupEntries {
+            data: self.data,
+            rest: &[],
+            members,
+            count_tag,
+            declared: 0,
+            yielded: 0,
+            error: None,
+            done: false,
+        };
+        if let Some(pos) = self.fields.iter().position(|field| field.0 == count_tag) {
+            match parse_digits(self.value(&self.fields[pos])) {
+                Ok(declared) => entries.declared = declared as usize,
+                Err(err) => entries.error = Some(err),
+            }
+            entries.rest = &self.fields[pos + 1..];
+        }
+        entries
+    }
+}
+
+/// Iterator over the entries of a repeating group of a `FixMessageRef`, see
+/// `FixMessageRef::group`. An entry not opening with the delimiter field
+/// yields `InvalidFormat`, and a NoXxx count that does not match the entries
+/// found yields `GroupCountMismatch` after the last of them; either ends the
+/// iteration.
+#[derive(Debug)]
+pub struct GroupEntries<'m, 'a> {
+    data: &'a [u8],
+    // The fields after the count field, to the end of the message
+    rest: &'m [(u32, u32, u32)],
+    members: &'m [u32],
+    count_tag: u32,
+    declared: usize,
+    yielded: usize,
+    error: Option<FixError>,
+    done: bool,
+}
+
+impl<'m, 'a> Iterator for GroupEntries<'m, 'a> {
+    type Item = Result<GroupEntryRef<'m, 'a>, FixError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if let Some(err) = self.error.take() {
+            self.done = true;
+            return Some(Err(err));
+        }
+        let Some(&delimiter) = self.members.first() else {
+            // Without a delimiter no entry can be split off
+            self.done = true;
+            return (self.declared > 0).then_some(Err(FixError::InvalidFormat));
+        };
+        let Some(&(first, _, _)) = self.rest.first().filter(|field| self.members.contains(&field.0)) else {
+            self.done = true;
+            if self.yielded != self.declared {
+                let (count_tag, declared, actual) = (self.count_tag, self.declared, self.yielded);
+                return Some(Err(FixError::GroupCountMismatch { count_tag, declared, actual }));
+            }
+            return None;
+        };
+        if first != delimiter {
+            self.done = true;
+            return Some(Err(FixError::InvalidFormat));
+        }
+        let len = self.rest[1..]
+            .iter()
+            .position(|field| field.0 == delimiter || !self.members.contains(&field.0))
+            .map_or(self.rest.len(), |len| len + 1);
+        let (entry, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        self.yielded += 1;
+        Some(Ok(GroupEntryRef { data: self.data, fields: entry }))
+    }
+}
+
+/// One entry of a repeating group, borrowing from the decoded buffer
+#[derive(Debug, Clone, Copy)]
+pub struct GroupEntryRef<'m, 'a> {
+    data: &'a [u8],
+    fields: &'m [(u32, u32, u32)],
+}
+
+impl<'m, 'a: 'm> GroupEntryRef<'m, 'a> {
+    /// Value of `tag` in this entry
+    pub fn get_field(&self, tag: u32) -> Option<&'a [u8]> {
+        self.iter().find(|&(t, _)| t == tag).map(|(_, value)| value)
+    }
+
+    /// Tags and values in wire order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a [u8])> + 'm {
+        let data = self.data;
+        self.fields.iter().map(move |&(tag, start, end)| (tag, &data[start as usize..end as usize]))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Always false: an entry holds at least its delimiter field
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// Decodes every message in `data`, e.g. a capture file, together with the
+/// offset it starts at. Bytes before a message start (see
+/// `framing::find_message_start`) are skipped. A message that fails to
+/// frame or decode is yielded as an error at its offset, and decoding
+/// resumes at the next message start after it rather than trusting its
+/// BodyLength. One whose BodyLength runs past the end of `data` fails with
+/// `FixError::InvalidBodyLength`.
+pub fn decode_stream(data: &[u8]) -> impl Iterator<Item = (Result<FixMessage, FixError>, usize)> + '_ {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let start = pos + find_message_start(&data[pos..])?;
+        let rest = &data[start..];
+        let decoded = match frame_len(rest) {
+            Ok(len) if len <= rest.len() => FixMessage::decode(&rest[..len]).map(|msg| (msg, len)),
+            Ok(len) => Err(truncated_body(rest, len)),
+            Err(err) => Err(err),
+        };
+        match decoded {
+            Ok((msg, len)) => {
+                pos = start + len;
+                Some((Ok(msg), start))
+            }
+            Err(err) => {
+                pos = start + 1;
+                Some((Err(err), start))
+            }
+        }
+    })
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use super::group_entries;
+use crate::decimal::{FixDecimal, Qty};
+use crate::error::FixError;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+
+const ALLOC_ACCOUNT: u32 = Tag::AllocAccount.value();
+const ALLOC_QTY: u32 = Tag::AllocQty.value();
+
+pub(crate) const ALLOC_GROUP: [u32; 2] = [ALLOC_ACCOUNT, ALLOC_QTY];
+
+/// Checks that the AllocQty (80) of the NoAllocs (78) entries add up to
+/// Quantity (53), as an AllocationInstruction (35=J) splitting an order
+/// across accounts must. The sum is exact, whatever the scale of each
+/// AllocQty, and one that overflows is `DecimalOverflow`. A message without
+/// NoAllocs allocates nothing and passes; an entry without AllocQty is
+/// `MissingField(80)` and a sum that differs is `AllocationMismatch`.
+pub fn validate_allocations(msg: &FixMessage) -> Result<(), FixError> {
+    let entries = group_entries(msg, Tag::NoAllocs.value(), &ALLOC_GROUP)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let quantity = msg
+        .get_qty(Tag::Quantity.value())?
+        .ok_or(FixError::MissingField(Tag::Quantity.value()))?
+        .value();
+
+    let mut allocated = FixDecimal::ZERO;
+    for entry in entries {
+        let field = entry
+            .iter()
+            .find(|field| field.tag() == ALLOC_QTY)
+            .ok_or(FixError::MissingField(ALLOC_QTY))?;
+        let qty: Qty = field.parse_value().map_err(|err| field.with_tag(err))?;
+        allocated = allocated.checked_add(qty.value())?;
+    }
+    if allocated != quantity {
+        return Err(FixError::AllocationMismatch { allocated, quantity });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::tags::msg_type;
+
+    fn allocation(quantity: &str, allocs: &[(&str, &str)]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::ALLOCATION_INSTRUCTION.to_vec()));
+        msg.add_field(FixField::new(Tag::Quantity.value(), quantity.as_bytes().to_vec()));
+        msg.add_field(FixField::from_value(Tag::NoAllocs.value(), &(allocs.len() as u64)));
+        for (account, qty) in allocs {
+            msg.add_field(FixField::new(ALLOC_ACCOUNT, account.as_bytes().to_vec()));
+            msg.add_field(FixField::new(ALLOC_QTY, qty.as_bytes().to_vec()));
+        }
+        msg
+    }
+
+    #[test]
+    fn test_allocations_sum_to_quantity() {
+        validate_allocations(&allocation("1000", &[("A1", "600"), ("A2", "400")])).unwrap();
+        // Scales may differ between the entries and Quantity
+        validate_allocations(&allocation("100.5", &[("A1", "50.25"), ("A2", "50.250")])).unwrap();
+
+        let short = validate_allocations(&allocation("1000", &[("A1", "600"), ("A2", "300")]));
+        assert!(matches!(
+            short,
+            Err(FixError::AllocationMismatch { allocated, quantity })
+                if allocated == "900".parse().unwrap() && quantity == "1000".parse().unwrap()
+        ));
+
+        let overflow = validate_allocations(&allocation("1", &[("A1", "9223372036854775807"), ("A2", "1")]));
+        assert!(matches!(overflow, Err(FixError::DecimalOverflow)));
+    }
+
+    #[test]
+    fn test_invalid_allocations() {
+        let mut msg = allocation("1000", &[("A1", "1000")]);
+        msg.remove_field(Tag::Quantity.value());
+        assert!(matches!(validate_allocations(&msg), Err(FixError::MissingField(53))));
+
+        let negative = validate_allocations(&allocation("1000", &[("A1", "1100"), ("A2", "-100")]));
+        assert!(matches!(negative, Err(FixError::InvalidFieldValueFor { tag: 80 })));
+
+        let mut no_qty = allocation("1000", &[]);
+        no_qty.set_value(Tag::NoAllocs.value(), &1u64);
+        no_qty.add_field(FixField::new(ALLOC_ACCOUNT, b"A1".to_vec()));
+        assert!(matches!(validate_allocations(&no_qty), Err(FixError::MissingField(80))));
+
+        // Nothing allocated, nothing to check
+        let mut unallocated = FixMessage::new();
+        unallocated.add_field(FixField::new(Tag::Quantity.value(), b"1000".to_vec()));
+        validate_allocations(&unallocated).unwrap();
+    }
+}
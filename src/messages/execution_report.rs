@@ -0,0 +1,290 @@
+use super::{require_msg_type, required, single_char};
+use crate::decimal::{FixDecimal, Qty};
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::{msg_type, Tag};
+
+/// OrdStatus (39) values
+pub mod ord_status {
+    pub const NEW: u8 = b'0';
+    pub const PARTIALLY_FILLED: u8 = b'1';
+    pub const FILLED: u8 = b'2';
+    pub const DONE_FOR_DAY: u8 = b'3';
+    pub const CANCELED: u8 = b'4';
+    /// FIX 4.2 and earlier
+    pub const REPLACED: u8 = b'5';
+    pub const PENDING_CANCEL: u8 = b'6';
+    pub const STOPPED: u8 = b'7';
+    pub const REJECTED: u8 = b'8';
+    pub const SUSPENDED: u8 = b'9';
+    pub const PENDING_NEW: u8 = b'A';
+    pub const CALCULATED: u8 = b'B';
+    pub const EXPIRED: u8 = b'C';
+    pub const ACCEPTED_FOR_BIDDING: u8 = b'D';
+    pub const PENDING_REPLACE: u8 = b'E';
+
+    /// Every value, in code order
+    pub const ALL: [u8; 15] = *b"0123456789ABCDE";
+}
+
+/// ExecType (150) values
+pub mod exec_type {
+    pub const NEW: u8 = b'0';
+    /// FIX 4.2 and earlier; Trade since
+    pub const PARTIAL_FILL: u8 = b'1';
+    /// FIX 4.2 and earlier; Trade since
+    pub const FILL: u8 = b'2';
+    pub const DONE_FOR_DAY: u8 = b'3';
+    pub const CANCELED: u8 = b'4';
+    pub const REPLACED: u8 = b'5';
+    pub const PENDING_CANCEL: u8 = b'6';
+    pub const STOPPED: u8 = b'7';
+    pub const REJECTED: u8 = b'8';
+    pub const SUSPENDED: u8 = b'9';
+    pub const PENDING_NEW: u8 = b'A';
+    pub const CALCULATED: u8 = b'B';
+    pub const EXPIRED: u8 = b'C';
+    pub const RESTATED: u8 = b'D';
+    pub const PENDING_REPLACE: u8 = b'E';
+    pub const TRADE: u8 = b'F';
+    pub const ORDER_STATUS: u8 = b'I';
+}
+
+/// ExecutionReport (35=8) with the order and fill fields order handling
+/// needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub order_id: Vec<u8>,
+    pub cl_ord_id: Option<Vec<u8>>,
+    /// Set when the report answers a cancel or cancel/replace request
+    pub orig_cl_ord_id: Option<Vec<u8>>,
+    pub exec_id: Vec<u8>,
+    pub exec_type: u8,
+    pub ord_status: u8,
+    pub side: u8,
+    pub symbol: Option<Vec<u8>>,
+    pub order_qty: Option<Qty>,
+    pub last_qty: Option<Qty>,
+    pub last_px: Option<FixDecimal>,
+    pub leaves_qty: Qty,
+    pub cum_qty: Qty,
+    pub avg_px: Option<FixDecimal>,
+    pub text: Option<Vec<u8>>,
+}
+
+impl ExecutionReport {
+    pub fn new(
+        order_id: impl Into<Vec<u8>>,
+        exec_id: impl Into<Vec<u8>>,
+        exec_type: u8,
+        ord_status: u8,
+        side: u8,
+        leaves_qty: Qty,
+        cum_qty: Qty,
+    ) -> Self {
+        Self {
+            order_id: order_id.into(),
+            cl_ord_id: None,
+            orig_cl_ord_id: None,
+            exec_id: exec_id.into(),
+            exec_type,
+            ord_status,
+            side,
+            symbol: None,
+            order_qty: None,
+            last_qty: None,
+            last_px: None,
+            leaves_qty,
+            cum_qty,
+            avg_px: None,
+            text: None,
+        }
+    }
+
+    pub fn with_cl_ord_id(mut self, cl_ord_id: impl Into<Vec<u8>>) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.into());
+        self
+    }
+
+    pub fn with_orig_cl_ord_id(mut self, orig_cl_ord_id: impl Into<Vec<u8>>) -> Self {
+        self.orig_cl_ord_id = Some(orig_cl_ord_id.into());
+        self
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<Vec<u8>>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_order_qty(mut self, order_qty: Qty) -> Self {
+        self.order_qty = Some(order_qty);
+        self
+    }
+
+    /// LastQty (32) and LastPx (31) of a fill
+    pub fn with_fill(mut self, last_qty: Qty, last_px: FixDecimal) -> Self {
+        self.last_qty = Some(last_qty);
+        self.last_px = Some(last_px);
+        self
+    }
+
+    pub fn with_avg_px(mut self, avg_px: FixDecimal) -> Self {
+        self.avg_px = Some(avg_px);
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<Vec<u8>>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// OrderQty less CumQty, the quantity still to be filled; `None` without
+    /// OrderQty. Negative for an overfilled order, unlike LeavesQty, which
+    /// is also zero once the order is done.
+    pub fn remaining_qty(&self) -> Result<Option<FixDecimal>, FixError> {
+        self.order_qty
+            .map(|order_qty| order_qty.value().checked_sub(self.cum_qty.value()))
+            .transpose()
+    }
+
+    /// Builds the message. Header fields beyond BeginString and MsgType are
+    /// left to the caller.
+    pub fn to_message(&self, begin_string: &[u8]) -> Result<FixMessage, FixError> {
+        let mut msg = FixMessage::with_capacity(18);
+        msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+        self.write_body(&mut msg);
+        Ok(msg)
+    }
+
+    pub(crate) fn write_body(&self, msg: &mut FixMessage) {
+        msg.add_field(FixField::new(Tag::OrderID.value(), self.order_id.clone()));
+        if let Some(cl_ord_id) = &self.cl_ord_id {
+            msg.add_field(FixField::new(Tag::ClOrdID.value(), cl_ord_id.clone()));
+        }
+        if let Some(orig_cl_ord_id) = &self.orig_cl_ord_id {
+            msg.add_field(FixField::new(Tag::OrigClOrdID.value(), orig_cl_ord_id.clone()));
+        }
+        msg.add_field(FixField::new(Tag::ExecID.value(), self.exec_id.clone()));
+        msg.add_field(FixField::new(Tag::ExecType.value(), vec![self.exec_type]));
+        msg.add_field(FixField::new(Tag::OrdStatus.value(), vec![self.ord_status]));
+        if let Some(symbol) = &self.symbol {
+            msg.add_field(FixField::new(Tag::Symbol.value(), symbol.clone()));
+        }
+        msg.add_field(FixField::new(Tag::Side.value(), vec![self.side]));
+        if let Some(order_qty) = &self.order_qty {
+            msg.add_field(FixField::from_value(Tag::OrderQty.value(), order_qty));
+        }
+        if let Some(last_qty) = &self.last_qty {
+            msg.add_field(FixField::from_value(Tag::LastQty.value(), last_qty));
+        }
+        if let Some(last_px) = &self.last_px {
+            msg.add_field(FixField::from_value(Tag::LastPx.value(), last_px));
+        }
+        msg.add_field(FixField::from_value(Tag::LeavesQty.value(), &self.leaves_qty));
+        msg.add_field(FixField::from_value(Tag::CumQty.value(), &self.cum_qty));
+        if let Some(avg_px) = &self.avg_px {
+            msg.add_field(FixField::from_value(Tag::AvgPx.value(), avg_px));
+        }
+        if let Some(text) = &self.text {
+            msg.add_field(FixField::new(Tag::Text.value(), text.clone()));
+        }
+    }
+
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        require_msg_type(msg, msg_type::EXECUTION_REPORT)?;
+        let bytes = |tag: Tag| msg.get_field(tag.value()).map(|f| f.value().to_vec());
+        Ok(Self {
+            order_id: required(msg, Tag::OrderID.value())?.to_vec(),
+            cl_ord_id: bytes(Tag::ClOrdID),
+            orig_cl_ord_id: bytes(Tag::OrigClOrdID),
+            exec_id: required(msg, Tag::ExecID.value())?.to_vec(),
+            exec_type: single_char(required(msg, Tag::ExecType.value())?)?,
+            ord_status: single_char(required(msg, Tag::OrdStatus.value())?)?,
+            side: single_char(required(msg, Tag::Side.value())?)?,
+            symbol: bytes(Tag::Symbol),
+            order_qty: msg.get_qty(Tag::OrderQty.value())?,
+            last_qty: msg.get_qty(Tag::LastQty.value())?,
+            last_px: msg.get_decimal(Tag::LastPx.value())?,
+            leaves_qty: msg.get_qty(Tag::LeavesQty.value())?.ok_or(FixError::MissingField(Tag::LeavesQty.value()))?,
+            cum_qty: msg.get_qty(Tag::CumQty.value())?.ok_or(FixError::MissingField(Tag::CumQty.value()))?,
+            avg_px: msg.get_decimal(Tag::AvgPx.value())?,
+            text: bytes(Tag::Text),
+        })
+    }
+}
+
+impl FixMessage {
+    /// Builds an ExecutionReport (35=8); see [`ExecutionReport::to_message`]
+    pub fn execution_report(begin_string: &[u8], report: &ExecutionReport) -> Result<Self, FixError> {
+        report.to_message(begin_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::fix_version;
+
+    fn qty(value: &str) -> Qty {
+        Qty::new(value.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let report = ExecutionReport::new("O-1", "E-2", exec_type::TRADE, ord_status::PARTIALLY_FILLED, b'1', qty("60"), qty("40"))
+            .with_cl_ord_id("C-2")
+            .with_orig_cl_ord_id("C-1")
+            .with_symbol("VOD.L")
+            .with_order_qty(qty("100"))
+            .with_fill(qty("40"), "101.25".parse().unwrap())
+            .with_avg_px("101.25".parse().unwrap());
+        let encoded = FixMessage::execution_report(fix_version::FIX_4_4, &report)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert!(encoded.windows(8).any(|w| w == b"\x01151=60\x01"));
+
+        let decoded = ExecutionReport::from_message(&FixMessage::decode(&encoded).unwrap()).unwrap();
+        assert_eq!(decoded, report);
+    }
+
+    #[test]
+    fn test_remaining_qty() {
+        let partial = ExecutionReport::new("O-1", "E-1", exec_type::TRADE, ord_status::PARTIALLY_FILLED, b'1', qty("60"), qty("40.5"))
+            .with_order_qty(qty("100"));
+        assert_eq!(partial.remaining_qty().unwrap(), Some("59.5".parse().unwrap()));
+
+        let canceled = ExecutionReport::new("O-1", "E-2", exec_type::CANCELED, ord_status::CANCELED, b'1', qty("0"), qty("40.5"))
+            .with_order_qty(qty("100"));
+        assert_eq!(canceled.remaining_qty().unwrap(), Some("59.5".parse().unwrap()));
+
+        let overfilled = ExecutionReport::new("O-1", "E-3", exec_type::TRADE, ord_status::FILLED, b'1', qty("0"), qty("110"))
+            .with_order_qty(qty("100"));
+        assert_eq!(overfilled.remaining_qty().unwrap(), Some("-10".parse().unwrap()));
+
+        let no_order_qty = ExecutionReport::new("O-1", "E-4", exec_type::NEW, ord_status::NEW, b'1', qty("100"), qty("0"));
+        assert_eq!(no_order_qty.remaining_qty().unwrap(), None);
+
+        let huge = ExecutionReport::new("O-1", "E-5", exec_type::TRADE, ord_status::FILLED, b'1', qty("0"), qty("0.000001"))
+            .with_order_qty(qty("9223372036854775807"));
+        assert!(matches!(huge.remaining_qty(), Err(FixError::DecimalOverflow)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_fields() {
+        let report = ExecutionReport::new("O-1", "E-1", exec_type::NEW, ord_status::NEW, b'1', qty("100"), qty("0"));
+        let mut msg = report.to_message(fix_version::FIX_4_4).unwrap();
+        msg.set_field(FixField::new(Tag::CumQty.value(), b"-5".to_vec()));
+        assert!(matches!(
+            ExecutionReport::from_message(&msg),
+            Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::CumQty.value()
+        ));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::EXECUTION_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::OrderID.value(), b"O-1".to_vec()));
+        assert!(matches!(ExecutionReport::from_message(&msg), Err(FixError::MissingField(17))));
+    }
+}
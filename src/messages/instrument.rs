@@ -0,0 +1,77 @@
+use super::required;
+use crate::codes::{Country, Currency, Mic};
+use crate::datetime::MonthYear;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+
+/// The Instrument component, limited to the fields the typed messages use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instrument {
+    pub symbol: Vec<u8>,
+    pub maturity_month_year: Option<MonthYear>,
+    pub security_exchange: Option<Mic>,
+    pub country_of_issue: Option<Country>,
+    pub strike_currency: Option<Currency>,
+}
+
+impl Instrument {
+    pub fn new(symbol: impl Into<Vec<u8>>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            maturity_month_year: None,
+            security_exchange: None,
+            country_of_issue: None,
+            strike_currency: None,
+        }
+    }
+
+    pub fn with_maturity_month_year(mut self, maturity: MonthYear) -> Self {
+        self.maturity_month_year = Some(maturity);
+        self
+    }
+
+    pub fn with_security_exchange(mut self, exchange: Mic) -> Self {
+        self.security_exchange = Some(exchange);
+        self
+    }
+
+    pub fn with_country_of_issue(mut self, country: Country) -> Self {
+        self.country_of_issue = Some(country);
+        self
+    }
+
+    pub fn with_strike_currency(mut self, currency: Currency) -> Self {
+        self.strike_currency = Some(currency);
+        self
+    }
+
+    /// Appends the component's fields to `msg`
+    pub fn write_to(&self, msg: &mut FixMessage) {
+        msg.add_field(FixField::new(Tag::Symbol.value(), self.symbol.clone()));
+        if let Some(maturity) = &self.maturity_month_year {
+            msg.add_field(FixField::from_value(Tag::MaturityMonthYear.value(), maturity));
+        }
+        if let Some(exchange) = &self.security_exchange {
+            msg.add_field(FixField::from_value(Tag::SecurityExchange.value(), exchange));
+        }
+        if let Some(country) = &self.country_of_issue {
+            msg.add_field(FixField::from_value(Tag::CountryOfIssue.value(), country));
+        }
+        if let Some(currency) = &self.strike_currency {
+            msg.add_field(FixField::from_value(Tag::StrikeCurrency.value(), currency));
+        }
+    }
+
+    /// Reads the component from `msg`, which must carry Symbol (55)
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        Ok(Self {
+            symbol: required(msg, Tag::Symbol.value())?.to_vec(),
+            maturity_month_year: msg.get_value(Tag::MaturityMonthYear.value())?,
+            security_exchange: msg.get_value(Tag::SecurityExchange.value())?,
+            country_of_issue: msg.get_value(Tag::CountryOfIssue.value())?,
+            strike_currency: msg.get_value(Tag::StrikeCurrency.value())?,
+        })
+    }
+}
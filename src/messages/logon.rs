@@ -0,0 +1,100 @@
+use super::{int_in_range, require_msg_type};
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::{msg_type, Tag};
+
+/// EncryptMethod (98): 0 (none) to 6 (PEM/DES-MD5)
+pub const ENCRYPT_METHOD_RANGE: (i64, i64) = (0, 6);
+
+/// HeartBtInt (108) in seconds; 0 disables heartbeats
+pub const HEART_BT_INT_RANGE: (i64, i64) = (0, u32::MAX as i64);
+
+/// Logon (35=A)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Logon {
+    pub encrypt_method: u8,
+    pub heart_bt_int: u32,
+    pub reset_seq_num_flag: bool,
+}
+
+impl Logon {
+    /// An unencrypted logon
+    pub fn new(heart_bt_int: u32) -> Self {
+        Self {
+            encrypt_method: 0,
+            heart_bt_int,
+            reset_seq_num_flag: false,
+        }
+    }
+
+    pub fn with_reset_seq_num_flag(mut self, reset: bool) -> Self {
+        self.reset_seq_num_flag = reset;
+        self
+    }
+
+    pub fn to_message(&self, begin_string: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::with_capacity(5);
+        msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::LOGON.to_vec()));
+        self.write_body(&mut msg);
+        msg
+    }
+
+    pub(crate) fn write_body(&self, msg: &mut FixMessage) {
+        msg.add_field(FixField::from_value(Tag::EncryptMethod.value(), &(self.encrypt_method as u64)));
+        msg.add_field(FixField::from_value(Tag::HeartBtInt.value(), &(self.heart_bt_int as u64)));
+        if self.reset_seq_num_flag {
+            msg.add_field(FixField::from_value(Tag::ResetSeqNumFlag.value(), &true));
+        }
+    }
+
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        require_msg_type(msg, msg_type::LOGON)?;
+        let required = |tag: u32, range| int_in_range(msg, tag, range)?.ok_or(FixError::MissingField(tag));
+
+        Ok(Self {
+            encrypt_method: required(Tag::EncryptMethod.value(), ENCRYPT_METHOD_RANGE)? as u8,
+            heart_bt_int: required(Tag::HeartBtInt.value(), HEART_BT_INT_RANGE)? as u32,
+            reset_seq_num_flag: msg.get_bool_or(Tag::ResetSeqNumFlag.value(), false)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::fix_version;
+
+    fn logon_with(encrypt_method: &[u8], heart_bt_int: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::LOGON.to_vec()));
+        msg.add_field(FixField::new(Tag::EncryptMethod.value(), encrypt_method.to_vec()));
+        msg.add_field(FixField::new(Tag::HeartBtInt.value(), heart_bt_int.to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let logon = Logon::new(30).with_reset_seq_num_flag(true);
+        let encoded = logon.to_message(fix_version::FIX_4_4).encode().unwrap();
+        let decoded = Logon::from_message(&FixMessage::decode(&encoded).unwrap()).unwrap();
+        assert_eq!(decoded, logon);
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_values() {
+        assert!(matches!(
+            Logon::from_message(&logon_with(b"0", b"-5")),
+            Err(FixError::ValueOutOfRange { tag: 108, value: -5, min: 0, .. })
+        ));
+        assert!(matches!(
+            Logon::from_message(&logon_with(b"7", b"30")),
+            Err(FixError::ValueOutOfRange { tag: 98, value: 7, min: 0, max: 6 })
+        ));
+        assert!(matches!(
+            Logon::from_message(&logon_with(b"0", b"thirty")),
+            Err(FixError::InvalidFieldValue)
+        ));
+    }
+}
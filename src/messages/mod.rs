@@ -0,0 +1,109 @@
+pub mod allocation;
+pub mod execution_report;
+pub mod instrument;
+pub mod logon;
+pub mod order_cancel_reject;
+pub mod resend_request;
+pub mod trade_capture_report;
+
+pub use allocation::validate_allocations;
+pub use execution_report::ExecutionReport;
+pub use instrument::Instrument;
+pub use logon::Logon;
+pub use order_cancel_reject::OrderCancelReject;
+pub use resend_request::ResendRequest;
+pub use trade_capture_report::{TradeCaptureReport, TradeCaptureSide};
+
+use crate::datetime::parse_digits;
+use crate::error::FixError;
+use crate::field::{parse_int, FixField};
+use crate::message::FixMessage;
+
+pub(crate) fn require_msg_type(msg: &FixMessage, expected: &[u8]) -> Result<(), FixError> {
+    let msg_type = required(msg, crate::tags::Tag::MsgType.value())?;
+    if msg_type != expected {
+        return Err(FixError::UnexpectedMsgType(String::from_utf8_lossy(msg_type).into_owned()));
+    }
+    Ok(())
+}
+
+#[inline]
+pub(crate) fn required(msg: &FixMessage, tag: u32) -> Result<&[u8], FixError> {
+    msg.get_field(tag)
+        .map(FixField::value)
+        .ok_or(FixError::MissingField(tag))
+}
+
+/// Reads an integer field constrained to `min..=max`, naming the tag, value
+/// and range when it falls outside
+pub(crate) fn int_in_range(msg: &FixMessage, tag: u32, (min, max): (i64, i64)) -> Result<Option<i64>, FixError> {
+    let Some(field) = msg.get_field(tag) else {
+        return Ok(None);
+    };
+    check_range(tag, parse_int(field.value())?, (min, max)).map(Some)
+}
+
+#[inline]
+pub(crate) fn check_range(tag: u32, value: i64, (min, max): (i64, i64)) -> Result<i64, FixError> {
+    if value < min || value > max {
+        return Err(FixError::ValueOutOfRange { tag, value, min, max });
+    }
+    Ok(value)
+}
+
+#[inline]
+pub(crate) fn single_char(value: &[u8]) -> Result<u8, FixError> {
+    match value {
+        [c] => Ok(*c),
+        _ => Err(FixError::InvalidFieldValue),
+    }
+}
+
+/// Splits the repeating group introduced by `count_tag` into its entries.
+/// `members[0]` is the delimiter field that starts every entry; the group ends
+/// at the first field that is not one of `members`.
+pub(crate) fn group_entries<'a>(
+    msg: &'a FixMessage,
+    count_tag: u32,
+    members: &[u32],
+) -> Result<Vec<&'a [FixField]>, FixError> {
+    let fields = msg.field_slice();
+    let Some(count_pos) = fields.iter().position(|f| f.tag() == count_tag) else {
+        return Ok(Vec::new());
+    };
+    let declared = parse_digits(fields[count_pos].value())? as usize;
+
+    let start = count_pos + 1;
+    let end = fields[start..]
+        .iter()
+        .position(|f| !members.contains(&f.tag()))
+        .map_or(fields.len(), |len| start + len);
+
+    // The count comes off the wire, so it only bounds the allocation by the
+    // fields actually present
+    let mut entries = Vec::with_capacity(declared.min(end - start));
+    let mut entry_start = start;
+    for i in start..end {
+        if fields[i].tag() == members[0] {
+            if i > entry_start {
+                entries.push(&fields[entry_start..i]);
+            }
+            entry_start = i;
+        } else if i == start {
+            // Every entry must open with the delimiter field
+            return Err(FixError::InvalidFormat);
+        }
+    }
+    if end > entry_start {
+        entries.push(&fields[entry_start..end]);
+    }
+
+    if entries.len() != declared {
+        return Err(FixError::GroupCountMismatch {
+            count_tag,
+            declared,
+            actual: entries.len(),
+        });
+    }
+    Ok(entries)
+}
@@ -0,0 +1,154 @@
+use super::{check_range, int_in_range, require_msg_type, required, single_char};
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::{msg_type, Tag};
+
+/// CxlRejReason (102); standard codes stop well short of 99 ("Other")
+pub const CXL_REJ_REASON_RANGE: (i64, i64) = (0, 99);
+
+/// CxlRejResponseTo (434) values
+pub mod cxl_rej_response_to {
+    pub const ORDER_CANCEL_REQUEST: u8 = b'1';
+    pub const ORDER_CANCEL_REPLACE_REQUEST: u8 = b'2';
+}
+
+/// OrderCancelReject (35=9), sent when a cancel or cancel/replace request
+/// cannot be honoured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCancelReject {
+    pub order_id: Vec<u8>,
+    pub cl_ord_id: Vec<u8>,
+    pub orig_cl_ord_id: Option<Vec<u8>>,
+    pub ord_status: u8,
+    pub cxl_rej_response_to: u8,
+    pub cxl_rej_reason: Option<u8>,
+    pub text: Option<Vec<u8>>,
+}
+
+impl OrderCancelReject {
+    /// `order_id` is "NONE" when the venue does not know the order
+    pub fn new(
+        order_id: impl Into<Vec<u8>>,
+        cl_ord_id: impl Into<Vec<u8>>,
+        ord_status: u8,
+        cxl_rej_response_to: u8,
+    ) -> Self {
+        Self {
+            order_id: order_id.into(),
+            cl_ord_id: cl_ord_id.into(),
+            orig_cl_ord_id: None,
+            ord_status,
+            cxl_rej_response_to,
+            cxl_rej_reason: None,
+            text: None,
+        }
+    }
+
+    pub fn with_orig_cl_ord_id(mut self, orig_cl_ord_id: impl Into<Vec<u8>>) -> Self {
+        self.orig_cl_ord_id = Some(orig_cl_ord_id.into());
+        self
+    }
+
+    pub fn with_cxl_rej_reason(mut self, reason: u8) -> Self {
+        self.cxl_rej_reason = Some(reason);
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<Vec<u8>>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Builds the message. Header fields beyond BeginString and MsgType are
+    /// left to the caller.
+    pub fn to_message(&self, begin_string: &[u8]) -> Result<FixMessage, FixError> {
+        let mut msg = FixMessage::with_capacity(9);
+        msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::ORDER_CANCEL_REJECT.to_vec()));
+        self.write_body(&mut msg)?;
+        Ok(msg)
+    }
+
+    pub(crate) fn write_body(&self, msg: &mut FixMessage) -> Result<(), FixError> {
+        msg.add_field(FixField::new(Tag::OrderID.value(), self.order_id.clone()));
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), self.cl_ord_id.clone()));
+        if let Some(orig_cl_ord_id) = &self.orig_cl_ord_id {
+            msg.add_field(FixField::new(Tag::OrigClOrdID.value(), orig_cl_ord_id.clone()));
+        }
+        msg.add_field(FixField::new(Tag::OrdStatus.value(), vec![self.ord_status]));
+        msg.add_field(FixField::new(Tag::CxlRejResponseTo.value(), vec![self.cxl_rej_response_to]));
+        if let Some(reason) = self.cxl_rej_reason {
+            let tag = Tag::CxlRejReason.value();
+            check_range(tag, reason as i64, CXL_REJ_REASON_RANGE)?;
+            msg.add_field(FixField::from_value(tag, &(reason as u64)));
+        }
+        if let Some(text) = &self.text {
+            msg.add_field(FixField::new(Tag::Text.value(), text.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        require_msg_type(msg, msg_type::ORDER_CANCEL_REJECT)?;
+        Ok(Self {
+            order_id: required(msg, Tag::OrderID.value())?.to_vec(),
+            cl_ord_id: required(msg, Tag::ClOrdID.value())?.to_vec(),
+            orig_cl_ord_id: msg.get_field(Tag::OrigClOrdID.value()).map(|f| f.value().to_vec()),
+            ord_status: single_char(required(msg, Tag::OrdStatus.value())?)?,
+            cxl_rej_response_to: single_char(required(msg, Tag::CxlRejResponseTo.value())?)?,
+            cxl_rej_reason: int_in_range(msg, Tag::CxlRejReason.value(), CXL_REJ_REASON_RANGE)?
+                .map(|reason| reason as u8),
+            text: msg.get_field(Tag::Text.value()).map(|f| f.value().to_vec()),
+        })
+    }
+}
+
+impl FixMessage {
+    /// Builds an OrderCancelReject (35=9); see [`OrderCancelReject::to_message`]
+    pub fn order_cancel_reject(begin_string: &[u8], reject: &OrderCancelReject) -> Result<Self, FixError> {
+        reject.to_message(begin_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::fix_version;
+
+    #[test]
+    fn test_round_trip() {
+        let reject = OrderCancelReject::new("NONE", "C-2", b'8', cxl_rej_response_to::ORDER_CANCEL_REQUEST)
+            .with_orig_cl_ord_id("C-1")
+            .with_cxl_rej_reason(1)
+            .with_text("Unknown order");
+        let encoded = FixMessage::order_cancel_reject(fix_version::FIX_4_4, &reject)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert!(encoded.windows(6).any(|w| w == b"\x0135=9\x01"));
+        assert!(encoded.windows(7).any(|w| w == b"\x01434=1\x01"));
+
+        let decoded = OrderCancelReject::from_message(&FixMessage::decode(&encoded).unwrap()).unwrap();
+        assert_eq!(decoded, reject);
+    }
+
+    #[test]
+    fn test_rejects_invalid_fields() {
+        let reject = OrderCancelReject::new("O-1", "C-2", b'0', cxl_rej_response_to::ORDER_CANCEL_REPLACE_REQUEST);
+        assert!(matches!(
+            reject.clone().with_cxl_rej_reason(100).to_message(fix_version::FIX_4_4),
+            Err(FixError::ValueOutOfRange { tag: 102, value: 100, .. })
+        ));
+
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::ORDER_CANCEL_REJECT.to_vec()));
+        msg.add_field(FixField::new(Tag::OrderID.value(), b"O-1".to_vec()));
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), b"C-2".to_vec()));
+        msg.add_field(FixField::new(Tag::OrdStatus.value(), b"0".to_vec()));
+        assert!(matches!(
+            OrderCancelReject::from_message(&msg),
+            Err(FixError::MissingField(434))
+        ));
+    }
+}
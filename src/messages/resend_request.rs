@@ -0,0 +1,80 @@
+use super::require_msg_type;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::seqnum::{EndSeqNo, SeqNum};
+use crate::tags::{msg_type, Tag};
+
+/// ResendRequest (35=2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResendRequest {
+    pub begin_seq_no: SeqNum,
+    pub end_seq_no: EndSeqNo,
+}
+
+impl ResendRequest {
+    pub fn new(begin_seq_no: SeqNum, end_seq_no: EndSeqNo) -> Self {
+        Self { begin_seq_no, end_seq_no }
+    }
+
+    /// Whether `seq` is one of the requested messages
+    #[inline]
+    pub fn includes(&self, seq: SeqNum) -> bool {
+        seq >= self.begin_seq_no && self.end_seq_no.includes(seq)
+    }
+
+    pub fn to_message(&self, begin_string: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::with_capacity(4);
+        msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::RESEND_REQUEST.to_vec()));
+        self.write_body(&mut msg);
+        msg
+    }
+
+    pub(crate) fn write_body(&self, msg: &mut FixMessage) {
+        msg.add_field(FixField::from_value(Tag::BeginSeqNo.value(), &self.begin_seq_no));
+        msg.add_field(FixField::from_value(Tag::EndSeqNo.value(), &self.end_seq_no));
+    }
+
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        require_msg_type(msg, msg_type::RESEND_REQUEST)?;
+        Ok(Self {
+            begin_seq_no: msg
+                .get_value(Tag::BeginSeqNo.value())?
+                .ok_or(FixError::MissingField(Tag::BeginSeqNo.value()))?,
+            end_seq_no: msg
+                .get_value(Tag::EndSeqNo.value())?
+                .ok_or(FixError::MissingField(Tag::EndSeqNo.value()))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::fix_version;
+
+    #[test]
+    fn test_unbounded_round_trip() {
+        let request = ResendRequest::new(SeqNum::new(8).unwrap(), EndSeqNo::Unbounded);
+        let encoded = request.to_message(fix_version::FIX_4_4).encode().unwrap();
+        assert!(encoded.windows(5).any(|w| w == b"\x0116=0"));
+
+        let decoded = ResendRequest::from_message(&FixMessage::decode(&encoded).unwrap()).unwrap();
+        assert_eq!(decoded, request);
+        assert!(decoded.includes(SeqNum::new(1_000).unwrap()));
+        assert!(!decoded.includes(SeqNum::new(7).unwrap()));
+    }
+
+    #[test]
+    fn test_rejects_zero_begin_seq_no() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::RESEND_REQUEST.to_vec()));
+        msg.add_field(FixField::new(Tag::BeginSeqNo.value(), b"0".to_vec()));
+        msg.add_field(FixField::new(Tag::EndSeqNo.value(), b"0".to_vec()));
+        assert!(matches!(
+            ResendRequest::from_message(&msg),
+            Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::BeginSeqNo.value()
+        ));
+    }
+}
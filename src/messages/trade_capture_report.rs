@@ -0,0 +1,273 @@
+use super::{group_entries, require_msg_type, required, single_char, Instrument};
+use crate::codes::Currency;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::{msg_type, Tag};
+
+const SIDE: u32 = Tag::Side.value();
+const ORDER_ID: u32 = Tag::OrderID.value();
+const CL_ORD_ID: u32 = Tag::ClOrdID.value();
+const ACCOUNT: u32 = Tag::Account.value();
+
+pub(crate) const SIDE_GROUP: [u32; 4] = [SIDE, ORDER_ID, CL_ORD_ID, ACCOUNT];
+
+/// One entry of the NoSides (552) group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeCaptureSide {
+    pub side: u8,
+    pub order_id: Vec<u8>,
+    pub cl_ord_id: Option<Vec<u8>>,
+    pub account: Option<Vec<u8>>,
+}
+
+impl TradeCaptureSide {
+    pub fn new(side: u8, order_id: impl Into<Vec<u8>>) -> Self {
+        Self {
+            side,
+            order_id: order_id.into(),
+            cl_ord_id: None,
+            account: None,
+        }
+    }
+
+    pub fn with_cl_ord_id(mut self, cl_ord_id: impl Into<Vec<u8>>) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.into());
+        self
+    }
+
+    pub fn with_account(mut self, account: impl Into<Vec<u8>>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+
+    fn from_entry(entry: &[FixField]) -> Result<Self, FixError> {
+        let mut side = None;
+        let mut order_id = None;
+        let mut cl_ord_id = None;
+        let mut account = None;
+
+        for field in entry {
+            let value = field.value();
+            match field.tag() {
+                SIDE => side = Some(single_char(value)?),
+                ORDER_ID => order_id = Some(value.to_vec()),
+                CL_ORD_ID => cl_ord_id = Some(value.to_vec()),
+                ACCOUNT => account = Some(value.to_vec()),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            side: side.ok_or(FixError::MissingField(SIDE))?,
+            order_id: order_id.ok_or(FixError::MissingField(ORDER_ID))?,
+            cl_ord_id,
+            account,
+        })
+    }
+}
+
+/// TradeCaptureReport (35=AE) with one or two sides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeCaptureReport {
+    pub trade_report_id: Vec<u8>,
+    pub exec_type: u8,
+    pub last_qty: Vec<u8>,
+    pub last_px: Vec<u8>,
+    pub instrument: Option<Instrument>,
+    pub currency: Option<Currency>,
+    pub sides: Vec<TradeCaptureSide>,
+}
+
+impl TradeCaptureReport {
+    pub fn new(
+        trade_report_id: impl Into<Vec<u8>>,
+        exec_type: u8,
+        last_qty: impl Into<Vec<u8>>,
+        last_px: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            trade_report_id: trade_report_id.into(),
+            exec_type,
+            last_qty: last_qty.into(),
+            last_px: last_px.into(),
+            instrument: None,
+            currency: None,
+            sides: Vec::with_capacity(2),
+        }
+    }
+
+    pub fn with_instrument(mut self, instrument: Instrument) -> Self {
+        self.instrument = Some(instrument);
+        self
+    }
+
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    pub fn with_side(mut self, side: TradeCaptureSide) -> Self {
+        self.sides.push(side);
+        self
+    }
+
+    /// Builds the message. Header fields beyond BeginString and MsgType are
+    /// left to the caller.
+    pub fn to_message(&self, begin_string: &[u8]) -> Result<FixMessage, FixError> {
+        check_side_count(self.sides.len())?;
+
+        let mut msg = FixMessage::with_capacity(8 + self.sides.len() * SIDE_GROUP.len());
+        msg.add_field(FixField::new(Tag::BeginString.value(), begin_string.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::TRADE_CAPTURE_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::TradeReportID.value(), self.trade_report_id.clone()));
+        msg.add_field(FixField::new(Tag::ExecType.value(), vec![self.exec_type]));
+        if let Some(instrument) = &self.instrument {
+            instrument.write_to(&mut msg);
+        }
+        msg.add_field(FixField::new(Tag::LastQty.value(), self.last_qty.clone()));
+        msg.add_field(FixField::new(Tag::LastPx.value(), self.last_px.clone()));
+        if let Some(currency) = &self.currency {
+            msg.add_field(FixField::from_value(Tag::Currency.value(), currency));
+        }
+        msg.add_field(FixField::new(Tag::NoSides.value(), self.sides.len().to_string().into_bytes()));
+
+        for side in &self.sides {
+            msg.add_field(FixField::new(SIDE, vec![side.side]));
+            msg.add_field(FixField::new(ORDER_ID, side.order_id.clone()));
+            if let Some(cl_ord_id) = &side.cl_ord_id {
+                msg.add_field(FixField::new(CL_ORD_ID, cl_ord_id.clone()));
+            }
+            if let Some(account) = &side.account {
+                msg.add_field(FixField::new(ACCOUNT, account.clone()));
+            }
+        }
+
+        Ok(msg)
+    }
+
+    pub fn from_message(msg: &FixMessage) -> Result<Self, FixError> {
+        require_msg_type(msg, msg_type::TRADE_CAPTURE_REPORT)?;
+
+        let sides = group_entries(msg, Tag::NoSides.value(), &SIDE_GROUP)?
+            .into_iter()
+            .map(TradeCaptureSide::from_entry)
+            .collect::<Result<Vec<_>, _>>()?;
+        check_side_count(sides.len())?;
+
+        Ok(Self {
+            trade_report_id: required(msg, Tag::TradeReportID.value())?.to_vec(),
+            exec_type: single_char(required(msg, Tag::ExecType.value())?)?,
+            last_qty: required(msg, Tag::LastQty.value())?.to_vec(),
+            last_px: required(msg, Tag::LastPx.value())?.to_vec(),
+            instrument: match msg.get_field(Tag::Symbol.value()) {
+                Some(_) => Some(Instrument::from_message(msg)?),
+                None => None,
+            },
+            currency: msg.get_value(Tag::Currency.value())?,
+            sides,
+        })
+    }
+}
+
+// A trade capture report has one side, or two for a matched trade
+#[inline]
+fn check_side_count(count: usize) -> Result<(), FixError> {
+    match count {
+        0 => Err(FixError::MissingField(Tag::NoSides.value())),
+        1 | 2 => Ok(()),
+        _ => Err(FixError::InvalidFieldValue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::fix_version;
+
+    fn two_sided() -> TradeCaptureReport {
+        TradeCaptureReport::new("TR1", b'F', "100", "25.5")
+            .with_side(TradeCaptureSide::new(b'1', "BUY1").with_cl_ord_id("C1").with_account("ACC1"))
+            .with_side(TradeCaptureSide::new(b'2', "SELL1"))
+    }
+
+    #[test]
+    fn test_two_sided_round_trip() {
+        let report = two_sided();
+        let encoded = report.to_message(fix_version::FIX_4_4).unwrap().encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        assert_eq!(TradeCaptureReport::from_message(&decoded).unwrap(), report);
+    }
+
+    #[test]
+    fn test_single_side_round_trip() {
+        let report = TradeCaptureReport::new("TR2", b'F', "10", "99")
+            .with_side(TradeCaptureSide::new(b'1', "BUY2").with_account("ACC2"));
+        let encoded = report.to_message(fix_version::FIX_4_4).unwrap().encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+
+        assert_eq!(TradeCaptureReport::from_message(&decoded).unwrap(), report);
+    }
+
+    #[test]
+    fn test_instrument_round_trip() {
+        let instrument = Instrument::new("VOD")
+            .with_security_exchange("XLON".parse().unwrap())
+            .with_country_of_issue("GB".parse().unwrap());
+        let report = two_sided()
+            .with_instrument(instrument)
+            .with_currency("GBP".parse().unwrap());
+        let encoded = report.to_message(fix_version::FIX_4_4).unwrap().encode().unwrap();
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(TradeCaptureReport::from_message(&decoded).unwrap(), report);
+
+        // Exchange codes are checked when read, not passed through as text
+        let msg = report.to_message(fix_version::FIX_4_4).unwrap();
+        let mut bad = FixMessage::new();
+        for field in msg.field_slice() {
+            let value = if field.tag() == Tag::SecurityExchange.value() { &b"LSE"[..] } else { field.value() };
+            bad.add_field(FixField::new(field.tag(), value.to_vec()));
+        }
+        assert!(matches!(
+            TradeCaptureReport::from_message(&bad),
+            Err(FixError::InvalidFieldValueFor { tag }) if tag == Tag::SecurityExchange.value()
+        ));
+    }
+
+    #[test]
+    fn test_side_count_mismatch() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::TRADE_CAPTURE_REPORT.to_vec()));
+        msg.add_field(FixField::new(Tag::TradeReportID.value(), b"TR3".to_vec()));
+        msg.add_field(FixField::new(Tag::NoSides.value(), b"2".to_vec()));
+        msg.add_field(FixField::new(SIDE, b"1".to_vec()));
+        msg.add_field(FixField::new(ORDER_ID, b"BUY3".to_vec()));
+        assert!(matches!(
+            TradeCaptureReport::from_message(&msg),
+            Err(FixError::GroupCountMismatch { count_tag: 552, declared: 2, actual: 1 })
+        ));
+
+        // A hostile count is reported, not allocated for
+        let mut hostile = FixMessage::new();
+        for field in msg.field_slice() {
+            let value = if field.tag() == Tag::NoSides.value() { &b"4294967295"[..] } else { field.value() };
+            hostile.add_field(FixField::new(field.tag(), value.to_vec()));
+        }
+        assert!(matches!(
+            TradeCaptureReport::from_message(&hostile),
+            Err(FixError::GroupCountMismatch { count_tag: 552, actual: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_other_message_types() {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        assert!(matches!(
+            TradeCaptureReport::from_message(&msg),
+            Err(FixError::UnexpectedMsgType(_))
+        ));
+        assert!(TradeCaptureReport::new("TR4", b'F', "1", "1").to_message(fix_version::FIX_4_4).is_err());
+    }
+}
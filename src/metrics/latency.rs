@@ -0,0 +1,367 @@
+use crate::error::FixError;
+use crate::framing::Framer;
+use crate::message::FixMessage;
+use crate::session::{Session, SessionEvent};
+use crate::tags::Tag;
+use bytes::{Bytes, BytesMut};
+use std::time::{Duration, Instant};
+
+/// Points in the pipeline at which a hook is called, in the order an inbound
+/// message passes them. A reply sent while the application handles a message
+/// reaches `EncodeComplete` and `BytesWritten` before that message reaches
+/// `ApplicationReturned`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    /// The first byte of the message was handed to the framer
+    BytesReceived,
+    /// The framer holds the whole message
+    FrameComplete,
+    DecodeComplete,
+    HandedToApplication,
+    ApplicationReturned,
+    EncodeComplete,
+    /// The transport accepted the encoded message for writing
+    BytesWritten,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 7] = [
+        Stage::BytesReceived,
+        Stage::FrameComplete,
+        Stage::DecodeComplete,
+        Stage::HandedToApplication,
+        Stage::ApplicationReturned,
+        Stage::EncodeComplete,
+        Stage::BytesWritten,
+    ];
+}
+
+/// The message a stage was reached for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MessageCtx<'a> {
+    pub msg_type: Option<&'a [u8]>,
+    pub seq_num: Option<u64>,
+}
+
+impl<'a> MessageCtx<'a> {
+    pub fn of(msg: &'a FixMessage) -> Self {
+        Self {
+            msg_type: msg.get_field(Tag::MsgType.value()).map(|f| f.value()),
+            seq_num: msg.msg_seq_num().ok().flatten().map(|seq| seq.get()),
+        }
+    }
+}
+
+/// Receives a timestamp at each stage. Stages before `DecodeComplete` are
+/// reported once the message is decoded, with the times they were reached,
+/// so every call carries the full context.
+pub trait LatencyHook {
+    /// False only for `NoHook`, letting the pipeline skip reading the clock
+    const ENABLED: bool = true;
+
+    fn on_stage(&mut self, stage: Stage, ts: Instant, ctx: &MessageCtx<'_>);
+
+    /// The clock stages are timed with; replaceable for tests
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Reports `stage` as reached now
+    #[inline]
+    fn stage(&mut self, stage: Stage, ctx: &MessageCtx<'_>) {
+        if Self::ENABLED {
+            let ts = self.now();
+            self.on_stage(stage, ts, ctx);
+        }
+    }
+}
+
+/// A hook that does nothing and compiles away
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoHook;
+
+impl LatencyHook for NoHook {
+    const ENABLED: bool = false;
+
+    #[inline(always)]
+    fn on_stage(&mut self, _stage: Stage, _ts: Instant, _ctx: &MessageCtx<'_>) {}
+}
+
+/// A complete frame with the times of the first two stages
+#[derive(Debug, Clone)]
+pub struct TimedFrame {
+    pub frame: Bytes,
+    pub received: Instant,
+    pub complete: Instant,
+}
+
+impl Framer {
+    /// `next_frame`, also noting when the frame was received and completed
+    pub fn next_timed_frame<H: LatencyHook>(&mut self, hook: &H) -> Result<Option<TimedFrame>, FixError> {
+        let received = self.received_at();
+        Ok(self.next_frame()?.map(|frame| {
+            let complete = hook.now();
+            TimedFrame { frame, received: received.unwrap_or(complete), complete }
+        }))
+    }
+}
+
+/// Decodes a frame, reporting `BytesReceived`, `FrameComplete` and
+/// `DecodeComplete`
+pub fn decode_frame<H: LatencyHook>(frame: &TimedFrame, hook: &mut H) -> Result<FixMessage, FixError> {
+    let msg = FixMessage::decode(&frame.frame)?;
+    if H::ENABLED {
+        let decoded = hook.now();
+        let ctx = MessageCtx::of(&msg);
+        hook.on_stage(Stage::BytesReceived, frame.received, &ctx);
+        hook.on_stage(Stage::FrameComplete, frame.complete, &ctx);
+        hook.on_stage(Stage::DecodeComplete, decoded, &ctx);
+    }
+    Ok(msg)
+}
+
+/// Encodes `msg`, reporting `EncodeComplete`. The caller reports
+/// `BytesWritten` once the transport has taken the bytes.
+pub fn encode_message<H: LatencyHook>(msg: &FixMessage, hook: &mut H) -> Result<BytesMut, FixError> {
+    let encoded = msg.encode()?;
+    hook.stage(Stage::EncodeComplete, &MessageCtx::of(msg));
+    Ok(encoded)
+}
+
+impl Session {
+    /// `process_inbound`, then hands the message and its event to
+    /// `handler`, reporting `HandedToApplication` and `ApplicationReturned`
+    /// around it
+    pub fn dispatch_inbound<H, F, R>(&mut self, msg: &FixMessage, hook: &mut H, handler: F) -> Result<R, FixError>
+    where
+        H: LatencyHook,
+        F: FnOnce(&FixMessage, SessionEvent, &mut H) -> R,
+    {
+        let event = self.process_inbound(msg)?;
+        let ctx = MessageCtx::of(msg);
+        hook.stage(Stage::HandedToApplication, &ctx);
+        let result = handler(msg, event, hook);
+        hook.stage(Stage::ApplicationReturned, &ctx);
+        Ok(result)
+    }
+}
+
+/// Counts of durations in power-of-two nanosecond buckets: bucket `i` holds
+/// durations below 2^i ns and at least 2^(i-1) ns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; 64],
+    count: u64,
+    sum_nanos: u128,
+    max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self { buckets: [0; 64], count: 0, sum_nanos: 0, max: Duration::ZERO }
+    }
+}
+
+impl Histogram {
+    pub fn record(&mut self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - nanos.leading_zeros()).min(63) as usize;
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_nanos += nanos as u128;
+        self.max = self.max.max(duration);
+    }
+
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    #[inline]
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos((self.sum_nanos / self.count as u128) as u64))
+    }
+
+    /// Upper bound of the bucket holding quantile `q` (0.0 to 1.0), capped
+    /// at the largest duration recorded
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|&n| {
+                seen += n;
+                seen >= rank
+            })
+            .unwrap_or(63);
+        let bound = if bucket == 0 { Duration::ZERO } else { Duration::from_nanos((1u64 << bucket) - 1) };
+        Some(bound.min(self.max))
+    }
+}
+
+/// Provided hook recording, for each stage, the time since the stage
+/// reported before it, and tick-to-trade: from `BytesReceived` of an inbound
+/// message to `BytesWritten` of each message sent while it was handled.
+/// Assumes the pipeline handles one inbound message at a time.
+#[derive(Debug, Clone, Default)]
+pub struct HistogramRecorder {
+    since_previous: [Histogram; 7],
+    tick_to_trade: Histogram,
+    previous: Option<Instant>,
+    received: Option<Instant>,
+}
+
+impl HistogramRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time from the previous stage to `stage`
+    pub fn since_previous(&self, stage: Stage) -> &Histogram {
+        &self.since_previous[stage as usize]
+    }
+
+    #[inline]
+    pub fn tick_to_trade(&self) -> &Histogram {
+        &self.tick_to_trade
+    }
+}
+
+impl LatencyHook for HistogramRecorder {
+    fn on_stage(&mut self, stage: Stage, ts: Instant, _ctx: &MessageCtx<'_>) {
+        // The gap before BytesReceived is idle time, not latency
+        if stage == Stage::BytesReceived {
+            self.received = Some(ts);
+        } else if let Some(previous) = self.previous {
+            self.since_previous[stage as usize].record(ts.saturating_duration_since(previous));
+        }
+        if let (Stage::BytesWritten, Some(received)) = (stage, self.received) {
+            self.tick_to_trade.record(ts.saturating_duration_since(received));
+        }
+        self.previous = Some(ts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::session::SessionConfig;
+    use crate::tags::{fix_version, msg_type};
+    use std::cell::Cell;
+
+    // Stage, time since the hook was created, MsgType and MsgSeqNum
+    type Event = (Stage, Duration, Option<Vec<u8>>, Option<u64>);
+
+    // Advances one microsecond per reading
+    struct FakeHook {
+        start: Instant,
+        ticks: Cell<u64>,
+        events: Vec<Event>,
+        recorder: HistogramRecorder,
+    }
+
+    impl FakeHook {
+        fn new() -> Self {
+            Self { start: Instant::now(), ticks: Cell::new(0), events: Vec::new(), recorder: HistogramRecorder::new() }
+        }
+    }
+
+    impl LatencyHook for FakeHook {
+        fn on_stage(&mut self, stage: Stage, ts: Instant, ctx: &MessageCtx<'_>) {
+            let at = ts - self.start;
+            self.events.push((stage, at, ctx.msg_type.map(<[u8]>::to_vec), ctx.seq_num));
+            self.recorder.on_stage(stage, ts, ctx);
+        }
+
+        fn now(&self) -> Instant {
+            self.ticks.set(self.ticks.get() + 1);
+            self.start + Duration::from_micros(self.ticks.get())
+        }
+    }
+
+    fn message(config: &SessionConfig, msg_type: &[u8], seq: u64) -> FixMessage {
+        let mut msg = config.new_message(msg_type);
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg
+    }
+
+    #[test]
+    fn test_stage_order_and_context() {
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let mut session = Session::new(config.clone());
+        let mut hook = FakeHook::new();
+        let mut framer = Framer::new();
+
+        let wire = message(&config, msg_type::NEW_ORDER_SINGLE, 1).encode().unwrap();
+        let (first, rest) = wire.split_at(10);
+        framer.extend_from_slice_at(first, hook.now());
+        assert!(framer.next_timed_frame(&hook).unwrap().is_none());
+        framer.extend_from_slice_at(rest, hook.now());
+        let frame = framer.next_timed_frame(&hook).unwrap().unwrap();
+
+        let inbound = decode_frame(&frame, &mut hook).unwrap();
+        let written = session
+            .dispatch_inbound(&inbound, &mut hook, |_, event, hook| {
+                assert_eq!(event, SessionEvent::Application);
+                let report = message(&config, msg_type::EXECUTION_REPORT, 7);
+                let encoded = encode_message(&report, hook).unwrap();
+                hook.stage(Stage::BytesWritten, &MessageCtx::of(&report));
+                encoded.len()
+            })
+            .unwrap();
+        assert!(written > 0);
+
+        let stages: Vec<Stage> = hook.events.iter().map(|e| e.0).collect();
+        assert_eq!(
+            stages,
+            [
+                Stage::BytesReceived,
+                Stage::FrameComplete,
+                Stage::DecodeComplete,
+                Stage::HandedToApplication,
+                Stage::EncodeComplete,
+                Stage::BytesWritten,
+                Stage::ApplicationReturned,
+            ]
+        );
+        // Received with the first chunk, not the second
+        let micros: Vec<u64> = hook.events.iter().map(|e| e.1.as_micros() as u64).collect();
+        assert_eq!(micros, [1, 3, 4, 5, 6, 7, 8]);
+
+        for (stage, _, msg_type, seq) in &hook.events {
+            let expected: (&[u8], u64) = match stage {
+                Stage::EncodeComplete | Stage::BytesWritten => (msg_type::EXECUTION_REPORT, 7),
+                _ => (msg_type::NEW_ORDER_SINGLE, 1),
+            };
+            assert_eq!((msg_type.as_deref(), *seq), (Some(expected.0), Some(expected.1)), "{stage:?}");
+        }
+
+        let recorder = &hook.recorder;
+        assert_eq!(recorder.tick_to_trade().count(), 1);
+        assert_eq!(recorder.tick_to_trade().max(), Duration::from_micros(6));
+        assert_eq!(recorder.since_previous(Stage::FrameComplete).mean(), Some(Duration::from_micros(2)));
+        assert_eq!(recorder.since_previous(Stage::BytesReceived).count(), 0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for micros in [1, 2, 3, 100] {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.mean(), Some(Duration::from_nanos(26_500)));
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_nanos(2047)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_micros(100)));
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(feature = "latency")]
+pub mod latency;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "latency")]
+pub use latency::{HistogramRecorder, LatencyHook, MessageCtx, NoHook, Stage};
+#[cfg(feature = "prometheus")]
+pub use prometheus::render_prometheus;
+
+use crate::session::{OutboundQueue, SessionEvent};
+
+/// Running totals for one session: inbound messages by how the session
+/// classified them, and messages sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionCounters {
+    pub admin: u64,
+    pub application: u64,
+    pub possible_resends: u64,
+    pub duplicates: u64,
+    pub sequence_gaps: u64,
+    pub sequence_too_low: u64,
+    pub future_timestamps: u64,
+    pub sent: u64,
+}
+
+impl SessionCounters {
+    pub fn record_inbound(&mut self, event: &SessionEvent) {
+        let counter = match event {
+            SessionEvent::Admin => &mut self.admin,
+            SessionEvent::Application => &mut self.application,
+            SessionEvent::PossibleResend => &mut self.possible_resends,
+            SessionEvent::Duplicate(_) => &mut self.duplicates,
+            SessionEvent::SequenceGap { .. } => &mut self.sequence_gaps,
+            SessionEvent::SequenceTooLow { .. } => &mut self.sequence_too_low,
+            SessionEvent::FutureTimestamp { .. } => &mut self.future_timestamps,
+        };
+        *counter += 1;
+    }
+
+    #[inline]
+    pub fn record_outbound(&mut self) {
+        self.sent += 1;
+    }
+
+    /// Inbound messages of every kind
+    pub fn received(&self) -> u64 {
+        self.admin
+            + self.application
+            + self.possible_resends
+            + self.duplicates
+            + self.sequence_gaps
+            + self.sequence_too_low
+            + self.future_timestamps
+    }
+}
+
+/// Errors seen below the session layer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodecCounters {
+    /// Frames that failed to decode
+    pub decode_errors: u64,
+    /// Times a framer skipped bytes to find the next message
+    pub framer_resyncs: u64,
+}
+
+/// A source of per-session metrics, e.g. `Session`
+pub trait SessionMetrics {
+    /// Stable identifier used as the `session` label, e.g.
+    /// "FIX.4.4:CLIENT->VENUE", or "FIX.4.4:CLIENT->VENUE:DROPCOPY" for a
+    /// qualified session
+    fn session_id(&self) -> String;
+
+    fn counters(&self) -> SessionCounters;
+
+    /// Outbound messages waiting to be written; 0 for sources without a queue
+    fn queue_depth(&self) -> usize {
+        0
+    }
+}
+
+/// One session's metrics at the time `MetricsRegistry::record` was called
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSnapshot {
+    pub session_id: String,
+    pub counters: SessionCounters,
+    pub queue_depth: usize,
+}
+
+/// Latest snapshot of every session plus the codec counters, as rendered by
+/// the exporters
+#[derive(Debug, Clone, Default)]
+pub struct MetricsRegistry {
+    sessions: Vec<SessionSnapshot>,
+    pub codec: CodecCounters,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of `session`, replacing the previous one with the
+    /// same id
+    pub fn record(&mut self, session: &dyn SessionMetrics) {
+        self.insert(session, session.queue_depth());
+    }
+
+    /// `record`, taking the queue depth from the `queue` feeding the
+    /// session
+    pub fn record_queued(&mut self, session: &dyn SessionMetrics, queue: &OutboundQueue) {
+        self.insert(session, queue.len());
+    }
+
+    fn insert(&mut self, session: &dyn SessionMetrics, queue_depth: usize) {
+        let snapshot = SessionSnapshot { session_id: session.session_id(), counters: session.counters(), queue_depth };
+        match self.sessions.iter_mut().find(|s| s.session_id == snapshot.session_id) {
+            Some(existing) => *existing = snapshot,
+            None => self.sessions.push(snapshot),
+        }
+    }
+
+    /// Snapshots in the order sessions were first recorded
+    #[inline]
+    pub fn sessions(&self) -> &[SessionSnapshot] {
+        &self.sessions
+    }
+}
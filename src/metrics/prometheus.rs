@@ -0,0 +1,223 @@
+use super::{MetricsRegistry, SessionCounters};
+use std::fmt::Write;
+
+// Metric names are part of the exported interface; dashboards and alerts
+// depend on them, so they must not change:
+//
+// fix_session_inbound_messages_total{session, event}  counter
+//     event is one of admin, application, possible_resend, duplicate,
+//     sequence_gap, sequence_too_low, future_timestamp
+// fix_session_outbound_messages_total{session}        counter
+// fix_session_queue_depth{session}                    gauge
+// fix_codec_decode_errors_total                       counter
+// fix_framer_resyncs_total                            counter
+//
+// `session` is `SessionMetrics::session_id`, e.g. "FIX.4.4:CLIENT->VENUE".
+
+fn inbound_events(c: &SessionCounters) -> [(&'static str, u64); 7] {
+    [
+        ("admin", c.admin),
+        ("application", c.application),
+        ("possible_resend", c.possible_resends),
+        ("duplicate", c.duplicates),
+        ("sequence_gap", c.sequence_gaps),
+        ("sequence_too_low", c.sequence_too_low),
+        ("future_timestamp", c.future_timestamps),
+    ]
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+// Label values escape backslash, double quote and newline
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders the registry in the Prometheus text exposition format (0.0.4),
+/// ready to be served from a scrape endpoint
+pub fn render_prometheus(registry: &MetricsRegistry) -> String {
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "fix_session_inbound_messages_total",
+        "counter",
+        "Inbound messages by how the session classified them",
+    );
+    for session in registry.sessions() {
+        let id = escape(&session.session_id);
+        for (event, value) in inbound_events(&session.counters) {
+            let _ = writeln!(
+                out,
+                "fix_session_inbound_messages_total{{session=\"{id}\",event=\"{event}\"}} {value}"
+            );
+        }
+    }
+
+    header(&mut out, "fix_session_outbound_messages_total", "counter", "Messages sent");
+    for session in registry.sessions() {
+        let _ = writeln!(
+            out,
+            "fix_session_outbound_messages_total{{session=\"{}\"}} {}",
+            escape(&session.session_id),
+            session.counters.sent
+        );
+    }
+
+    header(&mut out, "fix_session_queue_depth", "gauge", "Outbound messages waiting to be written");
+    for session in registry.sessions() {
+        let _ = writeln!(
+            out,
+            "fix_session_queue_depth{{session=\"{}\"}} {}",
+            escape(&session.session_id),
+            session.queue_depth
+        );
+    }
+
+    header(&mut out, "fix_codec_decode_errors_total", "counter", "Frames that failed to decode");
+    let _ = writeln!(out, "fix_codec_decode_errors_total {}", registry.codec.decode_errors);
+
+    header(&mut out, "fix_framer_resyncs_total", "counter", "Times a framer skipped bytes to find the next message");
+    let _ = writeln!(out, "fix_framer_resyncs_total {}", registry.codec.framer_resyncs);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::message::FixMessage;
+    use crate::framing::Framer;
+    use crate::session::{OutboundQueue, Session, SessionConfig};
+    use crate::tags::{fix_version, msg_type, Tag};
+    use std::collections::HashMap;
+
+    fn is_metric_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    }
+
+    // A minimal checker of the text format: comments are HELP or TYPE lines,
+    // every sample follows a TYPE line for its metric, label values are
+    // quoted with valid escapes and values parse as floats. Returns the
+    // samples keyed by their name and labels as written.
+    fn check_exposition(text: &str) -> HashMap<String, f64> {
+        let mut types = HashMap::new();
+        let mut samples = HashMap::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let (keyword, name, rest) = (parts.next().unwrap(), parts.next().unwrap(), parts.next().unwrap());
+                assert!(is_metric_name(name), "{line}");
+                match keyword {
+                    "HELP" => {}
+                    "TYPE" => {
+                        assert!(["counter", "gauge"].contains(&rest), "{line}");
+                        assert!(types.insert(name.to_owned(), rest.to_owned()).is_none(), "{line}");
+                    }
+                    _ => panic!("unexpected comment {line}"),
+                }
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let value: f64 = value.parse().unwrap();
+            let name = match series.split_once('{') {
+                Some((name, labels)) => {
+                    let labels = labels.strip_suffix('}').unwrap();
+                    let mut rest = labels;
+                    while !rest.is_empty() {
+                        let (label, after) = rest.split_once("=\"").unwrap();
+                        assert!(is_metric_name(label), "{line}");
+                        let mut chars = after.char_indices();
+                        let end = loop {
+                            match chars.next().unwrap() {
+                                (_, '\\') => assert!(matches!(chars.next().unwrap().1, '\\' | '"' | 'n')),
+                                (i, '"') => break i,
+                                _ => {}
+                            }
+                        };
+                        rest = after[end + 1..].strip_prefix(',').unwrap_or(&after[end + 1..]);
+                    }
+                    name
+                }
+                None => series,
+            };
+            assert!(types.contains_key(name), "sample before TYPE: {line}");
+            if types[name] == "counter" {
+                assert!(name.ends_with("_total") && value >= 0.0, "{line}");
+            }
+            assert!(samples.insert(series.to_owned(), value).is_none(), "duplicate {line}");
+        }
+        samples
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let mut session = Session::new(SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VE\"NUE"));
+        for (msg_type, seq) in [(msg_type::LOGON, 1), (msg_type::NEW_ORDER_SINGLE, 2), (msg_type::NEW_ORDER_SINGLE, 5)] {
+            let mut msg = FixMessage::new();
+            msg.add_field(FixField::new(Tag::MsgType.value(), msg_type.to_vec()));
+            msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &(seq as u64)));
+            session.process_inbound(&msg).unwrap();
+        }
+        session.record_outbound();
+
+        let mut registry = MetricsRegistry::new();
+        registry.record(&session);
+        registry.record(&Session::new(SessionConfig::new(fix_version::FIX_4_2, "A", "B")));
+        registry.codec.decode_errors = 3;
+
+        let samples = check_exposition(&render_prometheus(&registry));
+        let inbound = |event: &str| {
+            samples[&format!(
+                "fix_session_inbound_messages_total{{session=\"FIX.4.4:CLIENT->VE\\\"NUE\",event=\"{event}\"}}"
+            )]
+        };
+        assert_eq!(inbound("admin"), 1.0);
+        assert_eq!(inbound("application"), 1.0);
+        assert_eq!(inbound("sequence_gap"), 1.0);
+        assert_eq!(samples["fix_session_outbound_messages_total{session=\"FIX.4.4:CLIENT->VE\\\"NUE\"}"], 1.0);
+        assert_eq!(samples["fix_session_queue_depth{session=\"FIX.4.2:A->B\"}"], 0.0);
+        assert_eq!(samples["fix_codec_decode_errors_total"], 3.0);
+        assert_eq!(samples["fix_framer_resyncs_total"], 0.0);
+        assert_eq!(samples.len(), 2 * (7 + 2) + 2);
+    }
+
+    #[test]
+    fn test_counters_from_sources() {
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let mut queue = OutboundQueue::new();
+        queue.push(config.heartbeat(None));
+        queue.push(config.heartbeat(None));
+
+        let heartbeat = b"8=FIX.4.4\x019=5\x0135=0\x0110=163\x01";
+        let mut corrupt = heartbeat.to_vec();
+        corrupt[heartbeat.len() - 2] = b'4';
+        let mut framer = Framer::new();
+        framer.extend_from_slice(&[&b"junk"[..], heartbeat, &corrupt[..], heartbeat].concat());
+        while framer.next_message().is_some() {}
+
+        let mut registry = MetricsRegistry::new();
+        registry.record_queued(&Session::new(config), &queue);
+        registry.codec = framer.counters();
+        let samples = check_exposition(&render_prometheus(&registry));
+        assert_eq!(samples["fix_session_queue_depth{session=\"FIX.4.4:CLIENT->VENUE\"}"], 2.0);
+        assert_eq!(samples["fix_codec_decode_errors_total"], 1.0);
+        assert_eq!(samples["fix_framer_resyncs_total"], 1.0);
+    }
+}
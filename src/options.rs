@@ -0,0 +1,119 @@
+use crate::decimal::DecimalFormat;
+use crate::field::PaddingRule;
+use crate::tags;
+use rustc_hash::FxHashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Settings applied by `FixMessage::encode_with_options`
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptions {
+    /// Reject messages whose complete encoding, trailer included, would be
+    /// longer than this many bytes (e.g. a venue's 8 KB limit)
+    pub max_encoded_len: Option<usize>,
+    /// Per-tag decimal formatting; values of these tags are re-formatted on
+    /// encode and fail the encode if they do not fit
+    pub decimal_formats: FxHashMap<u32, DecimalFormat>,
+    /// Per-tag fixed-width padding, applied after any decimal formatting
+    pub padding_rules: FxHashMap<u32, PaddingRule>,
+}
+
+impl EncodeOptions {
+    pub fn with_decimal_format(mut self, tag: u32, format: DecimalFormat) -> Self {
+        self.decimal_formats.insert(tag, format);
+        self
+    }
+
+    pub fn with_padding_rule(mut self, tag: u32, rule: PaddingRule) -> Self {
+        self.padding_rules.insert(tag, rule);
+        self
+    }
+
+    #[inline]
+    pub fn decimal_format(&self, tag: u32) -> Option<&DecimalFormat> {
+        self.decimal_formats.get(&tag)
+    }
+
+    #[inline]
+    pub fn padding_rule(&self, tag: u32) -> Option<&PaddingRule> {
+        self.padding_rules.get(&tag)
+    }
+}
+
+/// Checks applied by `FixMessage::decode_with_options`. Both are on by
+/// default, as in `FixMessage::decode`. A BodyLength mismatch let through
+/// is reported by `FixMessage::decode_with_warnings`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Reject a CheckSum (10) that does not match the bytes before it. The
+    /// field must be present either way.
+    pub verify_checksum: bool,
+    /// Reject a BodyLength (9) that does not match the body, rather than
+    /// only warn of it
+    pub verify_body_length: bool,
+    /// Length and value tags of data fields read by their declared length
+    /// rather than up to the next SOH, on top of the standard
+    /// `DATA_FIELDS`. None by default.
+    pub data_fields: Option<Arc<[(u32, u32)]>>,
+}
+
+impl DecodeOptions {
+    pub fn with_verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    pub fn with_verify_body_length(mut self, verify: bool) -> Self {
+        self.verify_body_length = verify;
+        self
+    }
+
+    /// Also reads the data fields of `pairs`, e.g. a venue's custom length
+    /// and value tags, given as a slice, a `Vec` or an `Arc` shared between
+    /// options. A pair whose length tag is already known replaces it.
+    pub fn with_data_fields(mut self, pairs: impl Into<Arc<[(u32, u32)]>>) -> Self {
+        let pairs = pairs.into();
+        self.data_fields = Some(match self.data_fields.take() {
+            Some(known) => {
+                let kept = known.iter().filter(|&&(len, _)| !pairs.iter().any(|&(new, _)| new == len));
+                kept.chain(pairs.iter()).copied().collect()
+            }
+            None => pairs,
+        });
+        self
+    }
+
+    /// The data field whose byte length `tag` declares, if `tag` is a length
+    #[inline]
+    pub fn data_field_for(&self, tag: u32) -> Option<u32> {
+        let custom = self.data_fields.as_deref().unwrap_or_default();
+        match custom.iter().find(|&&(len, _)| len == tag) {
+            Some(&(_, data)) => Some(data),
+            None => tags::data_field_for(tag),
+        }
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self { verify_checksum: true, verify_body_length: true, data_fields: None }
+    }
+}
+
+/// A problem `FixMessage::decode_with_warnings` let through because its
+/// check was turned off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeWarning {
+    /// BodyLength declared `declared` bytes of body but there were `actual`
+    BodyLength { declared: u64, actual: u64 },
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeWarning::BodyLength { declared, actual } => {
+                write!(f, "BodyLength is {} but the body is {} bytes", declared, actual)
+            }
+        }
+    }
+}
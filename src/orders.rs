@@ -0,0 +1,396 @@
+use crate::decimal::{FixDecimal, Qty, RoundingMode};
+use crate::error::FixError;
+use crate::messages::execution_report::{exec_type, ord_status, ExecutionReport};
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+/// Whether OrdStatus (39) may change from `from` to `to`, following the
+/// order state change matrices of the FIX specification: filled, canceled,
+/// rejected and expired orders never change again, only an order pending
+/// acceptance may be rejected, and an order with fills never reports New.
+pub fn is_valid_transition(from: u8, to: u8) -> bool {
+    use ord_status::*;
+
+    if from == to {
+        return true;
+    }
+    match (from, to) {
+        (FILLED | CANCELED | REJECTED | EXPIRED, _) => false,
+        (_, PENDING_NEW) => false,
+        (_, REJECTED) => from == PENDING_NEW,
+        (PARTIALLY_FILLED | CALCULATED, NEW) => false,
+        (_, ACCEPTED_FOR_BIDDING) => matches!(from, PENDING_NEW | NEW),
+        _ => true,
+    }
+}
+
+/// An order as its ExecutionReports describe it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderState {
+    pub cl_ord_id: Vec<u8>,
+    /// ClOrdIDs the order had before, oldest first, one per cancel/replace
+    pub previous_cl_ord_ids: Vec<Vec<u8>>,
+    pub order_id: Vec<u8>,
+    pub side: u8,
+    pub symbol: Option<Vec<u8>>,
+    pub ord_status: u8,
+    pub order_qty: Option<Qty>,
+    pub cum_qty: Qty,
+    pub leaves_qty: Qty,
+    /// Sum of LastPx * LastQty over the fills reported
+    pub notional: FixDecimal,
+    /// AvgPx (6) as last reported
+    pub avg_px: Option<FixDecimal>,
+    pub last_exec_id: Vec<u8>,
+}
+
+impl OrderState {
+    fn new(cl_ord_id: &[u8], report: &ExecutionReport) -> Self {
+        Self {
+            cl_ord_id: cl_ord_id.to_vec(),
+            previous_cl_ord_ids: Vec::new(),
+            order_id: report.order_id.clone(),
+            side: report.side,
+            symbol: report.symbol.clone(),
+            ord_status: report.ord_status,
+            order_qty: report.order_qty,
+            cum_qty: report.cum_qty,
+            leaves_qty: report.leaves_qty,
+            notional: FixDecimal::ZERO,
+            avg_px: report.avg_px,
+            last_exec_id: report.exec_id.clone(),
+        }
+    }
+
+    /// Whether the order can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.ord_status,
+            ord_status::FILLED | ord_status::CANCELED | ord_status::REJECTED | ord_status::EXPIRED
+        )
+    }
+
+    /// Average price of the fills reported, to `places` decimal places;
+    /// `None` before the first fill. Reports applied before the tracker
+    /// first saw the order are not included.
+    pub fn fill_avg_px(&self, places: u8) -> Result<Option<FixDecimal>, FixError> {
+        let filled = self.cum_qty.value();
+        if self.notional.is_zero() || filled.is_zero() {
+            return Ok(None);
+        }
+        self.notional.checked_div(filled, places, RoundingMode::HalfEven).map(Some)
+    }
+}
+
+/// What one ExecutionReport changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderUpdate {
+    pub cl_ord_id: Vec<u8>,
+    /// The ClOrdID the order had until this report, on a cancel/replace
+    pub replaced: Option<Vec<u8>>,
+    /// `None` for the first report of an order
+    pub previous_status: Option<u8>,
+    pub ord_status: u8,
+    /// LastQty and LastPx of a fill
+    pub fill: Option<(Qty, FixDecimal)>,
+}
+
+/// Why an ExecutionReport was not applied. `cl_ord_id` is the order's
+/// current ClOrdID.
+#[derive(Debug, Error)]
+pub enum StateError {
+    #[error("ExecutionReport has no ClOrdID")]
+    MissingClOrdID,
+    #[error("Order {cl_ord_id}: OrdStatus cannot change from {} to {}", *.from as char, *.to as char)]
+    IllegalTransition { cl_ord_id: String, from: u8, to: u8 },
+    #[error("Order {cl_ord_id}: CumQty decreased from {previous} to {reported}")]
+    CumQtyDecreased { cl_ord_id: String, previous: Qty, reported: Qty },
+    #[error("Order {cl_ord_id}: {detail}")]
+    QuantityMismatch { cl_ord_id: String, detail: String },
+    #[error(transparent)]
+    Fix(#[from] FixError),
+}
+
+fn is_fill(report: &ExecutionReport) -> bool {
+    matches!(report.exec_type, exec_type::TRADE | exec_type::PARTIAL_FILL | exec_type::FILL)
+}
+
+/// Tracks orders by ClOrdID from their ExecutionReports, following
+/// cancel/replace chains through OrigClOrdID (41) so an order can be looked
+/// up by any ClOrdID it has had. A report that would make an order
+/// inconsistent is refused and leaves the order unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct OrderTracker {
+    orders: FxHashMap<Vec<u8>, OrderState>,
+    // Earlier ClOrdID to current one
+    replaced: FxHashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The order known by `cl_ord_id`, now or before a cancel/replace
+    pub fn order(&self, cl_ord_id: &[u8]) -> Option<&OrderState> {
+        self.orders.get(cl_ord_id).or_else(|| self.orders.get(self.replaced.get(cl_ord_id)?))
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &OrderState> {
+        self.orders.values()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Forgets filled, canceled, rejected and expired orders
+    pub fn remove_terminal(&mut self) {
+        self.orders.retain(|_, order| !order.is_terminal());
+        let orders = &self.orders;
+        self.replaced.retain(|_, current| orders.contains_key(current));
+    }
+
+    pub fn apply(&mut self, report: &ExecutionReport) -> Result<OrderUpdate, StateError> {
+        let cl_ord_id = report.cl_ord_id.as_deref().ok_or(StateError::MissingClOrdID)?;
+        let existing = self
+            .order(cl_ord_id)
+            .or_else(|| report.orig_cl_ord_id.as_deref().and_then(|orig| self.order(orig)));
+        let fill = report.last_qty.zip(report.last_px).filter(|(qty, _)| is_fill(report) && !qty.value().is_zero());
+
+        let mut order = match existing {
+            Some(previous) => {
+                check_against(previous, report, fill)?;
+                previous.clone()
+            }
+            None => OrderState::new(cl_ord_id, report),
+        };
+        let previous_status = existing.map(|previous| previous.ord_status);
+        if let Some((qty, px)) = fill {
+            order.notional = order.notional.checked_add(px.checked_mul(qty.value())?)?;
+        }
+        order.ord_status = report.ord_status;
+        order.order_qty = report.order_qty.or(order.order_qty);
+        order.cum_qty = report.cum_qty;
+        order.leaves_qty = report.leaves_qty;
+        order.avg_px = report.avg_px.or(order.avg_px);
+        order.order_id.clone_from(&report.order_id);
+        order.last_exec_id.clone_from(&report.exec_id);
+        check_quantities(&order)?;
+
+        let replaced = (order.cl_ord_id != cl_ord_id).then(|| std::mem::replace(&mut order.cl_ord_id, cl_ord_id.to_vec()));
+        if let Some(old) = &replaced {
+            self.orders.remove(old);
+            order.previous_cl_ord_ids.push(old.clone());
+            for earlier in &order.previous_cl_ord_ids {
+                self.replaced.insert(earlier.clone(), order.cl_ord_id.clone());
+            }
+        }
+        let update = OrderUpdate {
+            cl_ord_id: order.cl_ord_id.clone(),
+            replaced,
+            previous_status,
+            ord_status: order.ord_status,
+            fill,
+        };
+        self.orders.insert(order.cl_ord_id.clone(), order);
+        Ok(update)
+    }
+}
+
+fn name(order: &OrderState) -> String {
+    String::from_utf8_lossy(&order.cl_ord_id).into_owned()
+}
+
+fn mismatch(order: &OrderState, detail: String) -> StateError {
+    StateError::QuantityMismatch { cl_ord_id: name(order), detail }
+}
+
+// Checks a report against the order's state before it
+fn check_against(order: &OrderState, report: &ExecutionReport, fill: Option<(Qty, FixDecimal)>) -> Result<(), StateError> {
+    if !is_valid_transition(order.ord_status, report.ord_status) {
+        return Err(StateError::IllegalTransition {
+            cl_ord_id: name(order),
+            from: order.ord_status,
+            to: report.ord_status,
+        });
+    }
+    if report.cum_qty < order.cum_qty {
+        return Err(StateError::CumQtyDecreased {
+            cl_ord_id: name(order),
+            previous: order.cum_qty,
+            reported: report.cum_qty,
+        });
+    }
+    if let Some((qty, _)) = fill {
+        let expected = order.cum_qty.value().checked_add(qty.value())?;
+        if report.cum_qty.value() != expected {
+            return Err(mismatch(
+                order,
+                format!("CumQty {} is not {} plus LastQty {}", report.cum_qty, order.cum_qty, qty),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Checks the quantities of an order's new state against each other
+fn check_quantities(order: &OrderState) -> Result<(), StateError> {
+    let (cum, leaves) = (order.cum_qty.value(), order.leaves_qty.value());
+    if let Some(order_qty) = order.order_qty {
+        if cum.checked_add(leaves)? > order_qty.value() {
+            return Err(mismatch(
+                order,
+                format!("CumQty {} plus LeavesQty {} exceeds OrderQty {}", cum, leaves, order_qty),
+            ));
+        }
+    }
+    match order.ord_status {
+        ord_status::FILLED if !leaves.is_zero() => Err(mismatch(order, format!("filled with LeavesQty {}", leaves))),
+        ord_status::NEW | ord_status::PENDING_NEW if !cum.is_zero() => {
+            Err(mismatch(order, format!("reported as new with CumQty {}", cum)))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ord_status::*;
+
+    fn qty(value: &str) -> Qty {
+        Qty::new(value.parse().unwrap()).unwrap()
+    }
+
+    fn px(value: &str) -> FixDecimal {
+        value.parse().unwrap()
+    }
+
+    fn report(cl_ord_id: &str, exec: u8, status: u8, cum: &str, leaves: &str) -> ExecutionReport {
+        ExecutionReport::new("O-1", format!("E-{}-{}", cl_ord_id, cum), exec, status, b'1', qty(leaves), qty(cum))
+            .with_cl_ord_id(cl_ord_id)
+            .with_order_qty(qty("100"))
+    }
+
+    fn trade(cl_ord_id: &str, status: u8, last: &str, last_px: &str, cum: &str, leaves: &str) -> ExecutionReport {
+        report(cl_ord_id, exec_type::TRADE, status, cum, leaves).with_fill(qty(last), px(last_px))
+    }
+
+    // Permitted OrdStatus changes, one row per current status in code
+    // order, taken from the order state change matrices in Volume 4 of the
+    // FIX 4.4 specification
+    const MATRIX: [(u8, &str); 15] = [
+        (NEW, "012345679BCDE"),
+        (PARTIALLY_FILLED, "12345679BCE"),
+        (FILLED, "2"),
+        (DONE_FOR_DAY, "012345679BCE"),
+        (CANCELED, "4"),
+        (REPLACED, "012345679BCE"),
+        (PENDING_CANCEL, "012345679BCE"),
+        (STOPPED, "012345679BCE"),
+        (REJECTED, "8"),
+        (SUSPENDED, "012345679BCE"),
+        (PENDING_NEW, "0123456789ABCDE"),
+        (CALCULATED, "12345679BCE"),
+        (EXPIRED, "C"),
+        (ACCEPTED_FOR_BIDDING, "012345679BCDE"),
+        (PENDING_REPLACE, "012345679BCE"),
+    ];
+
+    #[test]
+    fn test_transition_matrix() {
+        for (from, allowed) in MATRIX {
+            for to in ALL {
+                assert_eq!(
+                    is_valid_transition(from, to),
+                    allowed.as_bytes().contains(&to),
+                    "{} -> {}",
+                    from as char,
+                    to as char
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fills_and_average_price() {
+        let mut tracker = OrderTracker::new();
+        let update = tracker.apply(&report("C-1", exec_type::PENDING_NEW, PENDING_NEW, "0", "100")).unwrap();
+        assert_eq!(update.previous_status, None);
+        tracker.apply(&report("C-1", exec_type::NEW, NEW, "0", "100")).unwrap();
+        tracker.apply(&trade("C-1", PARTIALLY_FILLED, "40", "101.25", "40", "60")).unwrap();
+        let update = tracker.apply(&trade("C-1", FILLED, "60", "101.30", "100", "0")).unwrap();
+        assert_eq!(update.previous_status, Some(PARTIALLY_FILLED));
+        assert_eq!(update.fill, Some((qty("60"), px("101.30"))));
+
+        let order = tracker.order(b"C-1").unwrap();
+        assert!(order.is_terminal());
+        assert_eq!(order.notional, px("10128.0000"));
+        assert_eq!(order.fill_avg_px(4).unwrap(), Some(px("101.28")));
+
+        // Filled -> PartiallyFilled is refused and changes nothing
+        let err = tracker.apply(&trade("C-1", PARTIALLY_FILLED, "10", "101", "100", "0")).unwrap_err();
+        assert!(matches!(err, StateError::IllegalTransition { from: FILLED, to: PARTIALLY_FILLED, .. }), "{err}");
+        assert_eq!(tracker.order(b"C-1").unwrap().ord_status, FILLED);
+
+        tracker.remove_terminal();
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_quantity_inconsistencies() {
+        let mut tracker = OrderTracker::new();
+        tracker.apply(&trade("C-1", PARTIALLY_FILLED, "40", "10", "40", "60")).unwrap();
+
+        let err = tracker.apply(&report("C-1", exec_type::ORDER_STATUS, PARTIALLY_FILLED, "30", "70")).unwrap_err();
+        assert!(matches!(err, StateError::CumQtyDecreased { .. }), "{err}");
+
+        let err = tracker.apply(&trade("C-1", PARTIALLY_FILLED, "10", "10", "60", "40")).unwrap_err();
+        assert_eq!(err.to_string(), "Order C-1: CumQty 60 is not 40 plus LastQty 10");
+
+        let err = tracker.apply(&report("C-1", exec_type::ORDER_STATUS, PARTIALLY_FILLED, "40", "70")).unwrap_err();
+        assert!(matches!(err, StateError::QuantityMismatch { .. }), "{err}");
+
+        assert_eq!(tracker.order(b"C-1").unwrap().cum_qty, qty("40"));
+        assert!(matches!(
+            tracker.apply(&ExecutionReport::new("O-1", "E-9", exec_type::NEW, NEW, b'1', qty("1"), qty("0"))),
+            Err(StateError::MissingClOrdID)
+        ));
+    }
+
+    #[test]
+    fn test_cancel_replace_chain() {
+        let mut tracker = OrderTracker::new();
+        tracker.apply(&report("C-1", exec_type::NEW, NEW, "0", "100")).unwrap();
+        tracker.apply(&trade("C-1", PARTIALLY_FILLED, "30", "50", "30", "70")).unwrap();
+
+        let pending = report("C-2", exec_type::PENDING_REPLACE, PENDING_REPLACE, "30", "70").with_orig_cl_ord_id("C-1");
+        let update = tracker.apply(&pending).unwrap();
+        assert_eq!(update.replaced.as_deref(), Some(&b"C-1"[..]));
+
+        let replaced = report("C-2", exec_type::REPLACED, PARTIALLY_FILLED, "30", "120")
+            .with_orig_cl_ord_id("C-1")
+            .with_order_qty(qty("150"));
+        assert_eq!(tracker.apply(&replaced).unwrap().replaced, None);
+
+        let cancel = report("C-3", exec_type::CANCELED, CANCELED, "30", "0")
+            .with_orig_cl_ord_id("C-2")
+            .with_order_qty(qty("150"));
+        tracker.apply(&cancel).unwrap();
+
+        assert_eq!(tracker.len(), 1);
+        let order = tracker.order(b"C-1").unwrap();
+        assert_eq!(order.cl_ord_id, b"C-3");
+        assert_eq!(order.previous_cl_ord_ids, [b"C-1".to_vec(), b"C-2".to_vec()]);
+        assert_eq!(order.order_qty, Some(qty("150")));
+        assert_eq!(order.fill_avg_px(2).unwrap(), Some(px("50")));
+        assert_eq!(tracker.order(b"C-2"), Some(order));
+    }
+}
@@ -0,0 +1,386 @@
+use crate::error::FixError;
+use crate::message::FixMessage;
+use crate::messages::ResendRequest;
+use crate::seqnum::SeqNum;
+use crate::session::{Clock, MemoryStore, SessionConfig, SessionEvent};
+use crate::tags::{msg_type, Tag};
+use crate::transport::websocket::WsTransport;
+use crate::transport::TransportConfig;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::MaybeTlsStream;
+
+type Transport = WsTransport<MaybeTlsStream<TcpStream>>;
+
+const DEFAULT_TEST_REQUESTS: u32 = 3;
+const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a probe does: the session to log on as, where to connect, how many
+/// TestRequests to send and how long to wait for each answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeConfig {
+    pub session: SessionConfig,
+    pub transport: TransportConfig,
+    pub test_requests: u32,
+    /// How long to wait for the connection and for each expected reply
+    pub response_timeout: Duration,
+}
+
+impl ProbeConfig {
+    pub fn new(session: SessionConfig, transport: TransportConfig) -> Self {
+        Self {
+            session,
+            transport,
+            test_requests: DEFAULT_TEST_REQUESTS,
+            response_timeout: DEFAULT_RESPONSE_TIMEOUT,
+        }
+    }
+
+    pub fn with_test_requests(mut self, count: u32) -> Self {
+        self.test_requests = count;
+        self
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+}
+
+/// One TestRequest and the Heartbeat answering it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrip {
+    pub test_req_id: Vec<u8>,
+    pub rtt: Duration,
+    /// The Heartbeat's SendingTime less our clock halfway through the round
+    /// trip, in nanoseconds; positive when the peer's clock is ahead
+    pub clock_skew_nanos: Option<i64>,
+}
+
+/// Something the probe saw that a healthy session would not show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    /// The peer answered our Logon with a Logout
+    LogonRejected { text: Option<String> },
+    /// A Reject (35=3) of one of our messages
+    Reject { ref_seq_num: Option<u64>, text: Option<String> },
+    /// The session classified an inbound message as out of order or from
+    /// the future
+    Session(SessionEvent),
+    /// The peer asked for messages again
+    ResendRequested(ResendRequest),
+    /// The peer logged out before we did
+    UnsolicitedLogout { text: Option<String> },
+    /// A message the probe did not expect, by MsgType
+    Unexpected(Vec<u8>),
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = |text: &Option<String>| text.as_deref().map(|text| format!(": {}", text)).unwrap_or_default();
+        match self {
+            Anomaly::LogonRejected { text: reason } => write!(f, "Logon rejected{}", text(reason)),
+            Anomaly::Reject { ref_seq_num, text: reason } => match ref_seq_num {
+                Some(seq) => write!(f, "Reject of MsgSeqNum {}{}", seq, text(reason)),
+                None => write!(f, "Reject{}", text(reason)),
+            },
+            Anomaly::Session(event) => write!(f, "Session event {:?}", event),
+            Anomaly::ResendRequested(request) => {
+                write!(f, "ResendRequest from {} to {}", request.begin_seq_no, request.end_seq_no)
+            }
+            Anomaly::UnsolicitedLogout { text: reason } => write!(f, "Logout by the peer{}", text(reason)),
+            Anomaly::Unexpected(msg_type) => write!(f, "Unexpected MsgType {}", String::from_utf8_lossy(msg_type)),
+        }
+    }
+}
+
+/// What a probe found. Stages after a failure are left empty.
+#[derive(Debug, Default)]
+pub struct ProbeReport {
+    /// Time to open the connection
+    pub connect: Option<Duration>,
+    /// From sending our Logon to receiving the peer's
+    pub logon: Option<Duration>,
+    pub round_trips: Vec<RoundTrip>,
+    /// From sending our Logout to receiving the peer's
+    pub logout: Option<Duration>,
+    pub total: Duration,
+    /// MsgSeqNum of the first and last messages received
+    pub first_inbound_seq: Option<SeqNum>,
+    pub last_inbound_seq: Option<SeqNum>,
+    /// MsgSeqNum of the last message sent
+    pub last_outbound_seq: Option<SeqNum>,
+    pub anomalies: Vec<Anomaly>,
+    /// The failure that ended the probe early, a timeout included
+    pub error: Option<FixError>,
+}
+
+impl ProbeReport {
+    /// Logged on and out, every TestRequest answered and nothing amiss
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none() && self.logon.is_some() && self.logout.is_some() && self.anomalies.is_empty()
+    }
+
+    /// The clock skew measured on the fastest round trip, where the
+    /// estimate is least affected by network delay
+    pub fn clock_skew_nanos(&self) -> Option<i64> {
+        self.round_trips
+            .iter()
+            .filter(|trip| trip.clock_skew_nanos.is_some())
+            .min_by_key(|trip| trip.rtt)
+            .and_then(|trip| trip.clock_skew_nanos)
+    }
+}
+
+/// Checks connectivity and credentials against a venue without trading:
+/// connects with the initiator transport, logs on, sends TestRequests and
+/// times the Heartbeats answering them, then logs out. Sequence numbers
+/// start at 1 and nothing is persisted, so the probe should use a session
+/// of its own or reset sequence numbers on logon.
+#[derive(Debug, Clone, Default)]
+pub struct SessionProbe {
+    clock: Clock,
+}
+
+impl SessionProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the system clock the peer's SendingTime is compared with
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Runs the probe. Failures are reported rather than returned, so the
+    /// report always says how far the probe got.
+    pub async fn run(&self, config: &ProbeConfig) -> ProbeReport {
+        let started = Instant::now();
+        let mut run = ProbeRun {
+            clock: &self.clock,
+            config,
+            store: MemoryStore::default(),
+            report: ProbeReport::default(),
+        };
+        if let Err(err) = run.probe().await {
+            run.report.error = Some(err);
+        }
+        run.report.total = started.elapsed();
+        run.report
+    }
+}
+
+struct ProbeRun<'a> {
+    clock: &'a Clock,
+    config: &'a ProbeConfig,
+    store: MemoryStore,
+    report: ProbeReport,
+}
+
+impl ProbeRun<'_> {
+    async fn probe(&mut self) -> Result<(), FixError> {
+        let started = Instant::now();
+        let connecting = Transport::initiate(self.config.session.clone(), &self.config.transport);
+        let mut transport = self.timed(connecting).await??;
+        self.report.connect = Some(started.elapsed());
+
+        let started = Instant::now();
+        self.send(&mut transport, self.config.session.logon()).await?;
+        let logon = self.expect(&mut transport, msg_type::LOGON, None).await?;
+        if logon_rejected(&logon) {
+            let _ = transport.close().await;
+            return Ok(());
+        }
+        self.report.logon = Some(started.elapsed());
+
+        for n in 1..=self.config.test_requests {
+            let test_req_id = format!("PROBE-{}", n).into_bytes();
+            let sent_at = self.clock.now_nanos();
+            let started = Instant::now();
+            self.send(&mut transport, self.config.session.test_request(&test_req_id)).await?;
+            let Some(heartbeat) = self.expect(&mut transport, msg_type::HEARTBEAT, Some(&test_req_id)).await? else {
+                return Ok(());
+            };
+            let rtt = started.elapsed();
+            let midpoint = sent_at + (self.clock.now_nanos() - sent_at) / 2;
+            let sending_time = heartbeat.get_utc_timestamp(Tag::SendingTime.value())?;
+            self.report.round_trips.push(RoundTrip {
+                test_req_id,
+                rtt,
+                clock_skew_nanos: sending_time.map(|time| time.unix_nanos() - midpoint),
+            });
+        }
+
+        let started = Instant::now();
+        self.send(&mut transport, self.config.session.logout(None)).await?;
+        if self.expect(&mut transport, msg_type::LOGOUT, None).await?.is_some() {
+            self.report.logout = Some(started.elapsed());
+        }
+        transport.close().await
+    }
+
+    async fn timed<T>(&self, step: impl Future<Output = T>) -> Result<T, FixError> {
+        let timeout = self.config.response_timeout;
+        tokio::time::timeout(timeout, step)
+            .await
+            .map_err(|_| FixError::Transport(format!("no response within {:?}", timeout)))
+    }
+
+    async fn send(&mut self, transport: &mut Transport, mut msg: FixMessage) -> Result<(), FixError> {
+        let seq = transport.send_sequenced(&mut msg, &mut self.store).await?;
+        self.report.last_outbound_seq = Some(seq);
+        Ok(())
+    }
+
+    // Reads until a message of `expected` type (with `test_req_id`, if
+    // given) arrives, answering TestRequests and ResendRequests on the way.
+    // `None` once the peer logs out or closes the connection.
+    async fn expect(
+        &mut self,
+        transport: &mut Transport,
+        expected: &[u8],
+        test_req_id: Option<&[u8]>,
+    ) -> Result<Option<FixMessage>, FixError> {
+        loop {
+            let Some((msg, event)) = self.timed(transport.recv()).await?? else {
+                return Err(FixError::Transport("connection closed by the peer".to_owned()));
+            };
+            if let Ok(Some(seq)) = msg.msg_seq_num() {
+                self.report.first_inbound_seq.get_or_insert(seq);
+                self.report.last_inbound_seq = Some(seq);
+            }
+            if !matches!(event, SessionEvent::Admin | SessionEvent::Application | SessionEvent::PossibleResend) {
+                self.report.anomalies.push(Anomaly::Session(event));
+            }
+
+            let msg_type = msg.get_field(Tag::MsgType.value()).map_or(&[][..], |field| field.value());
+            let text = msg.get_str_owned(Tag::Text.value());
+            match msg_type {
+                msg_type::LOGOUT if expected == msg_type::LOGON => {
+                    self.report.anomalies.push(Anomaly::LogonRejected { text });
+                    return Ok(Some(msg));
+                }
+                msg_type::LOGOUT if expected != msg_type::LOGOUT => {
+                    self.report.anomalies.push(Anomaly::UnsolicitedLogout { text });
+                    return Ok(None);
+                }
+                _ if msg_type == expected
+                    && test_req_id.is_none_or(|id| msg.get_field(Tag::TestReqID.value()).is_some_and(|f| f.value() == id)) =>
+                {
+                    return Ok(Some(msg));
+                }
+                msg_type::HEARTBEAT => {}
+                msg_type::TEST_REQUEST => {
+                    let id = msg.get_field(Tag::TestReqID.value()).map(|field| field.value().to_vec());
+                    self.send(transport, self.config.session.heartbeat(id.as_deref())).await?;
+                }
+                msg_type::RESEND_REQUEST => {
+                    let request = ResendRequest::from_message(&msg)?;
+                    for resent in transport.session().resend(&request, &self.store)? {
+                        transport.send(&resent).await?;
+                    }
+                    self.report.anomalies.push(Anomaly::ResendRequested(request));
+                }
+                msg_type::REJECT => {
+                    let ref_seq_num = msg.get_uint(Tag::RefSeqNum.value())?;
+                    self.report.anomalies.push(Anomaly::Reject { ref_seq_num, text });
+                }
+                _ => self.report.anomalies.push(Anomaly::Unexpected(msg_type.to_vec())),
+            }
+        }
+    }
+}
+
+fn logon_rejected(response: &Option<FixMessage>) -> bool {
+    response
+        .as_ref()
+        .and_then(|msg| msg.get_field(Tag::MsgType.value()))
+        .is_none_or(|field| field.value() == msg_type::LOGOUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Session;
+    use crate::tags::fix_version;
+    use tokio::net::TcpListener;
+
+    const SKEW: i64 = 2_000_000_000;
+
+    // The in-crate acceptor: a venue session whose clock runs SKEW ahead
+    // of ours, answering TestRequests and Logout
+    async fn venue() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let clock = Clock::from_fn(|| Clock::system().now_nanos() + SKEW);
+                let session = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "PROBE")).with_clock(clock);
+                tokio::spawn(serve(WsTransport::new(ws, session)));
+            }
+        });
+        port
+    }
+
+    async fn serve(mut transport: WsTransport<TcpStream>) {
+        let mut store = MemoryStore::default();
+        if !transport.accept_logon(&mut store).await.unwrap() {
+            return;
+        }
+        let config = transport.session().config().clone();
+        while let Ok(Some((msg, _))) = transport.recv().await {
+            let mut response = match msg.get_field(Tag::MsgType.value()).unwrap().value() {
+                msg_type::TEST_REQUEST => config.heartbeat(Some(msg.get_field(Tag::TestReqID.value()).unwrap().value())),
+                msg_type::LOGOUT => config.logout(None),
+                _ => continue,
+            };
+            transport.send_sequenced(&mut response, &mut store).await.unwrap();
+        }
+    }
+
+    fn config(target: &str, port: u16) -> ProbeConfig {
+        ProbeConfig::new(
+            SessionConfig::new(fix_version::FIX_4_4, "PROBE", target),
+            TransportConfig::initiator("127.0.0.1", port),
+        )
+        .with_response_timeout(Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn test_healthy_session() {
+        let report = SessionProbe::new().run(&config("VENUE", venue().await)).await;
+        assert!(report.is_healthy(), "{:?}", report);
+        assert!(report.connect.is_some() && report.logon.is_some());
+        assert_eq!(report.round_trips.len(), 3);
+        assert_eq!(report.round_trips[2].test_req_id, b"PROBE-3");
+
+        // Logon, three Heartbeats and Logout each way
+        assert_eq!(report.first_inbound_seq, SeqNum::new(1));
+        assert_eq!(report.last_inbound_seq, SeqNum::new(5));
+        assert_eq!(report.last_outbound_seq, SeqNum::new(5));
+
+        // SendingTime has microsecond precision and the round trip adds
+        // uncertainty
+        let skew = report.clock_skew_nanos().unwrap();
+        assert!((skew - SKEW).abs() < 500_000_000, "{}", skew);
+    }
+
+    #[tokio::test]
+    async fn test_logon_rejected_and_unreachable() {
+        let report = SessionProbe::new().run(&config("OTHER", venue().await)).await;
+        assert!(!report.is_healthy());
+        assert!(report.error.is_none(), "{:?}", report.error);
+        assert!(report.connect.is_some() && report.logon.is_none());
+        assert_eq!(report.anomalies, [Anomaly::LogonRejected { text: Some("Invalid TargetCompID".to_owned()) }]);
+        assert_eq!(report.anomalies[0].to_string(), "Logon rejected: Invalid TargetCompID");
+
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let report = SessionProbe::new().run(&config("VENUE", port)).await;
+        assert!(matches!(report.error, Some(FixError::Transport(_))), "{:?}", report.error);
+        assert!(report.connect.is_none() && report.round_trips.is_empty());
+    }
+}
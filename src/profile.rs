@@ -0,0 +1,246 @@
+use crate::dictionary::{DataDictionary, FieldSpec, FieldType};
+use crate::error::FixError;
+use crate::message::FixMessage;
+use crate::options::{DecodeOptions, EncodeOptions};
+use crate::session::{Session, SessionConfig};
+use crate::tags::{fix_version, msg_type, Tag};
+use bytes::BytesMut;
+use std::time::Duration;
+
+// Username (553) and Password (554) on Logon
+const USERNAME: u32 = 553;
+const PASSWORD: u32 = 554;
+
+/// Session settings a venue fixes, applied over a session's own
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOverrides {
+    pub heart_bt_int: Option<u32>,
+    pub reset_on_logon: Option<bool>,
+    pub reject_future_sending_time: Option<Duration>,
+}
+
+/// Everything particular to one venue in one place: its dictionary, how
+/// messages to it are encoded and from it decoded, the session settings it
+/// fixes and the custom fields it requires on each message type. A session
+/// or codec built from a profile gets all of it at once.
+#[derive(Debug, Clone)]
+pub struct VenueProfile {
+    pub name: String,
+    pub begin_string: Vec<u8>,
+    pub dictionary: DataDictionary,
+    pub encode: EncodeOptions,
+    pub decode: DecodeOptions,
+    pub session: SessionOverrides,
+    /// Whether inbound messages must pass `validate_header_order`
+    pub strict_header_order: bool,
+    // Tags required on a message type beyond what the dictionary requires
+    required: Vec<(Vec<u8>, u32)>,
+}
+
+impl VenueProfile {
+    /// A profile with default encode and decode options
+    pub fn new(name: impl Into<String>, begin_string: impl Into<Vec<u8>>, dictionary: DataDictionary) -> Self {
+        Self {
+            name: name.into(),
+            begin_string: begin_string.into(),
+            dictionary,
+            encode: EncodeOptions::default(),
+            decode: DecodeOptions::default(),
+            session: SessionOverrides::default(),
+            strict_header_order: false,
+            required: Vec::new(),
+        }
+    }
+
+    /// A FIX 4.4 venue that enforces the standard: defined tags and OrdType
+    /// values only, header fields in order, an 8 KB message limit and a
+    /// Username on Logon
+    pub fn strict_fix44() -> Self {
+        let ord_types = ["1", "2", "3", "4"];
+        let dictionary = DataDictionary::fix44()
+            .with_unknown_tag_reporting(true)
+            .with_field(FieldSpec::new(Tag::OrdType.value(), "OrdType", FieldType::Char).with_values(ord_types))
+            .with_field(FieldSpec::new(USERNAME, "Username", FieldType::String))
+            .with_field(FieldSpec::new(PASSWORD, "Password", FieldType::String));
+        let encode = EncodeOptions { max_encoded_len: Some(8192), ..EncodeOptions::default() };
+        Self::new("strict FIX 4.4", fix_version::FIX_4_4, dictionary)
+            .with_encode_options(encode)
+            .with_strict_header_order(true)
+            .with_required_field(msg_type::LOGON, USERNAME)
+    }
+
+    /// A FIX 4.2 legacy bridge that tolerates a wrong CheckSum, custom tags
+    /// and values, and expects a desk code in tag 5000 on every order. It
+    /// resets sequence numbers at each Logon with a 60 second heartbeat.
+    pub fn lenient_fix42() -> Self {
+        let session = SessionOverrides { heart_bt_int: Some(60), reset_on_logon: Some(true), ..SessionOverrides::default() };
+        Self::new("lenient FIX 4.2", fix_version::FIX_4_2, DataDictionary::new())
+            .with_decode_options(DecodeOptions::default().with_verify_checksum(false))
+            .with_session_overrides(session)
+            .with_required_field(msg_type::NEW_ORDER_SINGLE, 5000)
+    }
+
+    pub fn with_encode_options(mut self, options: EncodeOptions) -> Self {
+        self.encode = options;
+        self
+    }
+
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.decode = options;
+        self
+    }
+
+    pub fn with_session_overrides(mut self, overrides: SessionOverrides) -> Self {
+        self.session = overrides;
+        self
+    }
+
+    pub fn with_strict_header_order(mut self, strict: bool) -> Self {
+        self.strict_header_order = strict;
+        self
+    }
+
+    /// Requires `tag` on every message of type `msg_type`
+    pub fn with_required_field(mut self, msg_type: &[u8], tag: u32) -> Self {
+        self.required.push((msg_type.to_vec(), tag));
+        self
+    }
+
+    /// Tags required on `msg_type`: the dictionary's, then the profile's
+    pub fn required_fields<'a>(&'a self, msg_type: &'a [u8]) -> impl Iterator<Item = u32> + 'a {
+        let defined = self.dictionary.message(msg_type).map_or(&[][..], |spec| spec.required());
+        let custom = self.required.iter().filter(move |(t, _)| t[..] == *msg_type).map(|&(_, tag)| tag);
+        defined.iter().copied().chain(custom)
+    }
+
+    /// A session configuration for this venue: its BeginString, with the
+    /// profile's session settings applied over the defaults
+    pub fn session_config(&self, sender_comp_id: impl Into<Vec<u8>>, target_comp_id: impl Into<Vec<u8>>) -> SessionConfig {
+        self.apply(SessionConfig::new(self.begin_string.clone(), sender_comp_id, target_comp_id))
+    }
+
+    /// `config` with this venue's BeginString and session settings
+    pub fn apply(&self, mut config: SessionConfig) -> SessionConfig {
+        config.begin_string.clone_from(&self.begin_string);
+        if let Some(heart_bt_int) = self.session.heart_bt_int {
+            config.heart_bt_int = heart_bt_int;
+        }
+        if let Some(reset) = self.session.reset_on_logon {
+            config.reset_on_logon = reset;
+        }
+        if let Some(tolerance) = self.session.reject_future_sending_time {
+            config.reject_future_sending_time = Some(tolerance);
+        }
+        config
+    }
+
+    pub fn session(&self, sender_comp_id: impl Into<Vec<u8>>, target_comp_id: impl Into<Vec<u8>>) -> Session {
+        Session::new(self.session_config(sender_comp_id, target_comp_id))
+    }
+
+    /// Checks `msg` against the dictionary and the required fields of its
+    /// type
+    pub fn check(&self, msg: &FixMessage) -> Result<(), FixError> {
+        msg.validate(&self.dictionary)?;
+        let msg_type = msg.get_field(Tag::MsgType.value()).ok_or(FixError::MissingField(Tag::MsgType.value()))?;
+        match self.required_fields(msg_type.value()).find(|&tag| msg.get_field(tag).is_none()) {
+            Some(missing) => Err(FixError::MissingField(missing)),
+            None => Ok(()),
+        }
+    }
+
+    /// Decodes a message from the venue with the profile's decode options
+    /// and checks it
+    pub fn decode(&self, data: &[u8]) -> Result<FixMessage, FixError> {
+        let msg = FixMessage::decode_with_options(data, &self.decode)?;
+        if self.strict_header_order {
+            msg.validate_header_order()?;
+        }
+        self.check(&msg)?;
+        Ok(msg)
+    }
+
+    /// Checks a message for the venue and encodes it with the profile's
+    /// encode options
+    pub fn encode(&self, msg: &FixMessage) -> Result<BytesMut, FixError> {
+        self.check(msg)?;
+        msg.encode_with_options(&self.encode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+
+    // A wire message with `body` exactly as given, '|' for SOH, and either
+    // the right CheckSum or `checksum`
+    fn frame(begin_string: &str, body: &str, checksum: Option<&str>) -> Vec<u8> {
+        let body = body.replace('|', "\x01");
+        let mut wire = format!("8={}\x019={}\x01{}", begin_string, body.len(), body).into_bytes();
+        let sum = wire.iter().map(|&b| b as u32).sum::<u32>() % 256;
+        let checksum = checksum.map_or_else(|| format!("{:03}", sum), str::to_owned);
+        wire.extend_from_slice(format!("10={}\x01", checksum).as_bytes());
+        wire
+    }
+
+    const ORDER: &str = "35=D|49=CLIENT|56=VENUE|34=1|52=20240101-12:00:00.000|11=ORD-1|55=VOD.L|54=1|\
+                         60=20240101-12:00:00.000|38=100|";
+
+    #[test]
+    fn test_strict_rejects_what_lenient_accepts() {
+        let strict = VenueProfile::strict_fix44();
+        let lenient = VenueProfile::lenient_fix42();
+
+        // A desk-coded order with a venue-specific OrdType and a CheckSum
+        // the bridge gets wrong
+        let body = format!("{}40=Z|5000=DESK7|", ORDER);
+        let wire = frame("FIX.4.2", &body, Some("000"));
+        let decoded = lenient.decode(&wire).unwrap();
+        assert_eq!(decoded.get_field(5000).unwrap().value(), b"DESK7");
+        assert!(lenient.encode(&decoded).is_ok());
+
+        assert!(matches!(strict.decode(&wire), Err(FixError::InvalidChecksum)));
+        let wire = frame("FIX.4.4", &body, None);
+        assert!(matches!(strict.decode(&wire), Err(FixError::InvalidFieldValue)));
+        let wire = frame("FIX.4.4", &format!("{}40=2|5000=DESK7|", ORDER), None);
+        assert!(matches!(strict.decode(&wire), Err(FixError::UnknownTag(5000))));
+    }
+
+    #[test]
+    fn test_lenient_rejects_what_strict_accepts() {
+        let strict = VenueProfile::strict_fix44();
+        let lenient = VenueProfile::lenient_fix42();
+
+        let wire = frame("FIX.4.4", &format!("{}40=2|", ORDER), None);
+        let decoded = strict.decode(&wire).unwrap();
+        assert_eq!(strict.encode(&decoded).unwrap(), wire);
+        assert!(matches!(lenient.decode(&wire), Err(FixError::MissingField(5000))));
+
+        // Fields out of the standard header order
+        let reordered = ORDER.replacen("49=CLIENT|56=VENUE|34=1|", "34=1|49=CLIENT|56=VENUE|", 1);
+        let wire = frame("FIX.4.4", &format!("{}40=2|", reordered), None);
+        assert!(matches!(strict.decode(&wire), Err(FixError::HeaderOutOfOrder(49))));
+        assert!(lenient.decode(&frame("FIX.4.2", &format!("{}40=2|5000=D|", reordered), None)).is_ok());
+
+        // A custom tag required on Logon
+        let mut logon = strict.session_config("CLIENT", "VENUE").logon();
+        assert!(matches!(strict.encode(&logon), Err(FixError::MissingField(USERNAME))));
+        logon.add_field(FixField::new(USERNAME, b"trader".to_vec()));
+        assert!(strict.encode(&logon).is_ok());
+        assert!(lenient.encode(&lenient.session_config("CLIENT", "VENUE").logon()).is_ok());
+    }
+
+    #[test]
+    fn test_session_overrides() {
+        let config = VenueProfile::lenient_fix42().apply(SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"));
+        assert_eq!(config.begin_string, fix_version::FIX_4_2);
+        assert_eq!(config.heart_bt_int, 60);
+        assert!(config.reset_on_logon);
+
+        let session = VenueProfile::strict_fix44().session("CLIENT", "VENUE");
+        assert_eq!(session.config().begin_string, fix_version::FIX_4_4);
+        assert_eq!(session.config().heart_bt_int, 30);
+        assert!(!session.config().reset_on_logon);
+    }
+}
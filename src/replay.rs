@@ -0,0 +1,391 @@
+use crate::datetime::UtcTimestamp;
+use crate::error::FixError;
+use crate::field::{parse_uint, FixField};
+use crate::framing::DEFAULT_MAX_FRAME_LEN;
+use crate::log::Direction;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Tags that legitimately differ between a recording and its replay:
+/// BodyLength, CheckSum, SendingTime and TransactTime
+pub const VOLATILE_TAGS: &[u32] = &[
+    Tag::BodyLength.value(),
+    Tag::CheckSum.value(),
+    Tag::SendingTime.value(),
+    Tag::TransactTime.value(),
+];
+
+/// One message of a recorded session, as it crossed the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayRecord {
+    pub timestamp: UtcTimestamp,
+    pub direction: Direction,
+    pub raw: Vec<u8>,
+}
+
+/// Writes a replay file. Each record is a header line,
+/// "<RFC 3339 timestamp> <IN|OUT> <length>", followed by exactly `length`
+/// raw message bytes and a newline, so messages keep their SOH delimiters
+/// and any binary data fields.
+pub struct Recorder<W> {
+    writer: W,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Records bytes exactly as they were sent or received
+    pub fn record(&mut self, timestamp: UtcTimestamp, direction: Direction, raw: &[u8]) -> io::Result<()> {
+        writeln!(self.writer, "{} {} {}", timestamp.to_rfc3339(), direction.marker(), raw.len())?;
+        self.writer.write_all(raw)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Records `msg` as `encode` frames it
+    pub fn record_message(&mut self, timestamp: UtcTimestamp, direction: Direction, msg: &FixMessage) -> io::Result<()> {
+        let encoded = msg.encode().map_err(io::Error::other)?;
+        self.record(timestamp, direction, &encoded)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads every record of a replay file written by `Recorder`
+pub fn read_records(mut reader: impl BufRead) -> io::Result<Vec<ReplayRecord>> {
+    let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("replay file: {}", what));
+
+    let mut records = Vec::new();
+    let mut header = Vec::new();
+    loop {
+        header.clear();
+        if reader.read_until(b'\n', &mut header)? == 0 {
+            return Ok(records);
+        }
+        let mut parts = header.trim_ascii_end().split(|&b| b == b' ');
+        let (Some(timestamp), Some(marker), Some(len), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("malformed record header"));
+        };
+        let timestamp = UtcTimestamp::parse_rfc3339(timestamp).map_err(|_| invalid("bad timestamp"))?;
+        let direction = Direction::from_marker(marker).ok_or_else(|| invalid("bad direction"))?;
+        // The length comes from the file, so it's bounded by the largest frame
+        // the engine accepts before anything is allocated for it
+        let len = parse_uint(len)
+            .ok()
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|&len| len <= DEFAULT_MAX_FRAME_LEN)
+            .ok_or_else(|| invalid("bad length"))?;
+
+        let mut raw = vec![0; len + 1];
+        reader.read_exact(&mut raw)?;
+        if raw.pop() != Some(b'\n') {
+            return Err(invalid("record longer than its declared length"));
+        }
+        records.push(ReplayRecord { timestamp, direction, raw });
+    }
+}
+
+/// How quickly inbound messages are fed to the handler
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pace {
+    /// Back to back, for unit tests
+    #[default]
+    Unpaced,
+    /// With the recorded gaps between inbound messages
+    Original,
+    /// With the recorded gaps divided by the factor
+    Accelerated(f64),
+}
+
+/// The application side of a replay: it receives the recorded inbound
+/// messages and returns what it would send in response
+pub trait ReplayHandler {
+    /// Messages sent before anything is received, e.g. an initiator's Logon
+    fn on_start(&mut self) -> Vec<FixMessage> {
+        Vec::new()
+    }
+
+    fn on_inbound(&mut self, msg: &FixMessage) -> Result<Vec<FixMessage>, FixError>;
+}
+
+impl<F> ReplayHandler for F
+where
+    F: FnMut(&FixMessage) -> Result<Vec<FixMessage>, FixError>,
+{
+    fn on_inbound(&mut self, msg: &FixMessage) -> Result<Vec<FixMessage>, FixError> {
+        self(msg)
+    }
+}
+
+/// One field that differs between a recorded and a replayed message,
+/// compared position by position once the ignored tags are removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub position: usize,
+    pub expected: Option<FixField>,
+    pub actual: Option<FixField>,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |field: &Option<FixField>| match field {
+            Some(field) => format!("{}={}", field.tag(), String::from_utf8_lossy(field.value())),
+            None => "nothing".to_string(),
+        };
+        write!(f, "field {}: expected {}, got {}", self.position, show(&self.expected), show(&self.actual))
+    }
+}
+
+/// Why a replay diverged from its recording. `record` is the index of the
+/// record in the replay file.
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("Record {record} could not be decoded: {source}")]
+    Decode { record: usize, source: FixError },
+    #[error("Handler failed on record {record}: {source}")]
+    Handler { record: usize, source: FixError },
+    #[error("Record {record} differs from the replayed message: {}", join(.diffs))]
+    Mismatch { record: usize, diffs: Vec<FieldDiff> },
+    #[error("Record {record} was recorded as sent but the handler sent nothing")]
+    Missing { record: usize },
+    #[error("Handler sent {} message(s) that were not recorded", .extra.len())]
+    Unexpected { extra: Vec<FixMessage> },
+}
+
+fn join(diffs: &[FieldDiff]) -> String {
+    diffs.iter().map(FieldDiff::to_string).collect::<Vec<_>>().join("; ")
+}
+
+/// The differences between two messages once the `ignored` tags are removed
+/// from both, compared position by position
+pub fn diff_fields(expected: &FixMessage, actual: &FixMessage, ignored: &[u32]) -> Vec<FieldDiff> {
+    let kept = |msg: &FixMessage| -> Vec<FixField> {
+        msg.field_slice()
+            .iter()
+            .filter(|f| !ignored.contains(&f.tag()))
+            .cloned()
+            .collect()
+    };
+    let (expected, actual) = (kept(expected), kept(actual));
+
+    (0..expected.len().max(actual.len()))
+        .filter_map(|position| {
+            let (e, a) = (expected.get(position), actual.get(position));
+            (e != a).then(|| FieldDiff {
+                position,
+                expected: e.cloned(),
+                actual: a.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Feeds the inbound half of a recording to a handler and checks that the
+/// handler's output matches the outbound half
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    records: Vec<ReplayRecord>,
+    ignored_tags: Vec<u32>,
+    pace: Pace,
+}
+
+impl Replayer {
+    /// Ignores `VOLATILE_TAGS` and runs unpaced
+    pub fn new(records: Vec<ReplayRecord>) -> Self {
+        Self {
+            records,
+            ignored_tags: VOLATILE_TAGS.to_vec(),
+            pace: Pace::Unpaced,
+        }
+    }
+
+    pub fn from_read(reader: impl BufRead) -> io::Result<Self> {
+        read_records(reader).map(Self::new)
+    }
+
+    pub fn with_pace(mut self, pace: Pace) -> Self {
+        self.pace = pace;
+        self
+    }
+
+    /// Also ignores `tag` when comparing outbound messages
+    pub fn with_ignored_tag(mut self, tag: u32) -> Self {
+        self.ignored_tags.push(tag);
+        self
+    }
+
+    /// Replaces the ignore-list
+    pub fn with_ignored_tags(mut self, tags: &[u32]) -> Self {
+        self.ignored_tags = tags.to_vec();
+        self
+    }
+
+    #[inline]
+    pub fn records(&self) -> &[ReplayRecord] {
+        &self.records
+    }
+
+    /// Replays the recording, stopping at the first divergence
+    pub fn run(&self, handler: &mut impl ReplayHandler) -> Result<(), ReplayError> {
+        let mut sent: VecDeque<FixMessage> = handler.on_start().into();
+        let mut last_inbound: Option<&UtcTimestamp> = None;
+
+        for (record, entry) in self.records.iter().enumerate() {
+            let decode = |raw| FixMessage::decode(raw).map_err(|source| ReplayError::Decode { record, source });
+            match entry.direction {
+                Direction::Inbound => {
+                    if let Some(previous) = last_inbound {
+                        self.wait(previous, &entry.timestamp);
+                    }
+                    last_inbound = Some(&entry.timestamp);
+
+                    let msg = decode(&entry.raw)?;
+                    let replies = handler
+                        .on_inbound(&msg)
+                        .map_err(|source| ReplayError::Handler { record, source })?;
+                    sent.extend(replies);
+                }
+                Direction::Outbound => {
+                    let expected = decode(&entry.raw)?;
+                    let actual = sent.pop_front().ok_or(ReplayError::Missing { record })?;
+                    let diffs = self.diff(&expected, &actual);
+                    if !diffs.is_empty() {
+                        return Err(ReplayError::Mismatch { record, diffs });
+                    }
+                }
+            }
+        }
+
+        if sent.is_empty() {
+            Ok(())
+        } else {
+            Err(ReplayError::Unexpected { extra: sent.into() })
+        }
+    }
+
+    /// The differences between two messages, ignoring the configured tags
+    pub fn diff(&self, expected: &FixMessage, actual: &FixMessage) -> Vec<FieldDiff> {
+        diff_fields(expected, actual, &self.ignored_tags)
+    }
+
+    fn wait(&self, previous: &UtcTimestamp, next: &UtcTimestamp) {
+        let factor = match self.pace {
+            Pace::Unpaced => return,
+            Pace::Original => 1.0,
+            Pace::Accelerated(factor) => factor,
+        };
+        let gap = next.unix_nanos().saturating_sub(previous.unix_nanos());
+        if gap > 0 && factor > 0.0 {
+            std::thread::sleep(Duration::from_nanos((gap as f64 / factor) as u64));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionConfig;
+    use crate::tags::{fix_version, msg_type};
+
+    fn venue() -> SessionConfig {
+        SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT")
+    }
+
+    fn client() -> SessionConfig {
+        SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE")
+    }
+
+    fn at(second: u8) -> UtcTimestamp {
+        UtcTimestamp::new(2024, 1, 1, 12, 0, second).unwrap()
+    }
+
+    fn stamped(mut msg: FixMessage, second: u8) -> FixMessage {
+        msg.set_utc_timestamp(Tag::SendingTime.value(), at(second));
+        msg
+    }
+
+    // A venue that answers TestRequests with a Heartbeat echoing TestReqID
+    fn responder(msg: &FixMessage) -> Result<Vec<FixMessage>, FixError> {
+        let mut replies = Vec::new();
+        if msg.get_field(Tag::MsgType.value()).map(FixField::value) == Some(msg_type::TEST_REQUEST) {
+            let id = msg.get_field(Tag::TestReqID.value()).ok_or(FixError::MissingField(112))?;
+            replies.push(stamped(venue().heartbeat(Some(id.value())), 59));
+        }
+        Ok(replies)
+    }
+
+    fn recording(reply_id: &[u8]) -> Vec<u8> {
+        let mut recorder = Recorder::new(Vec::new());
+        for (second, id) in [(1, &b"T1"[..]), (2, b"T2")] {
+            let request = stamped(client().test_request(id), second);
+            recorder.record_message(at(second), Direction::Inbound, &request).unwrap();
+            let reply = stamped(venue().heartbeat(Some(if second == 2 { reply_id } else { id })), second);
+            recorder.record_message(at(second), Direction::Outbound, &reply).unwrap();
+        }
+        recorder.into_inner()
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let file = recording(b"T2");
+        let replayer = Replayer::from_read(&file[..]).unwrap();
+        assert_eq!(replayer.records().len(), 4);
+        assert_eq!(replayer.records()[0].direction, Direction::Inbound);
+        assert_eq!(replayer.records()[0].timestamp, at(1));
+        replayer.run(&mut responder).unwrap();
+        replayer.clone().with_pace(Pace::Accelerated(1_000.0)).run(&mut responder).unwrap();
+
+        // SendingTime differs once it is no longer ignored
+        let strict = replayer.with_ignored_tags(&[Tag::BodyLength.value(), Tag::CheckSum.value()]);
+        assert!(matches!(strict.run(&mut responder), Err(ReplayError::Mismatch { record: 1, .. })));
+    }
+
+    #[test]
+    fn test_replay_divergence() {
+        let replayer = Replayer::from_read(&recording(b"XX")[..]).unwrap();
+        let err = replayer.run(&mut responder).unwrap_err();
+        let ReplayError::Mismatch { record, diffs } = &err else {
+            panic!("unexpected {:?}", err);
+        };
+        assert_eq!(*record, 3);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].expected.as_ref().unwrap().value(), b"XX");
+        assert_eq!(diffs[0].actual.as_ref().unwrap().value(), b"T2");
+        assert!(err.to_string().contains("expected 112=XX, got 112=T2"));
+
+        let mut silent = |_: &FixMessage| -> Result<Vec<FixMessage>, FixError> { Ok(Vec::new()) };
+        assert!(matches!(replayer.run(&mut silent), Err(ReplayError::Missing { record: 1 })));
+
+        let mut chatty = |msg: &FixMessage| -> Result<Vec<FixMessage>, FixError> {
+            let mut replies = responder(msg)?;
+            replies.push(venue().heartbeat(None));
+            Ok(replies)
+        };
+        let relaxed = Replayer::from_read(&recording(b"T2")[..]).unwrap();
+        assert!(matches!(relaxed.run(&mut chatty), Err(ReplayError::Mismatch { record: 3, .. })));
+
+        assert!(read_records(&b"2024-01-01T12:00:00Z IN 10\n8=FIX\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_oversized_record_length() {
+        for len in ["18446744073709551615", "99999999999999999999", "1048577"] {
+            let file = format!("2024-01-01T12:00:00Z IN {}\n8=FIX\n", len);
+            let err = read_records(file.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", len);
+        }
+    }
+}
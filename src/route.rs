@@ -0,0 +1,336 @@
+use crate::error::FixError;
+use crate::message::{decode_header, FixMessage};
+use crate::tags::{is_header_tag, msg_type::is_admin, Tag};
+use std::fmt;
+
+/// The MsgType values a route accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MsgTypePattern {
+    Any,
+    /// Session-level messages, see `msg_type::is_admin`
+    Admin,
+    /// Every message that is not session-level
+    Application,
+    OneOf(Vec<Vec<u8>>),
+}
+
+impl MsgTypePattern {
+    pub fn exact(msg_type: &[u8]) -> Self {
+        MsgTypePattern::OneOf(vec![msg_type.to_vec()])
+    }
+
+    pub fn matches(&self, msg_type: &[u8]) -> bool {
+        match self {
+            MsgTypePattern::Any => true,
+            MsgTypePattern::Admin => is_admin(msg_type),
+            MsgTypePattern::Application => !is_admin(msg_type),
+            MsgTypePattern::OneOf(types) => types.iter().any(|t| t == msg_type),
+        }
+    }
+}
+
+impl fmt::Display for MsgTypePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MsgTypePattern::Any => write!(f, "35=*"),
+            MsgTypePattern::Admin => write!(f, "35=<admin>"),
+            MsgTypePattern::Application => write!(f, "35=<app>"),
+            MsgTypePattern::OneOf(types) => write!(f, "35={}", join(types)),
+        }
+    }
+}
+
+fn join(values: &[Vec<u8>]) -> String {
+    values.iter().map(|v| String::from_utf8_lossy(v)).collect::<Vec<_>>().join("|")
+}
+
+/// What a route requires of one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Equals(Vec<u8>),
+    OneOf(Vec<Vec<u8>>),
+    Present,
+    Absent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagPredicate {
+    pub tag: u32,
+    pub condition: Condition,
+}
+
+impl TagPredicate {
+    /// Whether the field's first value, `None` if absent, satisfies it
+    pub fn matches(&self, value: Option<&[u8]>) -> bool {
+        match (&self.condition, value) {
+            (Condition::Equals(expected), Some(value)) => expected == value,
+            (Condition::OneOf(expected), Some(value)) => expected.iter().any(|e| e == value),
+            (Condition::Present, value) => value.is_some(),
+            (Condition::Absent, value) => value.is_none(),
+            (_, None) => false,
+        }
+    }
+}
+
+impl fmt::Display for TagPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.condition {
+            Condition::Equals(value) => write!(f, "{}={}", self.tag, String::from_utf8_lossy(value)),
+            Condition::OneOf(values) => write!(f, "{}={}", self.tag, join(values)),
+            Condition::Present => write!(f, "{}=*", self.tag),
+            Condition::Absent => write!(f, "{}=<absent>", self.tag),
+        }
+    }
+}
+
+/// What a message must look like to take a route: its MsgType and every
+/// predicate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSpec {
+    pub msg_type: MsgTypePattern,
+    pub predicates: Vec<TagPredicate>,
+}
+
+impl RouteSpec {
+    pub fn new(msg_type: MsgTypePattern) -> Self {
+        Self { msg_type, predicates: Vec::new() }
+    }
+
+    /// Messages of exactly this MsgType
+    pub fn msg_type(msg_type: &[u8]) -> Self {
+        Self::new(MsgTypePattern::exact(msg_type))
+    }
+
+    pub fn with_predicate(mut self, tag: u32, condition: Condition) -> Self {
+        self.predicates.push(TagPredicate { tag, condition });
+        self
+    }
+
+    pub fn with_tag(self, tag: u32, value: &[u8]) -> Self {
+        self.with_predicate(tag, Condition::Equals(value.to_vec()))
+    }
+
+    pub fn with_tag_in(self, tag: u32, values: &[&[u8]]) -> Self {
+        self.with_predicate(tag, Condition::OneOf(values.iter().map(|v| v.to_vec()).collect()))
+    }
+
+    /// Whether matching looks at fields beyond the standard header, and so
+    /// needs the message fully decoded
+    pub fn needs_body(&self) -> bool {
+        self.predicates.iter().any(|p| !is_header_tag(p.tag))
+    }
+
+    pub fn matches(&self, msg: &FixMessage) -> bool {
+        let msg_type = msg.get_field(Tag::MsgType.value()).map_or(&[][..], |f| f.value());
+        self.msg_type.matches(msg_type)
+            && self.predicates.iter().all(|p| p.matches(msg.get_field(p.tag).map(|f| f.value())))
+    }
+}
+
+impl fmt::Display for RouteSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg_type)?;
+        for predicate in &self.predicates {
+            write!(f, " {}", predicate)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Route<H> {
+    pub spec: RouteSpec,
+    pub handler: H,
+}
+
+/// The handler a message was routed to
+#[derive(Debug)]
+pub struct Routed<'r, H> {
+    pub handler: &'r H,
+    /// Position of the route in the table; `None` for the default route
+    pub route: Option<usize>,
+    /// The decoded message, when a predicate on a body field needed it, so
+    /// the handler need not decode it again
+    pub message: Option<FixMessage>,
+}
+
+/// Picks a handler for each message from routes tried in the order they
+/// were added, falling back to the default route. Routing an encoded
+/// message reads only its header with `decode_header`; the full message is
+/// decoded, once, only when a route whose MsgType matches has a predicate
+/// on a body field.
+///
+/// `Display` renders the table for diagnostics, one route per line.
+#[derive(Debug, Clone)]
+pub struct Router<H> {
+    routes: Vec<Route<H>>,
+    default: Option<H>,
+}
+
+impl<H> Default for Router<H> {
+    fn default() -> Self {
+        Self { routes: Vec::new(), default: None }
+    }
+}
+
+impl<H> Router<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_route(mut self, spec: RouteSpec, handler: H) -> Self {
+        self.add_route(spec, handler);
+        self
+    }
+
+    /// Adds a route, tried after every route added before it
+    pub fn add_route(&mut self, spec: RouteSpec, handler: H) {
+        self.routes.push(Route { spec, handler });
+    }
+
+    /// Handles messages no route matches
+    pub fn with_default(mut self, handler: H) -> Self {
+        self.default = Some(handler);
+        self
+    }
+
+    #[inline]
+    pub fn routes(&self) -> &[Route<H>] {
+        &self.routes
+    }
+
+    #[inline]
+    pub fn default_handler(&self) -> Option<&H> {
+        self.default.as_ref()
+    }
+
+    /// Routes an encoded message. `None` means no route matched and there
+    /// is no default. Fails if the header is malformed, or if decoding was
+    /// needed and failed.
+    pub fn route(&self, data: &[u8]) -> Result<Option<Routed<'_, H>>, FixError> {
+        let header = decode_header(data)?;
+        let mut message = None;
+        for (index, route) in self.routes.iter().enumerate() {
+            if !route.spec.msg_type.matches(header.msg_type()) {
+                continue;
+            }
+            if message.is_none() && route.spec.needs_body() {
+                message = Some(FixMessage::decode(data)?);
+            }
+            let matched = match &message {
+                Some(msg) => route.spec.matches(msg),
+                None => route.spec.predicates.iter().all(|p| p.matches(header.get(p.tag))),
+            };
+            if matched {
+                return Ok(Some(Routed { handler: &route.handler, route: Some(index), message }));
+            }
+        }
+        Ok(self.default.as_ref().map(|handler| Routed { handler, route: None, message }))
+    }
+
+    /// Routes a message that is already decoded
+    pub fn route_message(&self, msg: &FixMessage) -> Option<Routed<'_, H>> {
+        match self.routes.iter().position(|route| route.spec.matches(msg)) {
+            Some(index) => Some(Routed { handler: &self.routes[index].handler, route: Some(index), message: None }),
+            None => self.default.as_ref().map(|handler| Routed { handler, route: None, message: None }),
+        }
+    }
+}
+
+impl<H> fmt::Display for Router<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, route) in self.routes.iter().enumerate() {
+            writeln!(f, "{:>3}  {}", index, route.spec)?;
+        }
+        if self.default.is_some() {
+            writeln!(f, "  -  default")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::tags::{fix_version, msg_type};
+
+    // Desk a venue stamps on its ExecutionReports
+    const DESK: u32 = 9999;
+    const SENDER_SUB_ID: u32 = 50;
+
+    fn encoded(msg_type: &[u8], desk: Option<&[u8]>) -> Vec<u8> {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), b"VENUE".to_vec()));
+        msg.add_field(FixField::new(SENDER_SUB_ID, b"ALGO".to_vec()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), b"CLIENT".to_vec()));
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"7".to_vec()));
+        msg.add_field(FixField::new(Tag::OrderID.value(), b"X1".to_vec()));
+        if let Some(desk) = desk {
+            msg.add_field(FixField::new(DESK, desk.to_vec()));
+        }
+        msg.encode().unwrap().to_vec()
+    }
+
+    fn router() -> Router<&'static str> {
+        Router::new()
+            .with_route(RouteSpec::new(MsgTypePattern::Admin), "session")
+            .with_route(RouteSpec::msg_type(msg_type::EXECUTION_REPORT).with_tag(DESK, b"EQ"), "equities")
+            .with_route(
+                RouteSpec::msg_type(msg_type::EXECUTION_REPORT).with_tag_in(DESK, &[b"FX", b"RATES"]),
+                "fixed_income",
+            )
+            .with_route(RouteSpec::new(MsgTypePattern::Application).with_tag(SENDER_SUB_ID, b"ALGO"), "algo")
+            .with_default("unrouted")
+    }
+
+    #[test]
+    fn test_routes_execution_reports_by_custom_tag() {
+        let router = router();
+        let route = |data: &[u8]| {
+            let routed = router.route(data).unwrap().unwrap();
+            (*routed.handler, routed.route, routed.message.is_some())
+        };
+
+        assert_eq!(route(&encoded(msg_type::EXECUTION_REPORT, Some(b"EQ"))), ("equities", Some(1), true));
+        assert_eq!(route(&encoded(msg_type::EXECUTION_REPORT, Some(b"RATES"))), ("fixed_income", Some(2), true));
+        assert_eq!(route(&encoded(msg_type::EXECUTION_REPORT, None)), ("algo", Some(3), true));
+
+        // Header-only routes never decode the body
+        assert_eq!(route(&encoded(msg_type::HEARTBEAT, None)), ("session", Some(0), false));
+        assert_eq!(route(&encoded(msg_type::NEW_ORDER_SINGLE, Some(b"EQ"))), ("algo", Some(3), false));
+
+        let decoded = FixMessage::decode(&encoded(msg_type::EXECUTION_REPORT, Some(b"FX"))).unwrap();
+        assert_eq!(*router.route_message(&decoded).unwrap().handler, "fixed_income");
+
+        // A body predicate on a message with a bad CheckSum fails rather
+        // than routing on unverified content
+        let mut corrupt = encoded(msg_type::EXECUTION_REPORT, Some(b"EQ"));
+        let len = corrupt.len();
+        corrupt[len - 2] = if corrupt[len - 2] == b'0' { b'1' } else { b'0' };
+        assert!(matches!(router.route(&corrupt), Err(FixError::InvalidChecksum)));
+        assert!(matches!(router.route(b"35=8\x01"), Err(FixError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_default_route_and_table() {
+        let router = router();
+        let mut no_sub_id = FixMessage::decode(&encoded(msg_type::NEW_ORDER_SINGLE, None)).unwrap();
+        no_sub_id.set_field(FixField::new(SENDER_SUB_ID, b"DESK".to_vec()));
+        let routed = router.route(&no_sub_id.encode().unwrap()).unwrap().unwrap();
+        assert_eq!((*routed.handler, routed.route), ("unrouted", None));
+
+        let without_default = Router::new().with_route(RouteSpec::new(MsgTypePattern::Admin), ());
+        assert!(without_default.route(&encoded(msg_type::NEW_ORDER_SINGLE, None)).unwrap().is_none());
+
+        assert_eq!(router.routes().len(), 4);
+        assert!(router.routes()[1].spec.needs_body());
+        assert!(!router.routes()[3].spec.needs_body());
+        assert_eq!(
+            router.to_string(),
+            "  0  35=<admin>\n  1  35=8 9999=EQ\n  2  35=8 9999=FX|RATES\n  3  35=<app> 50=ALGO\n  -  default\n"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+// The one loop in the crate that splits encoded messages into fields. The
+// decoders, decode_header and the templated fast path all read through it,
+// so they agree on what a well-formed field is.
+
+use crate::checksum::RollingChecksum;
+use crate::error::FixError;
+use crate::field::{parse_uint, SOH};
+use crate::options::{DecodeOptions, DecodeWarning};
+use crate::tags::Tag;
+use memchr::memchr;
+
+const BODY_LENGTH_TAG: u32 = Tag::BodyLength.value();
+const CHECKSUM_TAG: u32 = Tag::CheckSum.value();
+
+// Every message opens with these, in this order
+const LEADING_TAGS: [u32; 3] = [Tag::BeginString.value(), BODY_LENGTH_TAG, Tag::MsgType.value()];
+
+// Tags are plain ASCII digits; anything else, including bytes that are not
+// UTF-8, is a malformed field
+#[inline]
+fn parse_tag(digits: &[u8]) -> Result<u32, FixError> {
+    parse_uint(digits)
+        .ok()
+        .and_then(|tag| u32::try_from(tag).ok())
+        .ok_or(FixError::InvalidFormat)
+}
+
+// Offset of the delimiter ending a data field of `len` bytes at the start
+// of `rest`, or `None` when `rest` does not start with `data_tag`
+fn data_field_end(rest: &[u8], data_tag: u32, len: usize, delimiter: u8) -> Result<Option<usize>, FixError> {
+    let Some(equals_pos) = memchr(b'=', rest) else {
+        return Ok(None);
+    };
+    if parse_uint(&rest[..equals_pos]).ok() != Some(data_tag as u64) {
+        return Ok(None);
+    }
+    let end = len.checked_add(equals_pos + 1).ok_or(FixError::InvalidFormat)?;
+    match rest.get(end) {
+        Some(&b) if b == delimiter => Ok(Some(end)),
+        _ => Err(FixError::InvalidFormat),
+    }
+}
+
+/// Reads an encoded message one field at a time, front to back, storing
+/// nothing. Each call to `next` splits off one field and parses its tag;
+/// data fields (e.g. RawData, or those set by
+/// `DecodeOptions::with_data_fields`) are split by their declared length, so
+/// a SOH inside one is not mistaken for the end of the field.
+///
+/// The message must open with BeginString, BodyLength and MsgType. Scanning
+/// stops at the first malformed field, which `error` then reports, and at a
+/// field with no SOH after it, e.g. the end of a partly received message.
+/// BodyLength and CheckSum are only checked by `finish`, so a reader after a
+/// few fields can stop as soon as it has them. The CheckSum is the last
+/// field read; `finish` rejects any bytes after it.
+#[derive(Debug)]
+pub struct FieldScanner<'a> {
+    data: &'a [u8],
+    pos: usize,
+    options: DecodeOptions,
+    delimiter: u8,
+    // Fields split off so far
+    fields: usize,
+    body_length: &'a [u8],
+    // Offsets of the body, from after BodyLength's SOH to before CheckSum
+    body_start: usize,
+    body_end: Option<usize>,
+    checksum: RollingChecksum,
+    checksum_value: Option<&'a [u8]>,
+    // Tag and declared length of a data field announced by the previous field
+    pending_data: Option<(u32, usize)>,
+    error: Option<FixError>,
+}
+
+impl<'a> FieldScanner<'a> {
+    /// A scanner at the start of `data` that `finish` checks as `decode` does
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            options: DecodeOptions::default(),
+            delimiter: SOH,
+            fields: 0,
+            body_length: &[],
+            body_start: 0,
+            body_end: None,
+            checksum: RollingChecksum::new(),
+            checksum_value: None,
+            pending_data: None,
+            error: None,
+        }
+    }
+
+    /// Chooses which of the BodyLength and CheckSum checks `finish` makes.
+    /// Without CheckSum verification the bytes are not summed at all.
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Splits fields at `delimiter` instead of SOH, e.g. '|' for the log
+    /// form. The CheckSum is still that of the message with SOH delimiters.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Offset of the next field to be read
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Bytes from the next field to the end of the data
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Why scanning stopped early, if a field was malformed
+    #[inline]
+    pub fn error(&self) -> Option<&FixError> {
+        self.error.as_ref()
+    }
+
+    /// Reads the rest of the message and checks it: the header is complete,
+    /// every byte belongs to a field, the CheckSum is present and nothing
+    /// follows it and, unless turned off, BodyLength and CheckSum match
+    pub fn finish(self) -> Result<(), FixError> {
+        self.finish_with_warnings(&mut Vec::new())
+    }
+
+    /// `finish`, recording a mismatch of a check turned off by the options
+    /// in `warnings` rather than ignoring it
+    pub(crate) fn finish_with_warnings(mut self, warnings: &mut Vec<DecodeWarning>) -> Result<(), FixError> {
+        while self.next().is_some() {}
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        if self.pos < self.data.len() || self.fields < LEADING_TAGS.len() {
+            return Err(FixError::InvalidFormat);
+        }
+        let declared = parse_uint(self.body_length).map_err(|_| FixError::InvalidFormat)?;
+
+        let Some(checksum_value) = self.checksum_value else {
+            return Err(FixError::MissingField(CHECKSUM_TAG));
+        };
+        // BodyLength counts from after its own SOH to the SOH before CheckSum
+        let actual = (self.body_end.unwrap_or(self.pos) - self.body_start) as u64;
+        if actual != declared {
+            if self.options.verify_body_length {
+                return Err(FixError::InvalidBodyLength { declared, actual });
+            }
+            warnings.push(DecodeWarning::BodyLength { declared, actual });
+        }
+        if self.options.verify_checksum {
+            self.checksum.verify(checksum_value)?;
+        }
+        Ok(())
+    }
+
+    // Adds a field and its delimiter to the checksum, which is accumulated
+    // while scanning rather than in a second pass and covers everything
+    // before the CheckSum field, counting each delimiter as SOH
+    #[inline]
+    fn sum(&mut self, field: &[u8]) {
+        if !self.options.verify_checksum || self.checksum_value.is_some() {
+            return;
+        }
+        if self.delimiter == SOH {
+            // The SOH follows the field in the buffer
+            self.checksum.update(&self.data[self.pos..self.pos + field.len() + 1]);
+        } else {
+            self.checksum.update(field);
+            self.checksum.update(&[SOH]);
+        }
+    }
+
+    fn fail(&mut self, err: FixError) -> Option<(u32, &'a [u8])> {
+        self.error = Some(err);
+        None
+    }
+}
+
+impl<'a> Iterator for FieldScanner<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The message ends at its CheckSum
+        if self.error.is_some() || self.checksum_value.is_some() {
+            return None;
+        }
+        // Text without '=' between two delimiters after the header is not a
+        // field; it is stepped over, still counting towards the CheckSum
+        let (rest, field_end, equals_pos) = loop {
+            let rest = &self.data[self.pos..];
+            let field_end = match self.pending_data.take() {
+                Some((data_tag, len)) => match data_field_end(rest, data_tag, len, self.delimiter) {
+                    Ok(end) => end.or_else(|| memchr(self.delimiter, rest)),
+                    Err(err) => return self.fail(err),
+                },
+                None => memchr(self.delimiter, rest),
+            }?;
+            match memchr(b'=', &rest[..field_end]) {
+                Some(equals_pos) => break (rest, field_end, equals_pos),
+                None if self.fields < LEADING_TAGS.len() => return self.fail(FixError::InvalidFormat),
+                None => {
+                    self.sum(&rest[..field_end]);
+                    self.pos += field_end + 1;
+                }
+            }
+        };
+
+        let field = &rest[..field_end];
+        let tag = match parse_tag(&field[..equals_pos]) {
+            Ok(tag) => tag,
+            Err(err) => return self.fail(err),
+        };
+        match LEADING_TAGS.get(self.fields) {
+            Some(&expected) if expected == tag => {}
+            // A message whose second field is not BodyLength is missing it
+            Some(&BODY_LENGTH_TAG) => return self.fail(FixError::MissingField(BODY_LENGTH_TAG)),
+            Some(_) => return self.fail(FixError::InvalidFormat),
+            None => {}
+        }
+
+        let value = &field[equals_pos + 1..];
+        if tag == BODY_LENGTH_TAG && self.fields == 1 {
+            self.body_length = value;
+            self.body_start = self.pos + field_end + 1;
+        }
+        if tag == CHECKSUM_TAG {
+            self.checksum_value = Some(value);
+            self.body_end = Some(self.pos);
+        }
+        if let Some(data_tag) = self.options.data_field_for(tag) {
+            match parse_uint(value) {
+                Ok(len) => self.pending_data = Some((data_tag, len as usize)),
+                Err(_) => return self.fail(FixError::InvalidFormat),
+            }
+        }
+        self.sum(field);
+        self.pos += field_end + 1;
+        self.fields += 1;
+        Some((tag, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::message::FixMessage;
+    use crate::tags::fix_version;
+
+    fn order() -> Vec<u8> {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), b"D".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), b"VOD.L".to_vec()));
+        msg.add_field(FixField::new(95, b"3".to_vec()));
+        msg.add_field(FixField::new(96, b"a\x01b".to_vec()));
+        msg.add_field(FixField::new(Tag::Price.value(), b"101.5".to_vec()));
+        msg.add_field(FixField::new(Tag::OrderQty.value(), b"100".to_vec()));
+        msg.encode().unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_read_some_and_stop() {
+        let wire = order();
+        let mut scanner = FieldScanner::new(&wire);
+        let (mut symbol, mut price, mut qty) = (None, None, None);
+        for (tag, value) in &mut scanner {
+            match tag {
+                55 => symbol = Some(value),
+                44 => price = Some(value),
+                38 => qty = Some(value),
+                _ => {}
+            }
+            if symbol.is_some() && price.is_some() && qty.is_some() {
+                break;
+            }
+        }
+        assert_eq!((symbol, price, qty), (Some(&b"VOD.L"[..]), Some(&b"101.5"[..]), Some(&b"100"[..])));
+        assert!(scanner.error().is_none());
+        assert!(scanner.position() < wire.len());
+        // The rest is read, and the whole checked, only now
+        scanner.finish().unwrap();
+
+        let tags: Vec<u32> = FieldScanner::new(&wire).map(|(tag, _)| tag).collect();
+        assert_eq!(tags, [8, 9, 35, 55, 95, 96, 44, 38, 10]);
+    }
+
+    #[test]
+    fn test_finish_checks_integrity() {
+        let wire = order();
+        let mut bad = wire.clone();
+        let n = bad.len();
+        bad[n - 2] = if bad[n - 2] == b'0' { b'1' } else { b'0' };
+        assert_eq!(FieldScanner::new(&bad).count(), 9);
+        assert!(matches!(FieldScanner::new(&bad).finish(), Err(FixError::InvalidChecksum)));
+        let lenient = DecodeOptions::default().with_verify_checksum(false);
+        assert!(FieldScanner::new(&bad).with_decode_options(lenient).finish().is_ok());
+
+        // A partly received message stops at the incomplete field
+        let mut partial = FieldScanner::new(&wire[..n - 3]);
+        assert_eq!(partial.by_ref().count(), 8);
+        assert!(partial.error().is_none());
+        assert!(matches!(partial.finish(), Err(FixError::InvalidFormat)));
+
+        let mut malformed = FieldScanner::new(b"8=FIX.4.4\x019=5\x0135=0\x01x=1\x0110=000\x01");
+        assert_eq!(malformed.by_ref().count(), 3);
+        assert!(matches!(malformed.error(), Some(FixError::InvalidFormat)));
+        let mut no_length = FieldScanner::new(b"8=FIX.4.4\x0135=0\x0110=000\x01");
+        assert_eq!(no_length.by_ref().count(), 1);
+        assert!(matches!(no_length.finish(), Err(FixError::MissingField(9))));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::FixError;
+use crate::field::{impl_text_traits, parse_uint, FieldValue};
+use smallvec::SmallVec;
+use std::num::NonZeroU64;
+
+/// A message sequence number. FIX numbers messages from 1, so zero is not
+/// representable; where the protocol gives 0 a special meaning (EndSeqNo) it
+/// is modelled separately by `EndSeqNo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeqNum(NonZeroU64);
+
+impl SeqNum {
+    pub const FIRST: SeqNum = SeqNum(NonZeroU64::MIN);
+
+    /// Returns `None` for zero
+    #[inline]
+    pub const fn new(value: u64) -> Option<Self> {
+        match NonZeroU64::new(value) {
+            Some(value) => Some(SeqNum(value)),
+            None => None,
+        }
+    }
+
+    #[inline]
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+
+    /// The following sequence number, or `None` on overflow
+    #[inline]
+    pub fn next(self) -> Option<Self> {
+        self.0.checked_add(1).map(SeqNum)
+    }
+
+    /// Number of sequence numbers missing between `self`, the next expected
+    /// number, and `received`. Returns `None` when `received` is not ahead of
+    /// `self`, i.e. there is no gap.
+    #[inline]
+    pub fn gap_to(self, received: SeqNum) -> Option<u64> {
+        received.get().checked_sub(self.get()).filter(|&gap| gap > 0)
+    }
+}
+
+impl From<SeqNum> for u64 {
+    #[inline]
+    fn from(seq: SeqNum) -> Self {
+        seq.get()
+    }
+}
+
+impl TryFrom<u64> for SeqNum {
+    type Error = FixError;
+
+    #[inline]
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        SeqNum::new(value).ok_or(FixError::InvalidFieldValue)
+    }
+}
+
+impl FieldValue for SeqNum {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        SeqNum::try_from(parse_uint(value)?)
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        buf.extend_from_slice(itoa::Buffer::new().format(self.get()).as_bytes());
+    }
+}
+
+impl_text_traits!(SeqNum);
+
+/// EndSeqNo (16) of a ResendRequest, where 0 on the wire requests every
+/// message after BeginSeqNo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndSeqNo {
+    Bounded(SeqNum),
+    Unbounded,
+}
+
+impl EndSeqNo {
+    /// Whether `seq` falls at or before the end of the range
+    #[inline]
+    pub fn includes(self, seq: SeqNum) -> bool {
+        match self {
+            EndSeqNo::Bounded(end) => seq <= end,
+            EndSeqNo::Unbounded => true,
+        }
+    }
+}
+
+impl FieldValue for EndSeqNo {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        Ok(SeqNum::new(parse_uint(value)?).map_or(EndSeqNo::Unbounded, EndSeqNo::Bounded))
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        match self {
+            EndSeqNo::Bounded(seq) => seq.write(buf),
+            EndSeqNo::Unbounded => buf.push(b'0'),
+        }
+    }
+}
+
+impl_text_traits!(EndSeqNo);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seq_num_arithmetic() {
+        let seq = SeqNum::new(5).unwrap();
+        assert_eq!(seq.next(), SeqNum::new(6));
+        assert_eq!(SeqNum::new(u64::MAX).unwrap().next(), None);
+        assert_eq!(SeqNum::FIRST.get(), 1);
+
+        assert_eq!(seq.gap_to(SeqNum::new(8).unwrap()), Some(3));
+        assert_eq!(seq.gap_to(seq), None);
+        assert_eq!(seq.gap_to(SeqNum::FIRST), None);
+    }
+
+    #[test]
+    fn test_seq_num_parsing() {
+        assert_eq!("42".parse::<SeqNum>().unwrap().get(), 42);
+        assert_eq!(SeqNum::new(42).unwrap().to_string(), "42");
+        for value in ["0", "", "-1", "+1", "1 ", "18446744073709551616"] {
+            assert!(value.parse::<SeqNum>().is_err(), "{:?} should be rejected", value);
+        }
+        assert!(SeqNum::try_from(0).is_err());
+    }
+
+    #[test]
+    fn test_end_seq_no() {
+        assert_eq!("0".parse::<EndSeqNo>().unwrap(), EndSeqNo::Unbounded);
+        assert_eq!(
+            "10".parse::<EndSeqNo>().unwrap(),
+            EndSeqNo::Bounded(SeqNum::new(10).unwrap())
+        );
+        assert_eq!(EndSeqNo::Unbounded.to_string(), "0");
+
+        let end = EndSeqNo::Bounded(SeqNum::new(10).unwrap());
+        assert!(end.includes(SeqNum::new(10).unwrap()));
+        assert!(!end.includes(SeqNum::new(11).unwrap()));
+        assert!(EndSeqNo::Unbounded.includes(SeqNum::new(u64::MAX).unwrap()));
+    }
+}
@@ -0,0 +1,207 @@
+use super::SessionConfig;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+
+/// What an acceptor knows about an inbound connection once its Logon has
+/// arrived
+#[derive(Debug, Clone, Copy)]
+pub struct InboundConnection<'a> {
+    /// Port the connection was accepted on
+    pub local_port: u16,
+    pub logon: &'a FixMessage,
+}
+
+/// Chooses which of several qualified sessions between the same CompIDs an
+/// inbound connection belongs to
+pub trait SessionSelector {
+    /// One of `candidates`, or `None` when none fits the connection
+    fn select<'c>(&self, connection: &InboundConnection<'_>, candidates: &[&'c SessionConfig]) -> Option<&'c SessionConfig>;
+}
+
+/// Assigns connections by the port they arrive on, e.g. orders on one port
+/// and drop copy on another
+#[derive(Debug, Clone, Default)]
+pub struct ByLocalPort {
+    ports: Vec<(u16, String)>,
+}
+
+impl ByLocalPort {
+    pub fn new<Q: Into<String>>(ports: impl IntoIterator<Item = (u16, Q)>) -> Self {
+        Self { ports: ports.into_iter().map(|(port, qualifier)| (port, qualifier.into())).collect() }
+    }
+}
+
+impl SessionSelector for ByLocalPort {
+    fn select<'c>(&self, connection: &InboundConnection<'_>, candidates: &[&'c SessionConfig]) -> Option<&'c SessionConfig> {
+        let (_, qualifier) = self.ports.iter().find(|(port, _)| *port == connection.local_port)?;
+        find_qualified(candidates, qualifier.as_bytes())
+    }
+}
+
+/// Assigns connections by a tag of the Logon whose value is the session
+/// qualifier, such as a venue-defined custom tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByLogonTag(pub u32);
+
+impl SessionSelector for ByLogonTag {
+    fn select<'c>(&self, connection: &InboundConnection<'_>, candidates: &[&'c SessionConfig]) -> Option<&'c SessionConfig> {
+        find_qualified(candidates, connection.logon.get_field(self.0)?.value())
+    }
+}
+
+fn find_qualified<'c>(candidates: &[&'c SessionConfig], qualifier: &[u8]) -> Option<&'c SessionConfig> {
+    candidates
+        .iter()
+        .copied()
+        .find(|config| config.session_qualifier.as_deref().map(str::as_bytes) == Some(qualifier))
+}
+
+/// The sessions an acceptor serves. A Logon is matched to the sessions
+/// whose BeginString and CompIDs answer it; when several qualified sessions
+/// match, the selector decides.
+#[derive(Debug, Clone)]
+pub struct SessionAcceptor<S> {
+    sessions: Vec<SessionConfig>,
+    selector: S,
+}
+
+impl<S: SessionSelector> SessionAcceptor<S> {
+    pub fn new(selector: S) -> Self {
+        Self { sessions: Vec::new(), selector }
+    }
+
+    /// Adds a session, given as the acceptor sees it: our CompID as sender
+    pub fn with_session(mut self, config: SessionConfig) -> Self {
+        self.sessions.push(config);
+        self
+    }
+
+    #[inline]
+    pub fn sessions(&self) -> &[SessionConfig] {
+        &self.sessions
+    }
+
+    /// The session `connection` belongs to, or `UnknownSession` naming the
+    /// Logon's BeginString and CompIDs when there is none
+    pub fn assign(&self, connection: &InboundConnection<'_>) -> Result<&SessionConfig, FixError> {
+        let logon = connection.logon;
+        let value = |tag: Tag| logon.get_field(tag.value()).map(FixField::value);
+        let candidates: Vec<&SessionConfig> = self
+            .sessions
+            .iter()
+            .filter(|config| {
+                value(Tag::BeginString) == Some(&config.begin_string[..])
+                    && value(Tag::SenderCompID) == Some(&config.target_comp_id[..])
+                    && value(Tag::TargetCompID) == Some(&config.sender_comp_id[..])
+            })
+            .collect();
+
+        let assigned = match candidates[..] {
+            [only] if only.session_qualifier.is_none() => Some(only),
+            _ => self.selector.select(connection, &candidates),
+        };
+        assigned.ok_or_else(|| {
+            let text = |tag: Tag| String::from_utf8_lossy(value(tag).unwrap_or_default()).into_owned();
+            FixError::UnknownSession(format!(
+                "{}:{}->{}",
+                text(Tag::BeginString),
+                text(Tag::SenderCompID),
+                text(Tag::TargetCompID)
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{FileMessageStore, MessageStore, Session, SessionEvent};
+    use crate::tags::fix_version;
+
+    fn logon(seq: u64, fields: &[(u32, &[u8])]) -> FixMessage {
+        let mut msg = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").logon();
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        for &(tag, value) in fields {
+            msg.add_field(FixField::new(tag, value.to_vec()));
+        }
+        msg
+    }
+
+    fn venue(qualifier: &str) -> SessionConfig {
+        SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT").with_session_qualifier(qualifier)
+    }
+
+    #[test]
+    fn test_assign_by_port_and_tag() {
+        let by_port = SessionAcceptor::new(ByLocalPort::new([(9001, "ORDERS"), (9002, "DROPCOPY")]))
+            .with_session(venue("ORDERS"))
+            .with_session(venue("DROPCOPY"));
+        let logon = logon(1, &[]);
+        let assigned = |port| by_port.assign(&InboundConnection { local_port: port, logon: &logon });
+        assert_eq!(assigned(9001).unwrap().session_qualifier.as_deref(), Some("ORDERS"));
+        assert_eq!(assigned(9002).unwrap().session_qualifier.as_deref(), Some("DROPCOPY"));
+        assert!(matches!(assigned(9003), Err(FixError::UnknownSession(id)) if id == "FIX.4.4:CLIENT->VENUE"));
+
+        let by_tag = SessionAcceptor::new(ByLogonTag(5001))
+            .with_session(venue("ORDERS"))
+            .with_session(venue("DROPCOPY"));
+        let drop_copy = self::logon(1, &[(5001, b"DROPCOPY")]);
+        let connection = InboundConnection { local_port: 9001, logon: &drop_copy };
+        assert_eq!(by_tag.assign(&connection).unwrap().session_id(), "FIX.4.4:VENUE->CLIENT:DROPCOPY");
+        let untagged = InboundConnection { local_port: 9001, logon: &logon };
+        assert!(by_tag.assign(&untagged).is_err());
+
+        // A lone unqualified session needs no selector, and other CompIDs
+        // never match
+        let single = SessionAcceptor::new(ByLogonTag(5001))
+            .with_session(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+        assert!(single.assign(&untagged).is_ok());
+        let other = SessionAcceptor::new(ByLogonTag(5001))
+            .with_session(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "OTHER"));
+        assert!(other.assign(&untagged).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_qualified_sessions() {
+        let dir = std::env::temp_dir().join(format!("fix_engine_acceptor_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let acceptor = SessionAcceptor::new(ByLocalPort::new([(9001, "ORDERS"), (9002, "DROPCOPY")]))
+            .with_session(venue("ORDERS"))
+            .with_session(venue("DROPCOPY"));
+
+        // Each connection runs on its own thread with its own store
+        let run = |port: u16, messages: u64| {
+            let first = logon(1, &[]);
+            let config = acceptor.assign(&InboundConnection { local_port: port, logon: &first }).unwrap();
+            let mut store = FileMessageStore::open_for(&dir, config).unwrap();
+            let mut session = Session::new(config.clone());
+            let mut response = session.accept_logon(&first).unwrap();
+            session.prepare_outbound(&mut response, &mut store).unwrap();
+            for seq in 2..=messages {
+                let mut heartbeat = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").heartbeat(None);
+                heartbeat.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+                assert_eq!(session.process_inbound(&heartbeat).unwrap(), SessionEvent::Admin);
+                session.prepare_outbound(&mut config.heartbeat(None), &mut store).unwrap();
+            }
+            (session, store)
+        };
+        let ((orders, orders_store), (drop_copy, drop_copy_store)) = std::thread::scope(|scope| {
+            let orders = scope.spawn(|| run(9001, 50));
+            let drop_copy = scope.spawn(|| run(9002, 20));
+            (orders.join().unwrap(), drop_copy.join().unwrap())
+        });
+
+        assert_eq!((orders.next_inbound_seq().get(), orders.next_outbound_seq().get()), (51, 51));
+        assert_eq!((drop_copy.next_inbound_seq().get(), drop_copy.next_outbound_seq().get()), (21, 21));
+        assert_ne!(orders_store.path(), drop_copy_store.path());
+        assert_eq!(orders_store.last_seq().unwrap().unwrap().get(), 50);
+        assert_eq!(drop_copy_store.last_seq().unwrap().unwrap().get(), 20);
+        assert_eq!(
+            FileMessageStore::open(orders_store.path()).unwrap().last_seq().unwrap().unwrap().get(),
+            50
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::datetime::UtcTimestamp;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time for session checks, replaceable so tests and
+/// simulations can control time
+#[derive(Clone)]
+pub struct Clock {
+    now: Arc<dyn Fn() -> i64 + Send + Sync>,
+}
+
+impl Clock {
+    /// The system clock
+    pub fn system() -> Self {
+        Self::from_fn(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_nanos() as i64)
+        })
+    }
+
+    /// A clock reading `now`, in nanoseconds since the Unix epoch
+    pub fn from_fn(now: impl Fn() -> i64 + Send + Sync + 'static) -> Self {
+        Self { now: Arc::new(now) }
+    }
+
+    /// A clock stopped at `at`
+    pub fn fixed(at: UtcTimestamp) -> Self {
+        let nanos = at.unix_nanos();
+        Self::from_fn(move || nanos)
+    }
+
+    /// Nanoseconds since the Unix epoch
+    #[inline]
+    pub fn now_nanos(&self) -> i64 {
+        (self.now)()
+    }
+
+    /// The current time with microsecond precision
+    pub fn now(&self) -> UtcTimestamp {
+        // Out of range only for a clock set outside years 0 to 9999
+        UtcTimestamp::from_unix_nanos(self.now_nanos(), 6).unwrap_or_else(|_| {
+            UtcTimestamp::new(1970, 1, 1, 0, 0, 0).expect("the epoch is a valid timestamp")
+        })
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock").field("now", &self.now_nanos()).finish()
+    }
+}
@@ -0,0 +1,207 @@
+use crate::field::FixField;
+use crate::datetime::UtcTimeOnly;
+use crate::framing::FramingMode;
+use crate::message::FixMessage;
+use crate::messages::{Logon, ResendRequest};
+use crate::seqnum::SeqNum;
+use crate::tags::{msg_type, Tag};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DEFAULT_HEART_BT_INT: u32 = 30;
+
+/// Settings shared by every message of one session. The admin constructors
+/// stamp BeginString, MsgType and the CompIDs so the version is chosen in a
+/// single place; MsgSeqNum and SendingTime are left to the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionConfig {
+    pub begin_string: Vec<u8>,
+    pub sender_comp_id: Vec<u8>,
+    pub target_comp_id: Vec<u8>,
+    /// HeartBtInt (108) in seconds
+    pub heart_bt_int: u32,
+    /// Whether our Logon asks the counterparty to reset sequence numbers
+    pub reset_on_logon: bool,
+    /// Daily start and end of the session in UTC; `None` for always open
+    pub schedule: Option<(UtcTimeOnly, UtcTimeOnly)>,
+    /// Dictionary file used to validate this session's messages
+    pub data_dictionary: Option<PathBuf>,
+    /// How a stream transport delimits this session's messages
+    pub framing: FramingMode,
+    /// How far ahead of our clock an inbound SendingTime (52) may be;
+    /// `None` disables the check
+    pub reject_future_sending_time: Option<Duration>,
+    /// Tells apart sessions between the same CompIDs, e.g. an order session
+    /// and a drop copy session to one venue. Never sent on the wire.
+    pub session_qualifier: Option<String>,
+}
+
+impl SessionConfig {
+    pub fn new(
+        begin_string: impl Into<Vec<u8>>,
+        sender_comp_id: impl Into<Vec<u8>>,
+        target_comp_id: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            begin_string: begin_string.into(),
+            sender_comp_id: sender_comp_id.into(),
+            target_comp_id: target_comp_id.into(),
+            heart_bt_int: DEFAULT_HEART_BT_INT,
+            reset_on_logon: false,
+            schedule: None,
+            data_dictionary: None,
+            framing: FramingMode::Native,
+            reject_future_sending_time: None,
+            session_qualifier: None,
+        }
+    }
+
+    pub fn with_heart_bt_int(mut self, seconds: u32) -> Self {
+        self.heart_bt_int = seconds;
+        self
+    }
+
+    pub fn with_reset_on_logon(mut self, reset: bool) -> Self {
+        self.reset_on_logon = reset;
+        self
+    }
+
+    pub fn with_schedule(mut self, start: UtcTimeOnly, end: UtcTimeOnly) -> Self {
+        self.schedule = Some((start, end));
+        self
+    }
+
+    pub fn with_data_dictionary(mut self, path: impl Into<PathBuf>) -> Self {
+        self.data_dictionary = Some(path.into());
+        self
+    }
+
+    pub fn with_framing(mut self, framing: FramingMode) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    pub fn with_reject_future_sending_time(mut self, tolerance: Duration) -> Self {
+        self.reject_future_sending_time = Some(tolerance);
+        self
+    }
+
+    pub fn with_session_qualifier(mut self, qualifier: impl Into<String>) -> Self {
+        self.session_qualifier = Some(qualifier.into());
+        self
+    }
+
+    /// "BeginString:SenderCompID->TargetCompID", with ":qualifier" after it
+    /// when there is one: unique per session, so usable to key its store
+    /// and label its metrics
+    pub fn session_id(&self) -> String {
+        let mut id = format!(
+            "{}:{}->{}",
+            String::from_utf8_lossy(&self.begin_string),
+            String::from_utf8_lossy(&self.sender_comp_id),
+            String::from_utf8_lossy(&self.target_comp_id),
+        );
+        if let Some(qualifier) = &self.session_qualifier {
+            id.push(':');
+            id.push_str(qualifier);
+        }
+        id
+    }
+
+    /// A message of type `msg_type` carrying this session's BeginString and
+    /// CompIDs, ready for body fields to be appended
+    pub fn new_message(&self, msg_type: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), self.begin_string.clone()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type.to_vec()));
+        msg.add_field(FixField::new(Tag::SenderCompID.value(), self.sender_comp_id.clone()));
+        msg.add_field(FixField::new(Tag::TargetCompID.value(), self.target_comp_id.clone()));
+        msg
+    }
+
+    /// Logon (35=A) without encryption, with ResetSeqNumFlag when
+    /// `reset_on_logon` is set
+    pub fn logon(&self) -> FixMessage {
+        let mut msg = self.new_message(msg_type::LOGON);
+        Logon::new(self.heart_bt_int)
+            .with_reset_seq_num_flag(self.reset_on_logon)
+            .write_body(&mut msg);
+        msg
+    }
+
+    /// Heartbeat (35=0), echoing TestReqID when answering a TestRequest
+    pub fn heartbeat(&self, test_req_id: Option<&[u8]>) -> FixMessage {
+        let mut msg = self.new_message(msg_type::HEARTBEAT);
+        if let Some(test_req_id) = test_req_id {
+            msg.add_field(FixField::new(Tag::TestReqID.value(), test_req_id.to_vec()));
+        }
+        msg
+    }
+
+    /// TestRequest (35=1)
+    pub fn test_request(&self, test_req_id: &[u8]) -> FixMessage {
+        let mut msg = self.new_message(msg_type::TEST_REQUEST);
+        msg.add_field(FixField::new(Tag::TestReqID.value(), test_req_id.to_vec()));
+        msg
+    }
+
+    /// ResendRequest (35=2)
+    pub fn resend_request(&self, request: &ResendRequest) -> FixMessage {
+        let mut msg = self.new_message(msg_type::RESEND_REQUEST);
+        request.write_body(&mut msg);
+        msg
+    }
+
+    /// SequenceReset (35=4), in gap-fill mode or as a hard reset
+    pub fn sequence_reset(&self, new_seq_no: SeqNum, gap_fill: bool) -> FixMessage {
+        let mut msg = self.new_message(msg_type::SEQUENCE_RESET);
+        if gap_fill {
+            msg.add_field(FixField::from_value(Tag::GapFillFlag.value(), &true));
+        }
+        msg.add_field(FixField::from_value(Tag::NewSeqNo.value(), &new_seq_no));
+        msg
+    }
+
+    /// Logout (35=5) with an optional reason
+    pub fn logout(&self, text: Option<&[u8]>) -> FixMessage {
+        let mut msg = self.new_message(msg_type::LOGOUT);
+        if let Some(text) = text {
+            msg.add_field(FixField::new(Tag::Text.value(), text.to_vec()));
+        }
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seqnum::EndSeqNo;
+    use crate::tags::fix_version;
+
+    #[test]
+    fn test_admin_messages_use_session_begin_string() {
+        let config = SessionConfig::new(fix_version::FIX_4_2, "CLIENT", "VENUE").with_heart_bt_int(10);
+        let seq = SeqNum::new(5).unwrap();
+        let messages = [
+            config.logon(),
+            config.heartbeat(Some(b"T1")),
+            config.test_request(b"T2"),
+            config.resend_request(&ResendRequest::new(seq, EndSeqNo::Unbounded)),
+            config.sequence_reset(seq, true),
+            config.logout(None),
+        ];
+
+        for msg in &messages {
+            assert_eq!(msg.get_field(Tag::BeginString.value()).unwrap().value(), b"FIX.4.2");
+            assert_eq!(msg.get_field(Tag::SenderCompID.value()).unwrap().value(), b"CLIENT");
+            assert_eq!(msg.get_field(Tag::TargetCompID.value()).unwrap().value(), b"VENUE");
+        }
+        assert_eq!(messages[0].get_uint(Tag::HeartBtInt.value()).unwrap(), Some(10));
+
+        let resend = FixMessage::decode(&messages[3].encode().unwrap()).unwrap();
+        assert_eq!(
+            ResendRequest::from_message(&resend).unwrap(),
+            ResendRequest::new(seq, EndSeqNo::Unbounded)
+        );
+    }
+}
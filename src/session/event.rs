@@ -0,0 +1,27 @@
+use crate::datetime::UtcTimestamp;
+use crate::seqnum::SeqNum;
+
+/// What the session concluded about an inbound message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// A session-level message in sequence
+    Admin,
+    /// An application message in sequence, for the business layer
+    Application,
+    /// An application message in sequence carrying PossResend=Y. It is new to
+    /// the session but the business layer may have seen its content already
+    /// and must check by business key.
+    PossibleResend,
+    /// PossDupFlag=Y with a sequence number already processed; ignore it
+    Duplicate(SeqNum),
+    /// Messages between `expected` and `received` are missing and should be
+    /// requested with a ResendRequest
+    SequenceGap { expected: SeqNum, received: SeqNum },
+    /// A sequence number lower than expected without PossDupFlag, which the
+    /// session must treat as fatal
+    SequenceTooLow { expected: SeqNum, received: SeqNum },
+    /// A message in sequence whose SendingTime is further ahead of our clock
+    /// than `SessionConfig::reject_future_sending_time` allows; it should be
+    /// rejected rather than processed
+    FutureTimestamp { sending_time: UtcTimestamp, now: UtcTimestamp },
+}
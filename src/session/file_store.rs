@@ -0,0 +1,582 @@
+use super::{MessageStore, SessionConfig};
+#[cfg(feature = "compression")]
+use crate::compression::{BlockCompression, MAX_DECOMPRESSED_LEN, SKIPPABLE_MAGIC};
+use crate::field::parse_uint;
+use crate::seqnum::{EndSeqNo, SeqNum};
+use bytes::Bytes;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+// A plain journal is a series of records, each a header line
+// "<MsgSeqNum> <length>" followed by exactly `length` raw bytes and a
+// newline. A compressed journal holds the same records in blocks: a zstd
+// skippable frame with the block's lowest and highest MsgSeqNum and
+// compressed length, then one zstd frame of the block's records.
+
+// Skippable frame magic and size, lowest and highest MsgSeqNum, length of
+// the zstd frame that follows
+#[cfg(feature = "compression")]
+const BLOCK_HEADER_LEN: usize = 4 + 4 + 8 + 8 + 4;
+
+// Largest a block may expand to. Writing starts a new block before one
+// would grow past it, and reading refuses to expand a block further, so a
+// corrupt journal cannot exhaust memory.
+#[cfg(feature = "compression")]
+const MAX_BLOCK_LEN: usize = MAX_DECOMPRESSED_LEN;
+
+// Upper bound of a record's header line and trailing newline
+#[cfg(feature = "compression")]
+const RECORD_OVERHEAD: usize = 2 * 20 + 3;
+
+fn invalid(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("journal: {}", what))
+}
+
+fn parse_header(line: &[u8]) -> io::Result<(SeqNum, usize)> {
+    let mut parts = line.trim_ascii_end().split(|&b| b == b' ');
+    let (Some(seq), Some(len), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid("malformed record header"));
+    };
+    let seq = parse_uint(seq).ok().and_then(SeqNum::new).ok_or_else(|| invalid("bad sequence number"))?;
+    let len = parse_uint(len).map_err(|_| invalid("bad length"))? as usize;
+    Ok((seq, len))
+}
+
+// Appends a record, returning the length of its header
+fn encode_record(out: &mut Vec<u8>, seq: SeqNum, raw: &[u8]) -> usize {
+    let start = out.len();
+    let _ = writeln!(out, "{} {}", seq.get(), raw.len());
+    let header_len = out.len() - start;
+    out.extend_from_slice(raw);
+    out.push(b'\n');
+    header_len
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    offset: u64,
+    len: usize,
+}
+
+#[cfg(feature = "compression")]
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    first: SeqNum,
+    last: SeqNum,
+    location: Location,
+}
+
+#[cfg(feature = "compression")]
+#[derive(Debug)]
+struct Blocks {
+    compression: BlockCompression,
+    blocks: Vec<Block>,
+    // Saved since the last block was written
+    pending: Vec<(SeqNum, Bytes)>,
+    // Length of the pending records once encoded
+    pending_len: usize,
+}
+
+/// A `MessageStore` appending to a journal file and indexing it in memory,
+/// so a resend reads only the records or blocks it needs. A record or block
+/// cut short by a crash is dropped when the journal is reopened.
+///
+/// Plain journals are written through on every save. Compressed journals
+/// hold saved messages in memory until their block fills or `flush` is
+/// called; a block of one record writes through at the cost of
+/// compression.
+#[derive(Debug)]
+pub struct FileMessageStore {
+    path: PathBuf,
+    file: File,
+    // Where the next record or block starts
+    end: u64,
+    records: BTreeMap<SeqNum, Location>,
+    #[cfg(feature = "compression")]
+    blocks: Option<Blocks>,
+}
+
+impl FileMessageStore {
+    /// Opens the journal at `path`, creating a plain one if needed. A
+    /// compressed journal is recognised by its leading magic bytes and
+    /// appended to with the default `BlockCompression`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (file, prefix) = Self::open_file(path.as_ref())?;
+        #[cfg(feature = "compression")]
+        if prefix.starts_with(&SKIPPABLE_MAGIC) {
+            return Self::load_blocks(path.as_ref(), file, BlockCompression::default());
+        }
+        Self::load_plain(path.as_ref(), file, &prefix)
+    }
+
+    /// Where the journal of the session `config` is kept in `dir`: a file
+    /// named after its session id, qualifier included, so sessions between
+    /// the same CompIDs never share one
+    pub fn path_for(dir: impl AsRef<Path>, config: &SessionConfig) -> PathBuf {
+        let name: String = config
+            .session_id()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_') { c } else { '-' })
+            .collect();
+        dir.as_ref().join(format!("{}.journal", name.replace("->", "-")))
+    }
+
+    /// Opens the journal of the session `config` in `dir`, see `path_for`
+    pub fn open_for(dir: impl AsRef<Path>, config: &SessionConfig) -> io::Result<Self> {
+        Self::open(Self::path_for(dir, config))
+    }
+
+    /// Opens the journal at `path`, creating a compressed one if needed.
+    /// An existing plain journal stays plain.
+    #[cfg(feature = "compression")]
+    pub fn open_compressed(path: impl AsRef<Path>, compression: BlockCompression) -> io::Result<Self> {
+        let (file, prefix) = Self::open_file(path.as_ref())?;
+        if prefix.is_empty() || prefix.starts_with(&SKIPPABLE_MAGIC) {
+            Self::load_blocks(path.as_ref(), file, compression)
+        } else {
+            Self::load_plain(path.as_ref(), file, &prefix)
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the journal is written in compressed blocks
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        #[cfg(feature = "compression")]
+        let compressed = self.blocks.is_some();
+        #[cfg(not(feature = "compression"))]
+        let compressed = false;
+        compressed
+    }
+
+    /// Writes any partly filled block
+    pub fn flush(&mut self) -> io::Result<()> {
+        #[cfg(feature = "compression")]
+        self.write_block()?;
+        self.file.flush()
+    }
+
+    /// Flushes and syncs the journal to disk
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.file.sync_data()
+    }
+
+    // Opens for reading and appending, returning up to four leading bytes
+    fn open_file(path: &Path) -> io::Result<(File, Vec<u8>)> {
+        let file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut prefix = Vec::with_capacity(4);
+        (&file).take(4).read_to_end(&mut prefix)?;
+        Ok((file, prefix))
+    }
+
+    fn load_plain(path: &Path, file: File, prefix: &[u8]) -> io::Result<Self> {
+        if prefix.first().is_some_and(|b| !b.is_ascii_digit()) {
+            return Err(invalid("not a plain journal; compressed journals need the compression feature"));
+        }
+
+        let file_len = file.metadata()?.len();
+        let mut reader = BufReader::new(&file);
+        reader.seek(SeekFrom::Start(0))?;
+        let mut records = BTreeMap::new();
+        let mut header = Vec::new();
+        let mut end = 0;
+        loop {
+            header.clear();
+            let read = reader.read_until(b'\n', &mut header)?;
+            if read == 0 || header.last() != Some(&b'\n') {
+                break;
+            }
+            let (seq, len) = parse_header(&header)?;
+            let offset = end + read as u64;
+            let record_end = offset
+                .checked_add(len as u64)
+                .and_then(|record_end| record_end.checked_add(1))
+                .ok_or_else(|| invalid("bad length"))?;
+            if record_end > file_len {
+                break;
+            }
+            reader.seek_relative(len as i64 + 1)?;
+            records.insert(seq, Location { offset, len });
+            end = record_end;
+        }
+        if end < file_len {
+            file.set_len(end)?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            end,
+            records,
+            #[cfg(feature = "compression")]
+            blocks: None,
+        })
+    }
+
+    #[cfg(feature = "compression")]
+    fn load_blocks(path: &Path, file: File, compression: BlockCompression) -> io::Result<Self> {
+        let file_len = file.metadata()?.len();
+        let mut reader = BufReader::new(&file);
+        reader.seek(SeekFrom::Start(0))?;
+        let mut blocks = Vec::new();
+        let mut header = [0; BLOCK_HEADER_LEN];
+        let mut end = 0;
+        while end + BLOCK_HEADER_LEN as u64 <= file_len {
+            reader.read_exact(&mut header)?;
+            let word = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+            let seq = |at: usize| {
+                let value = u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+                SeqNum::new(value).ok_or_else(|| invalid("bad sequence number"))
+            };
+            if header[..4] != SKIPPABLE_MAGIC || word(4) as usize != BLOCK_HEADER_LEN - 8 {
+                return Err(invalid("malformed block header"));
+            }
+            let location = Location { offset: end + BLOCK_HEADER_LEN as u64, len: word(24) as usize };
+            if location.offset + location.len as u64 > file_len {
+                break;
+            }
+            reader.seek_relative(location.len as i64)?;
+            blocks.push(Block { first: seq(8)?, last: seq(16)?, location });
+            end = location.offset + location.len as u64;
+        }
+        if end < file_len {
+            file.set_len(end)?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            end,
+            records: BTreeMap::new(),
+            blocks: Some(Blocks { compression, blocks, pending: Vec::new(), pending_len: 0 }),
+        })
+    }
+
+    fn read_at(&self, location: Location) -> io::Result<Vec<u8>> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(location.offset))?;
+        let mut buf = vec![0; location.len];
+        file.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    #[cfg(feature = "compression")]
+    fn write_block(&mut self) -> io::Result<()> {
+        let Some(blocks) = self.blocks.as_mut().filter(|blocks| !blocks.pending.is_empty()) else {
+            return Ok(());
+        };
+        let mut records = Vec::new();
+        for (seq, raw) in &blocks.pending {
+            encode_record(&mut records, *seq, raw);
+        }
+        let data = blocks.compression.compress(&records)?;
+        let first = blocks.pending.iter().map(|(seq, _)| *seq).min().unwrap();
+        let last = blocks.pending.iter().map(|(seq, _)| *seq).max().unwrap();
+
+        let mut block = Vec::with_capacity(BLOCK_HEADER_LEN + data.len());
+        block.extend_from_slice(&SKIPPABLE_MAGIC);
+        block.extend_from_slice(&(BLOCK_HEADER_LEN as u32 - 8).to_le_bytes());
+        block.extend_from_slice(&first.get().to_le_bytes());
+        block.extend_from_slice(&last.get().to_le_bytes());
+        block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        block.extend_from_slice(&data);
+        self.file.write_all(&block)?;
+
+        let location = Location { offset: self.end + BLOCK_HEADER_LEN as u64, len: data.len() };
+        blocks.blocks.push(Block { first, last, location });
+        blocks.pending.clear();
+        blocks.pending_len = 0;
+        self.end += block.len() as u64;
+        Ok(())
+    }
+
+    // Messages of the blocks overlapping the range, later saves replacing
+    // earlier ones
+    #[cfg(feature = "compression")]
+    fn block_range(&self, blocks: &Blocks, begin: SeqNum, end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>> {
+        let wanted = |seq: SeqNum| seq >= begin && end.includes(seq);
+        let mut found = BTreeMap::new();
+        for block in blocks.blocks.iter().filter(|block| block.last >= begin && end.includes(block.first)) {
+            let mut records = Vec::new();
+            zstd::Decoder::new(&self.read_at(block.location)?[..])?
+                .take(MAX_BLOCK_LEN as u64 + 1)
+                .read_to_end(&mut records)?;
+            if records.len() > MAX_BLOCK_LEN {
+                return Err(invalid("block too large"));
+            }
+            let records = Bytes::from(records);
+            let mut pos = 0;
+            while pos < records.len() {
+                let header_end = memchr::memchr(b'\n', &records[pos..]).ok_or_else(|| invalid("truncated block"))? + pos;
+                let (seq, len) = parse_header(&records[pos..header_end])?;
+                let start = header_end + 1;
+                let record_end = start
+                    .checked_add(len)
+                    .and_then(|record_end| record_end.checked_add(1))
+                    .filter(|&record_end| record_end <= records.len())
+                    .ok_or_else(|| invalid("truncated block"))?;
+                if wanted(seq) {
+                    found.insert(seq, records.slice(start..start + len));
+                }
+                pos = record_end;
+            }
+        }
+        for (seq, raw) in blocks.pending.iter().filter(|(seq, _)| wanted(*seq)) {
+            found.insert(*seq, raw.clone());
+        }
+        Ok(found.into_iter().collect())
+    }
+}
+
+impl MessageStore for FileMessageStore {
+    fn save(&mut self, seq: SeqNum, raw: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "compression")]
+        if let Some(blocks) = &self.blocks {
+            if !blocks.pending.is_empty() && blocks.pending_len + raw.len() + RECORD_OVERHEAD > MAX_BLOCK_LEN {
+                self.write_block()?;
+            }
+            let blocks = self.blocks.as_mut().unwrap();
+            blocks.pending.push((seq, Bytes::copy_from_slice(raw)));
+            blocks.pending_len += raw.len() + RECORD_OVERHEAD;
+            if blocks.pending.len() >= blocks.compression.block_records {
+                self.write_block()?;
+            }
+            return Ok(());
+        }
+
+        let mut record = Vec::with_capacity(raw.len() + 24);
+        let header_len = encode_record(&mut record, seq, raw);
+        self.file.write_all(&record)?;
+        self.records.insert(seq, Location { offset: self.end + header_len as u64, len: raw.len() });
+        self.end += record.len() as u64;
+        Ok(())
+    }
+
+    fn range(&self, begin: SeqNum, end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>> {
+        #[cfg(feature = "compression")]
+        if let Some(blocks) = &self.blocks {
+            return self.block_range(blocks, begin, end);
+        }
+
+        let stored = match end {
+            EndSeqNo::Bounded(end) if end < begin => return Ok(Vec::new()),
+            EndSeqNo::Bounded(end) => self.records.range(begin..=end),
+            EndSeqNo::Unbounded => self.records.range(begin..),
+        };
+        stored
+            .map(|(&seq, &location)| Ok((seq, Bytes::from(self.read_at(location)?))))
+            .collect()
+    }
+
+    fn last_seq(&self) -> io::Result<Option<SeqNum>> {
+        #[cfg(feature = "compression")]
+        if let Some(blocks) = &self.blocks {
+            let written = blocks.blocks.iter().map(|block| block.last);
+            return Ok(written.chain(blocks.pending.iter().map(|(seq, _)| *seq)).max());
+        }
+        Ok(self.records.keys().next_back().copied())
+    }
+
+    fn get(&self, seq: SeqNum) -> io::Result<Option<Bytes>> {
+        #[cfg(feature = "compression")]
+        if let Some(blocks) = &self.blocks {
+            return Ok(self.block_range(blocks, seq, EndSeqNo::Bounded(seq))?.pop().map(|(_, raw)| raw));
+        }
+        self.records.get(&seq).map(|&location| Ok(Bytes::from(self.read_at(location)?))).transpose()
+    }
+}
+
+impl Drop for FileMessageStore {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_journal(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fix_engine_journal_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("journal")
+    }
+
+    fn seq(value: u64) -> SeqNum {
+        SeqNum::new(value).unwrap()
+    }
+
+    fn raw(value: u64) -> Vec<u8> {
+        format!("8=FIX.4.4\x0135=D\x0134={}\x0111=ORD{}\x01\n", value, value).into_bytes()
+    }
+
+    fn seqs(stored: &[(SeqNum, Bytes)]) -> Vec<u64> {
+        stored.iter().map(|(seq, _)| seq.get()).collect()
+    }
+
+    #[test]
+    fn test_plain_journal() {
+        let path = temp_journal("plain");
+        let mut store = FileMessageStore::open(&path).unwrap();
+        for value in 1..=5 {
+            store.save(seq(value), &raw(value)).unwrap();
+        }
+        store.save(seq(3), b"replaced").unwrap();
+        drop(store);
+
+        // A record cut short by a crash is dropped
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"6 40\n8=FIX").unwrap();
+
+        let mut store = FileMessageStore::open(&path).unwrap();
+        assert!(!store.is_compressed());
+        assert_eq!(store.last_seq().unwrap(), Some(seq(5)));
+        let stored = store.range(seq(2), EndSeqNo::Bounded(seq(4))).unwrap();
+        assert_eq!(seqs(&stored), [2, 3, 4]);
+        assert_eq!(stored[0].1, raw(2));
+        assert_eq!(stored[1].1, &b"replaced"[..]);
+
+        store.save(seq(6), &raw(6)).unwrap();
+        let reopened = FileMessageStore::open(&path).unwrap();
+        assert_eq!(seqs(&reopened.range(seq(5), EndSeqNo::Unbounded).unwrap()), [5, 6]);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_journal() {
+        let path = temp_journal("compressed");
+        let mut store = FileMessageStore::open_compressed(&path, BlockCompression::new(3, 4)).unwrap();
+        for value in 1..=10 {
+            store.save(seq(value), &raw(value)).unwrap();
+        }
+        // Seq 9 and 10 are still pending, and served from memory
+        assert_eq!(seqs(&store.range(seq(7), EndSeqNo::Unbounded).unwrap()), [7, 8, 9, 10]);
+        drop(store);
+
+        let file = std::fs::read(&path).unwrap();
+        assert!(file.starts_with(&SKIPPABLE_MAGIC));
+        let mut plain = Vec::new();
+        for value in 1..=10 {
+            encode_record(&mut plain, seq(value), &raw(value));
+        }
+        assert_eq!(zstd::stream::decode_all(&file[..]).unwrap(), plain);
+
+        // Compression is detected without being asked for
+        let mut store = FileMessageStore::open(&path).unwrap();
+        assert!(store.is_compressed());
+        assert_eq!(store.last_seq().unwrap(), Some(seq(10)));
+        let stored = store.range(seq(4), EndSeqNo::Bounded(seq(6))).unwrap();
+        assert_eq!(seqs(&stored), [4, 5, 6]);
+        assert_eq!(stored[2].1, raw(6));
+
+        store.save(seq(5), b"replaced").unwrap();
+        store.flush().unwrap();
+        let stored = store.range(seq(5), EndSeqNo::Bounded(seq(5))).unwrap();
+        assert_eq!(stored[0].1, &b"replaced"[..]);
+        assert!(store.range(seq(6), EndSeqNo::Bounded(seq(5))).unwrap().is_empty());
+
+        // An existing plain journal is not mixed with compressed blocks
+        let plain_path = temp_journal("stays_plain");
+        FileMessageStore::open(&plain_path).unwrap().save(seq(1), &raw(1)).unwrap();
+        assert!(!FileMessageStore::open_compressed(&plain_path, BlockCompression::default()).unwrap().is_compressed());
+    }
+
+    #[test]
+    fn test_hostile_lengths() {
+        let path = temp_journal("hostile_plain");
+        std::fs::write(&path, b"1 18446744073709551615\n8=FIX\n").unwrap();
+        assert_eq!(FileMessageStore::open(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_hostile_blocks() {
+        let journal = |name: &str, records: &[u8]| {
+            let data = zstd::bulk::compress(records, 3).unwrap();
+            let mut file = SKIPPABLE_MAGIC.to_vec();
+            file.extend_from_slice(&(BLOCK_HEADER_LEN as u32 - 8).to_le_bytes());
+            file.extend_from_slice(&1u64.to_le_bytes());
+            file.extend_from_slice(&1u64.to_le_bytes());
+            file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            file.extend_from_slice(&data);
+            let path = temp_journal(name);
+            std::fs::write(&path, file).unwrap();
+            FileMessageStore::open(&path).unwrap()
+        };
+
+        let mut bomb = b"1 3\nabc\n".to_vec();
+        bomb.resize(MAX_BLOCK_LEN + 1, b'\n');
+        let store = journal("bomb", &bomb);
+        let err = store.range(seq(1), EndSeqNo::Unbounded).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let store = journal("overflow", b"1 18446744073709551615\nabc\n");
+        let err = store.range(seq(1), EndSeqNo::Unbounded).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Large messages are spread over blocks that stay under the limit
+        let path = temp_journal("large");
+        let mut store = FileMessageStore::open_compressed(&path, BlockCompression::default()).unwrap();
+        let large = vec![b'A'; MAX_BLOCK_LEN / 3];
+        for value in 1..=4 {
+            store.save(seq(value), &large).unwrap();
+        }
+        store.flush().unwrap();
+        let reopened = FileMessageStore::open(&path).unwrap();
+        assert_eq!(seqs(&reopened.range(seq(1), EndSeqNo::Unbounded).unwrap()), [1, 2, 3, 4]);
+    }
+
+    // An order as first sent, or as resent with PossDupFlag and a new
+    // SendingTime
+    fn order(value: u64, symbol: &str, resent: bool) -> Bytes {
+        let mut msg = crate::message::FixMessage::new();
+        let sending_time = if resent { "20240101-13:00:00.000" } else { "20240101-12:00:00.000" };
+        let mut fields = vec![(8, "FIX.4.4".to_owned()), (35, "D".into()), (34, value.to_string())];
+        if resent {
+            fields.extend([(43, "Y".into()), (122, "20240101-12:00:00.000".into())]);
+        }
+        fields.extend([(52, sending_time.into()), (11, format!("ORD{}", value)), (55, symbol.into())]);
+        for (tag, value) in fields {
+            msg.add_field(crate::field::FixField::new(tag, value.into_bytes()));
+        }
+        msg.encode().unwrap().freeze()
+    }
+
+    #[test]
+    fn test_merge_resent() {
+        let path = temp_journal("merge");
+        let mut store = FileMessageStore::open(&path).unwrap();
+        for value in [1, 2, 3, 7, 8] {
+            store.save(seq(value), &order(value, "VOD.L", false)).unwrap();
+        }
+
+        // Two overlapping resends, out of order, one disagreeing about seq 8
+        let first = (2..=5).rev().map(|value| (seq(value), order(value, "VOD.L", true)));
+        let report = store.merge_resent(first).unwrap();
+        assert_eq!(report.inserted, [seq(4), seq(5)]);
+        assert_eq!(report.duplicates, [seq(2), seq(3)]);
+        assert!(report.is_consistent());
+
+        let second = (4..=9).map(|value| (seq(value), order(value, if value == 8 { "BARC.L" } else { "VOD.L" }, true)));
+        let report = store.merge_resent(second).unwrap();
+        assert_eq!(report.inserted, [seq(6), seq(9)]);
+        assert_eq!(report.duplicates, [seq(4), seq(5), seq(7)]);
+        assert_eq!(report.mismatched, [seq(8)]);
+        drop(store);
+
+        let store = FileMessageStore::open(&path).unwrap();
+        let stored = store.range(seq(1), EndSeqNo::Unbounded).unwrap();
+        assert_eq!(seqs(&stored), [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        // What was stored is kept, and gaps are filled with the resent copy
+        assert_eq!(stored[7].1, order(8, "VOD.L", false));
+        assert_eq!(stored[5].1, order(6, "VOD.L", true));
+    }
+}
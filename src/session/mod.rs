@@ -0,0 +1,21 @@
+pub mod acceptor;
+pub mod clock;
+pub mod config;
+pub mod event;
+pub mod file_store;
+pub mod queue;
+pub mod settings;
+pub mod snapshot;
+pub mod state;
+pub mod store;
+
+pub use acceptor::{ByLocalPort, ByLogonTag, InboundConnection, SessionAcceptor, SessionSelector};
+pub use clock::Clock;
+pub use config::SessionConfig;
+pub use event::SessionEvent;
+pub use file_store::FileMessageStore;
+pub use queue::OutboundQueue;
+pub use settings::{Settings, SettingsWarning};
+pub use snapshot::SessionStateSnapshot;
+pub use state::Session;
+pub use store::{MemoryStore, MergeReport, MessageStore};
@@ -0,0 +1,273 @@
+use super::Clock;
+use crate::datetime::UtcTimestamp;
+use crate::message::FixMessage;
+use crate::tags::{msg_type, Tag};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Queued {
+    msg: FixMessage,
+    // Nanoseconds since the Unix epoch after which the message is not sent
+    deadline: Option<i64>,
+}
+
+fn is_admin(msg: &FixMessage) -> bool {
+    msg.get_field(Tag::MsgType.value()).is_some_and(|f| msg_type::is_admin(f.value()))
+}
+
+// Saturates rather than wrapping negative for a Duration past i64 nanoseconds
+pub(super) fn nanos(duration: Duration) -> i64 {
+    duration.as_nanos().min(i64::MAX as u128) as i64
+}
+
+/// Messages waiting to be written, in the order queued. Nothing is stamped
+/// or encoded until `pop` hands a message out, so the caller passes it to
+/// `Session::prepare_outbound` then; `WsTransport::flush` does both.
+///
+/// A message may carry a deadline, for quotes that must not go out stale
+/// after sitting in a congested queue. One past its deadline when it
+/// reaches the front is dropped and handed to the `on_expired` callback
+/// instead, so it never takes a MsgSeqNum. Admin messages never expire.
+///
+/// An optional rate limit caps the application messages handed out in any
+/// window. While it holds them back they can still expire, and expired
+/// messages do not count against it. Admin messages are neither limited nor
+/// counted, and may overtake application messages held back by the limit so
+/// heartbeats keep the session alive.
+pub struct OutboundQueue {
+    clock: Clock,
+    queued: VecDeque<Queued>,
+    // At most this many application messages per window
+    rate_limit: Option<(usize, Duration)>,
+    // When the application messages in the current window were handed out
+    handed_out: VecDeque<i64>,
+    on_expired: Option<Box<dyn FnMut(FixMessage) + Send>>,
+}
+
+impl OutboundQueue {
+    pub fn new() -> Self {
+        Self {
+            clock: Clock::system(),
+            queued: VecDeque::new(),
+            rate_limit: None,
+            handed_out: VecDeque::new(),
+            on_expired: None,
+        }
+    }
+
+    /// Replaces the system clock used for deadlines and the rate limit
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Hands out at most `max` application messages in any `per`
+    pub fn with_rate_limit(mut self, max: usize, per: Duration) -> Self {
+        self.rate_limit = Some((max.max(1), per));
+        self
+    }
+
+    /// Called with each message dropped for expiring
+    pub fn with_on_expired(mut self, on_expired: impl FnMut(FixMessage) + Send + 'static) -> Self {
+        self.on_expired = Some(Box::new(on_expired));
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Queues a message that does not expire
+    pub fn push(&mut self, msg: FixMessage) {
+        self.queued.push_back(Queued { msg, deadline: None });
+    }
+
+    /// Queues a message not to be sent after `deadline`
+    pub fn push_with_deadline(&mut self, msg: FixMessage, deadline: UtcTimestamp) {
+        self.push_until(msg, deadline.unix_nanos());
+    }
+
+    /// Queues a message not to be sent once `ttl` has passed
+    pub fn push_with_ttl(&mut self, msg: FixMessage, ttl: Duration) {
+        let deadline = self.clock.now_nanos().saturating_add(nanos(ttl));
+        self.push_until(msg, deadline);
+    }
+
+    fn push_until(&mut self, msg: FixMessage, deadline: i64) {
+        let deadline = (!is_admin(&msg)).then_some(deadline);
+        self.queued.push_back(Queued { msg, deadline });
+    }
+
+    /// The next message to write now, if any. Expired messages reached on
+    /// the way are dropped.
+    pub fn pop(&mut self) -> Option<FixMessage> {
+        let now = self.clock.now_nanos();
+        let limited = self.is_limited(now);
+        let mut i = 0;
+        while i < self.queued.len() {
+            let queued = &self.queued[i];
+            if queued.deadline.is_some_and(|deadline| now > deadline) {
+                let expired = self.queued.remove(i).expect("index is in bounds");
+                if let Some(on_expired) = &mut self.on_expired {
+                    on_expired(expired.msg);
+                }
+            } else if is_admin(&queued.msg) {
+                return self.queued.remove(i).map(|queued| queued.msg);
+            } else if !limited {
+                if self.rate_limit.is_some() {
+                    self.handed_out.push_back(now);
+                }
+                return self.queued.remove(i).map(|queued| queued.msg);
+            } else {
+                i += 1;
+            }
+        }
+        None
+    }
+
+    /// How long until the rate limit lets the next application message
+    /// out; zero when it does not hold anything back
+    pub fn throttled_for(&mut self) -> Duration {
+        let now = self.clock.now_nanos();
+        match (self.is_limited(now), self.rate_limit, self.handed_out.front()) {
+            (true, Some((_, per)), Some(&oldest)) => {
+                Duration::from_nanos(oldest.saturating_add(nanos(per)).saturating_sub(now).max(0) as u64)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    // Forgets hand-outs older than the window and reports whether it is full
+    fn is_limited(&mut self, now: i64) -> bool {
+        let Some((max, per)) = self.rate_limit else {
+            return false;
+        };
+        let per = nanos(per);
+        while self.handed_out.front().is_some_and(|&at| now.saturating_sub(at) >= per) {
+            self.handed_out.pop_front();
+        }
+        self.handed_out.len() >= max
+    }
+}
+
+impl Default for OutboundQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for OutboundQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutboundQueue")
+            .field("clock", &self.clock)
+            .field("queued", &self.queued)
+            .field("rate_limit", &self.rate_limit)
+            .field("handed_out", &self.handed_out)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::session::{MemoryStore, Session, SessionConfig};
+    use crate::tags::fix_version;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const MILLI: i64 = 1_000_000;
+
+    fn config() -> SessionConfig {
+        SessionConfig::new(fix_version::FIX_4_4, "MM", "VENUE")
+    }
+
+    fn quote(id: &str) -> FixMessage {
+        let mut msg = config().new_message(b"S");
+        msg.add_field(FixField::new(Tag::QuoteID.value(), id.as_bytes().to_vec()));
+        msg
+    }
+
+    fn heartbeat() -> FixMessage {
+        config().heartbeat(None)
+    }
+
+    fn id(msg: &FixMessage) -> String {
+        let tag = if is_admin(msg) { Tag::MsgType } else { Tag::QuoteID };
+        String::from_utf8_lossy(msg.get_field(tag.value()).unwrap().value()).into_owned()
+    }
+
+    // A queue on a clock the test moves, and the IDs of the quotes it expired
+    fn queue() -> (OutboundQueue, Arc<AtomicI64>, Arc<Mutex<Vec<String>>>) {
+        let now = Arc::new(AtomicI64::new(0));
+        let clock = {
+            let now = now.clone();
+            Clock::from_fn(move || now.load(Ordering::Relaxed))
+        };
+        let expired = Arc::new(Mutex::new(Vec::new()));
+        let queue = {
+            let expired = expired.clone();
+            OutboundQueue::new()
+                .with_clock(clock)
+                .with_on_expired(move |msg| expired.lock().unwrap().push(id(&msg)))
+        };
+        (queue, now, expired)
+    }
+
+    #[test]
+    fn test_expired_messages_take_no_seq_num() {
+        let (mut queue, now, expired) = queue();
+        let mut session = Session::new(config());
+        let mut store = MemoryStore::new();
+
+        queue.push_with_ttl(quote("Q1"), Duration::from_millis(5));
+        queue.push_with_ttl(quote("Q2"), Duration::from_millis(20));
+        queue.push_with_ttl(heartbeat(), Duration::from_millis(1));
+        queue.push(quote("Q3"));
+        queue.push_with_deadline(quote("Q4"), UtcTimestamp::from_unix_nanos(8 * MILLI, 3).unwrap());
+
+        now.store(10 * MILLI, Ordering::Relaxed);
+        let mut sent = Vec::new();
+        while let Some(mut msg) = queue.pop() {
+            session.prepare_outbound(&mut msg, &mut store).unwrap();
+            sent.push((id(&msg), msg.msg_seq_num().unwrap().unwrap().get()));
+        }
+        assert_eq!(sent, [("Q2".into(), 1), ("0".into(), 2), ("Q3".into(), 3)]);
+        assert_eq!(*expired.lock().unwrap(), ["Q1", "Q4"]);
+        assert_eq!(store.len(), 3);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (queue, now, expired) = queue();
+        let mut queue = queue.with_rate_limit(2, Duration::from_millis(100));
+        for (i, ttl) in [1000, 1000, 50, 1000].into_iter().enumerate() {
+            queue.push_with_ttl(quote(&format!("Q{}", i + 1)), Duration::from_millis(ttl));
+        }
+        queue.push(heartbeat());
+
+        assert_eq!(queue.pop().map(|msg| id(&msg)).as_deref(), Some("Q1"));
+        now.store(30 * MILLI, Ordering::Relaxed);
+        assert_eq!(queue.pop().map(|msg| id(&msg)).as_deref(), Some("Q2"));
+        // Held back quotes do not stop the heartbeat behind them
+        assert_eq!(queue.pop().map(|msg| id(&msg)).as_deref(), Some("0"));
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.throttled_for(), Duration::from_millis(70));
+
+        // Q3 expired while held back; it neither goes out nor uses Q1's slot
+        now.store(100 * MILLI, Ordering::Relaxed);
+        assert_eq!(queue.pop().map(|msg| id(&msg)).as_deref(), Some("Q4"));
+        assert_eq!(*expired.lock().unwrap(), ["Q3"]);
+        assert_eq!(queue.throttled_for(), Duration::from_millis(30));
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use crate::datetime::UtcTimeOnly;
+use crate::error::FixError;
+use crate::field::FieldValue;
+use crate::session::SessionConfig;
+use crate::transport::TransportConfig;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// A key that was read but has no equivalent here and so has no effect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsWarning {
+    pub line: usize,
+    pub key: String,
+}
+
+impl fmt::Display for SettingsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: unsupported setting {}", self.line, self.key)
+    }
+}
+
+/// Sessions read from a QuickFIX settings file: a `[DEFAULT]` block whose
+/// keys every `[SESSION]` block inherits and may override, "Key=Value" lines
+/// and '#' comments. The commonly used keys are mapped:
+///
+/// - BeginString, SenderCompID, TargetCompID, SessionQualifier, HeartBtInt,
+///   ResetOnLogon, StartTime and EndTime (both or neither), DataDictionary
+/// - ConnectionType, SocketConnectHost, SocketConnectPort,
+///   SocketAcceptAddress, SocketAcceptPort, SocketUseSSL, ReconnectInterval
+///
+/// Any other key is reported in `warnings` rather than rejected.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub sessions: Vec<(SessionConfig, TransportConfig)>,
+    pub warnings: Vec<SettingsWarning>,
+}
+
+const MAPPED_KEYS: &[&str] = &[
+    "BeginString",
+    "SenderCompID",
+    "TargetCompID",
+    "SessionQualifier",
+    "HeartBtInt",
+    "ResetOnLogon",
+    "StartTime",
+    "EndTime",
+    "DataDictionary",
+    "ConnectionType",
+    "SocketConnectHost",
+    "SocketConnectPort",
+    "SocketAcceptAddress",
+    "SocketAcceptPort",
+    "SocketUseSSL",
+    "ReconnectInterval",
+];
+
+// Keys of one block, each with the line it was read from
+type Block<'a> = HashMap<&'a str, (&'a str, usize)>;
+
+fn invalid(line: usize, reason: impl Into<String>) -> FixError {
+    FixError::InvalidSetting { line, reason: reason.into() }
+}
+
+impl Settings {
+    pub fn parse(text: &str) -> Result<Self, FixError> {
+        let mut defaults = Block::new();
+        // Each session's block with the line of its header
+        let mut sessions: Vec<(usize, Block)> = Vec::new();
+        let mut in_default = None;
+        let mut warnings = Vec::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                match trimmed[1..trimmed.len() - 1].trim().to_ascii_uppercase().as_str() {
+                    "DEFAULT" => in_default = Some(true),
+                    "SESSION" => {
+                        in_default = Some(false);
+                        sessions.push((line, Block::new()));
+                    }
+                    other => return Err(invalid(line, format!("unknown section [{other}]"))),
+                }
+                continue;
+            }
+
+            let (key, value) = trimmed
+                .split_once('=')
+                .ok_or_else(|| invalid(line, "expected Key=Value"))?;
+            let (key, value) = (key.trim(), value.trim());
+            if !MAPPED_KEYS.contains(&key) {
+                warnings.push(SettingsWarning { line, key: key.to_owned() });
+            }
+            let block = match in_default {
+                Some(true) => &mut defaults,
+                Some(false) => &mut sessions.last_mut().expect("a session was opened").1,
+                None => return Err(invalid(line, "setting outside [DEFAULT] or [SESSION]")),
+            };
+            block.insert(key, (value, line));
+        }
+
+        let sessions = sessions
+            .into_iter()
+            .map(|(line, block)| {
+                let mut merged = defaults.clone();
+                merged.extend(block);
+                build(line, &merged)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { sessions, warnings })
+    }
+
+    /// Reads and parses a settings file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+// Builds one session from its merged block; `line` is that of its header,
+// reported for missing keys
+fn build(line: usize, block: &Block) -> Result<(SessionConfig, TransportConfig), FixError> {
+    let required = |key: &str| {
+        block
+            .get(key)
+            .copied()
+            .ok_or_else(|| invalid(line, format!("missing {key}")))
+    };
+    let number = |key: &str| -> Result<Option<u64>, FixError> {
+        block
+            .get(key)
+            .map(|&(value, at)| value.parse().map_err(|_| invalid(at, format!("{key} must be a number"))))
+            .transpose()
+    };
+    let port = |key: &str| -> Result<u16, FixError> {
+        let (value, at) = required(key)?;
+        value.parse().map_err(|_| invalid(at, format!("{key} must be a port number")))
+    };
+    let time = |key: &str| -> Result<Option<UtcTimeOnly>, FixError> {
+        block
+            .get(key)
+            .map(|&(value, at)| {
+                UtcTimeOnly::parse(value.as_bytes()).map_err(|_| invalid(at, format!("{key} must be HH:MM:SS")))
+            })
+            .transpose()
+    };
+
+    let mut config = SessionConfig::new(
+        required("BeginString")?.0,
+        required("SenderCompID")?.0,
+        required("TargetCompID")?.0,
+    );
+    if let Some(&(qualifier, _)) = block.get("SessionQualifier") {
+        config = config.with_session_qualifier(qualifier);
+    }
+    if let Some(seconds) = number("HeartBtInt")? {
+        let at = block["HeartBtInt"].1;
+        config = config.with_heart_bt_int(u32::try_from(seconds).map_err(|_| invalid(at, "HeartBtInt is too large"))?);
+    }
+    if let Some(&(value, at)) = block.get("ResetOnLogon") {
+        config = config.with_reset_on_logon(
+            bool::parse(value.as_bytes()).map_err(|_| invalid(at, "ResetOnLogon must be Y or N"))?,
+        );
+    }
+    match (time("StartTime")?, time("EndTime")?) {
+        (Some(start), Some(end)) => config = config.with_schedule(start, end),
+        (None, None) => {}
+        _ => return Err(invalid(line, "StartTime and EndTime must be given together")),
+    }
+    if let Some(&(path, _)) = block.get("DataDictionary") {
+        config = config.with_data_dictionary(path);
+    }
+
+    let (kind, at) = required("ConnectionType")?;
+    let mut transport = match kind.to_ascii_lowercase().as_str() {
+        "initiator" => TransportConfig::initiator(required("SocketConnectHost")?.0, port("SocketConnectPort")?),
+        "acceptor" => {
+            let transport = TransportConfig::acceptor(port("SocketAcceptPort")?);
+            match block.get("SocketAcceptAddress") {
+                Some(&(address, _)) => transport.with_host(address),
+                None => transport,
+            }
+        }
+        _ => return Err(invalid(at, "ConnectionType must be initiator or acceptor")),
+    };
+    if let Some(&(value, at)) = block.get("SocketUseSSL") {
+        transport =
+            transport.with_tls(bool::parse(value.as_bytes()).map_err(|_| invalid(at, "SocketUseSSL must be Y or N"))?);
+    }
+    if let Some(seconds) = number("ReconnectInterval")? {
+        transport = transport.with_reconnect_interval(Duration::from_secs(seconds));
+    }
+
+    Ok((config, transport))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::ConnectionType;
+
+    const SETTINGS: &str = "\
+# Migrated from QuickFIX/J
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=CLIENT
+HeartBtInt=20
+StartTime=08:00:00
+EndTime=17:30:00
+FileStorePath=store
+ReconnectInterval=5
+
+[SESSION]
+TargetCompID=VENUE1
+SocketConnectHost=fix1.example.com
+SocketConnectPort=9876
+ResetOnLogon=Y
+
+[SESSION]
+BeginString=FIX.4.2
+TargetCompID=VENUE2
+SessionQualifier=DROPCOPY
+ConnectionType=acceptor
+SocketAcceptPort=9877
+SocketUseSSL=Y
+DataDictionary=FIX42.xml
+HeartBtInt=30
+";
+
+    #[test]
+    fn test_default_inheritance() {
+        let settings = Settings::parse(SETTINGS).unwrap();
+        assert_eq!(settings.sessions.len(), 2);
+
+        let (config, transport) = &settings.sessions[0];
+        assert_eq!(config.begin_string, b"FIX.4.4");
+        assert_eq!(config.sender_comp_id, b"CLIENT");
+        assert_eq!(config.target_comp_id, b"VENUE1");
+        assert_eq!(config.heart_bt_int, 20);
+        assert!(config.reset_on_logon);
+        assert_eq!(config.session_qualifier, None);
+        assert_eq!(
+            config.schedule,
+            Some((UtcTimeOnly::new(8, 0, 0).unwrap(), UtcTimeOnly::new(17, 30, 0).unwrap()))
+        );
+        assert_eq!(
+            *transport,
+            TransportConfig::initiator("fix1.example.com", 9876).with_reconnect_interval(Duration::from_secs(5))
+        );
+
+        let (config, transport) = &settings.sessions[1];
+        assert_eq!(config.begin_string, b"FIX.4.2");
+        assert_eq!(config.session_id(), "FIX.4.2:CLIENT->VENUE2:DROPCOPY");
+        assert_eq!(config.heart_bt_int, 30);
+        assert!(!config.reset_on_logon);
+        assert_eq!(config.data_dictionary.as_deref(), Some(Path::new("FIX42.xml")));
+        assert_eq!(transport.connection_type, ConnectionType::Acceptor);
+        assert_eq!(transport.addr(), "0.0.0.0:9877");
+        assert!(transport.use_tls && !settings.sessions[0].1.use_tls);
+
+        assert_eq!(settings.warnings, vec![SettingsWarning { line: 9, key: "FileStorePath".to_owned() }]);
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let missing = "[SESSION]\nBeginString=FIX.4.4\nSenderCompID=A\nConnectionType=acceptor\n";
+        assert!(matches!(
+            Settings::parse(missing),
+            Err(FixError::InvalidSetting { line: 1, ref reason }) if reason == "missing TargetCompID"
+        ));
+
+        let bad_port = format!("{SETTINGS}\n[SESSION]\nTargetCompID=V3\nSocketConnectHost=h\nSocketConnectPort=x\n");
+        assert!(matches!(Settings::parse(&bad_port), Err(FixError::InvalidSetting { line: 31, .. })));
+
+        assert!(matches!(Settings::parse("HeartBtInt=30\n"), Err(FixError::InvalidSetting { line: 1, .. })));
+    }
+}
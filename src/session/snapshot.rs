@@ -0,0 +1,234 @@
+use super::state::store_error;
+use super::{MessageStore, Session};
+use crate::datetime::UtcTimestamp;
+use crate::error::FixError;
+use crate::seqnum::{EndSeqNo, SeqNum};
+use bytes::Bytes;
+
+/// A session's sequence state, exported by an active gateway so a standby
+/// can take the session over. Sequence numbers are plain integers and
+/// times are nanoseconds since the Unix epoch, keeping the serialized form
+/// stable.
+///
+/// The snapshot does not carry the outbound messages themselves, apart from
+/// the last `OUTBOUND_TAIL_LEN` in `outbound_tail`: everything older is
+/// resent from the `MessageStore` the gateways share, which must therefore
+/// hold every message the active side sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionStateSnapshot {
+    pub begin_string: Vec<u8>,
+    pub sender_comp_id: Vec<u8>,
+    pub target_comp_id: Vec<u8>,
+    pub next_inbound: u64,
+    pub next_outbound: u64,
+    pub last_sent: Option<i64>,
+    pub last_received: Option<i64>,
+    /// Inbound ranges, first and last inclusive, still awaited after a
+    /// sequence gap
+    pub pending_resends: Vec<(u64, u64)>,
+    /// The most recently sent messages as encoded, oldest first
+    pub outbound_tail: Vec<(u64, Vec<u8>)>,
+}
+
+fn invalid(reason: impl Into<String>) -> FixError {
+    FixError::InvalidSnapshot(reason.into())
+}
+
+fn seq(value: u64) -> Result<SeqNum, FixError> {
+    SeqNum::new(value).ok_or_else(|| invalid("sequence number 0"))
+}
+
+fn timestamp(nanos: Option<i64>) -> Result<Option<UtcTimestamp>, FixError> {
+    nanos.map(|nanos| UtcTimestamp::from_unix_nanos(nanos, 6)).transpose()
+}
+
+impl Session {
+    pub fn export_state(&self) -> SessionStateSnapshot {
+        SessionStateSnapshot {
+            begin_string: self.config().begin_string.clone(),
+            sender_comp_id: self.config().sender_comp_id.clone(),
+            target_comp_id: self.config().target_comp_id.clone(),
+            next_inbound: self.next_inbound.get(),
+            next_outbound: self.next_outbound.get(),
+            last_sent: self.last_sent.map(|t| t.unix_nanos()),
+            last_received: self.last_received.map(|t| t.unix_nanos()),
+            pending_resends: self.pending_resends.iter().map(|&(first, last)| (first.get(), last.get())).collect(),
+            outbound_tail: self.outbound_tail.iter().map(|(seq, raw)| (seq.get(), raw.to_vec())).collect(),
+        }
+    }
+
+    /// Takes over the session described by `snapshot`, which must be for
+    /// this session's BeginString and CompIDs.
+    ///
+    /// Messages in the snapshot's outbound tail that `store` lacks are saved
+    /// to it first, covering writes the active side had not persisted. The
+    /// store must then hold the last message the snapshot says was sent, or
+    /// resends could not be answered and restoring fails. A store that is
+    /// ahead, because the snapshot was taken before the last sends, moves
+    /// the outbound sequence past its last message so no number is reused.
+    ///
+    /// Inbound messages the active side received after the snapshot are not
+    /// recovered; they are requested again once the counterparty's next
+    /// message reveals the gap. The session is left logged out, as the
+    /// connection does not move with it.
+    pub fn restore(&mut self, snapshot: &SessionStateSnapshot, store: &mut impl MessageStore) -> Result<(), FixError> {
+        let config = self.config();
+        if snapshot.begin_string != config.begin_string
+            || snapshot.sender_comp_id != config.sender_comp_id
+            || snapshot.target_comp_id != config.target_comp_id
+        {
+            return Err(invalid("snapshot is for a different session"));
+        }
+        let next_inbound = seq(snapshot.next_inbound)?;
+        let mut next_outbound = seq(snapshot.next_outbound)?;
+        let pending_resends = snapshot
+            .pending_resends
+            .iter()
+            .map(|&(first, last)| Ok((seq(first)?, seq(last)?)))
+            .collect::<Result<_, FixError>>()?;
+        let outbound_tail = snapshot
+            .outbound_tail
+            .iter()
+            .map(|(value, raw)| Ok((seq(*value)?, Bytes::copy_from_slice(raw))))
+            .collect::<Result<_, FixError>>()?;
+        let (last_sent, last_received) = (timestamp(snapshot.last_sent)?, timestamp(snapshot.last_received)?);
+
+        let stored_last = store.last_seq().map_err(store_error)?;
+        for (value, raw) in &snapshot.outbound_tail {
+            let tail_seq = seq(*value)?;
+            if store.range(tail_seq, EndSeqNo::Bounded(tail_seq)).map_err(store_error)?.is_empty() {
+                store.save(tail_seq, raw).map_err(store_error)?;
+            }
+        }
+        let last = store.last_seq().map_err(store_error)?.map_or(0, SeqNum::get);
+        if last + 1 < next_outbound.get() {
+            return Err(invalid(format!(
+                "store ends at {last} but messages through {} were sent",
+                next_outbound.get() - 1
+            )));
+        }
+        if stored_last.is_some_and(|stored| stored >= next_outbound) {
+            next_outbound = stored_last.and_then(SeqNum::next).ok_or(FixError::InvalidFieldValue)?;
+        }
+
+        self.next_inbound = next_inbound;
+        self.next_outbound = next_outbound;
+        self.last_sent = last_sent;
+        self.last_received = last_received;
+        self.pending_resends = pending_resends;
+        self.outbound_tail = outbound_tail;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::message::FixMessage;
+    use crate::messages::ResendRequest;
+    use crate::session::{Clock, MemoryStore, SessionConfig, SessionEvent};
+    use crate::tags::{fix_version, msg_type, Tag};
+
+    fn config() -> SessionConfig {
+        SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE")
+    }
+
+    fn session(second: u8) -> Session {
+        Session::new(config()).with_clock(Clock::fixed(UtcTimestamp::new(2024, 1, 1, 12, 0, second).unwrap()))
+    }
+
+    fn order(id: u64) -> FixMessage {
+        let mut msg = config().new_message(msg_type::NEW_ORDER_SINGLE);
+        msg.add_field(FixField::from_value(Tag::ClOrdID.value(), &id));
+        msg
+    }
+
+    fn inbound(seq: u64) -> FixMessage {
+        let mut msg = SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT").new_message(msg_type::HEARTBEAT);
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg
+    }
+
+    fn seq_of(msg: &FixMessage) -> u64 {
+        msg.msg_seq_num().unwrap().unwrap().get()
+    }
+
+    #[test]
+    fn test_failover_mid_session() {
+        let mut store = MemoryStore::new();
+        let mut primary = session(0);
+        for seq in 1..=10 {
+            // Seq 9 is a Heartbeat, which is gap-filled rather than resent
+            let mut msg = if seq == 9 { config().heartbeat(None) } else { order(seq) };
+            let encoded = primary.prepare_outbound(&mut msg, &mut store).unwrap();
+            assert!(encoded.starts_with(b"8=FIX.4.4\x01"));
+        }
+        primary.process_inbound(&inbound(1)).unwrap();
+        assert!(matches!(primary.process_inbound(&inbound(4)).unwrap(), SessionEvent::SequenceGap { .. }));
+
+        let snapshot = primary.export_state();
+        assert_eq!((snapshot.next_outbound, snapshot.next_inbound), (11, 2));
+        assert_eq!(snapshot.pending_resends, [(2, 3)]);
+        #[cfg(feature = "serde")]
+        let snapshot: SessionStateSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        let mut standby = session(30);
+        standby.restore(&snapshot, &mut store).unwrap();
+        assert!(!standby.is_logged_on());
+        assert_eq!(standby.next_inbound_seq().get(), 2);
+        assert_eq!(standby.pending_resends(), [(SeqNum::new(2).unwrap(), SeqNum::new(3).unwrap())]);
+
+        let mut next = order(11);
+        standby.prepare_outbound(&mut next, &mut store).unwrap();
+        assert_eq!(seq_of(&next), 11);
+
+        let request = ResendRequest::new(SeqNum::new(8).unwrap(), EndSeqNo::Bounded(SeqNum::new(10).unwrap()));
+        let resent = standby.resend(&request, &store).unwrap();
+        assert_eq!(resent.iter().map(seq_of).collect::<Vec<_>>(), [8, 9, 10]);
+        assert_eq!(resent[0].get_uint(Tag::ClOrdID.value()).unwrap(), Some(8));
+        assert_eq!(resent[0].get_bool(Tag::PossDupFlag.value()).unwrap(), Some(true));
+        assert_eq!(resent[0].get_field(122).unwrap().value(), b"20240101-12:00:00.000000");
+        assert_eq!(
+            resent[0].get_utc_timestamp(Tag::SendingTime.value()).unwrap(),
+            Some(UtcTimestamp::new(2024, 1, 1, 12, 0, 30).unwrap().with_nanos(0, 6).unwrap())
+        );
+        assert_eq!(resent[1].get_field(Tag::MsgType.value()).unwrap().value(), msg_type::SEQUENCE_RESET);
+        assert_eq!(resent[1].get_uint(Tag::NewSeqNo.value()).unwrap(), Some(10));
+        assert_eq!(resent[2].get_uint(Tag::ClOrdID.value()).unwrap(), Some(10));
+    }
+
+    #[test]
+    fn test_restore_from_stale_store_and_snapshot() {
+        let mut shared = MemoryStore::new();
+        let mut local = MemoryStore::new();
+        let mut primary = session(0);
+        for seq in 1..=5 {
+            // The last two sends never reached the shared store
+            let store = if seq <= 3 { &mut shared } else { &mut local };
+            primary.prepare_outbound(&mut order(seq), store).unwrap();
+        }
+        let snapshot = primary.export_state();
+
+        let mut standby = session(0);
+        standby.restore(&snapshot, &mut shared).unwrap();
+        assert_eq!(shared.len(), 5);
+        assert_eq!(standby.next_outbound_seq().get(), 6);
+
+        // A snapshot older than the store never reuses a sequence number
+        primary.prepare_outbound(&mut order(6), &mut shared).unwrap();
+        let mut standby = session(0);
+        standby.restore(&snapshot, &mut shared).unwrap();
+        assert_eq!(standby.next_outbound_seq().get(), 7);
+
+        let mut empty = MemoryStore::new();
+        let mut no_tail = snapshot.clone();
+        no_tail.outbound_tail.clear();
+        assert!(matches!(session(0).restore(&no_tail, &mut empty), Err(FixError::InvalidSnapshot(_))));
+
+        let other = Session::new(SessionConfig::new(fix_version::FIX_4_2, "CLIENT", "VENUE")).export_state();
+        assert!(matches!(session(0).restore(&other, &mut empty), Err(FixError::InvalidSnapshot(_))));
+    }
+}
@@ -0,0 +1,623 @@
+use super::queue::nanos;
+use super::{Clock, MessageStore, SessionConfig, SessionEvent};
+use crate::datetime::UtcTimestamp;
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::messages::{require_msg_type, Logon, ResendRequest};
+use crate::metrics::{SessionCounters, SessionMetrics};
+use crate::seqnum::{EndSeqNo, SeqNum};
+use crate::tags::{msg_type, Tag};
+use bytes::{Bytes, BytesMut};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Number of most recently sent messages a session keeps in memory, so a
+/// state snapshot carries any the store may not have persisted yet
+pub const OUTBOUND_TAIL_LEN: usize = 64;
+
+/// Sans-io session state: classifies inbound messages and tracks sequence
+/// numbers. Reading and writing bytes is left to the caller.
+#[derive(Debug, Clone)]
+pub struct Session {
+    config: SessionConfig,
+    pub(super) next_inbound: SeqNum,
+    pub(super) next_outbound: SeqNum,
+    logged_on: bool,
+    clock: Clock,
+    counters: SessionCounters,
+    pub(super) last_sent: Option<UtcTimestamp>,
+    pub(super) last_received: Option<UtcTimestamp>,
+    // Inbound ranges missing since a SequenceGap, oldest first
+    pub(super) pending_resends: Vec<(SeqNum, SeqNum)>,
+    pub(super) outbound_tail: VecDeque<(SeqNum, Bytes)>,
+    // Requested by a Logon ahead of the expected MsgSeqNum, until sent
+    logon_resend: Option<ResendRequest>,
+}
+
+impl Session {
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            next_inbound: SeqNum::FIRST,
+            next_outbound: SeqNum::FIRST,
+            logged_on: false,
+            clock: Clock::system(),
+            counters: SessionCounters::default(),
+            last_sent: None,
+            last_received: None,
+            pending_resends: Vec::new(),
+            outbound_tail: VecDeque::new(),
+            logon_resend: None,
+        }
+    }
+
+    /// Replaces the system clock used for SendingTime checks
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[inline]
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// MsgSeqNum expected on the next inbound message
+    #[inline]
+    pub fn next_inbound_seq(&self) -> SeqNum {
+        self.next_inbound
+    }
+
+    /// MsgSeqNum `prepare_outbound` gives the next message sent
+    #[inline]
+    pub fn next_outbound_seq(&self) -> SeqNum {
+        self.next_outbound
+    }
+
+    /// Inbound ranges reported by a SequenceGap that have not arrived yet
+    #[inline]
+    pub fn pending_resends(&self) -> &[(SeqNum, SeqNum)] {
+        &self.pending_resends
+    }
+
+    /// Whether a Logon has been accepted by `accept_logon`
+    #[inline]
+    pub fn is_logged_on(&self) -> bool {
+        self.logged_on
+    }
+
+    /// Counts a message written by the transport
+    #[inline]
+    pub fn record_outbound(&mut self) {
+        self.counters.record_outbound();
+    }
+
+    /// Stamps the next MsgSeqNum and the current SendingTime on `msg`,
+    /// encodes it and saves it to `store` before it is written, so a
+    /// ResendRequest for it can always be answered
+    pub fn prepare_outbound(&mut self, msg: &mut FixMessage, store: &mut impl MessageStore) -> Result<BytesMut, FixError> {
+        let seq = self.next_outbound;
+        let now = self.clock.now();
+        msg.set_value(Tag::MsgSeqNum.value(), &seq);
+        msg.set_utc_timestamp(Tag::SendingTime.value(), now);
+        let encoded = msg.encode()?;
+        store.save(seq, &encoded).map_err(store_error)?;
+
+        if self.outbound_tail.len() == OUTBOUND_TAIL_LEN {
+            self.outbound_tail.pop_front();
+        }
+        self.outbound_tail.push_back((seq, Bytes::copy_from_slice(&encoded)));
+        self.next_outbound = seq.next().ok_or(FixError::InvalidFieldValue)?;
+        self.last_sent = Some(now);
+        Ok(encoded)
+    }
+
+    /// The messages answering `request`, read from `store`: application
+    /// messages again with PossDupFlag=Y and OrigSendingTime, while admin
+    /// messages and any missing from the store are covered by
+    /// SequenceReset-GapFill. An unbounded request ends at the last message
+    /// sent.
+    pub fn resend(&self, request: &ResendRequest, store: &impl MessageStore) -> Result<Vec<FixMessage>, FixError> {
+        let Some(last) = SeqNum::new(self.next_outbound.get() - 1) else {
+            return Ok(Vec::new());
+        };
+        let end = match request.end_seq_no {
+            EndSeqNo::Bounded(end) => end.min(last),
+            EndSeqNo::Unbounded => last,
+        };
+        let stored = store.range(request.begin_seq_no, EndSeqNo::Bounded(end)).map_err(store_error)?;
+
+        let mut resent = Vec::new();
+        // First sequence number of the gap being collected
+        let mut gap_start = None;
+        let mut next = request.begin_seq_no;
+        for (seq, raw) in stored {
+            let mut msg = FixMessage::decode(&raw)?;
+            let is_admin = msg.get_field(Tag::MsgType.value()).is_none_or(|f| msg_type::is_admin(f.value()));
+            if seq != next || is_admin {
+                gap_start.get_or_insert(next);
+            }
+            if is_admin {
+                next = seq.next().ok_or(FixError::InvalidFieldValue)?;
+                continue;
+            }
+            if let Some(start) = gap_start.take() {
+                resent.push(self.gap_fill(start, seq));
+            }
+            if let Some(original) = msg.get_field(Tag::SendingTime.value()).cloned() {
+                msg.set_field(FixField::new(Tag::OrigSendingTime.value(), original.value()));
+            }
+            msg.set_value(Tag::PossDupFlag.value(), &true);
+            msg.set_utc_timestamp(Tag::SendingTime.value(), self.clock.now());
+            resent.push(msg);
+            next = seq.next().ok_or(FixError::InvalidFieldValue)?;
+        }
+        if next <= end {
+            gap_start.get_or_insert(next);
+        }
+        if let Some(start) = gap_start {
+            resent.push(self.gap_fill(start, end.next().ok_or(FixError::InvalidFieldValue)?));
+        }
+        Ok(resent)
+    }
+
+    fn gap_fill(&self, seq: SeqNum, new_seq_no: SeqNum) -> FixMessage {
+        let mut msg = self.config.sequence_reset(new_seq_no, true);
+        msg.set_value(Tag::MsgSeqNum.value(), &seq);
+        msg.set_value(Tag::PossDupFlag.value(), &true);
+        msg.set_utc_timestamp(Tag::SendingTime.value(), self.clock.now());
+        msg
+    }
+
+    /// Acceptor side of the logon handshake. The inbound Logon must be
+    /// addressed from our TargetCompID to our SenderCompID, without
+    /// encryption and with a valid HeartBtInt; ResetSeqNumFlag=Y restarts
+    /// sequence numbers at 1 both ways, so our Logon goes out as MsgSeqNum 1
+    /// and the in-memory tail of sent messages is dropped. On success the
+    /// session is logged on and the returned message is our Logon, echoing
+    /// the counterparty's HeartBtInt and ResetSeqNumFlag. Otherwise it is a
+    /// Logout whose Text gives the reason. Only a message that is not a Logon is an error.
+    ///
+    /// A Logon whose SendingTime is further ahead than
+    /// `SessionConfig::reject_future_sending_time` allows is answered with a
+    /// Logout, as is one whose MsgSeqNum is too low.
+    ///
+    /// A Logon whose MsgSeqNum is ahead of the one expected is accepted, and
+    /// the missing messages are requested by the ResendRequest that
+    /// `take_logon_resend_request` then returns, to be sent after our Logon.
+    pub fn accept_logon(&mut self, inbound: &FixMessage) -> Result<FixMessage, FixError> {
+        require_msg_type(inbound, msg_type::LOGON)?;
+        if let Err(reason) = self.check_logon(inbound) {
+            return Ok(self.config.logout(Some(reason.as_bytes())));
+        }
+
+        // Validated by check_logon
+        let logon = Logon::from_message(inbound)?;
+        if logon.reset_seq_num_flag {
+            self.next_inbound = SeqNum::FIRST;
+            self.next_outbound = SeqNum::FIRST;
+            self.outbound_tail.clear();
+        }
+        match self.process_inbound(inbound)? {
+            SessionEvent::SequenceTooLow { expected, received } => {
+                let reason = format!("MsgSeqNum too low, expecting {} but received {}", expected, received);
+                return Ok(self.config.logout(Some(reason.as_bytes())));
+            }
+            SessionEvent::FutureTimestamp { .. } => {
+                return Ok(self.config.logout(Some(b"SendingTime accuracy problem")));
+            }
+            SessionEvent::SequenceGap { expected, .. } => {
+                self.logon_resend = Some(ResendRequest::new(expected, EndSeqNo::Unbounded));
+            }
+            _ => {}
+        }
+
+        self.logged_on = true;
+        let mut response = self.config.new_message(msg_type::LOGON);
+        Logon::new(logon.heart_bt_int)
+            .with_reset_seq_num_flag(logon.reset_seq_num_flag)
+            .write_body(&mut response);
+        Ok(response)
+    }
+
+    /// The ResendRequest for the gap found by the last `accept_logon`, if
+    /// any. It is returned once.
+    pub fn take_logon_resend_request(&mut self) -> Option<FixMessage> {
+        let request = self.logon_resend.take()?;
+        Some(self.config.resend_request(&request))
+    }
+
+    // The reason to reject a Logon, as sent in the Logout's Text
+    fn check_logon(&self, inbound: &FixMessage) -> Result<(), String> {
+        let comp_id = |tag: Tag| inbound.get_field(tag.value()).map(FixField::value);
+        if comp_id(Tag::SenderCompID) != Some(&self.config.target_comp_id[..]) {
+            return Err("Invalid SenderCompID".to_string());
+        }
+        if comp_id(Tag::TargetCompID) != Some(&self.config.sender_comp_id[..]) {
+            return Err("Invalid TargetCompID".to_string());
+        }
+
+        let logon = Logon::from_message(inbound).map_err(|err| format!("Invalid Logon: {}", err))?;
+        if logon.encrypt_method != 0 {
+            return Err(format!("Unsupported EncryptMethod {}", logon.encrypt_method));
+        }
+        if inbound.msg_seq_num().ok().flatten().is_none() {
+            return Err("Invalid Logon: missing or invalid MsgSeqNum".to_string());
+        }
+        Ok(())
+    }
+
+    // FutureTimestamp when SendingTime is ahead of the clock by more than
+    // the configured tolerance
+    fn check_sending_time(&self, msg: &FixMessage) -> Result<Option<SessionEvent>, FixError> {
+        let Some(tolerance) = self.config.reject_future_sending_time else {
+            return Ok(None);
+        };
+        let Some(sending_time) = msg.get_utc_timestamp(Tag::SendingTime.value())? else {
+            return Ok(None);
+        };
+        let now = self.clock.now_nanos();
+        if sending_time.unix_nanos().saturating_sub(now) > nanos(tolerance) {
+            return Ok(Some(SessionEvent::FutureTimestamp { sending_time, now: self.clock.now() }));
+        }
+        Ok(None)
+    }
+
+    /// Classifies an inbound message, advancing the expected sequence number
+    /// when it is the next in order
+    pub fn process_inbound(&mut self, msg: &FixMessage) -> Result<SessionEvent, FixError> {
+        let event = self.classify_inbound(msg)?;
+        self.counters.record_inbound(&event);
+        self.last_received = Some(self.clock.now());
+        self.track_resends(&event);
+        Ok(event)
+    }
+
+    fn track_resends(&mut self, event: &SessionEvent) {
+        if let SessionEvent::SequenceGap { expected, received } = *event {
+            // `received` is at least one ahead of `expected`
+            let last = SeqNum::new(received.get() - 1).expect("a gap ends after the first message");
+            match self.pending_resends.last_mut() {
+                Some(pending) if pending.0 <= expected => pending.1 = pending.1.max(last),
+                _ => self.pending_resends.push((expected, last)),
+            }
+        }
+        let next = self.next_inbound;
+        self.pending_resends.retain(|&(_, last)| last >= next);
+        if let Some(first) = self.pending_resends.first_mut() {
+            first.0 = first.0.max(next);
+        }
+    }
+
+    fn classify_inbound(&mut self, msg: &FixMessage) -> Result<SessionEvent, FixError> {
+        let received = msg
+            .msg_seq_num()?
+            .ok_or(FixError::MissingField(Tag::MsgSeqNum.value()))?;
+        let expected = self.next_inbound;
+
+        match received.cmp(&expected) {
+            Ordering::Less if msg.poss_dup()? => return Ok(SessionEvent::Duplicate(received)),
+            Ordering::Less => return Ok(SessionEvent::SequenceTooLow { expected, received }),
+            Ordering::Greater => return Ok(SessionEvent::SequenceGap { expected, received }),
+            Ordering::Equal => {}
+        }
+
+        let msg_type = msg
+            .get_field(Tag::MsgType.value())
+            .ok_or(FixError::MissingField(Tag::MsgType.value()))?
+            .value();
+        if let Some(event) = self.check_sending_time(msg)? {
+            self.next_inbound = expected.next().ok_or(FixError::InvalidFieldValue)?;
+            return Ok(event);
+        }
+        let event = if msg_type::is_admin(msg_type) {
+            SessionEvent::Admin
+        } else if msg.poss_resend()? {
+            SessionEvent::PossibleResend
+        } else {
+            SessionEvent::Application
+        };
+
+        self.next_inbound = expected.next().ok_or(FixError::InvalidFieldValue)?;
+        Ok(event)
+    }
+}
+
+pub(super) fn store_error(err: std::io::Error) -> FixError {
+    FixError::Store(err.to_string())
+}
+
+impl SessionMetrics for Session {
+    fn session_id(&self) -> String {
+        self.config.session_id()
+    }
+
+    fn counters(&self) -> SessionCounters {
+        self.counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::UtcTimestamp;
+    use crate::metrics::MetricsRegistry;
+    use crate::session::MemoryStore;
+    use crate::tags::fix_version;
+    use std::time::Duration;
+
+    fn session() -> Session {
+        Session::new(SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"))
+    }
+
+    fn inbound(msg_type: &[u8], seq: u64, flags: &[(Tag, bool)]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type.to_vec()));
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        for (tag, value) in flags {
+            msg.add_field(FixField::from_value(tag.value(), value));
+        }
+        msg
+    }
+
+    #[test]
+    fn test_poss_resend_versus_poss_dup() {
+        let mut session = session();
+        let order = msg_type::NEW_ORDER_SINGLE;
+
+        assert_eq!(session.process_inbound(&inbound(msg_type::LOGON, 1, &[])).unwrap(), SessionEvent::Admin);
+        assert_eq!(session.process_inbound(&inbound(order, 2, &[])).unwrap(), SessionEvent::Application);
+
+        // New sequence number, possibly old business content
+        let resent = inbound(order, 3, &[(Tag::PossResend, true)]);
+        assert!(resent.poss_resend().unwrap());
+        assert_eq!(session.process_inbound(&resent).unwrap(), SessionEvent::PossibleResend);
+
+        // Old sequence number replayed by the session layer
+        let duplicate = inbound(order, 2, &[(Tag::PossDupFlag, true)]);
+        assert_eq!(
+            session.process_inbound(&duplicate).unwrap(),
+            SessionEvent::Duplicate(SeqNum::new(2).unwrap())
+        );
+        assert_eq!(session.next_inbound_seq().get(), 4);
+
+        let explicit_no = inbound(order, 4, &[(Tag::PossResend, false)]);
+        assert_eq!(session.process_inbound(&explicit_no).unwrap(), SessionEvent::Application);
+    }
+
+    #[test]
+    fn test_sequence_errors() {
+        let mut session = session();
+        session.process_inbound(&inbound(msg_type::LOGON, 1, &[])).unwrap();
+
+        let expected = SeqNum::new(2).unwrap();
+        assert_eq!(
+            session.process_inbound(&inbound(msg_type::HEARTBEAT, 5, &[])).unwrap(),
+            SessionEvent::SequenceGap { expected, received: SeqNum::new(5).unwrap() }
+        );
+        assert_eq!(
+            session.process_inbound(&inbound(msg_type::HEARTBEAT, 1, &[])).unwrap(),
+            SessionEvent::SequenceTooLow { expected, received: SeqNum::FIRST }
+        );
+        assert_eq!(session.next_inbound_seq(), expected);
+
+        let mut no_seq = FixMessage::new();
+        no_seq.add_field(FixField::new(Tag::MsgType.value(), msg_type::HEARTBEAT.to_vec()));
+        assert!(matches!(session.process_inbound(&no_seq), Err(FixError::MissingField(34))));
+    }
+
+    fn logon_from(sender: &[u8], target: &[u8], seq: u64, body: &[(Tag, &[u8])]) -> FixMessage {
+        let config = SessionConfig::new(fix_version::FIX_4_4, sender, target);
+        let mut msg = config.new_message(msg_type::LOGON);
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        for (tag, value) in body {
+            msg.add_field(FixField::new(tag.value(), value.to_vec()));
+        }
+        msg
+    }
+
+    fn text(msg: &FixMessage) -> &str {
+        std::str::from_utf8(msg.get_field(Tag::Text.value()).unwrap().value()).unwrap()
+    }
+
+    #[test]
+    fn test_accept_logon() {
+        let mut acceptor = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+        let body: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"45")];
+
+        let response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, body)).unwrap();
+        assert_eq!(response.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGON);
+        assert_eq!(response.get_field(Tag::SenderCompID.value()).unwrap().value(), b"VENUE");
+        assert_eq!(response.get_uint(Tag::HeartBtInt.value()).unwrap(), Some(45));
+        assert!(acceptor.is_logged_on());
+        assert_eq!(acceptor.next_inbound_seq().get(), 2);
+
+        // A reconnect resetting sequence numbers
+        let reset: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30"), (Tag::ResetSeqNumFlag, b"Y")];
+        let response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, reset)).unwrap();
+        assert_eq!(response.get_bool(Tag::ResetSeqNumFlag.value()).unwrap(), Some(true));
+        assert_eq!(acceptor.next_inbound_seq().get(), 2);
+        assert!(acceptor.take_logon_resend_request().is_none());
+    }
+
+    #[test]
+    fn test_accept_logon_reset_restarts_outbound() {
+        let mut acceptor = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+        let mut store = MemoryStore::new();
+        let body: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30")];
+        let mut response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, body)).unwrap();
+        acceptor.prepare_outbound(&mut response, &mut store).unwrap();
+        for _ in 0..2 {
+            acceptor.prepare_outbound(&mut acceptor.config().heartbeat(None), &mut store).unwrap();
+        }
+        assert_eq!(acceptor.next_outbound_seq().get(), 4);
+
+        let reset: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30"), (Tag::ResetSeqNumFlag, b"Y")];
+        let mut response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, reset)).unwrap();
+        assert_eq!(acceptor.next_outbound_seq(), SeqNum::FIRST);
+        assert!(acceptor.outbound_tail.is_empty());
+        let encoded = acceptor.prepare_outbound(&mut response, &mut store).unwrap();
+        let sent = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(sent.get_bool(Tag::ResetSeqNumFlag.value()).unwrap(), Some(true));
+        assert_eq!(sent.msg_seq_num().unwrap(), Some(SeqNum::FIRST));
+        assert_eq!(acceptor.next_outbound_seq().get(), 2);
+    }
+
+    #[test]
+    fn test_accept_logon_with_gap() {
+        let mut acceptor = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+        let body: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30")];
+
+        let response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 5, body)).unwrap();
+        assert_eq!(response.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGON);
+        assert!(acceptor.is_logged_on());
+        assert_eq!(acceptor.next_inbound_seq(), SeqNum::FIRST);
+        assert_eq!(acceptor.pending_resends(), [(SeqNum::FIRST, SeqNum::new(4).unwrap())]);
+
+        let request = acceptor.take_logon_resend_request().unwrap();
+        assert_eq!(request.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::RESEND_REQUEST);
+        assert_eq!(request.get_field(Tag::TargetCompID.value()).unwrap().value(), b"CLIENT");
+        assert_eq!(
+            ResendRequest::from_message(&request).unwrap(),
+            ResendRequest::new(SeqNum::FIRST, EndSeqNo::Unbounded)
+        );
+        assert!(acceptor.take_logon_resend_request().is_none());
+    }
+
+    #[test]
+    fn test_reject_logon() {
+        let valid: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30")];
+        let cases: [(FixMessage, &str); 6] = [
+            (logon_from(b"OTHER", b"VENUE", 1, valid), "Invalid SenderCompID"),
+            (logon_from(b"CLIENT", b"OTHER", 1, valid), "Invalid TargetCompID"),
+            (
+                logon_from(b"CLIENT", b"VENUE", 1, &[(Tag::EncryptMethod, b"2"), (Tag::HeartBtInt, b"30")]),
+                "Unsupported EncryptMethod 2",
+            ),
+            (
+                logon_from(b"CLIENT", b"VENUE", 1, &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"-1")]),
+                "Invalid Logon: Value -1 of tag 108",
+            ),
+            (
+                logon_from(b"CLIENT", b"VENUE", 1, &[(Tag::EncryptMethod, b"0")]),
+                "Invalid Logon: Missing required field: 108",
+            ),
+            (
+                {
+                    let mut msg = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").logon();
+                    msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"0".to_vec()));
+                    msg
+                },
+                "Invalid Logon: missing or invalid MsgSeqNum",
+            ),
+        ];
+
+        for (logon, reason) in &cases {
+            let mut acceptor = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+            let response = acceptor.accept_logon(logon).unwrap();
+            assert_eq!(response.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGOUT);
+            assert!(text(&response).starts_with(reason), "{:?} vs {:?}", text(&response), reason);
+            assert!(!acceptor.is_logged_on());
+            assert_eq!(acceptor.next_inbound_seq(), SeqNum::FIRST);
+        }
+
+        let mut acceptor = Session::new(SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT"));
+        acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, valid)).unwrap();
+        let response = acceptor.accept_logon(&logon_from(b"CLIENT", b"VENUE", 1, valid)).unwrap();
+        assert_eq!(text(&response), "MsgSeqNum too low, expecting 2 but received 1");
+
+        let heartbeat = inbound(msg_type::HEARTBEAT, 1, &[]);
+        assert!(matches!(acceptor.accept_logon(&heartbeat), Err(FixError::UnexpectedMsgType(_))));
+    }
+
+    #[test]
+    fn test_future_sending_time() {
+        let now = UtcTimestamp::new(2024, 1, 1, 12, 0, 0).unwrap();
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE")
+            .with_reject_future_sending_time(Duration::from_secs(2));
+        let mut session = Session::new(config).with_clock(Clock::fixed(now));
+
+        let stamped = |seq: u64, second: u8| {
+            let mut msg = inbound(msg_type::NEW_ORDER_SINGLE, seq, &[]);
+            msg.set_utc_timestamp(Tag::SendingTime.value(), UtcTimestamp::new(2024, 1, 1, 12, 0, second).unwrap());
+            msg
+        };
+
+        // Within tolerance, and any amount in the past, is fine
+        assert_eq!(session.process_inbound(&stamped(1, 2)).unwrap(), SessionEvent::Application);
+        assert_eq!(session.process_inbound(&inbound(msg_type::HEARTBEAT, 2, &[])).unwrap(), SessionEvent::Admin);
+
+        let sending_time = UtcTimestamp::new(2024, 1, 1, 12, 0, 3).unwrap();
+        assert_eq!(
+            session.process_inbound(&stamped(3, 3)).unwrap(),
+            SessionEvent::FutureTimestamp { sending_time, now }
+        );
+        assert_eq!(session.next_inbound_seq().get(), 4);
+
+        // Sequence problems are reported first
+        assert!(matches!(
+            session.process_inbound(&stamped(9, 30)).unwrap(),
+            SessionEvent::SequenceGap { .. }
+        ));
+
+        let mut unchecked = Session::new(SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE"))
+            .with_clock(Clock::fixed(now));
+        assert_eq!(unchecked.process_inbound(&stamped(1, 59)).unwrap(), SessionEvent::Application);
+
+        // A tolerance past i64 nanoseconds accepts everything
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE")
+            .with_reject_future_sending_time(Duration::MAX);
+        let mut lenient = Session::new(config).with_clock(Clock::fixed(now));
+        assert_eq!(lenient.process_inbound(&stamped(1, 59)).unwrap(), SessionEvent::Application);
+    }
+
+    #[test]
+    fn test_logon_with_future_sending_time() {
+        let now = UtcTimestamp::new(2024, 1, 1, 12, 0, 0).unwrap();
+        let config = SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT")
+            .with_reject_future_sending_time(Duration::from_secs(2));
+        let mut acceptor = Session::new(config).with_clock(Clock::fixed(now));
+        let body: &[(Tag, &[u8])] = &[(Tag::EncryptMethod, b"0"), (Tag::HeartBtInt, b"30")];
+
+        let mut logon = logon_from(b"CLIENT", b"VENUE", 1, body);
+        logon.set_utc_timestamp(Tag::SendingTime.value(), UtcTimestamp::new(2024, 1, 1, 12, 0, 5).unwrap());
+        let response = acceptor.accept_logon(&logon).unwrap();
+        assert_eq!(response.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGOUT);
+        assert_eq!(text(&response), "SendingTime accuracy problem");
+        assert!(!acceptor.is_logged_on());
+
+        logon.set_utc_timestamp(Tag::SendingTime.value(), now);
+        logon.set_value(Tag::MsgSeqNum.value(), &2u64);
+        let response = acceptor.accept_logon(&logon).unwrap();
+        assert_eq!(response.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGON);
+        assert!(acceptor.is_logged_on());
+    }
+
+    #[test]
+    fn test_qualified_sessions_are_independent() {
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let mut orders = Session::new(config.clone().with_session_qualifier("ORDERS"));
+        let mut drop_copy = Session::new(config.with_session_qualifier("DROPCOPY"));
+        let (mut orders_store, mut drop_copy_store) = (MemoryStore::new(), MemoryStore::new());
+
+        for seq in 1..=3 {
+            orders.process_inbound(&inbound(msg_type::HEARTBEAT, seq, &[])).unwrap();
+            orders.prepare_outbound(&mut orders.config().heartbeat(None), &mut orders_store).unwrap();
+        }
+        drop_copy.process_inbound(&inbound(msg_type::HEARTBEAT, 1, &[])).unwrap();
+        drop_copy.prepare_outbound(&mut drop_copy.config().heartbeat(None), &mut drop_copy_store).unwrap();
+
+        assert_eq!((orders.next_inbound_seq().get(), orders.next_outbound_seq().get()), (4, 4));
+        assert_eq!((drop_copy.next_inbound_seq().get(), drop_copy.next_outbound_seq().get()), (2, 2));
+        assert_eq!(orders.session_id(), "FIX.4.4:CLIENT->VENUE:ORDERS");
+        assert_eq!(drop_copy.session_id(), "FIX.4.4:CLIENT->VENUE:DROPCOPY");
+
+        let mut registry = MetricsRegistry::new();
+        registry.record(&orders);
+        registry.record(&drop_copy);
+        assert_eq!(registry.sessions().len(), 2);
+        assert_eq!(registry.sessions()[0].counters.received(), 3);
+        assert_eq!(registry.sessions()[1].counters.received(), 1);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,149 @@
+use crate::hash::HASH_EXCLUDED_TAGS;
+use crate::message::FixMessage;
+use crate::seqnum::{EndSeqNo, SeqNum};
+use crate::tags::Tag;
+use bytes::Bytes;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Encoded outbound messages of one session keyed by MsgSeqNum, kept to
+/// answer ResendRequests. A store shared by an active gateway and its
+/// standby lets the standby resend what the active one sent.
+pub trait MessageStore {
+    /// Saves an encoded message, replacing any stored under `seq`
+    fn save(&mut self, seq: SeqNum, raw: &[u8]) -> io::Result<()>;
+
+    /// Stored messages from `begin` through `end` in sequence order.
+    /// Sequence numbers never saved are simply absent.
+    fn range(&self, begin: SeqNum, end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>>;
+
+    /// The highest sequence number stored
+    fn last_seq(&self) -> io::Result<Option<SeqNum>>;
+
+    /// The message stored under `seq`, if any
+    fn get(&self, seq: SeqNum) -> io::Result<Option<Bytes>> {
+        Ok(self.range(seq, EndSeqNo::Bounded(seq))?.pop().map(|(_, raw)| raw))
+    }
+
+    /// Merges messages the counterparty resent into the store, in sequence
+    /// order whatever order they are given in. Sequence numbers not yet
+    /// stored are saved; stored ones are kept, and the resent copy compared
+    /// with them on `canonical_hash` without the fields a resend changes.
+    /// Copies that differ are reported rather than replacing what is
+    /// stored.
+    fn merge_resent(&mut self, messages: impl IntoIterator<Item = (SeqNum, Bytes)>) -> io::Result<MergeReport>
+    where
+        Self: Sized,
+    {
+        let mut messages: Vec<(SeqNum, Bytes)> = messages.into_iter().collect();
+        messages.sort_by_key(|(seq, _)| *seq);
+        let mut report = MergeReport::default();
+        for (seq, raw) in messages {
+            match self.get(seq)? {
+                None => {
+                    self.save(seq, &raw)?;
+                    report.inserted.push(seq);
+                }
+                Some(stored) if same_content(&stored, &raw) => report.duplicates.push(seq),
+                Some(_) => report.mismatched.push(seq),
+            }
+        }
+        Ok(report)
+    }
+}
+
+// Fields that differ between a message and its resent copy
+const RESEND_TAGS: [u32; 3] = [Tag::PossDupFlag.value(), Tag::PossResend.value(), Tag::OrigSendingTime.value()];
+
+// Compares as messages where both decode, and byte for byte otherwise
+fn same_content(stored: &[u8], resent: &[u8]) -> bool {
+    let exclude: Vec<u32> = HASH_EXCLUDED_TAGS.iter().chain(&RESEND_TAGS).copied().collect();
+    match (FixMessage::decode(stored), FixMessage::decode(resent)) {
+        (Ok(stored), Ok(resent)) => stored.canonical_hash(&exclude) == resent.canonical_hash(&exclude),
+        _ => stored == resent,
+    }
+}
+
+/// The outcome of `MessageStore::merge_resent`, each list in sequence order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Saved from the resend
+    pub inserted: Vec<SeqNum>,
+    /// Already stored with the same content
+    pub duplicates: Vec<SeqNum>,
+    /// Already stored with different content, which was kept
+    pub mismatched: Vec<SeqNum>,
+}
+
+impl MergeReport {
+    /// Whether every resent copy of a stored message matched it
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        self.mismatched.is_empty()
+    }
+}
+
+impl<S: MessageStore + ?Sized> MessageStore for &mut S {
+    fn save(&mut self, seq: SeqNum, raw: &[u8]) -> io::Result<()> {
+        (**self).save(seq, raw)
+    }
+
+    fn range(&self, begin: SeqNum, end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>> {
+        (**self).range(begin, end)
+    }
+
+    fn last_seq(&self) -> io::Result<Option<SeqNum>> {
+        (**self).last_seq()
+    }
+
+    fn get(&self, seq: SeqNum) -> io::Result<Option<Bytes>> {
+        (**self).get(seq)
+    }
+}
+
+/// A store held in memory, for tests and sessions that need not survive a
+/// restart
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    messages: BTreeMap<SeqNum, Bytes>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl MessageStore for MemoryStore {
+    fn save(&mut self, seq: SeqNum, raw: &[u8]) -> io::Result<()> {
+        self.messages.insert(seq, Bytes::copy_from_slice(raw));
+        Ok(())
+    }
+
+    fn range(&self, begin: SeqNum, end: EndSeqNo) -> io::Result<Vec<(SeqNum, Bytes)>> {
+        let stored = match end {
+            EndSeqNo::Bounded(end) if end < begin => return Ok(Vec::new()),
+            EndSeqNo::Bounded(end) => self.messages.range(begin..=end),
+            EndSeqNo::Unbounded => self.messages.range(begin..),
+        };
+        Ok(stored.map(|(&seq, raw)| (seq, raw.clone())).collect())
+    }
+
+    fn last_seq(&self) -> io::Result<Option<SeqNum>> {
+        Ok(self.messages.keys().next_back().copied())
+    }
+
+    fn get(&self, seq: SeqNum) -> io::Result<Option<Bytes>> {
+        Ok(self.messages.get(&seq).cloned())
+    }
+}
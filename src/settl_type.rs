@@ -0,0 +1,169 @@
+use crate::error::FixError;
+use crate::field::{impl_text_traits, parse_uint, FieldValue};
+use smallvec::SmallVec;
+
+/// The coded values of SettlType (63)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettlCode {
+    Regular,
+    Cash,
+    NextDay,
+    TPlus2,
+    TPlus3,
+    TPlus4,
+    Future,
+    WhenAndIfIssued,
+    SellersOption,
+    TPlus5,
+    BrokenDate,
+    FxSpotNextSettlement,
+}
+
+impl SettlCode {
+    const ALL: [SettlCode; 12] = [
+        SettlCode::Regular,
+        SettlCode::Cash,
+        SettlCode::NextDay,
+        SettlCode::TPlus2,
+        SettlCode::TPlus3,
+        SettlCode::TPlus4,
+        SettlCode::Future,
+        SettlCode::WhenAndIfIssued,
+        SettlCode::SellersOption,
+        SettlCode::TPlus5,
+        SettlCode::BrokenDate,
+        SettlCode::FxSpotNextSettlement,
+    ];
+
+    /// The wire value, '0'-'9', 'B' or 'C'
+    #[inline]
+    pub fn as_byte(self) -> u8 {
+        match self {
+            SettlCode::BrokenDate => b'B',
+            SettlCode::FxSpotNextSettlement => b'C',
+            code => b'0' + code as u8,
+        }
+    }
+
+    #[inline]
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|code| code.as_byte() == byte)
+    }
+}
+
+/// Unit of a settlement tenor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TenorUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TenorUnit {
+    /// The wire prefix, 'D', 'W', 'M' or 'Y'
+    #[inline]
+    pub fn as_byte(self) -> u8 {
+        match self {
+            TenorUnit::Day => b'D',
+            TenorUnit::Week => b'W',
+            TenorUnit::Month => b'M',
+            TenorUnit::Year => b'Y',
+        }
+    }
+
+    #[inline]
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'D' => Some(TenorUnit::Day),
+            b'W' => Some(TenorUnit::Week),
+            b'M' => Some(TenorUnit::Month),
+            b'Y' => Some(TenorUnit::Year),
+            _ => None,
+        }
+    }
+}
+
+/// SettlType (63): either a single-character code or, in FX workflows, a
+/// tenor such as "M3" (three months) or "D7". A tenor count is positive and
+/// written without leading zeros, so "M03" and "M0" are rejected and every
+/// accepted value formats back to the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettlType {
+    Coded(SettlCode),
+    Tenor { unit: TenorUnit, count: u32 },
+}
+
+impl SettlType {
+    /// A tenor of `count` units, which must be positive
+    pub fn tenor(unit: TenorUnit, count: u32) -> Result<Self, FixError> {
+        if count == 0 {
+            return Err(FixError::InvalidFieldValue);
+        }
+        Ok(SettlType::Tenor { unit, count })
+    }
+}
+
+impl From<SettlCode> for SettlType {
+    fn from(code: SettlCode) -> Self {
+        SettlType::Coded(code)
+    }
+}
+
+impl FieldValue for SettlType {
+    fn parse(value: &[u8]) -> Result<Self, FixError> {
+        match value {
+            [code] => SettlCode::from_byte(*code).map(SettlType::Coded).ok_or(FixError::InvalidFieldValue),
+            [unit, digits @ ..] => {
+                let unit = TenorUnit::from_byte(*unit).ok_or(FixError::InvalidFieldValue)?;
+                if digits.first() == Some(&b'0') {
+                    return Err(FixError::InvalidFieldValue);
+                }
+                let count = u32::try_from(parse_uint(digits)?).map_err(|_| FixError::InvalidFieldValue)?;
+                SettlType::tenor(unit, count)
+            }
+            [] => Err(FixError::InvalidFieldValue),
+        }
+    }
+
+    fn write(&self, buf: &mut SmallVec<[u8; 32]>) {
+        match *self {
+            SettlType::Coded(code) => buf.push(code.as_byte()),
+            SettlType::Tenor { unit, count } => {
+                buf.push(unit.as_byte());
+                buf.extend_from_slice(itoa::Buffer::new().format(count).as_bytes());
+            }
+        }
+    }
+}
+
+impl_text_traits!(SettlType);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settl_type_forms() {
+        assert_eq!("0".parse::<SettlType>().unwrap(), SettlType::Coded(SettlCode::Regular));
+        assert_eq!("9".parse::<SettlType>().unwrap(), SettlType::Coded(SettlCode::TPlus5));
+        assert_eq!("B".parse::<SettlType>().unwrap(), SettlType::Coded(SettlCode::BrokenDate));
+        assert_eq!("C".parse::<SettlType>().unwrap(), SettlType::Coded(SettlCode::FxSpotNextSettlement));
+        assert_eq!("M3".parse::<SettlType>().unwrap(), SettlType::Tenor { unit: TenorUnit::Month, count: 3 });
+        assert_eq!("W2".parse::<SettlType>().unwrap(), SettlType::Tenor { unit: TenorUnit::Week, count: 2 });
+        assert_eq!("D7".parse::<SettlType>().unwrap(), SettlType::Tenor { unit: TenorUnit::Day, count: 7 });
+
+        for value in ["0", "4", "9", "B", "C", "M3", "W2", "D7", "Y1", "M12", "D365"] {
+            assert_eq!(value.parse::<SettlType>().unwrap().to_string(), value);
+        }
+    }
+
+    #[test]
+    fn test_settl_type_rejects_malformed() {
+        for value in ["", "A", "D", "M0", "M03", "m3", "3M", "M-3", "M 3", "W99999999999", "BB", "10"] {
+            assert!(value.parse::<SettlType>().is_err(), "{} should be rejected", value);
+        }
+        assert!(SettlType::tenor(TenorUnit::Month, 0).is_err());
+        assert_eq!(SettlType::tenor(TenorUnit::Year, 1).unwrap().to_string(), "Y1");
+    }
+}
@@ -1,42 +1,238 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Tag {
-    BeginString = 8,
-    BodyLength = 9,
-    CheckSum = 10,
-    MsgType = 35,
-    MsgSeqNum = 34,
-    SenderCompID = 49,
-    TargetCompID = 56,
-    SendingTime = 52,
-    // Add other tags as needed
-}
-
-impl Tag {
-    pub const fn value(&self) -> u32 {
-        *self as u32
-    }
-}
-
-// Message type values
-pub mod msg_type {
-    pub const HEARTBEAT: &[u8] = b"0";
-    pub const TEST_REQUEST: &[u8] = b"1";
-    pub const RESEND_REQUEST: &[u8] = b"2";
-    pub const REJECT: &[u8] = b"3";
-    pub const SEQUENCE_RESET: &[u8] = b"4";
-    pub const LOGOUT: &[u8] = b"5";
-    pub const LOGON: &[u8] = b"A";
-    pub const NEW_ORDER_SINGLE: &[u8] = b"D";
-    pub const EXECUTION_REPORT: &[u8] = b"8";
-    // Add other message types as needed
-}
-
-// FIX versions
-pub mod fix_version {
-    pub const FIX_4_0: &[u8] = b"FIX.4.0";
-    pub const FIX_4_1: &[u8] = b"FIX.4.1";
-    pub const FIX_4_2: &[u8] = b"FIX.4.2";
-    pub const FIX_4_3: &[u8] = b"FIX.4.3";
-    pub const FIX_4_4: &[u8] = b"FIX.4.4";
-    pub const FIX_5_0: &[u8] = b"FIX.5.0";
+use smallvec::SmallVec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tag {
+    Account = 1,
+    AvgPx = 6,
+    BeginSeqNo = 7,
+    BeginString = 8,
+    BodyLength = 9,
+    CheckSum = 10,
+    ClOrdID = 11,
+    CumQty = 14,
+    Currency = 15,
+    EndSeqNo = 16,
+    ExecID = 17,
+    LastPx = 31,
+    LastQty = 32,
+    MsgType = 35,
+    NewSeqNo = 36,
+    OrderID = 37,
+    OrderQty = 38,
+    OrdStatus = 39,
+    OrdType = 40,
+    OrigClOrdID = 41,
+    MsgSeqNum = 34,
+    PossDupFlag = 43,
+    Price = 44,
+    SenderCompID = 49,
+    TargetCompID = 56,
+    SendingTime = 52,
+    RefSeqNum = 45,
+    Quantity = 53,
+    Side = 54,
+    Symbol = 55,
+    Text = 58,
+    TransactTime = 60,
+    SettlType = 63,
+    SettlDate = 64,
+    AllocID = 70,
+    AllocTransType = 71,
+    NoOrders = 73,
+    TradeDate = 75,
+    NoAllocs = 78,
+    AllocAccount = 79,
+    AllocQty = 80,
+    PossResend = 97,
+    EncryptMethod = 98,
+    CxlRejReason = 102,
+    HeartBtInt = 108,
+    TestReqID = 112,
+    LocateReqd = 114,
+    QuoteID = 117,
+    OrigSendingTime = 122,
+    GapFillFlag = 123,
+    ResetSeqNumFlag = 141,
+    ExecType = 150,
+    LeavesQty = 151,
+    MaturityMonthYear = 200,
+    SecurityExchange = 207,
+    MDEntryTime = 273,
+    CxlRejResponseTo = 434,
+    MessageEncoding = 347,
+    EncodedTextLen = 354,
+    EncodedText = 355,
+    SessionRejectReason = 373,
+    CountryOfIssue = 470,
+    NoSides = 552,
+    AllocType = 626,
+    TradeReportID = 571,
+    StrikeCurrency = 947,
+    ApplVerID = 1128,
+    TZTransactTime = 1132,
+    // Add other tags as needed
+}
+
+impl Tag {
+    pub const fn value(&self) -> u32 {
+        *self as u32
+    }
+}
+
+/// Tags belonging to the standard header, including the NoHops group
+pub const HEADER_TAGS: &[u32] = &[
+    8, 9, 35, 49, 56, 115, 128, 90, 91, 34, 50, 142, 57, 143, 116, 144, 129, 145, 43, 97, 52, 122,
+    212, 213, 347, 369, 627, 628, 629, 630, 1128, 1129,
+];
+
+/// Tags belonging to the standard trailer
+pub const TRAILER_TAGS: &[u32] = &[93, 89, 10];
+
+/// Length and value tags of the data fields whose values may contain SOH.
+/// The length field always immediately precedes its value field.
+pub const DATA_FIELDS: &[(u32, u32)] = &[
+    (90, 91),
+    (93, 89),
+    (95, 96),
+    (212, 213),
+    (348, 349),
+    (350, 351),
+    (352, 353),
+    (354, 355),
+    (356, 357),
+    (358, 359),
+    (360, 361),
+    (362, 363),
+    (364, 365),
+];
+
+/// The data field whose byte length `tag` declares, if `tag` is a length
+#[inline]
+pub fn data_field_for(tag: u32) -> Option<u32> {
+    DATA_FIELDS.iter().find(|&&(len, _)| len == tag).map(|&(_, data)| data)
+}
+
+#[inline]
+pub fn is_header_tag(tag: u32) -> bool {
+    HEADER_TAGS.contains(&tag)
+}
+
+#[inline]
+pub fn is_trailer_tag(tag: u32) -> bool {
+    TRAILER_TAGS.contains(&tag)
+}
+
+// Message type values
+pub mod msg_type {
+    pub const HEARTBEAT: &[u8] = b"0";
+    pub const TEST_REQUEST: &[u8] = b"1";
+    pub const RESEND_REQUEST: &[u8] = b"2";
+    pub const REJECT: &[u8] = b"3";
+    pub const SEQUENCE_RESET: &[u8] = b"4";
+    pub const LOGOUT: &[u8] = b"5";
+    pub const LOGON: &[u8] = b"A";
+    pub const NEW_ORDER_SINGLE: &[u8] = b"D";
+    pub const EXECUTION_REPORT: &[u8] = b"8";
+    pub const ORDER_CANCEL_REQUEST: &[u8] = b"F";
+    pub const ORDER_CANCEL_REJECT: &[u8] = b"9";
+    pub const TRADE_CAPTURE_REPORT: &[u8] = b"AE";
+    pub const ALLOCATION_INSTRUCTION: &[u8] = b"J";
+    // Add other message types as needed
+
+    /// Whether `msg_type` is a session-level (administrative) message
+    #[inline]
+    pub fn is_admin(msg_type: &[u8]) -> bool {
+        [HEARTBEAT, TEST_REQUEST, RESEND_REQUEST, REJECT, SEQUENCE_RESET, LOGOUT, LOGON].contains(&msg_type)
+    }
+}
+
+/// A MsgType (35) value, for matching on message types rather than on
+/// their bytes. Types without a variant of their own are kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MsgType {
+    Heartbeat,
+    TestRequest,
+    ResendRequest,
+    Reject,
+    SequenceReset,
+    Logout,
+    Logon,
+    NewOrderSingle,
+    ExecutionReport,
+    OrderCancelRequest,
+    OrderCancelReject,
+    TradeCaptureReport,
+    /// Any other type, e.g. MarketDataSnapshotFullRefresh ("W"), as its
+    /// wire value
+    Other(SmallVec<[u8; 4]>),
+}
+
+impl MsgType {
+    // Every variant but `Other` with its wire value
+    const KNOWN: [(MsgType, &'static [u8]); 12] = [
+        (MsgType::Heartbeat, msg_type::HEARTBEAT),
+        (MsgType::TestRequest, msg_type::TEST_REQUEST),
+        (MsgType::ResendRequest, msg_type::RESEND_REQUEST),
+        (MsgType::Reject, msg_type::REJECT),
+        (MsgType::SequenceReset, msg_type::SEQUENCE_RESET),
+        (MsgType::Logout, msg_type::LOGOUT),
+        (MsgType::Logon, msg_type::LOGON),
+        (MsgType::NewOrderSingle, msg_type::NEW_ORDER_SINGLE),
+        (MsgType::ExecutionReport, msg_type::EXECUTION_REPORT),
+        (MsgType::OrderCancelRequest, msg_type::ORDER_CANCEL_REQUEST),
+        (MsgType::OrderCancelReject, msg_type::ORDER_CANCEL_REJECT),
+        (MsgType::TradeCaptureReport, msg_type::TRADE_CAPTURE_REPORT),
+    ];
+
+    /// The type `bytes` names, `Other` for one without a variant. Only an
+    /// empty value is not a MsgType at all.
+    pub fn from_bytes(bytes: &[u8]) -> Option<MsgType> {
+        if bytes.is_empty() {
+            return None;
+        }
+        let known = Self::KNOWN.iter().find(|(_, value)| *value == bytes);
+        Some(known.map_or_else(|| MsgType::Other(SmallVec::from_slice(bytes)), |(msg_type, _)| msg_type.clone()))
+    }
+
+    /// The wire value, as written in tag 35
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            MsgType::Other(bytes) => bytes,
+            known => Self::KNOWN.iter().find(|(msg_type, _)| msg_type == known).map_or(&[], |&(_, value)| value),
+        }
+    }
+
+    /// Whether this is a session-level (administrative) message, see
+    /// `msg_type::is_admin`
+    #[inline]
+    pub fn is_admin(&self) -> bool {
+        msg_type::is_admin(self.as_bytes())
+    }
+}
+
+// FIX versions
+pub mod fix_version {
+    pub const FIX_4_0: &[u8] = b"FIX.4.0";
+    pub const FIX_4_1: &[u8] = b"FIX.4.1";
+    pub const FIX_4_2: &[u8] = b"FIX.4.2";
+    pub const FIX_4_3: &[u8] = b"FIX.4.3";
+    pub const FIX_4_4: &[u8] = b"FIX.4.4";
+    pub const FIX_5_0: &[u8] = b"FIX.5.0";
+    /// The transport BeginString of FIX 5.0 and later, whose application
+    /// version is given by ApplVerID (1128), see `appl_ver_id`
+    pub const FIXT_1_1: &[u8] = b"FIXT.1.1";
+}
+
+// ApplVerID (1128) values
+pub mod appl_ver_id {
+    pub const FIX_2_7: &[u8] = b"0";
+    pub const FIX_3_0: &[u8] = b"1";
+    pub const FIX_4_0: &[u8] = b"2";
+    pub const FIX_4_1: &[u8] = b"3";
+    pub const FIX_4_2: &[u8] = b"4";
+    pub const FIX_4_3: &[u8] = b"5";
+    pub const FIX_4_4: &[u8] = b"6";
+    pub const FIX_5_0: &[u8] = b"7";
+    pub const FIX_5_0_SP1: &[u8] = b"8";
+    pub const FIX_5_0_SP2: &[u8] = b"9";
 }
\ No newline at end of file
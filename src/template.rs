@@ -0,0 +1,229 @@
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::scanner::FieldScanner;
+use crate::tags::Tag;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+const CHECKSUM_TAG: u32 = Tag::CheckSum.value();
+
+/// The exact tag sequence of a fixed message layout, e.g. a market data feed
+/// where every message carries the same fields in the same order
+#[derive(Debug, Clone)]
+pub struct Template {
+    tags: Vec<u32>,
+    positions: FxHashMap<u32, usize>,
+}
+
+impl Template {
+    /// `tags` is the full wire sequence starting with 8, 9 and 35. CheckSum
+    /// (10) is appended when it is not the final tag.
+    pub fn new(tags: impl IntoIterator<Item = u32>) -> Self {
+        let mut tags: Vec<u32> = tags.into_iter().collect();
+        if tags.last() != Some(&CHECKSUM_TAG) {
+            tags.push(CHECKSUM_TAG);
+        }
+
+        let mut positions = FxHashMap::with_capacity_and_hasher(tags.len(), Default::default());
+        for (i, &tag) in tags.iter().enumerate() {
+            positions.entry(tag).or_insert(i);
+        }
+        Self { tags, positions }
+    }
+
+    /// Takes the layout of an already decoded message
+    pub fn from_message(message: &FixMessage) -> Self {
+        Self::new(message.field_tags().copied())
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[u32] {
+        &self.tags
+    }
+
+    /// Index of the first occurrence of `tag` in the layout
+    #[inline]
+    pub fn position(&self, tag: u32) -> Option<usize> {
+        self.positions.get(&tag).copied()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+/// A message decoded against a `Template`, holding values by layout position
+#[derive(Debug, Clone)]
+pub struct TemplatedMessage<'t> {
+    template: &'t Template,
+    values: Vec<SmallVec<[u8; 32]>>,
+}
+
+impl<'t> TemplatedMessage<'t> {
+    #[inline]
+    pub fn template(&self) -> &'t Template {
+        self.template
+    }
+
+    /// Value of the first occurrence of `tag`
+    #[inline]
+    pub fn get(&self, tag: u32) -> Option<&[u8]> {
+        self.template.position(tag).map(|i| &self.values[i][..])
+    }
+
+    /// Value at `index` in the template layout, for callers that resolved the
+    /// position once up front
+    #[inline]
+    pub fn value_at(&self, index: usize) -> &[u8] {
+        &self.values[index]
+    }
+
+    pub fn to_message(&self) -> FixMessage {
+        let mut message = FixMessage::with_capacity(self.values.len());
+        for (&tag, value) in self.template.tags.iter().zip(&self.values) {
+            message.add_field(FixField::new(tag, value.clone()));
+        }
+        message
+    }
+
+    // Returns `None` whenever the data does not follow the template exactly,
+    // including a bad checksum, leaving the generic decoder to report why
+    fn scan(data: &[u8], template: &'t Template) -> Option<Self> {
+        let mut values = Vec::with_capacity(template.tags.len());
+        let mut scanner = FieldScanner::new(data);
+        for &expected in &template.tags {
+            let (tag, value) = scanner.next()?;
+            if tag != expected {
+                return None;
+            }
+            values.push(SmallVec::from_slice(value));
+        }
+
+        if scanner.next().is_some() {
+            return None;
+        }
+        scanner.finish().ok()?;
+        Some(Self { template, values })
+    }
+}
+
+/// Result of `FixMessage::decode_templated`
+#[derive(Debug, Clone)]
+pub enum TemplatedDecode<'t> {
+    /// The message followed the template
+    Matched(TemplatedMessage<'t>),
+    /// The layout differed, so the message went through the generic decoder
+    Fallback(FixMessage),
+}
+
+impl TemplatedDecode<'_> {
+    #[inline]
+    pub fn get(&self, tag: u32) -> Option<&[u8]> {
+        match self {
+            TemplatedDecode::Matched(message) => message.get(tag),
+            TemplatedDecode::Fallback(message) => message.get_field(tag).map(FixField::value),
+        }
+    }
+}
+
+impl FixMessage {
+    /// Decodes a message expected to follow `template`. A matching message is
+    /// stored by template position without hashing tags; anything else falls
+    /// back to `FixMessage::decode`, with the same validation either way.
+    pub fn decode_templated<'t>(data: &[u8], template: &'t Template) -> Result<TemplatedDecode<'t>, FixError> {
+        match TemplatedMessage::scan(data, template) {
+            Some(message) => Ok(TemplatedDecode::Matched(message)),
+            None => FixMessage::decode(data).map(TemplatedDecode::Fallback),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::SOH;
+
+    fn market_data(bid: &[u8]) -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(8, b"FIX.4.4".to_vec()));
+        msg.add_field(FixField::new(35, b"W".to_vec()));
+        msg.add_field(FixField::new(55, b"EUR/USD".to_vec()));
+        msg.add_field(FixField::new(268, b"2".to_vec()));
+        msg.add_field(FixField::new(269, b"0".to_vec()));
+        msg.add_field(FixField::new(270, bid.to_vec()));
+        msg.add_field(FixField::new(269, b"1".to_vec()));
+        msg.add_field(FixField::new(270, b"1.1002".to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_matching_layout() {
+        let encoded = market_data(b"1.1000").encode().unwrap();
+        let template = Template::from_message(&FixMessage::decode(&encoded).unwrap());
+
+        let encoded = market_data(b"1.0999").encode().unwrap();
+        let decoded = FixMessage::decode_templated(&encoded, &template).unwrap();
+        let TemplatedDecode::Matched(message) = &decoded else {
+            panic!("expected the template to match");
+        };
+
+        assert_eq!(message.get(55), Some(&b"EUR/USD"[..]));
+        assert_eq!(message.get(270), Some(&b"1.0999"[..]));
+        assert_eq!(message.value_at(7), b"1");
+        assert_eq!(message.value_at(8), b"1.1002");
+
+        let generic = FixMessage::decode(&encoded).unwrap();
+        assert_eq!(message.to_message().encode().unwrap(), generic.encode().unwrap());
+    }
+
+    #[test]
+    fn test_falls_back_on_different_layout() {
+        let template = Template::new([8, 9, 35, 55, 268, 269, 270, 269, 270]);
+
+        let mut msg = market_data(b"1.1000");
+        msg.add_field(FixField::new(271, b"1000000".to_vec()));
+        let encoded = msg.encode().unwrap();
+
+        let decoded = FixMessage::decode_templated(&encoded, &template).unwrap();
+        assert!(matches!(decoded, TemplatedDecode::Fallback(_)));
+        assert_eq!(decoded.get(271), Some(&b"1000000"[..]));
+    }
+
+    #[test]
+    fn test_checksum_still_verified() {
+        let template = Template::new([8, 9, 35, 55, 268, 269, 270, 269, 270]);
+        let mut encoded = market_data(b"1.1000").encode().unwrap();
+        let pos = encoded.len() - 10;
+        encoded[pos] = b'9';
+
+        assert!(matches!(
+            FixMessage::decode_templated(&encoded, &template),
+            Err(FixError::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn test_body_length_still_verified() {
+        let template = Template::new([8, 9, 35, 55, 268, 269, 270, 269, 270]);
+        let encoded = market_data(b"1.1000").encode().unwrap();
+
+        // Declare a longer body than there is, with a CheckSum to match
+        let mut wire = encoded[..encoded.len() - 7].to_vec();
+        let digit = wire.iter().position(|&b| b == SOH).unwrap() + 3;
+        wire[digit] += 1;
+        let sum = wire.iter().map(|&b| b as u32).sum::<u32>() % 256;
+        wire.extend_from_slice(format!("10={:03}\x01", sum).as_bytes());
+
+        assert!(matches!(
+            FixMessage::decode_templated(&wire, &template),
+            Err(FixError::InvalidBodyLength { .. })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+// Helpers for application tests that pin down how messages encode: golden
+// strings in the '|'-delimited log form, compared field by field, and golden
+// files that regenerate themselves on request.
+
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::replay::{diff_fields, FieldDiff};
+use std::fmt::Write;
+use std::path::Path;
+
+pub use crate::replay::VOLATILE_TAGS;
+
+/// Set to anything but "" or "0" to have `assert_golden_file` write the
+/// actual messages out instead of comparing against them
+pub const UPDATE_GOLDEN_ENV: &str = "FIX_UPDATE_GOLDEN";
+
+/// Asserts a message matches a '|'-delimited golden string, ignoring
+/// `VOLATILE_TAGS` (9, 10, 52 and 60) or the tags given as a third argument.
+/// A mismatch panics with the fields that differ.
+#[macro_export]
+macro_rules! assert_fix_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_fix_eq!($actual, $expected, $crate::testing::VOLATILE_TAGS)
+    };
+    ($actual:expr, $expected:expr, $ignored:expr $(,)?) => {
+        if let Some(report) = $crate::testing::golden_mismatch(&$actual, $expected, &$ignored) {
+            panic!("{}", report);
+        }
+    };
+}
+
+/// Reads a golden string as the fields it lists, in order. Unlike
+/// `FixMessage::from_print_string` nothing is checked, so a hand-edited
+/// golden string need not carry a correct BodyLength or CheckSum, or any.
+pub fn parse_golden(golden: &str) -> Result<FixMessage, FixError> {
+    let mut msg = FixMessage::new();
+    for pair in golden.trim().split('|').filter(|pair| !pair.is_empty()) {
+        let (tag, value) = pair.split_once('=').ok_or(FixError::InvalidFormat)?;
+        let tag = tag.parse().map_err(|_| FixError::InvalidFormat)?;
+        msg.add_field(FixField::new(tag, value.as_bytes().to_vec()));
+    }
+    Ok(msg)
+}
+
+/// What `assert_fix_eq!` panics with, or `None` when `actual` matches
+/// `golden` apart from the `ignored` tags. `actual` is compared as `encode`
+/// writes it, so with the header in wire order.
+pub fn golden_mismatch(actual: &FixMessage, golden: &str, ignored: &[u32]) -> Option<String> {
+    let expected = match parse_golden(golden) {
+        Ok(expected) => expected,
+        Err(_) => return Some(format!("golden string is not tag=value fields: {:?}", golden)),
+    };
+    let encoded = actual.encode().ok().and_then(|encoded| FixMessage::decode(&encoded).ok());
+    let diffs = diff_fields(&expected, encoded.as_ref().unwrap_or(actual), ignored);
+    if diffs.is_empty() {
+        return None;
+    }
+    Some(report(&diffs, actual, golden, ignored))
+}
+
+fn report(diffs: &[FieldDiff], actual: &FixMessage, golden: &str, ignored: &[u32]) -> String {
+    let ignored: Vec<String> = ignored.iter().map(u32::to_string).collect();
+    let mut report = format!("message differs from golden string, ignoring tags [{}]:\n", ignored.join(", "));
+    for diff in diffs {
+        let _ = writeln!(report, "  {}", diff);
+    }
+    let _ = write!(report, "expected: {}\n  actual: {}", golden.trim(), actual.to_print_string());
+    report
+}
+
+/// Asserts a message matches the golden string in the file at `path`, as
+/// `assert_fix_eq!` does. With `UPDATE_GOLDEN_ENV` set the file is
+/// (re)written from `actual` instead, creating its directory if needed.
+#[track_caller]
+pub fn assert_golden_file(actual: &FixMessage, path: impl AsRef<Path>, ignored: &[u32]) {
+    let update = std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|value| !value.is_empty() && value != "0");
+    check_golden_file(actual, path.as_ref(), ignored, update);
+}
+
+#[track_caller]
+fn check_golden_file(actual: &FixMessage, path: &Path, ignored: &[u32], update: bool) {
+    if update {
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, actual.to_print_string() + "\n"));
+        if let Err(err) = written {
+            panic!("cannot write golden file {}: {}", path.display(), err);
+        }
+        return;
+    }
+
+    let golden = match std::fs::read_to_string(path) {
+        Ok(golden) => golden,
+        Err(err) => panic!(
+            "cannot read golden file {}: {}; run with {}=1 to create it",
+            path.display(),
+            err,
+            UPDATE_GOLDEN_ENV
+        ),
+    };
+    if let Some(report) = golden_mismatch(actual, &golden, ignored) {
+        panic!("{}\n(golden file {}; run with {}=1 to update it)", report, path.display(), UPDATE_GOLDEN_ENV);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionConfig;
+    use crate::tags::{fix_version, msg_type, Tag};
+
+    fn order(symbol: &str) -> FixMessage {
+        let mut msg = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").new_message(msg_type::NEW_ORDER_SINGLE);
+        msg.add_field(FixField::new(Tag::MsgSeqNum.value(), b"1".to_vec()));
+        msg.add_field(FixField::new(Tag::SendingTime.value(), b"20240101-12:00:00.000".to_vec()));
+        msg.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+        msg.add_field(FixField::new(Tag::Symbol.value(), symbol.as_bytes().to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_assert_fix_eq() {
+        // Stale BodyLength, no CheckSum and another SendingTime
+        let golden = "8=FIX.4.4|9=1|35=D|49=CLIENT|56=VENUE|34=1|52=20250101-00:00:00.000|11=ORD1|55=VOD.L|";
+        assert_fix_eq!(order("VOD.L"), golden);
+        assert_fix_eq!(order("VOD.L"), &golden.replace("|52=20250101-00:00:00.000", ""), [9, 10, 52]);
+
+        let report = golden_mismatch(&order("BARC.L"), golden, VOLATILE_TAGS).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "message differs from golden string, ignoring tags [9, 10, 52, 60]:");
+        assert_eq!(lines[1], "  field 6: expected 55=VOD.L, got 55=BARC.L");
+        assert!(lines[3].starts_with("  actual: 8=FIX.4.4|9="));
+        assert!(golden_mismatch(&order("VOD.L"), golden, &[]).unwrap().contains("field 1: expected 9=1, got 9="));
+
+        assert!(golden_mismatch(&order("VOD.L"), "8=FIX.4.4|garbage|", &[]).unwrap().contains("not tag=value"));
+        let panicked = std::panic::catch_unwind(|| assert_fix_eq!(order("BARC.L"), golden));
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    fn test_golden_file() {
+        let dir = std::env::temp_dir().join(format!("fix_engine_golden_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("golden").join("order.fix");
+
+        let missing = std::panic::catch_unwind(|| check_golden_file(&order("VOD.L"), &path, VOLATILE_TAGS, false));
+        assert!(missing.is_err());
+
+        check_golden_file(&order("VOD.L"), &path, VOLATILE_TAGS, true);
+        let golden = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(golden, order("VOD.L").to_print_string() + "\n");
+        check_golden_file(&order("VOD.L"), &path, VOLATILE_TAGS, false);
+        let changed = std::panic::catch_unwind(|| check_golden_file(&order("BARC.L"), &path, VOLATILE_TAGS, false));
+        assert!(changed.is_err());
+
+        // Regenerating picks up the change
+        check_golden_file(&order("BARC.L"), &path, VOLATILE_TAGS, true);
+        check_golden_file(&order("BARC.L"), &path, VOLATILE_TAGS, false);
+    }
+}
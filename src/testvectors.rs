@@ -0,0 +1,289 @@
+// Golden wire messages the codec must decode and re-encode byte for byte,
+// and malformed ones it must reject. Regression cases from bug reports are
+// added here, with `source` naming the report.
+
+use crate::dictionary::DataDictionary;
+use crate::error::FixError;
+use crate::message::FixMessage;
+use crate::options::EncodeOptions;
+use crate::tags::Tag;
+use std::fmt;
+
+/// The error a malformed vector must be rejected with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedError {
+    InvalidFormat,
+    InvalidChecksum,
+    InvalidBodyLength,
+    IncompleteHeader,
+    MissingField(u32),
+}
+
+impl ExpectedError {
+    pub fn matches(self, err: &FixError) -> bool {
+        match (self, err) {
+            (ExpectedError::InvalidFormat, FixError::InvalidFormat)
+            | (ExpectedError::InvalidChecksum, FixError::InvalidChecksum)
+            | (ExpectedError::InvalidBodyLength, FixError::InvalidBodyLength { .. })
+            | (ExpectedError::IncompleteHeader, FixError::IncompleteHeader) => true,
+            (ExpectedError::MissingField(expected), FixError::MissingField(tag)) => expected == *tag,
+            _ => false,
+        }
+    }
+}
+
+/// What decoding a vector must produce
+#[derive(Debug, Clone, Copy)]
+pub enum Expected {
+    /// Decodes with this MsgType and these field values, and encodes back
+    /// to the same bytes
+    Valid { msg_type: &'static [u8], fields: &'static [(u32, &'static [u8])] },
+    /// Fails to decode with this error
+    Invalid(ExpectedError),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TestVector {
+    pub name: &'static str,
+    /// Where the message comes from, e.g. a spec section or a bug report
+    pub source: &'static str,
+    pub wire: &'static [u8],
+    pub expected: Expected,
+}
+
+pub const HEARTBEAT_44: TestVector = TestVector {
+    name: "heartbeat_44",
+    source: "FIX 4.4 Vol. 2, Heartbeat",
+    wire: b"8=FIX.4.4\x019=55\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=059\x01",
+    expected: Expected::Valid { msg_type: b"0", fields: &[(49, b"SENDER"), (56, b"TARGET"), (34, b"1")] },
+};
+
+pub const LOGON_42: TestVector = TestVector {
+    name: "logon_42",
+    source: "FIX 4.2 Vol. 2, Logon; SendingTime without milliseconds",
+    wire: b"8=FIX.4.2\x019=62\x0135=A\x0149=CLIENT\x0156=VENUE\x0134=1\x0152=20240101-08:00:00\x0198=0\x01108=30\x0110=098\x01",
+    expected: Expected::Valid { msg_type: b"A", fields: &[(98, b"0"), (108, b"30"), (52, b"20240101-08:00:00")] },
+};
+
+pub const NEW_ORDER_SINGLE_44: TestVector = TestVector {
+    name: "new_order_single_44",
+    source: "FIX 4.4 Vol. 4, NewOrderSingle limit order",
+    wire: b"8=FIX.4.4\x019=138\x0135=D\x0149=CLIENT\x0156=VENUE\x0134=2\x0152=20240101-08:00:01.250\x0111=ORD-0001\x0121=1\x0155=VOD.L\x0154=1\x0160=20240101-08:00:01.249\x0138=1000\x0140=2\x0144=101.25\x0159=0\x0110=029\x01",
+    expected: Expected::Valid {
+        msg_type: b"D",
+        fields: &[(11, b"ORD-0001"), (55, b"VOD.L"), (54, b"1"), (38, b"1000"), (44, b"101.25")],
+    },
+};
+
+pub const EXECUTION_REPORT_PARTIES_44: TestVector = TestVector {
+    name: "execution_report_parties_44",
+    source: "FIX 4.4 Vol. 4, ExecutionReport fill with a Parties group",
+    wire: b"8=FIX.4.4\x019=209\x0135=8\x0149=VENUE\x0156=CLIENT\x0134=7\x0152=20240101-08:00:02.000\x0137=EX-1\x0111=ORD-0001\x0117=EXEC-1\x01150=F\x0139=2\x01453=2\x01448=BROKER\x01447=D\x01452=1\x01448=DESK\x01447=D\x01452=24\x0155=VOD.L\x0154=1\x0138=1000\x0132=1000\x0131=101.25\x01151=0\x0114=1000\x016=101.25\x0110=045\x01",
+    expected: Expected::Valid {
+        msg_type: b"8",
+        fields: &[(17, b"EXEC-1"), (150, b"F"), (39, b"2"), (453, b"2"), (448, b"BROKER"), (6, b"101.25")],
+    },
+};
+
+pub const RESEND_REQUEST_UNBOUNDED_44: TestVector = TestVector {
+    name: "resend_request_unbounded_44",
+    source: "FIX 4.4 Vol. 2, ResendRequest with EndSeqNo=0",
+    wire: b"8=FIX.4.4\x019=63\x0135=2\x0149=CLIENT\x0156=VENUE\x0134=3\x0152=20240101-08:00:03.000\x017=5\x0116=0\x0110=127\x01",
+    expected: Expected::Valid { msg_type: b"2", fields: &[(7, b"5"), (16, b"0")] },
+};
+
+pub const RAW_DATA_WITH_SOH_44: TestVector = TestVector {
+    name: "raw_data_with_soh_44",
+    source: "FIX 4.4 Vol. 1, data fields; RawData holding SOH bytes",
+    wire: b"8=FIX.4.4\x019=81\x0135=B\x0149=VENUE\x0156=CLIENT\x0134=4\x0152=20240101-08:00:04.000\x01148=Notice\x0195=7\x0196=ab\x01cd\x01e\x0110=208\x01",
+    expected: Expected::Valid { msg_type: b"B", fields: &[(95, b"7"), (96, b"ab\x01cd\x01e")] },
+};
+
+pub const HEARTBEAT_FIXT: TestVector = TestVector {
+    name: "heartbeat_fixt",
+    source: "FIXT 1.1, Heartbeat with ApplVerID and microsecond SendingTime",
+    wire: b"8=FIXT.1.1\x019=65\x0135=0\x0149=SENDER\x0156=TARGET\x0134=9\x0152=20240101-12:00:00.000000\x011128=9\x0110=101\x01",
+    expected: Expected::Valid { msg_type: b"0", fields: &[(1128, b"9"), (52, b"20240101-12:00:00.000000")] },
+};
+
+pub const BAD_CHECKSUM: TestVector = TestVector {
+    name: "bad_checksum",
+    source: "heartbeat_44 with CheckSum 000",
+    wire: b"8=FIX.4.4\x019=55\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=000\x01",
+    expected: Expected::Invalid(ExpectedError::InvalidChecksum),
+};
+
+pub const NON_NUMERIC_CHECKSUM: TestVector = TestVector {
+    name: "non_numeric_checksum",
+    source: "heartbeat_44 with CheckSum 1x3",
+    wire: b"8=FIX.4.4\x019=55\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=1x3\x01",
+    expected: Expected::Invalid(ExpectedError::InvalidFormat),
+};
+
+pub const BAD_BODY_LENGTH: TestVector = TestVector {
+    name: "bad_body_length",
+    source: "heartbeat_44 with BodyLength 56 and a matching CheckSum",
+    wire: b"8=FIX.4.4\x019=56\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=060\x01",
+    expected: Expected::Invalid(ExpectedError::InvalidBodyLength),
+};
+
+pub const NO_BODY_LENGTH: TestVector = TestVector {
+    name: "no_body_length",
+    source: "heartbeat_44 without BodyLength",
+    wire: b"8=FIX.4.4\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=090\x01",
+    expected: Expected::Invalid(ExpectedError::MissingField(9)),
+};
+
+pub const TRUNCATED: TestVector = TestVector {
+    name: "truncated",
+    source: "heartbeat_44 cut off inside SendingTime",
+    wire: b"8=FIX.4.4\x019=55\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.0",
+    expected: Expected::Invalid(ExpectedError::InvalidFormat),
+};
+
+pub const NO_BEGIN_STRING: TestVector = TestVector {
+    name: "no_begin_string",
+    source: "heartbeat_44 without BeginString",
+    wire: b"9=55\x0135=0\x0149=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=059\x01",
+    expected: Expected::Invalid(ExpectedError::InvalidFormat),
+};
+
+pub const NON_NUMERIC_TAG: TestVector = TestVector {
+    name: "non_numeric_tag",
+    source: "heartbeat_44 with tag 4x",
+    wire: b"8=FIX.4.4\x019=55\x0135=0\x014x=SENDER\x0156=TARGET\x0134=1\x0152=20240101-12:00:00.000\x0110=122\x01",
+    expected: Expected::Invalid(ExpectedError::InvalidFormat),
+};
+
+pub const EMPTY: TestVector = TestVector {
+    name: "empty",
+    source: "zero-length input",
+    wire: b"",
+    expected: Expected::Invalid(ExpectedError::InvalidFormat),
+};
+
+/// Every vector, valid ones first
+pub const ALL: &[TestVector] = &[
+    HEARTBEAT_44,
+    LOGON_42,
+    NEW_ORDER_SINGLE_44,
+    EXECUTION_REPORT_PARTIES_44,
+    RESEND_REQUEST_UNBOUNDED_44,
+    RAW_DATA_WITH_SOH_44,
+    HEARTBEAT_FIXT,
+    BAD_CHECKSUM,
+    NON_NUMERIC_CHECKSUM,
+    BAD_BODY_LENGTH,
+    NO_BODY_LENGTH,
+    TRUNCATED,
+    NO_BEGIN_STRING,
+    NON_NUMERIC_TAG,
+    EMPTY,
+];
+
+/// A vector that did not behave as expected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub name: &'static str,
+    pub reason: String,
+}
+
+/// Outcome of `run_conformance`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    pub passed: Vec<&'static str>,
+    pub failures: Vec<ConformanceFailure>,
+}
+
+impl ConformanceReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} passed, {} failed", self.passed.len(), self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n  {}: {}", failure.name, failure.reason)?;
+        }
+        Ok(())
+    }
+}
+
+// Why `vector` does not conform, if it does not
+fn check(vector: &TestVector, options: &EncodeOptions, dictionary: Option<&DataDictionary>) -> Result<(), String> {
+    let (msg_type, expected_fields, msg) = match (vector.expected, FixMessage::decode(vector.wire)) {
+        (Expected::Valid { msg_type, fields }, Ok(msg)) => (msg_type, fields, msg),
+        (Expected::Valid { .. }, Err(err)) => return Err(format!("decode failed: {err}")),
+        (Expected::Invalid(expected), Err(err)) if expected.matches(&err) => return Ok(()),
+        (Expected::Invalid(expected), Err(err)) => return Err(format!("expected {expected:?}, got {err:?}")),
+        (Expected::Invalid(expected), Ok(_)) => return Err(format!("decoded; expected {expected:?}")),
+    };
+
+    let actual_type = msg.get_field(Tag::MsgType.value()).map(|f| f.value());
+    if actual_type != Some(msg_type) {
+        return Err(format!("MsgType {:?}", actual_type.map(String::from_utf8_lossy)));
+    }
+    for &(tag, value) in expected_fields {
+        let actual = msg.get_field(tag).map(|f| f.value());
+        if actual != Some(value) {
+            return Err(format!("tag {tag} is {:?}", actual.map(String::from_utf8_lossy)));
+        }
+    }
+    if let Some(dictionary) = dictionary {
+        if let Some((tag, err)) = msg.validate_all(dictionary).into_iter().next() {
+            return Err(format!("tag {tag} fails the dictionary: {err}"));
+        }
+    }
+    match msg.encode_with_options(options) {
+        Ok(encoded) if encoded[..] == *vector.wire => Ok(()),
+        Ok(encoded) => Err(format!("re-encoded as {:?}", String::from_utf8_lossy(&encoded))),
+        Err(err) => Err(format!("encode failed: {err}")),
+    }
+}
+
+/// Runs every vector against `options` and, when given, `dictionary`: valid
+/// messages must decode to the expected values, pass the dictionary and
+/// encode back byte for byte; malformed ones must fail with the expected
+/// error
+pub fn run_conformance(options: &EncodeOptions, dictionary: Option<&DataDictionary>) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for vector in ALL {
+        match check(vector, options, dictionary) {
+            Ok(()) => report.passed.push(vector.name),
+            Err(reason) => report.failures.push(ConformanceFailure { name: vector.name, reason }),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::{DecimalFormat, DecimalPadding};
+
+    #[test]
+    fn test_default_configuration_conforms() {
+        let report = run_conformance(&EncodeOptions::default(), None);
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.passed.len(), ALL.len());
+
+        let report = run_conformance(&EncodeOptions::default(), Some(&DataDictionary::fix44()));
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn test_reports_options_that_break_round_trips() {
+        // Four decimal places rewrites Price(44) and AvgPx(6)
+        let four_places = DecimalFormat::default().with_max_decimal_places(4).with_padding(DecimalPadding::Fixed);
+        let options = EncodeOptions::default()
+            .with_decimal_format(44, four_places)
+            .with_decimal_format(6, four_places);
+        let report = run_conformance(&options, None);
+        let failed: Vec<&str> = report.failures.iter().map(|f| f.name).collect();
+        assert_eq!(failed, ["new_order_single_44", "execution_report_parties_44"]);
+        assert!(report.to_string().starts_with("13 passed, 2 failed"));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::error::FixError;
+use crate::field::FixField;
+use crate::message::FixMessage;
+use crate::tags::Tag;
+
+const UTF_8: &[u8] = b"UTF-8";
+
+/// EncodedText (355) as received, with the MessageEncoding (347) that
+/// applies to it. `encoding` is `None` when 347 is absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodedText<'a> {
+    pub bytes: &'a [u8],
+    pub encoding: Option<&'a [u8]>,
+}
+
+impl EncodedText<'_> {
+    /// Transcodes to a `String`. UTF-8 is always supported; other encodings
+    /// need the `encoding` feature.
+    pub fn to_string(&self) -> Result<String, FixError> {
+        match self.encoding {
+            None => Err(FixError::MissingField(Tag::MessageEncoding.value())),
+            Some(encoding) if encoding.eq_ignore_ascii_case(UTF_8) => String::from_utf8(self.bytes.to_vec())
+                .map_err(|_| FixError::InvalidFieldValue),
+            Some(encoding) => transcode(encoding, self.bytes),
+        }
+    }
+}
+
+#[cfg(feature = "encoding")]
+fn transcode(encoding: &[u8], bytes: &[u8]) -> Result<String, FixError> {
+    let encoding = encoding_rs::Encoding::for_label(encoding)
+        .ok_or_else(|| FixError::UnsupportedEncoding(String::from_utf8_lossy(encoding).into_owned()))?;
+    let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if had_errors {
+        return Err(FixError::InvalidFieldValue);
+    }
+    Ok(text.into_owned())
+}
+
+#[cfg(not(feature = "encoding"))]
+fn transcode(encoding: &[u8], _bytes: &[u8]) -> Result<String, FixError> {
+    Err(FixError::UnsupportedEncoding(String::from_utf8_lossy(encoding).into_owned()))
+}
+
+impl FixMessage {
+    /// EncodedText (355) and its declared encoding, if present
+    pub fn encoded_text(&self) -> Option<EncodedText<'_>> {
+        let bytes = self.get_field(Tag::EncodedText.value())?.value();
+        let encoding = self.get_field(Tag::MessageEncoding.value()).map(FixField::value);
+        Some(EncodedText { bytes, encoding })
+    }
+
+    /// Sets MessageEncoding (347), EncodedTextLen (354) and EncodedText (355)
+    /// together. `bytes` may contain SOH.
+    pub fn set_encoded_text(&mut self, encoding: &[u8], bytes: &[u8]) {
+        self.set_field(FixField::new(Tag::MessageEncoding.value(), encoding));
+        self.set_value(Tag::EncodedTextLen.value(), &(bytes.len() as u64));
+        self.set_field(FixField::new(Tag::EncodedText.value(), bytes));
+    }
+
+    /// `set_encoded_text` with UTF-8 content
+    #[inline]
+    pub fn set_encoded_text_utf8(&mut self, text: &str) {
+        self.set_encoded_text(UTF_8, text.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::{fix_version, msg_type};
+
+    fn message() -> FixMessage {
+        let mut msg = FixMessage::new();
+        msg.add_field(FixField::new(Tag::BeginString.value(), fix_version::FIX_4_4.to_vec()));
+        msg.add_field(FixField::new(Tag::MsgType.value(), msg_type::NEW_ORDER_SINGLE.to_vec()));
+        msg
+    }
+
+    #[test]
+    fn test_utf8_round_trip_with_soh_in_value() {
+        let text = "注文\u{1}確認 ✓";
+        let mut msg = message();
+        msg.set_encoded_text_utf8(text);
+        msg.add_field(FixField::new(Tag::Text.value(), b"plain".to_vec()));
+
+        let encoded = msg.encode().unwrap();
+        assert!(encoded.windows(4).any(|w| w == "文\u{1}".as_bytes()));
+
+        let decoded = FixMessage::decode(&encoded).unwrap();
+        let encoded_text = decoded.encoded_text().unwrap();
+        assert_eq!(encoded_text.encoding, Some(&b"UTF-8"[..]));
+        assert_eq!(encoded_text.bytes, text.as_bytes());
+        assert_eq!(encoded_text.to_string().unwrap(), text);
+        assert_eq!(decoded.get_uint(Tag::EncodedTextLen.value()).unwrap(), Some(text.len() as u64));
+        assert_eq!(decoded.get_field(Tag::Text.value()).unwrap().value(), b"plain");
+    }
+
+    #[test]
+    fn test_setter_replaces_consistently() {
+        let mut msg = message();
+        msg.set_encoded_text_utf8("first");
+        msg.set_encoded_text(b"SHIFT_JIS", &[0x93, 0xfa, 0x96, 0x7b]);
+
+        let decoded = FixMessage::decode(&msg.encode().unwrap()).unwrap();
+        assert_eq!(decoded.get_uint(Tag::EncodedTextLen.value()).unwrap(), Some(4));
+        let encoded_text = decoded.encoded_text().unwrap();
+        assert_eq!(encoded_text.encoding, Some(&b"SHIFT_JIS"[..]));
+
+        #[cfg(feature = "encoding")]
+        assert_eq!(encoded_text.to_string().unwrap(), "日本");
+        #[cfg(not(feature = "encoding"))]
+        assert!(matches!(encoded_text.to_string(), Err(FixError::UnsupportedEncoding(_))));
+    }
+
+    #[test]
+    fn test_wrong_declared_length() {
+        let mut msg = message();
+        msg.set_encoded_text_utf8("abc\u{1}def");
+        msg.set_value(Tag::EncodedTextLen.value(), &5u64);
+        assert!(matches!(FixMessage::decode(&msg.encode().unwrap()), Err(FixError::InvalidFormat)));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::FixError;
+use crate::framing::DEFAULT_MAX_FRAME_LEN;
+use crate::log::writer::log_shared;
+use crate::log::{Direction, LogWriter};
+use crate::message::{frame_len, FixMessage};
+use crate::metrics::CodecCounters;
+use crate::options::{DecodeOptions, EncodeOptions};
+use bytes::BytesMut;
+use std::io;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Why a `FixCodec` stream or sink failed
+#[derive(Debug, Error)]
+pub enum CodecError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Fix(#[from] FixError),
+}
+
+/// Reads and writes tag=value messages on a byte stream, for use with
+/// `tokio_util::codec::Framed`. Inbound messages are delimited by their
+/// BodyLength. As with any `Framed` decoder, an error ends the stream: a
+/// malformed header or a BodyLength beyond the maximum frame length leaves
+/// nothing to resynchronise on, and a whole frame that fails to decode,
+/// e.g. on its CheckSum, is consumed before the error is returned.
+#[derive(Debug, Clone)]
+pub struct FixCodec {
+    max_frame_len: usize,
+    decode: DecodeOptions,
+    encode: EncodeOptions,
+    counters: CodecCounters,
+    log: Option<Arc<Mutex<LogWriter>>>,
+}
+
+impl FixCodec {
+    pub fn new() -> Self {
+        Self {
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            decode: DecodeOptions::default(),
+            encode: EncodeOptions::default(),
+            counters: CodecCounters::default(),
+            log: None,
+        }
+    }
+
+    /// Rejects an inbound message whose BodyLength makes it longer than
+    /// `max` bytes, or whose header has not ended within `max` bytes, with
+    /// `FixError::MessageTooLarge`
+    pub fn with_max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.decode = options;
+        self
+    }
+
+    pub fn with_encode_options(mut self, options: EncodeOptions) -> Self {
+        self.encode = options;
+        self
+    }
+
+    /// Writes every message decoded or encoded to `log`, as IN and OUT
+    /// lines. Clones of the codec, e.g. for the read and write halves of a
+    /// stream, share it.
+    pub fn with_log(mut self, log: Arc<Mutex<LogWriter>>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Inbound frames that failed to decode so far, the one ending the
+    /// stream included. A codec never resyncs.
+    #[inline]
+    pub fn counters(&self) -> CodecCounters {
+        self.counters
+    }
+
+    fn frame(&mut self, src: &mut BytesMut) -> Result<Option<FixMessage>, FixError> {
+        let len = match frame_len(src) {
+            Ok(len) => len,
+            // A header that has not ended within a maximum frame never will
+            Err(FixError::IncompleteHeader) if src.len() > self.max_frame_len => {
+                return Err(FixError::MessageTooLarge { len: src.len(), max: self.max_frame_len })
+            }
+            Err(FixError::IncompleteHeader) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if len > self.max_frame_len {
+            return Err(FixError::MessageTooLarge { len, max: self.max_frame_len });
+        }
+        if src.len() < len {
+            src.reserve(len - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(len);
+        FixMessage::decode_with_options(&frame, &self.decode).map(Some)
+    }
+}
+
+impl Default for FixCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for FixCodec {
+    type Item = FixMessage;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<FixMessage>, CodecError> {
+        let msg = self.frame(src).inspect_err(|_| self.counters.decode_errors += 1)?;
+        if let (Some(log), Some(msg)) = (&self.log, &msg) {
+            log_shared(log, Direction::Inbound, msg)?;
+        }
+        Ok(msg)
+    }
+}
+
+impl Encoder<FixMessage> for FixCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, msg: FixMessage, dst: &mut BytesMut) -> Result<(), CodecError> {
+        self.encode(&msg, dst)
+    }
+}
+
+impl Encoder<&FixMessage> for FixCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, msg: &FixMessage, dst: &mut BytesMut) -> Result<(), CodecError> {
+        msg.encode_into_with_options(dst, &self.encode)?;
+        if let Some(log) = &self.log {
+            log_shared(log, Direction::Outbound, msg)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::session::SessionConfig;
+    use crate::tags::{fix_version, MsgType, Tag};
+
+    fn heartbeat(seq: u64) -> FixMessage {
+        let mut msg = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE").heartbeat(None);
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg
+    }
+
+    #[test]
+    fn test_decode_partial_and_back_to_back() {
+        let mut codec = FixCodec::new();
+        let mut wire = BytesMut::new();
+        codec.encode(heartbeat(1), &mut wire).unwrap();
+        codec.encode(&heartbeat(2), &mut wire).unwrap();
+        let stream = wire.split().freeze();
+
+        let mut src = BytesMut::new();
+        let mut decoded = Vec::new();
+        for chunk in stream.chunks(5) {
+            src.extend_from_slice(chunk);
+            while let Some(msg) = codec.decode(&mut src).unwrap() {
+                decoded.push(msg.msg_seq_num().unwrap().unwrap().get());
+                assert_eq!(msg.msg_type(), Some(MsgType::Heartbeat));
+            }
+        }
+        assert_eq!(decoded, [1, 2]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let encoded = heartbeat(1).encode().unwrap();
+        let mut codec = FixCodec::new().with_max_frame_len(encoded.len() - 1);
+        let mut src = encoded.clone();
+        assert!(matches!(codec.decode(&mut src), Err(CodecError::Fix(FixError::MessageTooLarge { .. }))));
+
+        // A bad CheckSum is reported and its frame consumed
+        let mut src = encoded.clone();
+        let n = src.len();
+        src[n - 2] = if src[n - 2] == b'0' { b'1' } else { b'0' };
+        src.extend_from_slice(&encoded);
+        let mut codec = FixCodec::new();
+        assert!(matches!(codec.decode(&mut src), Err(CodecError::Fix(FixError::InvalidChecksum))));
+        assert_eq!(codec.counters().decode_errors, 1);
+        assert_eq!(src.len(), encoded.len());
+        let lenient = DecodeOptions::default().with_verify_checksum(false);
+        assert!(FixCodec::new().with_decode_options(lenient).decode(&mut encoded.clone()).unwrap().is_some());
+
+        let mut src = BytesMut::from(&b"garbage"[..]);
+        assert!(matches!(codec.decode(&mut src), Err(CodecError::Fix(FixError::InvalidFormat))));
+        assert_eq!(codec.counters().decode_errors, 2);
+
+        // A header that never ends is not buffered without bound
+        let mut codec = FixCodec::new().with_max_frame_len(64);
+        let mut src = BytesMut::from(&b"8=FIX.4.4"[..]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(&[b'4'; 64]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(CodecError::Fix(FixError::MessageTooLarge { len: 73, max: 64 }))
+        ));
+    }
+
+    #[test]
+    fn test_log() {
+        let dir = std::env::temp_dir().join(format!("fix_engine_codec_log_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("messages.log");
+        let log = Arc::new(Mutex::new(LogWriter::open(&path, Default::default()).unwrap()));
+
+        // Separate read and write halves log to the same file
+        let mut writer = FixCodec::new().with_log(log.clone());
+        let mut reader = writer.clone();
+        let mut wire = BytesMut::new();
+        writer.encode(heartbeat(1), &mut wire).unwrap();
+        assert_eq!(reader.decode(&mut wire).unwrap().unwrap().msg_seq_num().unwrap().unwrap().get(), 1);
+        drop((writer, reader, log));
+
+        let records: Vec<_> = crate::log::LogReader::open(&path).unwrap().map(Result::unwrap).collect();
+        let directions: Vec<_> = records.iter().map(|record| record.direction).collect();
+        assert_eq!(directions, [Some(Direction::Outbound), Some(Direction::Inbound)]);
+        assert!(records.iter().all(|record| record.message.as_ref().unwrap().msg_type() == Some(MsgType::Heartbeat)));
+    }
+}
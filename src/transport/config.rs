@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+const DEFAULT_RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Which side opens the connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionType {
+    Initiator,
+    Acceptor,
+}
+
+/// Where a session's connection is made or accepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportConfig {
+    pub connection_type: ConnectionType,
+    /// Host and port an initiator connects to, or the address and port an
+    /// acceptor listens on
+    pub host: String,
+    pub port: u16,
+    /// How long an initiator waits before reconnecting
+    pub reconnect_interval: Duration,
+    /// Whether the connection is made over TLS
+    pub use_tls: bool,
+}
+
+impl TransportConfig {
+    pub fn initiator(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            connection_type: ConnectionType::Initiator,
+            host: host.into(),
+            port,
+            reconnect_interval: DEFAULT_RECONNECT_INTERVAL,
+            use_tls: false,
+        }
+    }
+
+    /// Listens on all interfaces
+    pub fn acceptor(port: u16) -> Self {
+        Self {
+            connection_type: ConnectionType::Acceptor,
+            host: "0.0.0.0".to_owned(),
+            port,
+            reconnect_interval: DEFAULT_RECONNECT_INTERVAL,
+            use_tls: false,
+        }
+    }
+
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    pub fn with_reconnect_interval(mut self, interval: Duration) -> Self {
+        self.reconnect_interval = interval;
+        self
+    }
+
+    pub fn with_tls(mut self, use_tls: bool) -> Self {
+        self.use_tls = use_tls;
+        self
+    }
+
+    /// "host:port", as taken by `TcpStream::connect` and `TcpListener::bind`
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
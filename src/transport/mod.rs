@@ -0,0 +1,9 @@
+#[cfg(feature = "tokio")]
+pub mod codec;
+pub mod config;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+#[cfg(feature = "tokio")]
+pub use codec::{CodecError, FixCodec};
+pub use config::{ConnectionType, TransportConfig};
@@ -0,0 +1,457 @@
+use crate::error::FixError;
+use crate::log::writer::log_shared;
+use crate::log::{Direction, LogWriter};
+use crate::message::FixMessage;
+#[cfg(feature = "latency")]
+use crate::metrics::latency::{self, LatencyHook, MessageCtx, Stage, TimedFrame};
+use crate::messages::require_msg_type;
+use crate::session::{
+    InboundConnection, MessageStore, OutboundQueue, Session, SessionAcceptor, SessionConfig, SessionEvent, SessionSelector,
+};
+use crate::tags::msg_type;
+use crate::seqnum::SeqNum;
+use crate::transport::{ConnectionType, TransportConfig};
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, Message};
+#[cfg(feature = "websocket-tls")]
+use tokio_tungstenite::Connector;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Carries a session over WebSocket with one FIX message per frame.
+/// Messages are sent as binary frames and each inbound text or binary frame
+/// is decoded on its own, so no stream framer is involved. WebSocket pings
+/// run alongside FIX heartbeats: pongs are answered automatically and any
+/// frame from the peer counts as a sign of life.
+pub struct WsTransport<S> {
+    ws: WebSocketStream<S>,
+    session: Session,
+    last_sent: Instant,
+    last_received: Instant,
+    log: Option<Arc<Mutex<LogWriter>>>,
+}
+
+impl WsTransport<MaybeTlsStream<TcpStream>> {
+    /// Connects to a "ws://" URL, or with the websocket-tls feature a
+    /// "wss://" one whose server certificate chains to a webpki root
+    pub async fn connect(url: &str, session: Session) -> Result<Self, FixError> {
+        let (ws, _response) = tokio_tungstenite::connect_async(url).await.map_err(transport_error)?;
+        Ok(Self::new(ws, session))
+    }
+
+    /// Connects to a "wss://" URL with `tls`, e.g. to trust a venue's
+    /// private CA or present a client certificate
+    #[cfg(feature = "websocket-tls")]
+    pub async fn connect_tls(url: &str, session: Session, tls: Arc<rustls::ClientConfig>) -> Result<Self, FixError> {
+        let connector = Some(Connector::Rustls(tls));
+        let (ws, _response) = tokio_tungstenite::connect_async_tls_with_config(url, None, false, connector)
+            .await
+            .map_err(transport_error)?;
+        Ok(Self::new(ws, session))
+    }
+
+    /// Connects to "ws://host:port/" as given by a settings file entry, or
+    /// "wss://host:port/" when it sets SocketUseSSL
+    pub async fn initiate(config: SessionConfig, transport: &TransportConfig) -> Result<Self, FixError> {
+        if transport.connection_type != ConnectionType::Initiator {
+            return Err(FixError::Transport("session is configured as an acceptor".to_owned()));
+        }
+        let scheme = if transport.use_tls { "wss" } else { "ws" };
+        Self::connect(&format!("{}://{}/", scheme, transport.addr()), Session::new(config)).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WsTransport<S> {
+    /// Wraps an established WebSocket, e.g. one accepted by a server
+    pub fn new(ws: WebSocketStream<S>, session: Session) -> Self {
+        let now = Instant::now();
+        Self { ws, session, last_sent: now, last_received: now, log: None }
+    }
+
+    /// Writes every message sent or received to `log`, as OUT and IN lines.
+    /// Several transports may share one log.
+    pub fn with_log(mut self, log: Arc<Mutex<LogWriter>>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    #[inline]
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Encodes `msg` and sends it as one binary frame
+    pub async fn send(&mut self, msg: &FixMessage) -> Result<(), FixError> {
+        let encoded = msg.encode()?;
+        self.write(encoded.freeze()).await?;
+        self.log(Direction::Outbound, msg)
+    }
+
+    /// Stamps `msg` with the session's next MsgSeqNum and SendingTime,
+    /// saves it to `store` and sends it, returning the MsgSeqNum used
+    pub async fn send_sequenced(&mut self, msg: &mut FixMessage, store: &mut impl MessageStore) -> Result<SeqNum, FixError> {
+        let seq = self.session.next_outbound_seq();
+        let encoded = self.session.prepare_outbound(msg, store)?;
+        self.write(encoded.freeze()).await?;
+        self.log(Direction::Outbound, msg)?;
+        Ok(seq)
+    }
+
+    /// Sends what `queue` lets out now through `send_sequenced`, returning
+    /// how many messages were sent. Expired messages are dropped by the
+    /// queue before they are stamped, so they take no MsgSeqNum.
+    pub async fn flush(&mut self, queue: &mut OutboundQueue, store: &mut impl MessageStore) -> Result<usize, FixError> {
+        let mut sent = 0;
+        while let Some(mut msg) = queue.pop() {
+            self.send_sequenced(&mut msg, store).await?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Acceptor side of the logon: reads the first message, checks it with
+    /// `Session::accept_logon` and sends the Logon or Logout answering it,
+    /// followed by a ResendRequest if the Logon's MsgSeqNum was ahead.
+    /// Returns whether the session is now logged on.
+    pub async fn accept_logon(&mut self, store: &mut impl MessageStore) -> Result<bool, FixError> {
+        let Some(frame) = self.next_data_frame().await? else {
+            return Ok(false);
+        };
+        let logon = FixMessage::decode(payload(&frame))?;
+        self.log(Direction::Inbound, &logon)?;
+        self.answer_logon(&logon, store).await
+    }
+
+    /// Acceptor side of the logon when one listener serves several
+    /// sessions: reads the first message and wraps the connection in a new
+    /// session for the configuration `acceptor` assigns it to. The Logon is
+    /// returned with it for `answer_logon`, which needs that session's
+    /// store. `None` if the peer closes first.
+    pub async fn assign<Sel: SessionSelector>(
+        mut ws: WebSocketStream<S>,
+        local_port: u16,
+        acceptor: &SessionAcceptor<Sel>,
+    ) -> Result<Option<(Self, FixMessage)>, FixError> {
+        let Some(frame) = next_data_frame(&mut ws).await? else {
+            return Ok(None);
+        };
+        let logon = FixMessage::decode(payload(&frame))?;
+        require_msg_type(&logon, msg_type::LOGON)?;
+        let config = acceptor.assign(&InboundConnection { local_port, logon: &logon })?;
+        Ok(Some((Self::new(ws, Session::new(config.clone())), logon)))
+    }
+
+    /// Answers a Logon already read, as `accept_logon` does
+    pub async fn answer_logon(&mut self, logon: &FixMessage, store: &mut impl MessageStore) -> Result<bool, FixError> {
+        let mut response = self.session.accept_logon(logon)?;
+        self.send_sequenced(&mut response, store).await?;
+        if let Some(mut request) = self.session.take_logon_resend_request() {
+            self.send_sequenced(&mut request, store).await?;
+        }
+        Ok(self.session.is_logged_on())
+    }
+
+    async fn write(&mut self, encoded: Bytes) -> Result<(), FixError> {
+        self.ws.send(Message::Binary(encoded)).await.map_err(transport_error)?;
+        self.last_sent = Instant::now();
+        self.session.record_outbound();
+        Ok(())
+    }
+
+    /// The next message and what it means for the session, or `None` once
+    /// the peer closes the connection
+    pub async fn recv(&mut self) -> Result<Option<(FixMessage, SessionEvent)>, FixError> {
+        let Some(frame) = self.next_data_frame().await? else {
+            return Ok(None);
+        };
+        let msg = FixMessage::decode(payload(&frame))?;
+        self.log(Direction::Inbound, &msg)?;
+        let event = self.session.process_inbound(&msg)?;
+        Ok(Some((msg, event)))
+    }
+
+    // The next binary or text frame, or `None` once the peer closes
+    async fn next_data_frame(&mut self) -> Result<Option<Message>, FixError> {
+        let frame = next_data_frame(&mut self.ws).await;
+        self.last_received = Instant::now();
+        frame
+    }
+
+    /// `send`, reporting `EncodeComplete` and `BytesWritten` to `hook`
+    #[cfg(feature = "latency")]
+    pub async fn send_timed<H: LatencyHook>(&mut self, msg: &FixMessage, hook: &mut H) -> Result<(), FixError> {
+        let encoded = latency::encode_message(msg, hook)?;
+        self.write(encoded.freeze()).await?;
+        hook.stage(Stage::BytesWritten, &MessageCtx::of(msg));
+        self.log(Direction::Outbound, msg)
+    }
+
+    /// `recv`, handing the message and its event to `handler` and reporting
+    /// every inbound stage to `hook`. A WebSocket frame always holds a whole
+    /// message, so `BytesReceived` and `FrameComplete` coincide.
+    #[cfg(feature = "latency")]
+    pub async fn recv_timed<H, F, R>(&mut self, hook: &mut H, handler: F) -> Result<Option<R>, FixError>
+    where
+        H: LatencyHook,
+        F: FnOnce(&FixMessage, SessionEvent, &mut H) -> R,
+    {
+        let Some(frame) = self.next_data_frame().await? else {
+            return Ok(None);
+        };
+        let received = hook.now();
+        let timed = TimedFrame { frame: frame.into_data(), received, complete: received };
+        let msg = latency::decode_frame(&timed, hook)?;
+        self.log(Direction::Inbound, &msg)?;
+        self.session.dispatch_inbound(&msg, hook, handler).map(Some)
+    }
+
+    /// Sends a WebSocket ping if nothing has been sent for the session's
+    /// HeartBtInt. FIX Heartbeats stay the caller's job, as they carry
+    /// sequence numbers.
+    pub async fn keepalive(&mut self) -> Result<(), FixError> {
+        if self.last_sent.elapsed() >= self.heartbeat_interval() {
+            self.ws.send(Message::Ping(Default::default())).await.map_err(transport_error)?;
+            self.last_sent = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Whether anything, a pong included, has arrived within `timeout`
+    #[inline]
+    pub fn peer_alive(&self, timeout: Duration) -> bool {
+        self.last_received.elapsed() < timeout
+    }
+
+    pub async fn close(&mut self) -> Result<(), FixError> {
+        self.ws.close(None).await.map_err(transport_error)
+    }
+
+    fn log(&self, direction: Direction, msg: &FixMessage) -> Result<(), FixError> {
+        match &self.log {
+            Some(log) => log_shared(log, direction, msg),
+            None => Ok(()),
+        }
+    }
+
+    fn heartbeat_interval(&self) -> Duration {
+        Duration::from_secs(self.session.config().heart_bt_int as u64)
+    }
+}
+
+async fn next_data_frame<S: AsyncRead + AsyncWrite + Unpin>(ws: &mut WebSocketStream<S>) -> Result<Option<Message>, FixError> {
+    while let Some(frame) = ws.next().await {
+        match frame.map_err(transport_error)? {
+            frame @ (Message::Binary(_) | Message::Text(_)) => return Ok(Some(frame)),
+            Message::Close(_) => return Ok(None),
+            Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+        }
+    }
+    Ok(None)
+}
+
+fn payload(frame: &Message) -> &[u8] {
+    match frame {
+        Message::Binary(data) => data,
+        Message::Text(text) => text.as_bytes(),
+        _ => &[],
+    }
+}
+
+fn transport_error(err: tungstenite::Error) -> FixError {
+    FixError::Transport(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FixField;
+    use crate::session::{FileMessageStore, SessionConfig};
+    use crate::tags::{fix_version, msg_type, Tag};
+    use tokio::net::TcpListener;
+
+    // Echoes every data frame back, as a venue's loopback test endpoint does
+    async fn echo_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(frame)) = ws.next().await {
+                if frame.is_binary() || frame.is_text() {
+                    ws.send(frame).await.unwrap();
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    fn sequenced(mut msg: FixMessage, seq: u64) -> FixMessage {
+        msg.add_field(FixField::from_value(Tag::MsgSeqNum.value(), &seq));
+        msg
+    }
+
+    #[tokio::test]
+    async fn test_logon_and_exchange() {
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let session = Session::new(config.clone());
+        let mut transport = WsTransport::connect(&echo_server().await, session).await.unwrap();
+
+        transport.send(&sequenced(config.logon(), 1)).await.unwrap();
+        let (logon, event) = transport.recv().await.unwrap().unwrap();
+        assert_eq!(logon.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGON);
+        assert_eq!(event, SessionEvent::Admin);
+
+        let mut order = sequenced(config.new_message(msg_type::NEW_ORDER_SINGLE), 2);
+        order.add_field(FixField::new(Tag::ClOrdID.value(), b"ORD1".to_vec()));
+        transport.send(&order).await.unwrap();
+        let (echoed, event) = transport.recv().await.unwrap().unwrap();
+        assert_eq!(echoed.get_field(Tag::ClOrdID.value()).unwrap().value(), b"ORD1");
+        assert_eq!(event, SessionEvent::Application);
+        assert_eq!(transport.session().next_inbound_seq(), SeqNum::new(3).unwrap());
+        assert!(transport.peer_alive(Duration::from_secs(1)));
+
+        transport.keepalive().await.unwrap();
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_message_log() {
+        let dir = std::env::temp_dir().join(format!("fix_engine_ws_log_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("messages.log");
+        let log = Arc::new(Mutex::new(LogWriter::open(&path, Default::default()).unwrap()));
+
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let session = Session::new(config.clone());
+        let mut transport = WsTransport::connect(&echo_server().await, session).await.unwrap().with_log(log.clone());
+        transport.send(&sequenced(config.logon(), 1)).await.unwrap();
+        transport.recv().await.unwrap().unwrap();
+        transport.close().await.unwrap();
+        drop((transport, log));
+
+        let records: Vec<_> = crate::log::LogReader::open(&path).unwrap().map(Result::unwrap).collect();
+        let logged: Vec<_> = records
+            .iter()
+            .map(|record| (record.direction, record.message.as_ref().unwrap().msg_seq_num().unwrap().unwrap().get()))
+            .collect();
+        assert_eq!(logged, [(Some(Direction::Outbound), 1), (Some(Direction::Inbound), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_qualified_sessions_by_port() {
+        let dir = std::env::temp_dir().join(format!("fix_engine_ws_acceptor_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let orders = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let drop_copy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ports = [orders.local_addr().unwrap().port(), drop_copy.local_addr().unwrap().port()];
+        let venue = SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT");
+        let acceptor = std::sync::Arc::new(
+            SessionAcceptor::new(crate::session::ByLocalPort::new([(ports[0], "ORDERS"), (ports[1], "DROPCOPY")]))
+                .with_session(venue.clone().with_session_qualifier("ORDERS"))
+                .with_session(venue.with_session_qualifier("DROPCOPY")),
+        );
+
+        // Both listeners accept concurrently, each connection opening the
+        // journal of the session it is assigned to
+        for listener in [orders, drop_copy] {
+            let (acceptor, dir) = (acceptor.clone(), dir.clone());
+            tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let local_port = stream.local_addr().unwrap().port();
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let (mut transport, logon) = WsTransport::assign(ws, local_port, &acceptor).await.unwrap().unwrap();
+                let mut store = FileMessageStore::open_for(&dir, transport.session().config()).unwrap();
+                assert!(transport.answer_logon(&logon, &mut store).await.unwrap());
+                while let Some((msg, _)) = transport.recv().await.unwrap() {
+                    let mut echo = transport.session().config().new_message(msg_type::HEARTBEAT);
+                    echo.add_field(msg.get_field(Tag::TestReqID.value()).unwrap().clone());
+                    transport.send_sequenced(&mut echo, &mut store).await.unwrap();
+                }
+            });
+        }
+
+        let client = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let run = |port: u16, requests: u64| {
+            let client = client.clone();
+            async move {
+                let url = format!("ws://127.0.0.1:{}", port);
+                let mut transport = WsTransport::connect(&url, Session::new(client.clone())).await.unwrap();
+                transport.send(&sequenced(client.logon(), 1)).await.unwrap();
+                assert_eq!(transport.recv().await.unwrap().unwrap().1, SessionEvent::Admin);
+                for seq in 2..=requests + 1 {
+                    transport.send(&sequenced(client.test_request(b"T"), seq)).await.unwrap();
+                    assert_eq!(transport.recv().await.unwrap().unwrap().1, SessionEvent::Admin);
+                }
+                transport.close().await.unwrap();
+                transport.session().next_inbound_seq().get()
+            }
+        };
+        let (orders_seq, drop_copy_seq) = tokio::join!(run(ports[0], 5), run(ports[1], 2));
+        assert_eq!((orders_seq, drop_copy_seq), (7, 4));
+
+        let journal = |qualifier: &str| {
+            let config = SessionConfig::new(fix_version::FIX_4_4, "VENUE", "CLIENT").with_session_qualifier(qualifier);
+            FileMessageStore::open_for(&dir, &config).unwrap().last_seq().unwrap().map(SeqNum::get)
+        };
+        assert_eq!((journal("ORDERS"), journal("DROPCOPY")), (Some(6), Some(3)));
+    }
+
+    #[cfg(feature = "websocket-tls")]
+    #[tokio::test]
+    async fn test_wss_exchange() {
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+        // A localhost certificate issued by a test CA, valid until 2125
+        let ca = CertificateDer::from(&include_bytes!("../../tests/tls/ca.der")[..]);
+        let cert = CertificateDer::from(&include_bytes!("../../tests/tls/localhost.der")[..]);
+        let key = PrivatePkcs8KeyDer::from(&include_bytes!("../../tests/tls/localhost.key.der")[..]);
+
+        let server = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], PrivateKeyDer::Pkcs8(key))
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server));
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // A client that rejects the certificate fails the handshake
+                let Ok(tls) = acceptor.accept(stream).await else {
+                    continue;
+                };
+                let mut ws = tokio_tungstenite::accept_async(tls).await.unwrap();
+                while let Some(Ok(frame)) = ws.next().await {
+                    if frame.is_binary() {
+                        ws.send(frame).await.unwrap();
+                    }
+                }
+            }
+        });
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(ca).unwrap();
+        let client = rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        let config = SessionConfig::new(fix_version::FIX_4_4, "CLIENT", "VENUE");
+        let url = format!("wss://localhost:{}/", port);
+        let mut transport = WsTransport::connect_tls(&url, Session::new(config.clone()), Arc::new(client))
+            .await
+            .unwrap();
+        assert!(matches!(transport.ws.get_ref(), MaybeTlsStream::Rustls(_)));
+
+        transport.send(&sequenced(config.logon(), 1)).await.unwrap();
+        let (logon, event) = transport.recv().await.unwrap().unwrap();
+        assert_eq!(logon.get_field(Tag::MsgType.value()).unwrap().value(), msg_type::LOGON);
+        assert_eq!(event, SessionEvent::Admin);
+        transport.close().await.unwrap();
+
+        // The webpki roots do not include the test CA
+        let untrusted = WsTransport::connect(&url, Session::new(config)).await;
+        assert!(matches!(untrusted, Err(FixError::Transport(reason)) if reason.contains("certificate")));
+    }
+}
@@ -0,0 +1,56 @@
+// Compiles tests/ffi/decode.c against include/fix_engine.h and the cdylib,
+// then runs it. The package only declares an rlib, so the cdylib is built
+// here with `cargo rustc --crate-type cdylib` into a target directory of
+// its own.
+#![cfg(feature = "ffi")]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn build_cdylib(root: &Path) -> PathBuf {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    let status = Command::new(cargo)
+        .args(["rustc", "--lib", "--features", "ffi", "--crate-type", "cdylib", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(root)
+        .status()
+        .unwrap();
+    assert!(status.success(), "building the cdylib failed");
+    target_dir.join("debug")
+}
+
+#[test]
+fn c_program_uses_the_codec() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = build_cdylib(root);
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi_decode");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+    let status = Command::new(&cc)
+        .args(["-std=c99", "-Wall", "-Werror"])
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(root.join("tests/ffi/decode.c"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lfix_engine")
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .unwrap_or_else(|e| panic!("running {cc}: {e}"));
+    assert!(status.success(), "compiling decode.c failed");
+
+    let output = Command::new(&out)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "decode.c failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"ok\n");
+}
@@ -0,0 +1,57 @@
+/* Exercises the C interface as a gateway would: decode, read fields, walk
+ * the field list and encode into a caller buffer. Exits non-zero on the
+ * first failed check. */
+#include <stdio.h>
+#include <string.h>
+
+#include "fix_engine.h"
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,     \
+                    __LINE__, #cond);                                  \
+            return 1;                                                  \
+        }                                                              \
+    } while (0)
+
+static const char HEARTBEAT[] = "8=FIX.4.4\0019=5\00135=0\00110=163\001";
+
+int main(void) {
+    size_t wire_len = sizeof(HEARTBEAT) - 1;
+    FixMsgHandle *msg = NULL;
+    CHECK(fix_msg_decode((const uint8_t *)HEARTBEAT, wire_len, &msg) == FIX_STATUS_OK);
+    CHECK(msg != NULL);
+
+    const uint8_t *val = NULL;
+    size_t len = 0;
+    CHECK(fix_msg_get(msg, 35, &val, &len) == FIX_STATUS_OK);
+    CHECK(len == 1 && val[0] == '0');
+    CHECK(fix_msg_get(msg, 58, &val, &len) == FIX_STATUS_NOT_FOUND);
+
+    size_t count = 0;
+    CHECK(fix_msg_field_count(msg, &count) == FIX_STATUS_OK);
+    CHECK(count == 4);
+    uint32_t tag = 0;
+    CHECK(fix_msg_field_at(msg, 0, &tag, &val, &len) == FIX_STATUS_OK);
+    CHECK(tag == 8 && len == 7 && memcmp(val, "FIX.4.4", 7) == 0);
+    CHECK(fix_msg_field_at(msg, count, &tag, &val, &len) == FIX_STATUS_OUT_OF_RANGE);
+
+    uint8_t small[8];
+    size_t written = 0;
+    CHECK(fix_msg_encode(msg, small, sizeof(small), &written) == FIX_STATUS_BUFFER_TOO_SMALL);
+    CHECK(written == wire_len);
+    uint8_t buf[64];
+    CHECK(fix_msg_encode(msg, buf, sizeof(buf), &written) == FIX_STATUS_OK);
+    CHECK(written == wire_len && memcmp(buf, HEARTBEAT, wire_len) == 0);
+    fix_msg_free(msg);
+
+    FixMsgHandle *bad = NULL;
+    CHECK(fix_msg_decode((const uint8_t *)"8=FIX.4.4\001", 10, &bad) != FIX_STATUS_OK);
+    CHECK(bad == NULL);
+    CHECK(fix_msg_decode(NULL, 0, &bad) == FIX_STATUS_NULL_POINTER);
+    fix_msg_free(NULL);
+
+    puts("ok");
+    return 0;
+}